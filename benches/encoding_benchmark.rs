@@ -1,17 +1,15 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
 use hamming_rust::{
     encoding::bitvec::BitVec,
-    encoding::hamming::{Hamming, HammingCode},
+    encoding::block::BlockHamming,
+    encoding::hamming::{Hamming, HammingCode, HammingCodeBase},
+    encoding::matrix::MatrixHamming,
+    encoding::prepared::PreparedHamming,
+    encoding::syndrome::SyndromeTableDecoder,
 };
-use rand::Rng;
 
 fn generate_random_bitvec(size: usize) -> BitVec {
-    let mut rng = rand::rng();
-    let mut bv = BitVec::with_capacity(size);
-    for _ in 0..size {
-        bv.push(rng.random_bool(0.5));
-    }
-    bv
+    BitVec::random(size, &mut rand::rng())
 }
 
 fn benchmark_implementations(c: &mut Criterion) {
@@ -43,8 +41,203 @@ fn benchmark_implementations(c: &mut Criterion) {
         });
 
         group.finish();
+
+        // Decoding a clean codeword, where the in-place path skips the clone
+        let clean = Hamming.encode(&data).unwrap();
+
+        let mut group = c.benchmark_group(format!("hamming_decode_clean_{}_bits", size));
+
+        group.bench_function("standard", |b| {
+            let hamming = Hamming;
+            b.iter(|| hamming.decode(black_box(&clean)))
+        });
+        group.bench_function("in_place", |b| {
+            let hamming = Hamming;
+            let mut codeword = clean.clone();
+            b.iter(|| {
+                hamming.decode_in_place(black_box(&mut codeword)).unwrap();
+                hamming.extract_data(&codeword)
+            })
+        });
+
+        group.finish();
+    }
+}
+
+fn benchmark_small_messages(c: &mut Criterion) {
+    let messages: Vec<BitVec> = (0..1_000_000).map(|_| generate_random_bitvec(64)).collect();
+
+    let mut group = c.benchmark_group("hamming_encode_1m_64_bit_messages");
+    group.sample_size(10);
+
+    group.bench_function("standard", |b| {
+        let hamming = Hamming;
+        b.iter(|| {
+            for message in &messages {
+                black_box(hamming.encode(black_box(message)).unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn benchmark_buffer_reuse(c: &mut Criterion) {
+    let messages: Vec<BitVec> = (0..1_000_000)
+        .map(|_| generate_random_bitvec(128))
+        .collect();
+
+    let mut group = c.benchmark_group("hamming_encode_1m_128_bit_messages");
+    group.sample_size(10);
+
+    group.bench_function("allocating", |b| {
+        let hamming = Hamming;
+        b.iter(|| {
+            for message in &messages {
+                black_box(hamming.encode(black_box(message)).unwrap());
+            }
+        })
+    });
+    group.bench_function("encode_into", |b| {
+        let hamming = Hamming;
+        let mut codeword = BitVec::new();
+        b.iter(|| {
+            for message in &messages {
+                hamming
+                    .encode_into(black_box(message), &mut codeword)
+                    .unwrap();
+                black_box(&codeword);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn benchmark_matrix_encoder(c: &mut Criterion) {
+    for size in [4, 11, 26, 57, 128, 512, 2048] {
+        let data = generate_random_bitvec(size);
+        let matrix = MatrixHamming::new(size);
+
+        let mut group = c.benchmark_group(format!("hamming_encode_matrix_{}_bits", size));
+        group.bench_function("bit_loop", |b| {
+            let hamming = Hamming;
+            b.iter(|| hamming.encode(black_box(&data)))
+        });
+        group.bench_function("matrix", |b| b.iter(|| matrix.encode(black_box(&data))));
+        group.finish();
+    }
+}
+
+fn benchmark_block_tables(c: &mut Criterion) {
+    let data = generate_random_bitvec(64 * 1024 * 8);
+
+    for codec in [BlockHamming::hamming74(), BlockHamming::hamming1511()] {
+        let name = format!(
+            "block_hamming_{}_{}_64kib",
+            codec.code_bits_per_block(),
+            codec.data_bits_per_block()
+        );
+        let encoded = codec.encode(&data).unwrap();
+
+        let mut group = c.benchmark_group(format!("{}_encode", name));
+        group.bench_function("table", |b| b.iter(|| codec.encode(black_box(&data))));
+        group.bench_function("generic", |b| {
+            b.iter(|| codec.encode_generic(black_box(&data)))
+        });
+        group.finish();
+
+        let mut group = c.benchmark_group(format!("{}_decode", name));
+        group.bench_function("table", |b| b.iter(|| codec.decode(black_box(&encoded))));
+        group.bench_function("generic", |b| {
+            b.iter(|| codec.decode_generic(black_box(&encoded)))
+        });
+        group.finish();
+    }
+}
+
+fn benchmark_syndrome_table(c: &mut Criterion) {
+    let data = generate_random_bitvec(64 * 1024 * 8);
+
+    for r in [5, 6] {
+        let codec = BlockHamming::new(r).unwrap();
+        let decoder = SyndromeTableDecoder::for_block(r).unwrap();
+        let encoded = codec.encode(&data).unwrap();
+
+        let mut group = c.benchmark_group(format!(
+            "block_hamming_{}_{}_64kib_decode",
+            codec.code_bits_per_block(),
+            codec.data_bits_per_block()
+        ));
+        group.bench_function("syndrome_table", |b| {
+            b.iter(|| decoder.decode(black_box(&encoded)))
+        });
+        group.bench_function("generic", |b| {
+            b.iter(|| codec.decode_generic(black_box(&encoded)))
+        });
+        group.finish();
+    }
+}
+
+fn benchmark_prepared(c: &mut Criterion) {
+    let blocks: Vec<BitVec> = (0..100_000).map(|_| generate_random_bitvec(11)).collect();
+    let prepared = PreparedHamming::for_len(11);
+
+    let mut group = c.benchmark_group("hamming_encode_100k_11_bit_blocks");
+    group.bench_function("standard", |b| {
+        b.iter(|| {
+            for block in &blocks {
+                black_box(Hamming.encode(black_box(block)).unwrap());
+            }
+        })
+    });
+    group.bench_function("prepared", |b| {
+        b.iter(|| {
+            for block in &blocks {
+                black_box(prepared.encode(black_box(block)).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Parity and XOR over long codewords. Run with and without `--features simd`
+/// to compare the wide-word kernels against the byte-wise loops.
+fn benchmark_simd(c: &mut Criterion) {
+    let kernel = if cfg!(feature = "simd") {
+        "simd"
+    } else {
+        "scalar"
+    };
+
+    for size in [8192, 65536] {
+        let a = generate_random_bitvec(size);
+        let b = generate_random_bitvec(size);
+
+        let mut group = c.benchmark_group(format!("simd_{}_bits", size));
+        group.bench_function(format!("{}_parity", kernel), |bench| {
+            bench.iter(|| HammingCodeBase::basic_compute_parity(black_box(&a), black_box(64)))
+        });
+        group.bench_function(format!("{}_syndrome", kernel), |bench| {
+            bench.iter(|| Hamming.compute_syndrome(black_box(&a)))
+        });
+        group.bench_function(format!("{}_xor", kernel), |bench| {
+            let mut out = a.clone();
+            bench.iter(|| out.xor_assign(black_box(&b)))
+        });
+        group.finish();
     }
 }
 
-criterion_group!(benches, benchmark_implementations);
+criterion_group!(
+    benches,
+    benchmark_implementations,
+    benchmark_small_messages,
+    benchmark_buffer_reuse,
+    benchmark_matrix_encoder,
+    benchmark_block_tables,
+    benchmark_syndrome_table,
+    benchmark_prepared,
+    benchmark_simd
+);
 criterion_main!(benches);
@@ -1,5 +1,9 @@
-use std::str::FromStr;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
+use hamming_rust::proto::ContentType;
 use serde::{Deserialize, Serialize};
 
 use super::Subcommands;
@@ -10,6 +14,14 @@ pub enum DataType {
     Text,
     #[serde(rename = "binary")]
     Binary,
+    #[serde(rename = "hex")]
+    Hex,
+    #[serde(rename = "base64")]
+    Base64,
+    /// Bytes as they are, never read as text: shown as a hex preview, and
+    /// written out whole with `--output`.
+    #[serde(rename = "bytes")]
+    Bytes,
 }
 impl FromStr for DataType {
     type Err = anyhow::Error;
@@ -18,15 +30,46 @@ impl FromStr for DataType {
         match s.to_lowercase().as_str() {
             "text" => Ok(DataType::Text),
             "binary" => Ok(DataType::Binary),
+            "hex" => Ok(DataType::Hex),
+            "base64" => Ok(DataType::Base64),
+            "bytes" => Ok(DataType::Bytes),
             _ => Err(anyhow::anyhow!("Invalid data type: {}", s)),
         }
     }
 }
-impl ToString for DataType {
-    fn to_string(&self) -> String {
+impl DataType {
+    /// How to show a payload the sender said is `content_type`, unless the
+    /// user said otherwise. Content types this version doesn't know are shown
+    /// as binary.
+    pub fn for_payload(overridden: Option<DataType>, content_type: ContentType) -> DataType {
+        overridden.unwrap_or(match content_type {
+            ContentType::Text => DataType::Text,
+            ContentType::Hex => DataType::Hex,
+            ContentType::Base64 => DataType::Base64,
+            ContentType::Bytes => DataType::Bytes,
+            ContentType::Binary | ContentType::Unknown(_) => DataType::Binary,
+        })
+    }
+}
+impl From<&DataType> for ContentType {
+    fn from(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Text => ContentType::Text,
+            DataType::Binary => ContentType::Binary,
+            DataType::Hex => ContentType::Hex,
+            DataType::Base64 => ContentType::Base64,
+            DataType::Bytes => ContentType::Bytes,
+        }
+    }
+}
+impl Display for DataType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            DataType::Text => "text".to_string(),
-            DataType::Binary => "binary".to_string(),
+            DataType::Text => write!(f, "text"),
+            DataType::Binary => write!(f, "binary"),
+            DataType::Hex => write!(f, "hex"),
+            DataType::Base64 => write!(f, "base64"),
+            DataType::Bytes => write!(f, "bytes"),
         }
     }
 }
@@ -43,11 +86,47 @@ impl From<&Subcommands> for Mode {
         }
     }
 }
-impl ToString for Mode {
-    fn to_string(&self) -> String {
+impl Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Mode::Receiver => "receiver".to_string(),
-            Mode::Sender => "sender".to_string(),
+            Mode::Receiver => write!(f, "receiver"),
+            Mode::Sender => write!(f, "sender"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_payload() {
+        for data_type in [
+            DataType::Text,
+            DataType::Binary,
+            DataType::Hex,
+            DataType::Base64,
+            DataType::Bytes,
+        ] {
+            let content_type = ContentType::from(&data_type);
+            assert_eq!(
+                DataType::for_payload(None, content_type).to_string(),
+                data_type.to_string()
+            );
+        }
+        assert!(matches!(
+            DataType::for_payload(None, ContentType::Unknown(9)),
+            DataType::Binary
+        ));
+
+        // the flag always wins
+        assert!(matches!(
+            DataType::for_payload(Some(DataType::Binary), ContentType::Text),
+            DataType::Binary
+        ));
+        assert!(matches!(
+            DataType::for_payload(Some(DataType::Text), ContentType::Binary),
+            DataType::Text
+        ));
+    }
+}
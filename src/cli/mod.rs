@@ -2,7 +2,12 @@ pub mod enums;
 
 use clap::{Parser, Subcommand};
 use enums::DataType;
+use hamming_rust::proto;
 use log::LevelFilter;
+use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    path::Path,
+};
 
 #[derive(Parser, Debug)]
 pub struct Args {
@@ -10,9 +15,10 @@ pub struct Args {
     #[arg(long, short, default_value_t = LevelFilter::Info)]
     pub verbosity: LevelFilter,
 
-    /// The type of data to send
-    #[arg(long, short, default_value_t = DataType::Binary, id="type")]
-    pub type_data: DataType,
+    /// The type of data to send (binary by default). The receiver takes it
+    /// from the frame, unless it's given here
+    #[arg(long, short, id = "type")]
+    pub type_data: Option<DataType>,
 
     #[command(subcommand)]
     pub command: Subcommands,
@@ -23,12 +29,124 @@ pub enum Subcommands {
     Receiver(ReceiverArgs),
     Sender(SenderArgs),
 }
+impl Subcommands {
+    /// Whether the data received goes to stdout, leaving no room for the log.
+    pub fn writes_stdout(&self) -> bool {
+        match self {
+            Subcommands::Receiver(args) => args.output.as_deref() == Some(Path::new("-")),
+            Subcommands::Sender(_) => false,
+        }
+    }
+}
 #[derive(Parser, Debug)]
 pub struct SenderArgs {
-    /// The data to encode and send to the receiver
+    /// The data to encode and send to the receiver. Without it, or
+    /// `--file`, everything on stdin is sent, as bytes like `--file`
+    #[arg(long, short)]
+    pub data: Option<String>,
+
+    /// Send the contents of this file instead: its bytes as they are for
+    /// binary, hex, base64 or bytes, rather than spelled out in digits, and
+    /// UTF-8 for text
+    #[arg(long, conflicts_with = "data", value_name = "PATH")]
+    pub file: Option<std::path::PathBuf>,
+
+    /// Probability of flipping each bit of the codeword before sending it
+    #[arg(long, short, default_value_t = 0.0)]
+    pub flip_probability: f64,
+
+    /// Seed for the bit flips, to make them reproducible
+    #[arg(long, short)]
+    pub seed: Option<u64>,
+
+    /// Split the data into frames of at most this many bits each
     #[arg(long, short)]
-    pub data: String,
+    pub max_fragment_bits: Option<NonZeroUsize>,
+
+    /// Deflate the data before encoding it, for each frame it makes smaller
+    #[cfg(feature = "compress")]
+    #[arg(long)]
+    pub compress: bool,
+
+    /// Stamp each frame with the time it's sent, for the receiver to tell
+    /// how long it took to get there
+    #[arg(long)]
+    pub timestamp: bool,
+
+    /// Agree with the receiver on the version, codec and frame size to send
+    /// with first. Needs `--connect` or `--socket`, for the receiver to answer
+    #[arg(long)]
+    pub handshake: bool,
+
+    /// Once everything's sent, keep the link open and send a heartbeat every
+    /// this many seconds, until the receiver goes away
+    #[arg(long, value_name = "SECONDS")]
+    pub heartbeat: Option<NonZeroU64>,
+
+    /// Wait for the receiver to ack every frame, sending it again up to this
+    /// many times when it's nacked. The receiver has to be started with
+    /// `--reliable` too
+    #[arg(long, conflicts_with = "flip_probability")]
+    pub retries: Option<u32>,
+
+    /// Send the frames to a receiver listening at this address, e.g.
+    /// `host:7000`, rather than to stdout
+    #[arg(long)]
+    pub connect: Option<String>,
+
+    /// Send each frame in a datagram of its own, to the `--connect` address
+    #[arg(long, requires = "connect")]
+    pub udp: bool,
+
+    /// Send the frames to a receiver listening on the Unix socket at this
+    /// path
+    #[cfg(unix)]
+    #[arg(long, conflicts_with = "connect")]
+    pub socket: Option<std::path::PathBuf>,
 }
 
 #[derive(Parser, Debug)]
-pub struct ReceiverArgs {}
+pub struct ReceiverArgs {
+    /// Wait for a sender to connect at this address, e.g. `0.0.0.0:7000`,
+    /// rather than reading the frames off stdin
+    #[arg(long)]
+    pub listen: Option<String>,
+
+    /// Take frames as datagrams on the `--listen` address, for as long as
+    /// they keep coming. Frames that don't decode are skipped
+    #[arg(long, requires = "listen")]
+    pub udp: bool,
+
+    /// Ack every frame that decodes and nack every one that doesn't, for a
+    /// sender sending with `--retries` to send it again
+    #[arg(long)]
+    pub reliable: bool,
+
+    /// How many frames ahead of a missing one to wait for it, before giving
+    /// it up as lost. Frames that come in out of order within it are put
+    /// back in order
+    #[arg(long, default_value_t = proto::SequenceTracker::<()>::DEFAULT_WINDOW)]
+    pub reorder_window: u32,
+
+    /// Warn when nothing, not even a heartbeat, has come from the sender for
+    /// this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub idle_timeout: Option<NonZeroU64>,
+
+    /// Exit, with code 2, rather than just warn once the sender's been idle
+    /// for `--idle-timeout`
+    #[arg(long, requires = "idle_timeout")]
+    pub exit_on_idle: bool,
+
+    /// Write the data of every message to this file, byte for byte, rather
+    /// than showing it in the log. `-` writes it to stdout, and the log to
+    /// stderr
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Wait for a sender to connect on a Unix socket at this path. A socket
+    /// left behind there by an earlier receiver is replaced
+    #[cfg(unix)]
+    #[arg(long, conflicts_with = "listen")]
+    pub socket: Option<std::path::PathBuf>,
+}
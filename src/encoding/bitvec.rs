@@ -1,65 +1,166 @@
-/// Our own BitVec struct storing bits in a Vec<u8> (packed 8 bits per byte)
-#[derive(Debug, Clone)]
+use std::ops::{Bound, Range, RangeBounds};
+
+use rand::Rng;
+
+#[cfg(feature = "simd")]
+use crate::encoding::simd;
+use crate::encoding::storage::Storage;
+
+/// Upper bound on the length of BitVecs generated by the `Arbitrary` impl.
+#[cfg(feature = "arbitrary")]
+pub const MAX_ARBITRARY_BITS: usize = 1 << 20;
+
+/// Our own BitVec struct storing bits packed 8 bits per byte.
+///
+/// Up to 128 bits are stored inline, longer BitVecs spill to a `Vec<u8>`.
+#[derive(Debug, Clone, Default)]
 pub struct BitVec {
-    pub(crate) data: Vec<u8>,
+    pub(crate) data: Storage,
     pub(crate) len: usize, // number of bits stored
 }
 
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum BitVecError {
+    #[error("Index out of bounds")]
     IndexOutOfBounds,
+    /// The value does not fit in the requested number of bits.
+    #[error("Value doesn't fit in the bits given for it")]
+    ValueTooWide,
+    /// The bit range is too long to be read as a u64.
+    #[error("Too many bits to read as a u64")]
+    TooManyBits,
+    /// Interleaving depth must be at least 1.
+    #[error("Interleaving depth must be at least 1")]
+    InvalidDepth,
+    /// The two BitVecs need to be the same length.
+    #[error("Bit vectors of different lengths")]
+    LengthMismatch,
+    /// More bits were asked for than the bytes backing them hold.
+    #[error("Not enough bytes for the bits asked for")]
+    NotEnoughBytes,
+    /// Hex has two digits to a byte, so an odd count leaves the last one
+    /// without a pair.
+    #[error("Unpaired hex digit at index {index}")]
+    UnpairedHexDigit { index: usize },
+    #[error("Invalid hex digit at index {index}")]
+    InvalidHexDigit { index: usize },
 }
 
 #[allow(unused)]
 impl BitVec {
     pub fn new() -> Self {
         Self {
-            data: Vec::new(),
+            data: Storage::new(),
             len: 0,
         }
     }
 
     pub fn into_inner(self) -> Vec<u8> {
-        self.data
+        self.data.into_vec()
     }
 
-    pub fn from_bytes(bytes: Vec<u8>, bit_length: usize) -> Self {
-        Self {
-            data: bytes,
+    /// Takes the first `bit_length` bits of `bytes`, MSB first, dropping the
+    /// rest. Errors if `bytes` holds fewer bits than that.
+    pub fn from_bytes(mut bytes: Vec<u8>, bit_length: usize) -> Result<Self, BitVecError> {
+        if bit_length > bytes.len().saturating_mul(8) {
+            return Err(BitVecError::NotEnoughBytes);
+        }
+        bytes.truncate(bit_length.div_ceil(8));
+        let mut bv = Self {
+            data: Storage::from_vec(bytes),
             len: bit_length,
+        };
+        bv.clear_tail();
+        Ok(bv)
+    }
+
+    /// Every bit of `bytes`, MSB first.
+    pub fn from_whole_bytes(bytes: Vec<u8>) -> Self {
+        let len = bytes.len() * 8;
+        Self {
+            data: Storage::from_vec(bytes),
+            len,
         }
     }
 
     /// Allocate enough capacity to store `bits` bits.
     pub fn with_capacity(bits: usize) -> Self {
-        let byte_capacity = (bits + 7) / 8;
+        let byte_capacity = bits.div_ceil(8);
         Self {
-            data: Vec::with_capacity(byte_capacity),
+            data: Storage::with_capacity(byte_capacity),
             len: 0,
         }
     }
 
     /// Creates a BitVec of a given length (in bits), initialized to 0.
     pub fn zeros(bits: usize) -> Self {
-        let byte_capacity = (bits + 7) / 8;
+        let byte_capacity = bits.div_ceil(8);
         Self {
-            data: vec![0; byte_capacity],
+            data: Storage::zeroed(byte_capacity),
             len: bits,
         }
     }
 
     /// Creates a BitVec of a given length (in bits), initialized to 1.
     pub fn ones(bits: usize) -> Self {
-        let byte_capacity = (bits + 7) / 8;
-        Self {
-            data: vec![255; byte_capacity], // 255 (b10) == 11111111 (b2)
-            len: bits,
+        let mut bv = Self::zeros(bits);
+        bv.data.fill(255); // 255 (b10) == 11111111 (b2)
+        bv.clear_tail();
+        bv
+    }
+
+    /// Creates a BitVec of a given length (in bits), where bit `i` is `f(i)`.
+    pub fn from_fn(bits: usize, mut f: impl FnMut(usize) -> bool) -> Self {
+        let mut bv = Self::zeros(bits);
+        for i in 0..bits {
+            if f(i) {
+                bv.data[i / 8] |= 1 << (7 - (i % 8));
+            }
+        }
+        bv
+    }
+
+    /// Creates a BitVec of a given length (in bits) filled with random bits.
+    pub fn random(bits: usize, rng: &mut impl Rng) -> Self {
+        let mut bv = Self::zeros(bits);
+        rng.fill(&mut bv.data[..]);
+        bv.clear_tail();
+        bv
+    }
+
+    /// Creates a `width`-bit BitVec holding `value`, most significant bit first.
+    /// Errors if `value` doesn't fit in `width` bits.
+    pub fn from_uint(value: u64, width: usize) -> Result<Self, BitVecError> {
+        if width > 64 || (width < 64 && value >> width != 0) {
+            return Err(BitVecError::ValueTooWide);
+        }
+        Ok(Self::from_fn(width, |i| {
+            (value >> (width - 1 - i)) & 1 == 1
+        }))
+    }
+
+    /// Reads the whole BitVec as an unsigned integer, most significant bit first.
+    /// Errors if it is longer than 64 bits.
+    pub fn to_uint(&self) -> Result<u64, BitVecError> {
+        self.to_uint_range(0..self.len)
+    }
+
+    /// Reads the bits in `range` as an unsigned integer, most significant bit first.
+    pub fn to_uint_range(&self, range: Range<usize>) -> Result<u64, BitVecError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(BitVecError::IndexOutOfBounds);
+        }
+        if range.len() > 64 {
+            return Err(BitVecError::TooManyBits);
         }
+        Ok(range.fold(0u64, |acc, i| {
+            (acc << 1) | ((self.data[i / 8] >> (7 - (i % 8))) & 1) as u64
+        }))
     }
 
     /// Push a new bit onto the BitVec.
     pub fn push(&mut self, bit: bool) {
-        if self.len % 8 == 0 {
+        if self.len.is_multiple_of(8) {
             self.data.push(0);
         }
         let byte_index = self.len / 8;
@@ -70,6 +171,44 @@ impl BitVec {
         self.len += 1;
     }
 
+    /// Pushes all of `other`'s bits onto the end.
+    pub fn append(&mut self, other: &BitVec) {
+        for i in 0..other.len {
+            self.push(other.get(i).unwrap_or(false));
+        }
+    }
+
+    /// XORs `other` into `self` a byte at a time (or, with the `simd` feature,
+    /// 32 bytes at a time for long BitVecs). Both need the same length.
+    pub fn xor_assign(&mut self, other: &BitVec) -> Result<(), BitVecError> {
+        if self.len != other.len {
+            return Err(BitVecError::LengthMismatch);
+        }
+        let bytes = self.len.div_ceil(8);
+        #[cfg(feature = "simd")]
+        let start = if self.len >= simd::THRESHOLD_BITS {
+            simd::xor(&mut self.data[..bytes], &other.data[..bytes])
+        } else {
+            0
+        };
+        #[cfg(not(feature = "simd"))]
+        let start = 0;
+        for (byte, other) in self.data[start..bytes]
+            .iter_mut()
+            .zip(&other.data[start..bytes])
+        {
+            *byte ^= other;
+        }
+        self.clear_tail();
+        Ok(())
+    }
+
+    /// Removes all bits, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.data.truncate(0);
+        self.len = 0;
+    }
+
     /// Get the bit at the given index.
     /// Returns None if the index is out of bounds.
     pub fn get(&self, index: usize) -> Option<bool> {
@@ -81,6 +220,46 @@ impl BitVec {
         Some((self.data[byte_index] >> bit_index) & 1 == 1)
     }
 
+    /// The bytes `hex` spells out, two digits of either case to a byte.
+    /// Errors at the index of the first character that isn't a hex digit,
+    /// or at the last one if there's an odd number of them.
+    pub fn from_hex(hex: &str) -> Result<Self, BitVecError> {
+        let digits = hex
+            .chars()
+            .enumerate()
+            .map(|(index, c)| {
+                c.to_digit(16)
+                    .map(|digit| digit as u8)
+                    .ok_or(BitVecError::InvalidHexDigit { index })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !digits.len().is_multiple_of(2) {
+            return Err(BitVecError::UnpairedHexDigit {
+                index: digits.len() - 1,
+            });
+        }
+        let bytes = digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]);
+        Ok(Self::from_whole_bytes(bytes.collect()))
+    }
+
+    /// The bits in lowercase hex, two digits to a byte, the last one padded
+    /// with zeros.
+    pub fn to_hex(&self) -> String {
+        let Some((last, bytes)) = self.data[..self.len.div_ceil(8)].split_last() else {
+            return String::new();
+        };
+        // stray bits past `len` aren't part of the data
+        let last = match self.len % 8 {
+            0 => *last,
+            bits => last & 0xFF << (8 - bits),
+        };
+        bytes
+            .iter()
+            .chain([&last])
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
     /// Returns a vector of bits.
     pub fn to_vec(&self) -> Vec<bool> {
         let mut vec = Vec::with_capacity(self.len);
@@ -119,17 +298,249 @@ impl BitVec {
         Ok(())
     }
 
+    /// Shift the bit sequence `n` positions towards index 0, keeping the
+    /// length fixed. Vacated positions at the end are filled with zeros.
+    pub fn shift_left(&mut self, n: usize) {
+        if n >= self.len {
+            self.data.fill(0);
+            return;
+        }
+        // stray bits past `len` would otherwise be shifted into it
+        self.clear_tail();
+        let byte_shift = n / 8;
+        let bit_shift = n % 8;
+        let bytes = self.data.len();
+
+        for i in 0..bytes {
+            let src = i + byte_shift;
+            let mut byte = self.data.get(src).map_or(0, |b| b << bit_shift);
+            if bit_shift > 0 {
+                byte |= self.data.get(src + 1).map_or(0, |b| b >> (8 - bit_shift));
+            }
+            self.data[i] = byte;
+        }
+    }
+
+    /// Shift the bit sequence `n` positions away from index 0, keeping the
+    /// length fixed. Vacated positions at the start are filled with zeros.
+    pub fn shift_right(&mut self, n: usize) {
+        if n >= self.len {
+            self.data.fill(0);
+            return;
+        }
+        let byte_shift = n / 8;
+        let bit_shift = n % 8;
+
+        for i in (0..self.data.len()).rev() {
+            let byte = match i.checked_sub(byte_shift) {
+                Some(src) => {
+                    let mut byte = self.data[src] >> bit_shift;
+                    if bit_shift > 0 && src > 0 {
+                        byte |= self.data[src - 1] << (8 - bit_shift);
+                    }
+                    byte
+                }
+                None => 0,
+            };
+            self.data[i] = byte;
+        }
+
+        // bits shifted past `len` must not linger in the last byte
+        self.clear_tail();
+    }
+
+    /// Returns a copy of the bits in `range`.
+    pub fn slice(&self, range: Range<usize>) -> Result<Self, BitVecError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(BitVecError::IndexOutOfBounds);
+        }
+        Ok(Self::from_fn(range.len(), |i| {
+            (self.data[(range.start + i) / 8] >> (7 - ((range.start + i) % 8))) & 1 == 1
+        }))
+    }
+
+    /// Returns an iterator over all overlapping windows of `size` bits, like
+    /// [`slice::windows`]. Yields nothing if `size` is larger than the BitVec.
+    ///
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> Windows<'_> {
+        assert!(size != 0, "window size must be non-zero");
+        Windows {
+            bitvec: self,
+            size,
+            position: 0,
+        }
+    }
+
+    /// Keeps only the bits for which `f(index, bit)` returns true, compacting
+    /// them in place.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, bool) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            let bit = (self.data[i / 8] >> (7 - (i % 8))) & 1 == 1;
+            if f(i, bit) {
+                if bit {
+                    self.data[kept / 8] |= 1 << (7 - (kept % 8));
+                } else {
+                    self.data[kept / 8] &= !(1 << (7 - (kept % 8)));
+                }
+                kept += 1;
+            }
+        }
+
+        self.len = kept;
+        self.data.truncate(kept.div_ceil(8));
+        self.clear_tail();
+    }
+
+    /// Returns a copy holding only the bits for which `f(index, bit)` returns true.
+    pub fn filtered(&self, f: impl FnMut(usize, bool) -> bool) -> Self {
+        let mut filtered = self.clone();
+        filtered.retain(f);
+        filtered
+    }
+
+    /// Concatenates all `parts` into a single BitVec, allocating only once.
+    pub fn concat_all(parts: &[BitVec]) -> Self {
+        let total: usize = parts.iter().map(|part| part.len).sum();
+        let mut out = Self::with_capacity(total);
+
+        for part in parts {
+            let offset = out.len % 8;
+            let end = out.len + part.len;
+            let bytes = &part.data[..part.len.div_ceil(8)];
+
+            if offset == 0 {
+                // byte aligned, the part's bytes can be copied as they are
+                out.data.extend_from_slice(bytes);
+            } else {
+                for (i, byte) in bytes.iter().enumerate() {
+                    let last = out.data.len() - 1;
+                    out.data[last] |= byte >> offset;
+
+                    let written = out.len + (8 * (i + 1)).min(part.len);
+                    if out.data.len() * 8 < written {
+                        out.data.push(byte << (8 - offset));
+                    }
+                }
+            }
+            out.len = end;
+            // stray bits past the part's end would show through the next one
+            out.clear_tail();
+        }
+
+        out
+    }
+
+    /// Replaces the bits in `range` with `replacement` (which may have a different
+    /// length), returning the removed bits.
+    ///
+    /// An empty `replacement` just removes the range, and an empty range just
+    /// inserts `replacement` at its start.
+    pub fn splice<R: RangeBounds<usize>>(
+        &mut self,
+        range: R,
+        replacement: &BitVec,
+    ) -> Result<BitVec, BitVecError> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        if start > end || end > self.len {
+            return Err(BitVecError::IndexOutOfBounds);
+        }
+
+        let removed = Self::from_fn(end - start, |i| self.get(start + i).unwrap_or(false));
+
+        let mut spliced = Self::with_capacity(self.len - removed.len + replacement.len);
+        for i in 0..start {
+            spliced.push(self.get(i).ok_or(BitVecError::IndexOutOfBounds)?);
+        }
+        for i in 0..replacement.len {
+            spliced.push(replacement.get(i).ok_or(BitVecError::IndexOutOfBounds)?);
+        }
+        for i in end..self.len {
+            spliced.push(self.get(i).ok_or(BitVecError::IndexOutOfBounds)?);
+        }
+
+        *self = spliced;
+        Ok(removed)
+    }
+
+    /// Block-interleaves the bits over `depth` rows.
+    ///
+    /// The bits are laid out row by row into a matrix of `depth` rows and
+    /// `len.div_ceil(depth)` columns, then read back column by column. When the
+    /// length isn't a multiple of `depth` the missing cells of the last row are
+    /// skipped rather than padded, so the output is exactly as long as the input.
+    /// Adjacent output bits always come from different rows.
+    pub fn interleave(&self, depth: usize) -> Result<Self, BitVecError> {
+        let cols = self.interleave_cols(depth)?;
+        let mut out = Self::with_capacity(self.len);
+        for col in 0..cols {
+            for row in 0..depth {
+                let index = row * cols + col;
+                if index < self.len {
+                    out.push(self.get(index).ok_or(BitVecError::IndexOutOfBounds)?);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Inverts [`BitVec::interleave`] with the same `depth`.
+    pub fn deinterleave(&self, depth: usize) -> Result<Self, BitVecError> {
+        let cols = self.interleave_cols(depth)?;
+        let mut out = Self::zeros(self.len);
+        let mut source = 0;
+        for col in 0..cols {
+            for row in 0..depth {
+                let index = row * cols + col;
+                if index < self.len {
+                    out.set(
+                        index,
+                        self.get(source).ok_or(BitVecError::IndexOutOfBounds)?,
+                    )?;
+                    source += 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn interleave_cols(&self, depth: usize) -> Result<usize, BitVecError> {
+        if depth == 0 {
+            return Err(BitVecError::InvalidDepth);
+        }
+        Ok(self.len.div_ceil(depth))
+    }
+
+    /// Zero out the unused bits of the last byte (the ones past `len`).
+    fn clear_tail(&mut self) {
+        if !self.len.is_multiple_of(8)
+            && let Some(last) = self.data.last_mut()
+        {
+            *last &= 0xFF << (8 - self.len % 8);
+        }
+    }
+
     /// Constructs a new bit-vector from a vector of bools.
     pub fn from_vec(vec: Vec<bool>) -> Self {
-        let byte_capacity = vec.len() / 8;
-        let mut bytes = Vec::with_capacity(byte_capacity);
+        let byte_capacity = vec.len().div_ceil(8);
+        let mut bytes = Storage::with_capacity(byte_capacity);
 
         // aggregate the bits into a byte by chunking the iterator
         for chunk in vec.chunks(8) {
             let mut byte = 0u8;
-            for (i, bit) in chunk.into_iter().enumerate() {
+            for (i, bit) in chunk.iter().enumerate() {
                 if *bit {
-                    byte |= 1 << 7 - i;
+                    byte |= 1 << (7 - i);
                 }
             }
             bytes.push(byte);
@@ -145,11 +556,117 @@ impl BitVec {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn true_len(&self) -> usize {
         self.data.len()
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for BitVec {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(0..=MAX_ARBITRARY_BITS)?;
+        // don't ask for more bytes than the input can still provide
+        let len = len.min(u.len() * 8);
+
+        Self::from_bytes(u.bytes(len.div_ceil(8))?.to_vec(), len)
+            .map_err(|_| arbitrary::Error::NotEnoughData)
+    }
+}
+
+/// Iterator over overlapping windows of a [`BitVec`], see [`BitVec::windows`].
+pub struct Windows<'a> {
+    bitvec: &'a BitVec,
+    size: usize,
+    position: usize,
+}
+
+impl Iterator for Windows<'_> {
+    type Item = BitVec;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self
+            .bitvec
+            .slice(self.position..self.position + self.size)
+            .ok()?;
+        self.position += 1;
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.bitvec.len() + 1).saturating_sub(self.position + self.size);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Windows<'_> {}
+
+/// The first `len` bits of a borrowed byte slice, MSB first, laid out like a
+/// [`BitVec`]'s. For reading bits where they already are, e.g. a payload in
+/// the buffer it arrived in.
+#[derive(Debug, Clone, Copy)]
+pub struct BitSlice<'a> {
+    bytes: &'a [u8],
+    len: usize,
+}
+
+impl<'a> BitSlice<'a> {
+    /// Takes the first `bit_length` bits of `bytes`, the same as
+    /// [`BitVec::from_bytes`] without the copy. Errors if `bytes` holds fewer
+    /// bits than that.
+    pub fn new(bytes: &'a [u8], bit_length: usize) -> Result<Self, BitVecError> {
+        if bit_length > bytes.len().saturating_mul(8) {
+            return Err(BitVecError::NotEnoughBytes);
+        }
+        Ok(Self {
+            bytes: &bytes[..bit_length.div_ceil(8)],
+            len: bit_length,
+        })
+    }
+
+    /// The bytes holding the bits, the last one only partly if `len` isn't a
+    /// multiple of 8.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some((self.bytes[index / 8] >> (7 - index % 8)) & 1 == 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copies the bits into a [`BitVec`] of their own.
+    pub fn to_bitvec(&self) -> BitVec {
+        let mut bitvec =
+            BitVec::from_bytes(self.bytes.to_vec(), self.len).expect("the bytes hold len bits");
+        bitvec.clear_tail();
+        bitvec
+    }
+}
+
+impl BitVec {
+    /// Borrows the bits as a [`BitSlice`].
+    pub fn as_bitslice(&self) -> BitSlice<'_> {
+        BitSlice {
+            bytes: &self.data[..self.len.div_ceil(8)],
+            len: self.len,
+        }
+    }
+}
+
 mod tests {
     #[allow(unused_imports)]
     use super::*;
@@ -172,6 +689,19 @@ mod tests {
         assert_eq!(bv.data.len(), 1);
     }
 
+    #[test]
+    fn test_append() {
+        let mut bv = BitVec::from_vec(vec![true, false, true]);
+        bv.append(&BitVec::ones(7));
+        bv.append(&BitVec::new());
+        assert_eq!(bv.len(), 10);
+        assert_eq!(bv.data.len(), 2);
+        assert_eq!(
+            bv.to_vec(),
+            [true, false, true, true, true, true, true, true, true, true]
+        );
+    }
+
     #[test]
     fn test_get() {
         let mut bv = BitVec::new();
@@ -273,6 +803,429 @@ mod tests {
         (0..10).for_each(|i| {
             assert_eq!(bv.get(i), Some(true));
         });
+
+        // nothing set past the end for a push to pick up
+        let mut bv = BitVec::ones(5);
+        assert_eq!(bv.data[..], [0b1111_1000]);
+        bv.push(false);
+        assert_eq!(bv.to_vec(), [true, true, true, true, true, false]);
+
+        assert_eq!(BitVec::ones(129).into_inner(), {
+            let mut bytes = vec![0xFF; 16];
+            bytes.push(0x80);
+            bytes
+        });
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let bv = BitVec::from_fn(10, |i| i % 2 == 0);
+        let expected = BitVec::from_vec(vec![
+            true, false, true, false, true, false, true, false, true, false,
+        ]);
+
+        assert_eq!(bv.len(), 10);
+        assert_eq!(bv.data.len(), 2);
+        assert_eq!(bv.to_vec(), expected.to_vec());
+        assert_eq!(bv.into_inner(), expected.into_inner());
+
+        assert!(BitVec::from_fn(0, |_| true).is_empty());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = rand::rng();
+        for bits in [0, 1, 7, 8, 13, 64] {
+            let bv = BitVec::random(bits, &mut rng);
+            assert_eq!(bv.len(), bits);
+            assert_eq!(bv.data.len(), bits.div_ceil(8));
+
+            // bits past `len` must stay zeroed
+            if !bits.is_multiple_of(8) {
+                let last = bv.data.last().unwrap();
+                assert_eq!(last & (0xFF >> (bits % 8)), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_left() {
+        let mut bv = BitVec::from_fn(16, |i| i == 0 || i == 9 || i == 15);
+
+        bv.shift_left(1);
+        assert_eq!(
+            bv.to_vec(),
+            (0..16).map(|i| i == 8 || i == 14).collect::<Vec<_>>()
+        );
+
+        bv.shift_left(8);
+        assert_eq!(
+            bv.to_vec(),
+            (0..16).map(|i| i == 0 || i == 6).collect::<Vec<_>>()
+        );
+
+        let mut bv = BitVec::from_fn(20, |i| i >= 9);
+        bv.shift_left(9);
+        assert_eq!(bv.to_vec(), (0..20).map(|i| i < 11).collect::<Vec<_>>());
+
+        bv.shift_left(20);
+        assert_eq!(bv.to_vec(), vec![false; 20]);
+
+        // set bits stored past `len` don't come in at the end
+        let mut bv = BitVec::from_bytes(vec![0b1000_1111], 4).unwrap();
+        bv.shift_left(1);
+        assert_eq!(bv.to_vec(), vec![false; 4]);
+    }
+
+    #[test]
+    fn test_shift_right() {
+        let mut bv = BitVec::from_fn(16, |i| i == 0 || i == 7 || i == 15);
+
+        bv.shift_right(1);
+        assert_eq!(
+            bv.to_vec(),
+            (0..16).map(|i| i == 1 || i == 8).collect::<Vec<_>>()
+        );
+
+        bv.shift_right(8);
+        assert_eq!(bv.to_vec(), (0..16).map(|i| i == 9).collect::<Vec<_>>());
+
+        let mut bv = BitVec::ones(13);
+        bv.shift_right(9);
+        assert_eq!(bv.to_vec(), (0..13).map(|i| i >= 9).collect::<Vec<_>>());
+        // the bits pushed past `len` must not survive in the storage
+        assert_eq!(bv.data, vec![0b0000_0000, 0b0111_1000]);
+
+        bv.shift_right(100);
+        assert_eq!(bv.to_vec(), vec![false; 13]);
+    }
+
+    #[test]
+    fn test_shift_against_model() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let len = rng.random_range(1..100);
+            let n = rng.random_range(0..len + 10);
+            let bv = BitVec::random(len, &mut rng);
+            let model = bv.to_vec();
+
+            let mut left = bv.clone();
+            left.shift_left(n);
+            let expected: Vec<bool> = (0..len)
+                .map(|i| model.get(i + n).copied().unwrap_or(false))
+                .collect();
+            assert_eq!(left.to_vec(), expected);
+
+            let mut right = bv.clone();
+            right.shift_right(n);
+            let expected: Vec<bool> = (0..len).map(|i| i >= n && model[i - n]).collect();
+            assert_eq!(right.to_vec(), expected);
+            assert_eq!(right.data, BitVec::from_vec(expected).into_inner());
+        }
+    }
+
+    #[test]
+    fn test_uint_round_trip() {
+        for width in [1, 7, 8, 33, 64] {
+            let max = if width == 64 {
+                u64::MAX
+            } else {
+                (1 << width) - 1
+            };
+
+            for value in [0, 1, max] {
+                let bv = BitVec::from_uint(value, width).unwrap();
+                assert_eq!(bv.len(), width);
+                assert_eq!(bv.to_uint().unwrap(), value);
+            }
+
+            if width < 64 {
+                assert!(BitVec::from_uint(max + 1, width).is_err());
+            }
+        }
+
+        // most significant bit first
+        assert_eq!(
+            BitVec::from_uint(0b1011, 4).unwrap().to_vec(),
+            vec![true, false, true, true]
+        );
+        assert!(BitVec::from_uint(0, 65).is_err());
+        assert!(BitVec::zeros(65).to_uint().is_err());
+    }
+
+    #[test]
+    fn test_to_uint_range() {
+        let mut bv = BitVec::from_uint(0b101, 3).unwrap();
+        for bit in BitVec::from_uint(0xABCD, 16).unwrap().to_vec() {
+            bv.push(bit);
+        }
+        bv.push(true);
+
+        assert_eq!(bv.to_uint_range(3..19).unwrap(), 0xABCD);
+        assert_eq!(bv.to_uint_range(0..3).unwrap(), 0b101);
+        assert_eq!(bv.to_uint_range(5..5).unwrap(), 0);
+        assert!(bv.to_uint_range(19..21).is_err());
+    }
+
+    #[test]
+    fn test_interleave() {
+        // 2 rows x 3 columns: [0 1 2] [3 4 5] -> 0 3 1 4 2 5
+        let bv = BitVec::from_fn(6, |i| i == 1 || i == 5);
+        let interleaved = bv.interleave(2).unwrap();
+        assert_eq!(
+            interleaved.to_vec(),
+            vec![false, false, true, false, false, true]
+        );
+
+        assert_eq!(bv.interleave(1).unwrap().to_vec(), bv.to_vec());
+        assert!(bv.interleave(0).is_err());
+        assert!(bv.deinterleave(0).is_err());
+        assert!(BitVec::new().interleave(3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_interleave_round_trip() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let len = rng.random_range(0..200);
+            let depth = rng.random_range(1..20);
+            let bv = BitVec::random(len, &mut rng);
+
+            let interleaved = bv.interleave(depth).unwrap();
+            assert_eq!(interleaved.len(), len);
+
+            let restored = interleaved.deinterleave(depth).unwrap();
+            assert_eq!(restored.to_vec(), bv.to_vec());
+            assert_eq!(restored.into_inner(), bv.into_inner());
+        }
+    }
+
+    #[test]
+    fn test_interleave_spreads_bursts() {
+        // three 7-bit blocks, hit by a 3-bit burst on the wire
+        let mut interleaved = BitVec::zeros(21).interleave(3).unwrap();
+        for i in 9..12 {
+            interleaved.toggle(i).unwrap();
+        }
+
+        let restored = interleaved.deinterleave(3).unwrap().to_vec();
+        for block in restored.chunks(7) {
+            assert_eq!(block.iter().filter(|&&bit| bit).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        let bv = BitVec::from_uint(0b10_1101_1001, 10).unwrap();
+        assert_eq!(bv.slice(2..9).unwrap().to_uint().unwrap(), 0b110_1100);
+        assert!(bv.slice(4..4).unwrap().is_empty());
+        assert!(bv.slice(8..11).is_err());
+    }
+
+    #[test]
+    fn test_windows() {
+        let bv = BitVec::random(10, &mut rand::rng());
+        let windows = bv.windows(4);
+        assert_eq!(windows.len(), 7);
+
+        for (i, window) in windows.enumerate() {
+            assert_eq!(window.to_vec(), bv.to_vec()[i..i + 4]);
+        }
+
+        // mirrors `slice::windows`
+        assert_eq!(bv.windows(10).count(), 1);
+        assert_eq!(bv.windows(11).len(), 0);
+        assert_eq!(bv.windows(11).next().map(|w| w.len()), None);
+        assert_eq!(bv.windows(1).len(), 10);
+        assert_eq!(BitVec::new().windows(1).len(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_windows_zero_size() {
+        BitVec::ones(3).windows(0);
+    }
+
+    #[test]
+    fn test_xor_assign() {
+        let mut rng = rand::rng();
+        for len in [0, 1, 7, 8, 9, 130, 200] {
+            let a = BitVec::random(len, &mut rng);
+            let b = BitVec::random(len, &mut rng);
+            let mut xored = a.clone();
+            xored.xor_assign(&b).unwrap();
+
+            let expected: Vec<bool> = (0..len).map(|i| a.get(i) != b.get(i)).collect();
+            assert_eq!(xored.to_vec(), expected);
+        }
+
+        assert!(BitVec::zeros(3).xor_assign(&BitVec::zeros(4)).is_err());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut bv = BitVec::ones(300);
+        bv.clear();
+        assert!(bv.is_empty());
+        assert_eq!(bv.true_len(), 0);
+
+        bv.push(false);
+        bv.push(true);
+        assert_eq!(bv.to_vec(), vec![false, true]);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut bv = BitVec::from_fn(10, |i| i % 3 == 0);
+        bv.retain(|_, bit| bit);
+        assert_eq!(bv.len(), 4);
+        assert_eq!(bv.to_vec(), vec![true; 4]);
+
+        let mut bv = BitVec::ones(12);
+        bv.retain(|i, _| i < 3);
+        assert_eq!(bv.len(), 3);
+        assert_eq!(bv.data, vec![0b1110_0000]);
+
+        let bv = BitVec::ones(5);
+        assert!(bv.filtered(|_, _| false).is_empty());
+        assert_eq!(bv.len(), 5);
+    }
+
+    #[test]
+    fn test_retain_matches_data_extraction_loop() {
+        let mut rng = rand::rng();
+        for len in [3, 7, 12, 15, 31, 63, 100, 1000] {
+            let codeword = BitVec::random(len, &mut rng);
+
+            let mut expected = BitVec::new();
+            for i in 0..codeword.len() {
+                if !((i + 1).is_power_of_two()) {
+                    expected.push(codeword.get(i).unwrap());
+                }
+            }
+
+            let data = codeword.filtered(|i, _| !(i + 1).is_power_of_two());
+            assert_eq!(data.len(), expected.len());
+            assert_eq!(data.into_inner(), expected.into_inner());
+        }
+    }
+
+    #[test]
+    fn test_concat_all() {
+        let mut rng = rand::rng();
+        let parts: Vec<BitVec> = [5, 8, 13, 0, 21]
+            .into_iter()
+            .map(|len| BitVec::random(len, &mut rng))
+            .collect();
+
+        let mut expected = BitVec::new();
+        for part in &parts {
+            for bit in part.to_vec() {
+                expected.push(bit);
+            }
+        }
+
+        let concat = BitVec::concat_all(&parts);
+        assert_eq!(concat.len(), 47);
+        assert_eq!(concat.to_vec(), expected.to_vec());
+        assert_eq!(concat.data, expected.data);
+
+        // small enough to never leave the inline storage
+        assert!(!concat.data.spilled());
+
+        // large parts fit into the single up-front allocation
+        let parts: Vec<BitVec> = [300, 5, 1000, 0, 77]
+            .into_iter()
+            .map(|len| BitVec::random(len, &mut rng))
+            .collect();
+        let concat = BitVec::concat_all(&parts);
+        assert_eq!(concat.len(), 1382);
+        assert_eq!(concat.data.capacity(), 1382usize.div_ceil(8));
+
+        assert!(BitVec::concat_all(&[]).is_empty());
+
+        // set bits stored past a part's `len` aren't part of it
+        let dirty = BitVec::from_bytes(vec![0xFF], 4).unwrap();
+        let concat = BitVec::concat_all(&[dirty, BitVec::from_vec(vec![false; 4])]);
+        assert_eq!(
+            concat.to_vec(),
+            BitVec::from_uint(0b1111_0000, 8).unwrap().to_vec()
+        );
+        let dirty = BitVec::from_bytes(vec![0xFF], 3).unwrap();
+        let concat = BitVec::concat_all(&[dirty.clone(), dirty]);
+        assert_eq!(concat.data, vec![0b1111_1100]);
+    }
+
+    #[test]
+    fn test_splice() {
+        let mut bv = BitVec::from_uint(0b1111_0000, 8).unwrap();
+
+        // same-length replacement, e.g. rewriting a field in place
+        let removed = bv
+            .splice(2..6, &BitVec::from_uint(0b0101, 4).unwrap())
+            .unwrap();
+        assert_eq!(removed.to_uint().unwrap(), 0b1100);
+        assert_eq!(bv.to_uint().unwrap(), 0b1101_0100);
+
+        // empty replacement removes
+        let removed = bv.splice(..3, &BitVec::new()).unwrap();
+        assert_eq!(removed.to_uint().unwrap(), 0b110);
+        assert_eq!(bv.to_uint().unwrap(), 0b10100);
+
+        // empty range inserts
+        let removed = bv.splice(5..5, &BitVec::ones(3)).unwrap();
+        assert!(removed.is_empty());
+        assert_eq!(bv.to_uint().unwrap(), 0b1010_0111);
+
+        assert!(bv.splice(6..=8, &BitVec::new()).is_err());
+        assert!(bv.splice(9.., &BitVec::new()).is_err());
+    }
+
+    #[test]
+    fn test_splice_against_model() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let len = rng.random_range(0..64);
+            let start = rng.random_range(0..=len);
+            let end = rng.random_range(start..=len);
+            let mut bv = BitVec::random(len, &mut rng);
+            let replacement = BitVec::random(rng.random_range(0..32), &mut rng);
+
+            let mut model = bv.to_vec();
+            let expected_removed: Vec<bool> =
+                model.splice(start..end, replacement.to_vec()).collect();
+
+            let removed = bv.splice(start..end, &replacement).unwrap();
+            assert_eq!(removed.to_vec(), expected_removed);
+            assert_eq!(bv.to_vec(), model);
+            assert_eq!(bv.into_inner(), BitVec::from_vec(model).into_inner());
+        }
+    }
+
+    #[test]
+    fn test_inline_spill_boundary() {
+        let mut bv = BitVec::from_fn(128, |i| i % 3 == 0);
+        assert!(!bv.data.spilled());
+        assert_eq!(bv.data.len(), 16);
+
+        // the 129th bit needs a 17th byte and moves everything to the heap
+        bv.push(true);
+        assert!(bv.data.spilled());
+        assert_eq!(bv.len(), 129);
+        assert_eq!(bv.data.len(), 17);
+        for i in 0..128 {
+            assert_eq!(bv.get(i), Some(i % 3 == 0));
+        }
+        assert_eq!(bv.get(128), Some(true));
+
+        // shrinking back doesn't matter to the bits
+        bv.retain(|i, _| i < 8);
+        assert_eq!(bv.to_vec(), BitVec::from_fn(8, |i| i % 3 == 0).to_vec());
+
+        assert!(BitVec::zeros(129).data.spilled());
+        assert!(!BitVec::ones(128).data.spilled());
+        assert!(BitVec::ones(129).data.spilled());
+        assert_eq!(BitVec::ones(136).into_inner(), vec![0xFF; 17]);
     }
 
     #[test]
@@ -287,4 +1240,88 @@ mod tests {
             assert_eq!(bv.get(i), Some(i % 2 == 0));
         });
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let bv = BitVec::from_bytes(vec![0b1010_1111, 0b1100_0000], 10).unwrap();
+        assert_eq!(bv.len(), 10);
+        assert_eq!(bv.get(0), Some(true));
+        assert_eq!(bv.get(9), Some(true));
+        assert_eq!(bv.get(10), None);
+
+        assert!(BitVec::from_bytes(vec![0xFF], 8).is_ok());
+        assert!(matches!(
+            BitVec::from_bytes(vec![0xFF], 9),
+            Err(BitVecError::NotEnoughBytes)
+        ));
+        assert!(matches!(
+            BitVec::from_bytes(vec![], 1),
+            Err(BitVecError::NotEnoughBytes)
+        ));
+
+        // bits past `bit_length` are dropped, not kept around to show up later
+        let bv = BitVec::from_bytes(vec![0xFF, 0xFF], 4).unwrap();
+        assert_eq!(bv.clone().into_inner(), vec![0xF0]);
+        let concat = BitVec::concat_all(&[bv.clone(), BitVec::zeros(4)]);
+        assert_eq!(concat.into_inner(), vec![0xF0]);
+        let mut shifted = bv.clone();
+        shifted.shift_left(1);
+        assert_eq!(shifted.to_vec(), [true, true, true, false]);
+        let mut pushed = bv;
+        pushed.push(false);
+        assert_eq!(pushed.to_vec(), [true, true, true, true, false]);
+        assert_eq!(BitVec::from_whole_bytes(vec![0xAB, 0xCD]).len(), 16);
+    }
+
+    #[test]
+    fn test_hex() {
+        let bv = BitVec::from_hex("00fF10aB").unwrap();
+        assert_eq!(bv.into_inner(), vec![0x00, 0xff, 0x10, 0xab]);
+        assert_eq!(BitVec::from_hex("00fF10aB").unwrap().to_hex(), "00ff10ab");
+        assert!(BitVec::from_hex("").unwrap().is_empty());
+        // a partial byte comes out padded
+        assert_eq!(BitVec::from_vec(vec![true; 4]).to_hex(), "f0");
+        // without the bits stored past the end
+        let dirty = BitVec {
+            data: Storage::from_vec(vec![0xAB, 0xFF]),
+            len: 12,
+        };
+        assert_eq!(dirty.to_hex(), "abf0");
+
+        assert!(matches!(
+            BitVec::from_hex("abc"),
+            Err(BitVecError::UnpairedHexDigit { index: 2 })
+        ));
+        assert!(matches!(
+            BitVec::from_hex("00fg"),
+            Err(BitVecError::InvalidHexDigit { index: 3 })
+        ));
+        // a bad digit wins over an odd count
+        assert!(matches!(
+            BitVec::from_hex("é0f"),
+            Err(BitVecError::InvalidHexDigit { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_bitslice() {
+        let bytes = [0b1010_1111, 0b1100_0000, 0xFF];
+        let slice = BitSlice::new(&bytes, 10).unwrap();
+        assert_eq!(slice.len(), 10);
+        assert_eq!(slice.as_bytes(), &bytes[..2]);
+        assert_eq!(slice.get(9), Some(true));
+        assert_eq!(slice.get(10), None);
+        assert!(matches!(
+            BitSlice::new(&bytes, 25),
+            Err(BitVecError::NotEnoughBytes)
+        ));
+
+        // the same bits as the BitVec the bytes would make, tail cleared
+        let owned = slice.to_bitvec();
+        let bv = BitVec::from_bytes(bytes.to_vec(), 10).unwrap();
+        assert_eq!(owned.to_vec(), bv.to_vec());
+        assert_eq!(owned.into_inner(), vec![0b1010_1111, 0b1100_0000]);
+        assert_eq!(bv.as_bitslice().as_bytes(), &bytes[..2]);
+        assert!(BitSlice::new(&[], 0).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,371 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, Hamming, HammingCode, HammingCodeBase, HammingError},
+    prepared::PreparedHamming,
+    syndrome::SyndromeTableDecoder,
+    table::CodeTable,
+};
+
+/// Block-mode Hamming(2^r − 1, 2^r − 1 − r) code.
+///
+/// The data is split into blocks of `data_bits_per_block()` bits which are each
+/// encoded into their own `code_bits_per_block()`-bit codeword, so one error can
+/// be corrected in every block rather than one in the whole message.
+///
+/// Padding rule: if the data length isn't a multiple of the block size, the
+/// last block is padded with zeros. `decode` hands the padding back as-is, use
+/// [`BlockHamming::decode_with_len`] to strip it again.
+///
+/// `decode` reports every corrected bit, at most one per block, as
+/// [`DecodeStatus::CorrectedMany`].
+///
+/// Hamming(7,4) and Hamming(15,11) are encoded and decoded through precomputed
+/// lookup tables, larger blocks compute their parities bit by bit. Blocks of up
+/// to 63 bits are still decoded through a [`SyndromeTableDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHamming {
+    parity_bits: usize,
+}
+
+impl BlockHamming {
+    pub const MIN_PARITY_BITS: usize = 3;
+    pub const MAX_PARITY_BITS: usize = 32;
+
+    /// Creates a block codec with `parity_bits` parity bits per block, which must
+    /// be between [`Self::MIN_PARITY_BITS`] and [`Self::MAX_PARITY_BITS`].
+    pub fn new(parity_bits: usize) -> Result<Self, HammingError> {
+        if !(Self::MIN_PARITY_BITS..=Self::MAX_PARITY_BITS).contains(&parity_bits) {
+            return Err(HammingError::BlockSizeInvalid(parity_bits));
+        }
+        Ok(Self { parity_bits })
+    }
+
+    /// Hamming(7,4).
+    pub fn hamming74() -> Self {
+        Self { parity_bits: 3 }
+    }
+
+    /// Hamming(15,11).
+    pub fn hamming1511() -> Self {
+        Self { parity_bits: 4 }
+    }
+
+    /// Hamming(31,26).
+    pub fn hamming3126() -> Self {
+        Self { parity_bits: 5 }
+    }
+
+    pub fn parity_bits(&self) -> usize {
+        self.parity_bits
+    }
+
+    pub fn code_bits_per_block(&self) -> usize {
+        (1 << self.parity_bits) - 1
+    }
+
+    pub fn data_bits_per_block(&self) -> usize {
+        self.code_bits_per_block() - self.parity_bits
+    }
+
+    /// Decodes `codeword` and drops the padding, returning exactly `original_bits`
+    /// data bits.
+    pub fn decode_with_len(
+        &self,
+        codeword: &BitVec,
+        original_bits: usize,
+    ) -> Result<DecodeOutcome, HammingError> {
+        let DecodeOutcome { mut data, status } = self.decode(codeword)?;
+        if original_bits > data.len() || data.len() - original_bits >= self.data_bits_per_block() {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+        data.retain(|i, _| i < original_bits);
+
+        Ok(DecodeOutcome { data, status })
+    }
+
+    /// Encodes block by block through [`PreparedHamming`], bypassing the lookup
+    /// tables.
+    pub fn encode_generic(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let k = self.data_bits_per_block();
+        let codec = PreparedHamming::for_len(k);
+        let blocks = data.len().div_ceil(k);
+        let mut codewords = Vec::with_capacity(blocks);
+
+        for block in 0..blocks {
+            let start = block * k;
+            let chunk = BitVec::from_fn(k, |i| data.get(start + i).unwrap_or(false));
+            codewords.push(codec.encode(&chunk)?);
+        }
+
+        Ok(BitVec::concat_all(&codewords))
+    }
+
+    /// Decodes block by block through [`PreparedHamming`], bypassing the lookup
+    /// tables.
+    pub fn decode_generic(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let n = self.code_bits_per_block();
+        if !codeword.len().is_multiple_of(n) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+        let codec = PreparedHamming::for_len(self.data_bits_per_block());
+
+        let blocks = codeword.len() / n;
+        let mut decoded = Vec::with_capacity(blocks);
+        let mut corrected = Vec::new();
+
+        for block in 0..blocks {
+            let start = block * n;
+            let chunk = codeword
+                .slice(start..start + n)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+
+            let outcome = codec.decode(&chunk)?;
+            if let DecodeStatus::Corrected { codeword_pos, .. } = outcome.status {
+                corrected.push(start + codeword_pos);
+            }
+            decoded.push(outcome.data);
+        }
+
+        Ok(DecodeOutcome {
+            data: BitVec::concat_all(&decoded),
+            status: DecodeStatus::corrected_many(corrected),
+        })
+    }
+}
+
+impl HammingCode for BlockHamming {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        match CodeTable::for_parity_bits(self.parity_bits) {
+            Some(table) => Ok(table.encode(data)),
+            None => self.encode_generic(data),
+        }
+    }
+
+    /// Checks every block, stopping at the first inconsistent one.
+    fn verify(&self, codeword: &BitVec) -> Result<bool, HammingError> {
+        let n = self.code_bits_per_block();
+        if !codeword.len().is_multiple_of(n) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+
+        let table = CodeTable::for_parity_bits(self.parity_bits);
+        for start in (0..codeword.len()).step_by(n) {
+            let clean = match table {
+                Some(table) => {
+                    let value = codeword
+                        .to_uint_range(start..start + n)
+                        .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+                    table.syndrome(value as u32) == 0
+                }
+                None => Hamming.verify(
+                    &codeword
+                        .slice(start..start + n)
+                        .map_err(|_| HammingError::UnexpectedOutOfBounds)?,
+                )?,
+            };
+            if !clean {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        if !codeword.len().is_multiple_of(self.code_bits_per_block()) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+
+        match CodeTable::for_parity_bits(self.parity_bits) {
+            Some(table) => {
+                let (data, corrected) = table.decode(codeword);
+                Ok(DecodeOutcome {
+                    data,
+                    status: DecodeStatus::corrected_many(corrected),
+                })
+            }
+            None => match SyndromeTableDecoder::for_parity_bits(self.parity_bits) {
+                Some(decoder) => decoder.decode(codeword),
+                None => self.decode_generic(codeword),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codecs() -> [BlockHamming; 3] {
+        [
+            BlockHamming::hamming74(),
+            BlockHamming::hamming1511(),
+            BlockHamming::hamming3126(),
+        ]
+    }
+
+    #[test]
+    fn test_block_sizes() {
+        let sizes: Vec<_> = codecs()
+            .iter()
+            .map(|codec| (codec.code_bits_per_block(), codec.data_bits_per_block()))
+            .collect();
+        assert_eq!(sizes, vec![(7, 4), (15, 11), (31, 26)]);
+
+        assert_eq!(BlockHamming::new(4).unwrap(), BlockHamming::hamming1511());
+        assert!(BlockHamming::new(2).is_err());
+        assert!(BlockHamming::new(33).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = rand::rng();
+        for codec in codecs() {
+            for len in [0, 1, 3, 4, 5, 11, 13, 26, 64, 100] {
+                let data = BitVec::random(len, &mut rng);
+                let codeword = codec.encode(&data).unwrap();
+                assert_eq!(
+                    codeword.len(),
+                    len.div_ceil(codec.data_bits_per_block()) * codec.code_bits_per_block()
+                );
+
+                let outcome = codec.decode_with_len(&codeword, len).unwrap();
+                assert!(outcome.status.is_clean());
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn test_padding() {
+        let codec = BlockHamming::hamming74();
+        let data = BitVec::ones(5);
+        let codeword = codec.encode(&data).unwrap();
+
+        let padded = codec.decode(&codeword).unwrap().data;
+        assert_eq!(padded.to_vec(), (0..8).map(|i| i < 5).collect::<Vec<_>>());
+
+        // the original length has to agree with the number of blocks
+        assert!(codec.decode_with_len(&codeword, 3).is_err());
+        assert!(codec.decode_with_len(&codeword, 9).is_err());
+        assert!(codec.decode(&BitVec::zeros(8)).is_err());
+    }
+
+    #[test]
+    fn test_corrects_one_error_per_block() {
+        let mut rng = rand::rng();
+        for codec in codecs() {
+            let (n, k) = (codec.code_bits_per_block(), codec.data_bits_per_block());
+            let data = BitVec::random(k * 10, &mut rng);
+            let codeword = codec.encode(&data).unwrap();
+
+            for offset in 0..n {
+                let mut corrupted = codeword.clone();
+                let flips: Vec<usize> = (0..10)
+                    .map(|block| block * n + (offset + block) % n)
+                    .collect();
+                for &flip in &flips {
+                    corrupted.toggle(flip).unwrap();
+                }
+
+                let outcome = codec.decode_with_len(&corrupted, k * 10).unwrap();
+                assert_eq!(
+                    outcome.status,
+                    DecodeStatus::CorrectedMany {
+                        codeword_positions: flips
+                    }
+                );
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn test_hamming1511_reference_vector() {
+        let data = BitVec::from_uint(0b101_1001_1100, 11).unwrap();
+        let codeword = BlockHamming::hamming1511().encode(&data).unwrap();
+
+        // p1 p2 d1 p4 d2 d3 d4 p8 d5 .. d11
+        assert_eq!(codeword.to_uint().unwrap(), 0b001_0011_1001_1100);
+    }
+
+    #[test]
+    fn test_whole_message_codec_cant_do_the_same() {
+        let data = BitVec::random(40, &mut rand::rng());
+
+        let mut corrupted = Hamming.encode(&data).unwrap();
+        for block in 0..10 {
+            corrupted.toggle(block * 4 + 1).unwrap();
+        }
+        let decoded = Hamming.decode(&corrupted).unwrap().data;
+        assert_ne!(decoded.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_tables_match_generic_exhaustively() {
+        for codec in [BlockHamming::hamming74(), BlockHamming::hamming1511()] {
+            let (n, k) = (codec.code_bits_per_block(), codec.data_bits_per_block());
+
+            for value in 0..1u64 << k {
+                let data = BitVec::from_uint(value, k).unwrap();
+                let codeword = codec.encode(&data).unwrap();
+                assert_eq!(
+                    codeword.into_inner(),
+                    codec.encode_generic(&data).unwrap().into_inner()
+                );
+
+                let codeword = codec.encode_generic(&data).unwrap();
+                for flip in 0..n {
+                    let mut corrupted = codeword.clone();
+                    corrupted.toggle(flip).unwrap();
+
+                    let table = codec.decode(&corrupted).unwrap();
+                    let generic = codec.decode_generic(&corrupted).unwrap();
+                    assert_eq!(table.data.into_inner(), generic.data.into_inner());
+                    assert_eq!(table.status, generic.status);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tables_match_generic_on_ragged_lengths() {
+        let mut rng = rand::rng();
+        for codec in [BlockHamming::hamming74(), BlockHamming::hamming1511()] {
+            for len in [0, 1, 5, 12, 23, 100] {
+                let data = BitVec::random(len, &mut rng);
+                let table = codec.encode(&data).unwrap();
+                let generic = codec.encode_generic(&data).unwrap();
+                assert_eq!(table.len(), generic.len());
+                assert_eq!(table.into_inner(), generic.into_inner());
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut rng = rand::rng();
+        for codec in codecs() {
+            let data = BitVec::random(3 * codec.data_bits_per_block(), &mut rng);
+            let codeword = codec.encode(&data).unwrap();
+            assert!(codec.verify(&codeword).unwrap());
+
+            for i in 0..codeword.len() {
+                let mut corrupted = codeword.clone();
+                corrupted.toggle(i).unwrap();
+                assert!(!codec.verify(&corrupted).unwrap());
+            }
+
+            assert!(codec.verify(&BitVec::new()).unwrap());
+            assert!(codec.verify(&BitVec::zeros(1)).is_err());
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Simulated transmission channels that corrupt codewords on their way to the
+//! receiver.
+
+use std::ops::Range;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::encoding::bitvec::BitVec;
+
+/// Something bits are sent over, possibly corrupting them on the way.
+pub trait Channel {
+    fn transmit(&mut self, bits: &mut BitVec);
+}
+
+/// A perfect channel that never corrupts anything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdealChannel;
+
+impl Channel for IdealChannel {
+    fn transmit(&mut self, _bits: &mut BitVec) {}
+}
+
+/// Binary symmetric channel: every bit is flipped independently with the same
+/// probability.
+///
+/// With a seed the flipped positions are fully deterministic, which makes
+/// corruption reproducible in tests and simulations.
+#[derive(Debug, Clone)]
+pub struct BinarySymmetricChannel {
+    flip_probability: f64,
+    rng: StdRng,
+}
+
+impl BinarySymmetricChannel {
+    /// Creates a channel flipping each bit with `flip_probability`, clamped to
+    /// `0.0..=1.0` (NaN counts as 0). Without a seed the RNG is seeded from
+    /// the OS.
+    pub fn new(flip_probability: f64, seed: Option<u64>) -> Self {
+        Self {
+            flip_probability: clamp_probability(flip_probability),
+            rng: seeded_rng(seed),
+        }
+    }
+
+    pub fn flip_probability(&self) -> f64 {
+        self.flip_probability
+    }
+}
+
+impl Channel for BinarySymmetricChannel {
+    fn transmit(&mut self, bits: &mut BitVec) {
+        for i in 0..bits.len() {
+            if self.rng.random_bool(self.flip_probability) {
+                // `i` is always in bounds
+                let _ = bits.toggle(i);
+            }
+        }
+    }
+}
+
+/// Burst error channel: every now and then a contiguous run of bits is
+/// flipped at once, like interference would.
+///
+/// A burst starts at each bit with `burst_probability`, its length is drawn
+/// uniformly from `min_len..=max_len`, and the next burst can only start after
+/// it. Bursts running past the end of the buffer are cut off there.
+#[derive(Debug, Clone)]
+pub struct BurstChannel {
+    burst_probability: f64,
+    min_len: usize,
+    max_len: usize,
+    rng: StdRng,
+}
+
+impl BurstChannel {
+    /// Creates a burst channel. The probability is clamped like
+    /// [`BinarySymmetricChannel::new`]'s, `min_len` is at least 1 and
+    /// `max_len` at least `min_len`.
+    pub fn new(burst_probability: f64, min_len: usize, max_len: usize, seed: Option<u64>) -> Self {
+        let min_len = min_len.max(1);
+        Self {
+            burst_probability: clamp_probability(burst_probability),
+            min_len,
+            max_len: max_len.max(min_len),
+            rng: seeded_rng(seed),
+        }
+    }
+
+    pub fn burst_probability(&self) -> f64 {
+        self.burst_probability
+    }
+
+    /// Draws the bursts hitting a buffer of `len` bits.
+    fn bursts(&mut self, len: usize) -> Vec<Range<usize>> {
+        let mut bursts = Vec::new();
+        let mut i = 0;
+        while i < len {
+            if self.rng.random_bool(self.burst_probability) {
+                let burst_len = self.rng.random_range(self.min_len..=self.max_len);
+                let end = i.saturating_add(burst_len).min(len);
+                bursts.push(i..end);
+                i = end;
+            } else {
+                i += 1;
+            }
+        }
+        bursts
+    }
+}
+
+impl Channel for BurstChannel {
+    fn transmit(&mut self, bits: &mut BitVec) {
+        for burst in self.bursts(bits.len()) {
+            for i in burst {
+                // bursts are clipped to the buffer
+                let _ = bits.toggle(i);
+            }
+        }
+    }
+}
+
+fn clamp_probability(probability: f64) -> f64 {
+    if probability.is_nan() {
+        0.0
+    } else {
+        probability.clamp(0.0, 1.0)
+    }
+}
+
+fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{
+        block::BlockHamming,
+        hamming::HammingCode,
+        interleave::{Interleaved, Interleaver},
+    };
+
+    fn flipped(channel: &mut impl Channel, len: usize) -> Vec<usize> {
+        let mut bits = BitVec::zeros(len);
+        channel.transmit(&mut bits);
+        (0..len).filter(|&i| bits.get(i) == Some(true)).collect()
+    }
+
+    #[test]
+    fn test_ideal() {
+        let bits = BitVec::random(100, &mut rand::rng());
+        let mut received = bits.clone();
+        IdealChannel.transmit(&mut received);
+        assert_eq!(received.to_vec(), bits.to_vec());
+    }
+
+    #[test]
+    fn test_seeded_is_deterministic() {
+        let mut channel = BinarySymmetricChannel::new(0.05, Some(42));
+        let flips = flipped(&mut channel, 200);
+        assert_eq!(flips, vec![4, 9, 16, 43, 77, 153, 155, 159, 172, 190]);
+
+        let mut again = BinarySymmetricChannel::new(0.05, Some(42));
+        assert_eq!(flipped(&mut again, 200), flips);
+        // the channel keeps going where it left off
+        assert_ne!(flipped(&mut channel, 200), flips);
+    }
+
+    #[test]
+    fn test_flip_rate() {
+        let n = 200_000;
+        for p in [0.001, 0.01, 0.1, 0.5] {
+            let mut channel = BinarySymmetricChannel::new(p, Some(7));
+            let rate = flipped(&mut channel, n).len() as f64 / n as f64;
+            // well within 5 standard deviations
+            let sigma = (p * (1.0 - p) / n as f64).sqrt();
+            assert!((rate - p).abs() < 5.0 * sigma, "p = {}, rate = {}", p, rate);
+        }
+
+        assert!(flipped(&mut BinarySymmetricChannel::new(0.0, None), 1000).is_empty());
+        assert_eq!(
+            flipped(&mut BinarySymmetricChannel::new(1.0, None), 1000).len(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_probability_is_clamped() {
+        assert_eq!(
+            BinarySymmetricChannel::new(-1.0, None).flip_probability(),
+            0.0
+        );
+        assert_eq!(
+            BinarySymmetricChannel::new(2.0, None).flip_probability(),
+            1.0
+        );
+        assert_eq!(
+            BinarySymmetricChannel::new(f64::NAN, None).flip_probability(),
+            0.0
+        );
+    }
+
+    /// Runs of flipped bits after sending zeros.
+    fn runs(channel: &mut impl Channel, len: usize) -> Vec<Range<usize>> {
+        let flips = flipped(channel, len);
+        let mut runs: Vec<Range<usize>> = Vec::new();
+        for flip in flips {
+            match runs.last_mut() {
+                Some(run) if run.end == flip => run.end += 1,
+                _ => runs.push(flip..flip + 1),
+            }
+        }
+        runs
+    }
+
+    #[test]
+    fn test_burst_seeded() {
+        let expected = vec![
+            9..14,
+            49..52,
+            158..162,
+            172..174,
+            180..185,
+            196..201,
+            205..210,
+        ];
+
+        let mut channel = BurstChannel::new(0.02, 2, 5, Some(42));
+        assert_eq!(runs(&mut channel, 300), expected);
+
+        let mut again = BurstChannel::new(0.02, 2, 5, Some(42));
+        assert_eq!(again.bursts(300), expected);
+    }
+
+    #[test]
+    fn test_burst_lengths_and_clipping() {
+        let mut channel = BurstChannel::new(0.05, 3, 6, Some(1));
+        for _ in 0..100 {
+            let bursts = channel.bursts(50);
+            for (i, burst) in bursts.iter().enumerate() {
+                assert!(burst.end <= 50);
+                if burst.end < 50 {
+                    assert!((3..=6).contains(&burst.len()));
+                }
+                if let Some(next) = bursts.get(i + 1) {
+                    assert!(next.start >= burst.end);
+                }
+            }
+        }
+
+        // every burst starts right away and has to be cut off at the end
+        let mut channel = BurstChannel::new(1.0, 4, 4, Some(1));
+        assert_eq!(channel.bursts(10), vec![0..4, 4..8, 8..10]);
+        assert_eq!(flipped(&mut channel, 10).len(), 10);
+
+        let mut channel = BurstChannel::new(0.5, 0, 0, None);
+        assert!(channel.bursts(100).iter().all(|burst| burst.len() == 1));
+
+        // however long the burst, it stops at the end
+        let mut channel = BurstChannel::new(0.1, usize::MAX - 1, usize::MAX, Some(1));
+        let bursts = channel.bursts(100);
+        assert_eq!(bursts.len(), 1);
+        assert!(bursts[0].start > 0 && bursts[0].end == 100);
+    }
+
+    #[test]
+    fn test_interleaving_survives_bursts() {
+        let data = BitVec::random(256, &mut rand::rng());
+        let plain = BlockHamming::hamming74();
+        let interleaved = Interleaved::new(plain, Interleaver::new(8).unwrap());
+
+        let mut codeword = interleaved.encode(&data).unwrap();
+        BurstChannel::new(0.005, 2, 4, Some(3)).transmit(&mut codeword);
+        let outcome = interleaved.decode(&codeword).unwrap();
+        assert!(outcome.status.corrections() >= 2);
+        assert_eq!(outcome.data.to_vec(), data.to_vec());
+
+        let mut codeword = plain.encode(&data).unwrap();
+        BurstChannel::new(0.005, 2, 4, Some(3)).transmit(&mut codeword);
+        assert_ne!(
+            plain.decode(&codeword).unwrap().data.to_vec(),
+            data.to_vec()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! End-to-end integrity checks to use alongside the Hamming codes.
+//!
+//! Hamming can only correct a single error and may "correct" a multi-bit error
+//! into a different, valid looking codeword. A checksum over the data catches
+//! those cases.
+
+use crate::encoding::bitvec::BitVec;
+
+/// A checksum over a BitVec.
+///
+/// Whole bytes go through a lookup table, a trailing partial byte is fed in a
+/// bit at a time in BitVec order.
+pub trait Checksum {
+    fn compute(&self, data: &BitVec) -> u32;
+
+    fn verify(&self, data: &BitVec, expected: u32) -> bool {
+        self.compute(data) == expected
+    }
+}
+
+/// CRC-32 (IEEE 802.3): reflected polynomial 0xEDB88320, initial value and
+/// final xor 0xFFFFFFFF.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Crc32;
+
+/// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF, no
+/// reflection and no final xor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Crc16Ccitt;
+
+/// CRC-8/SMBUS: polynomial 0x07, initial value 0, no reflection and no final
+/// xor. Small enough to guard a header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Crc8;
+
+const CRC32_POLY: u32 = 0xEDB8_8320;
+const CRC16_POLY: u16 = 0x1021;
+const CRC8_POLY: u8 = 0x07;
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+static CRC16_TABLE: [u16; 256] = crc16_table();
+static CRC8_TABLE: [u8; 256] = crc8_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ CRC16_POLY
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn crc8_table() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ CRC8_POLY
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// The whole bytes of `data`, followed by the bits of the partial last byte.
+fn split_bits(data: &BitVec) -> (&[u8], impl Iterator<Item = bool> + '_) {
+    let whole = data.len() / 8;
+    let tail = (whole * 8..data.len()).filter_map(|i| data.get(i));
+    (&data.data[..whole], tail)
+}
+
+impl Checksum for Crc32 {
+    fn compute(&self, data: &BitVec) -> u32 {
+        let (bytes, tail) = split_bits(data);
+
+        let mut crc = bytes.iter().fold(0xFFFF_FFFF, |crc, &byte| {
+            (crc >> 8) ^ CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize]
+        });
+        for bit in tail {
+            crc ^= u32::from(bit);
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32_POLY
+            } else {
+                crc >> 1
+            };
+        }
+
+        crc ^ 0xFFFF_FFFF
+    }
+}
+
+impl Checksum for Crc16Ccitt {
+    fn compute(&self, data: &BitVec) -> u32 {
+        let (bytes, tail) = split_bits(data);
+
+        let mut crc = bytes.iter().fold(0xFFFFu16, |crc, &byte| {
+            (crc << 8) ^ CRC16_TABLE[((crc >> 8) ^ byte as u16) as usize]
+        });
+        for bit in tail {
+            crc ^= u16::from(bit) << 15;
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ CRC16_POLY
+            } else {
+                crc << 1
+            };
+        }
+
+        crc as u32
+    }
+}
+
+impl Checksum for Crc8 {
+    fn compute(&self, data: &BitVec) -> u32 {
+        let (bytes, tail) = split_bits(data);
+
+        let mut crc = bytes
+            .iter()
+            .fold(0u8, |crc, &byte| CRC8_TABLE[(crc ^ byte) as usize]);
+        for bit in tail {
+            crc ^= u8::from(bit) << 7;
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ CRC8_POLY
+            } else {
+                crc << 1
+            };
+        }
+
+        crc as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::hamming::{DecodeStatus, Hamming, HammingCode};
+
+    fn bytes(data: &[u8]) -> BitVec {
+        BitVec::from_whole_bytes(data.to_vec())
+    }
+
+    #[test]
+    fn test_known_answers() {
+        assert_eq!(Crc32.compute(&bytes(b"123456789")), 0xCBF4_3926);
+        assert_eq!(Crc32.compute(&bytes(b"")), 0);
+        assert_eq!(
+            Crc32.compute(&bytes(b"The quick brown fox jumps over the lazy dog")),
+            0x414F_A339
+        );
+
+        assert_eq!(Crc16Ccitt.compute(&bytes(b"123456789")), 0x29B1);
+        assert_eq!(Crc16Ccitt.compute(&bytes(b"")), 0xFFFF);
+        assert_eq!(Crc16Ccitt.compute(&bytes(b"A")), 0xB915);
+
+        assert_eq!(Crc8.compute(&bytes(b"123456789")), 0xF4);
+        assert_eq!(Crc8.compute(&bytes(b"")), 0);
+    }
+
+    #[test]
+    fn test_partial_bytes() {
+        // CRC-16 isn't reflected, so feeding every bit MSB first must agree
+        // with the table for the whole bytes
+        let reference = |bits: &BitVec| {
+            (0..bits.len()).fold(0xFFFFu16, |crc, i| {
+                let crc = crc ^ (u16::from(bits.get(i).unwrap()) << 15);
+                if crc & 0x8000 != 0 {
+                    (crc << 1) ^ CRC16_POLY
+                } else {
+                    crc << 1
+                }
+            }) as u32
+        };
+
+        let data = bytes(b"hamming");
+        let mut seen = std::collections::HashSet::new();
+        for len in 0..=data.len() {
+            let bits = data.slice(0..len).unwrap();
+            assert_eq!(Crc16Ccitt.compute(&bits), reference(&bits));
+            assert!(seen.insert(Crc32.compute(&bits)));
+        }
+
+        let checksum = Crc32.compute(&data);
+        assert!(Crc32.verify(&data, checksum));
+        assert!(!Crc32.verify(&data, checksum ^ 1));
+    }
+
+    #[test]
+    fn test_catches_hamming_miscorrection() {
+        let data = BitVec::random(64, &mut rand::rng());
+        let checksum = Crc32.compute(&data);
+        let mut codeword = Hamming.encode(&data).unwrap();
+
+        // positions 3, 5 and 7 xor to a syndrome of 1, so Hamming "corrects"
+        // the first parity bit and hands back three flipped data bits
+        for i in [2, 4, 6] {
+            codeword.toggle(i).unwrap();
+        }
+
+        let outcome = Hamming.decode(&codeword).unwrap();
+        assert_eq!(outcome.status, DecodeStatus::corrected_at(0));
+        assert_ne!(outcome.data.to_vec(), data.to_vec());
+        assert!(!Crc32.verify(&outcome.data, checksum));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingError},
+};
+
+/// Runs any codec over fixed-size chunks of the input.
+///
+/// The input is cut into chunks of `chunk_bits` bits, the last one padded with
+/// zeros, and every chunk is encoded on its own. Since every chunk encodes to
+/// the same number of bits, the encoded size only depends on the original
+/// length (see [`ChunkedCodec::encoded_len`]), and decoding takes that length
+/// back to strip the padding, or just how much padding there was (see
+/// [`ChunkedCodec::padding`]), as a frame header carries it.
+#[derive(Debug, Clone)]
+pub struct ChunkedCodec<C: HammingCode> {
+    inner: C,
+    chunk_bits: usize,
+    code_bits: usize,
+}
+
+impl<C: HammingCode> ChunkedCodec<C> {
+    /// Wraps `inner`, which has to encode `chunk_bits` bits at a time. Fails
+    /// with [`HammingError::DataBitsInvalid`] for empty chunks, or with
+    /// whatever `inner` fails with for a chunk that size.
+    pub fn new(inner: C, chunk_bits: usize) -> Result<Self, HammingError> {
+        if chunk_bits == 0 {
+            return Err(HammingError::DataBitsInvalid(chunk_bits));
+        }
+        let code_bits = inner.encode(&BitVec::zeros(chunk_bits))?.len();
+
+        Ok(Self {
+            inner,
+            chunk_bits,
+            code_bits,
+        })
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn chunk_bits(&self) -> usize {
+        self.chunk_bits
+    }
+
+    /// Encoded size of a single chunk.
+    pub fn chunk_code_bits(&self) -> usize {
+        self.code_bits
+    }
+
+    /// Number of bits `original_bits` of data encode to.
+    pub fn encoded_len(&self, original_bits: usize) -> usize {
+        original_bits.div_ceil(self.chunk_bits) * self.code_bits
+    }
+
+    /// Bits of padding `encode` puts after `original_bits` bits of data, always
+    /// less than a chunk.
+    pub fn padding(&self, original_bits: usize) -> usize {
+        original_bits.next_multiple_of(self.chunk_bits) - original_bits
+    }
+
+    pub fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let chunks = data.len().div_ceil(self.chunk_bits);
+        let mut codewords = Vec::with_capacity(chunks);
+
+        for chunk in 0..chunks {
+            let start = chunk * self.chunk_bits;
+            let chunk = BitVec::from_fn(self.chunk_bits, |i| data.get(start + i).unwrap_or(false));
+            codewords.push(self.inner.encode(&chunk)?);
+        }
+
+        Ok(BitVec::concat_all(&codewords))
+    }
+
+    /// Decodes `codeword` back into exactly `original_bits` data bits.
+    ///
+    /// Fails with [`HammingError::InvalidCodewordLength`] unless `codeword` is
+    /// exactly [`ChunkedCodec::encoded_len`] of `original_bits` long.
+    /// Corrected positions are reported within the whole codeword, and any
+    /// uncorrectable chunk makes the whole outcome uncorrectable.
+    pub fn decode(
+        &self,
+        codeword: &BitVec,
+        original_bits: usize,
+    ) -> Result<DecodeOutcome, HammingError> {
+        let expected = self.encoded_len(original_bits);
+        if codeword.len() != expected {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: expected,
+            });
+        }
+
+        let chunks = original_bits.div_ceil(self.chunk_bits);
+        let mut decoded = Vec::with_capacity(chunks);
+        let mut corrected = Vec::new();
+        let mut uncorrectable = false;
+
+        for chunk in 0..chunks {
+            let start = chunk * self.code_bits;
+            let outcome = self.inner.decode(
+                &codeword
+                    .slice(start..start + self.code_bits)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?,
+            )?;
+
+            match outcome.status {
+                DecodeStatus::Clean => {}
+                DecodeStatus::Corrected { codeword_pos, .. } => {
+                    corrected.push(start + codeword_pos)
+                }
+                DecodeStatus::CorrectedMany { codeword_positions } => {
+                    corrected.extend(codeword_positions.into_iter().map(|pos| start + pos))
+                }
+                DecodeStatus::Uncorrectable => uncorrectable = true,
+            }
+
+            // the inner codec may hand back padding of its own
+            let wanted = self.chunk_bits.min(original_bits - chunk * self.chunk_bits);
+            decoded.push(
+                outcome
+                    .data
+                    .slice(0..wanted)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?,
+            );
+        }
+
+        let status = if uncorrectable {
+            DecodeStatus::Uncorrectable
+        } else {
+            DecodeStatus::corrected_many(corrected)
+        };
+
+        Ok(DecodeOutcome {
+            data: BitVec::concat_all(&decoded),
+            status,
+        })
+    }
+
+    /// Decodes `codeword`, dropping the `padding` bits `encode` put after the
+    /// data, so the original length doesn't have to come along with it.
+    ///
+    /// Fails with [`HammingError::InvalidCodewordLength`] unless `codeword` is
+    /// whole chunks, and with [`HammingError::InvalidPadding`] for a whole
+    /// chunk's worth of padding or more, or any without a chunk to pad.
+    pub fn decode_padded(
+        &self,
+        codeword: &BitVec,
+        padding: usize,
+    ) -> Result<DecodeOutcome, HammingError> {
+        let chunks = codeword.len() / self.code_bits;
+        if codeword.len() != chunks * self.code_bits {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: (chunks + 1) * self.code_bits,
+            });
+        }
+        let original_bits = (chunks * self.chunk_bits)
+            .checked_sub(padding)
+            .filter(|_| padding < self.chunk_bits)
+            .ok_or(HammingError::InvalidPadding {
+                padding,
+                chunk_bits: self.chunk_bits,
+            })?;
+        self.decode(codeword, original_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::encoding::{block::BlockHamming, golay::Golay24, hamming::Hamming};
+    use crate::testing::bitvec_strategy;
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(data in bitvec_strategy(600), chunk_bits in 1usize..100) {
+            let codec = ChunkedCodec::new(Hamming, chunk_bits).unwrap();
+            let codeword = codec.encode(&data).unwrap();
+            prop_assert_eq!(codeword.len(), codec.encoded_len(data.len()));
+
+            let outcome = codec.decode(&codeword, data.len()).unwrap();
+            prop_assert_eq!(outcome.status, DecodeStatus::Clean);
+            prop_assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+
+        #[test]
+        fn prop_round_trip_padding_inner(data in bitvec_strategy(300), chunk_bits in 1usize..40) {
+            // Golay pads each chunk up to 12 bit blocks on top
+            let codec = ChunkedCodec::new(Golay24, chunk_bits).unwrap();
+            let codeword = codec.encode(&data).unwrap();
+            let outcome = codec.decode(&codeword, data.len()).unwrap();
+            prop_assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_empty_and_exact() {
+        let codec = ChunkedCodec::new(Hamming, 11).unwrap();
+        assert_eq!(codec.chunk_code_bits(), 15);
+
+        let codeword = codec.encode(&BitVec::new()).unwrap();
+        assert!(codeword.is_empty());
+        let outcome = codec.decode(&codeword, 0).unwrap();
+        assert!(outcome.data.is_empty());
+        assert!(outcome.status.is_clean());
+
+        let data = BitVec::random(11, &mut rand::rng());
+        let codeword = codec.encode(&data).unwrap();
+        assert_eq!(codeword.to_vec(), Hamming.encode(&data).unwrap().to_vec());
+        assert_eq!(
+            codec.decode(&codeword, 11).unwrap().data.to_vec(),
+            data.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_corrections_across_chunks() {
+        let codec = ChunkedCodec::new(BlockHamming::hamming74(), 8).unwrap();
+        assert_eq!(codec.chunk_code_bits(), 14);
+        let data = BitVec::random(20, &mut rand::rng());
+        let mut codeword = codec.encode(&data).unwrap();
+        assert_eq!(codeword.len(), 42);
+
+        codeword.toggle(3).unwrap();
+        codeword.toggle(30).unwrap();
+        let outcome = codec.decode(&codeword, 20).unwrap();
+        assert_eq!(outcome.data.to_vec(), data.to_vec());
+        assert_eq!(outcome.status, DecodeStatus::corrected_many(vec![3, 30]));
+    }
+
+    #[test]
+    fn test_inconsistent_lengths() {
+        assert_eq!(
+            ChunkedCodec::new(Hamming, 0).err(),
+            Some(HammingError::DataBitsInvalid(0))
+        );
+
+        let codec = ChunkedCodec::new(Hamming, 11).unwrap();
+        let codeword = codec.encode(&BitVec::zeros(20)).unwrap();
+        assert_eq!(codeword.len(), 30);
+        // 20 bits fit in two chunks, so does anything from 12 to 22
+        assert!(codec.decode(&codeword, 12).is_ok());
+        for original_bits in [0, 11, 23, 40] {
+            assert_eq!(
+                codec.decode(&codeword, original_bits).unwrap_err(),
+                HammingError::InvalidCodewordLength {
+                    got: 30,
+                    expected_for_r: codec.encoded_len(original_bits),
+                }
+            );
+        }
+    }
+    #[test]
+    fn test_padded() {
+        let codec = ChunkedCodec::new(Hamming, 11).unwrap();
+        for len in [1, 10, 11, 12, 33, 40] {
+            let data = BitVec::random(len, &mut rand::rng());
+            let codeword = codec.encode(&data).unwrap();
+            let outcome = codec.decode_padded(&codeword, codec.padding(len)).unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+
+        let codeword = codec.encode(&BitVec::zeros(20)).unwrap();
+        for (codeword, padding) in [(&codeword, 11), (&BitVec::new(), 1)] {
+            assert_eq!(
+                codec.decode_padded(codeword, padding).unwrap_err(),
+                HammingError::InvalidPadding {
+                    padding,
+                    chunk_bits: 11,
+                }
+            );
+        }
+        assert!(
+            codec
+                .decode_padded(&BitVec::new(), 0)
+                .unwrap()
+                .data
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use crate::encoding::bitvec::BitVec;
+
+/// Describes a set of bit flips to apply to a codeword.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Corruption {
+    /// Indices of the bits to flip, taken modulo the codeword length.
+    pub flips: Vec<usize>,
+}
+
+impl Corruption {
+    pub fn new(flips: Vec<usize>) -> Self {
+        Self { flips }
+    }
+
+    /// Flips every described bit in `bits`. Flipping the same index twice
+    /// cancels out, and nothing happens to an empty BitVec.
+    pub fn apply(&self, bits: &mut BitVec) {
+        if bits.is_empty() {
+            return;
+        }
+        for flip in &self.flips {
+            // the index is always in bounds after the modulo
+            let _ = bits.toggle(flip % bits.len());
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Corruption {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // flipping more than a handful of bits isn't interesting for a SEC code
+        let count = u.int_in_range(0..=8)?;
+        let flips = (0..count)
+            .map(|_| u.arbitrary())
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(Self { flips })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut bv = BitVec::zeros(8);
+        Corruption::new(vec![1, 9, 3]).apply(&mut bv);
+
+        // 9 wraps around to 1, cancelling the first flip
+        assert_eq!(bv.to_vec(), (0..8).map(|i| i == 3).collect::<Vec<_>>());
+
+        let mut empty = BitVec::new();
+        Corruption::new(vec![0]).apply(&mut empty);
+        assert!(empty.is_empty());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let raw: Vec<u8> = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut u = Unstructured::new(&raw);
+        for _ in 0..16 {
+            let mut bv = BitVec::arbitrary(&mut u).unwrap();
+            assert!(bv.len() <= crate::encoding::bitvec::MAX_ARBITRARY_BITS);
+            assert_eq!(bv.data.len(), bv.len().div_ceil(8));
+            assert_tail_zeroed(&bv);
+
+            Corruption::arbitrary(&mut u).unwrap().apply(&mut bv);
+            assert_tail_zeroed(&bv);
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    fn assert_tail_zeroed(bv: &BitVec) {
+        if !bv.len().is_multiple_of(8) {
+            let last = bv.data.last().unwrap();
+            assert_eq!(last & (0xFF >> (bv.len() % 8)), 0);
+        }
+    }
+}
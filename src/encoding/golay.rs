@@ -0,0 +1,320 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingCodeBase, HammingError},
+};
+
+/// The B matrix of the extended Golay code's generator `G = [I | B]`, one
+/// 12-bit row per data bit, MSB first. B is symmetric and its own inverse.
+const B: [u32; 12] = [
+    0b1101_1100_0101,
+    0b1011_1000_1011,
+    0b0111_0001_0111,
+    0b1110_0010_1101,
+    0b1100_0101_1011,
+    0b1000_1011_0111,
+    0b0001_0110_1111,
+    0b0010_1101_1101,
+    0b0101_1011_1001,
+    0b1011_0111_0001,
+    0b0110_1110_0011,
+    0b1111_1111_1110,
+];
+
+const MASK: u32 = 0xFFF;
+
+/// Multiplies the 12-bit row vector `v` by B over GF(2).
+fn times_b(v: u32) -> u32 {
+    (0..12)
+        .filter(|i| v & (1 << (11 - i)) != 0)
+        .fold(0, |acc, i| acc ^ B[i])
+}
+
+/// Extended binary Golay (24,12) code.
+///
+/// The data is split into 12-bit blocks which are each encoded into 24 bits,
+/// the data block followed by its 12 check bits. Every block can correct up to
+/// three errors, and four errors in a block are always detected and reported
+/// as [`DecodeStatus::Uncorrectable`].
+///
+/// Padding works like [`BlockHamming`](crate::encoding::block::BlockHamming):
+/// the last block is padded with zeros, `decode` hands the padding back and
+/// [`Golay24::decode_with_len`] strips it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Golay24;
+
+impl Golay24 {
+    pub const DATA_BITS: usize = 12;
+    pub const CODE_BITS: usize = 24;
+
+    pub fn encode_block(&self, data: u32) -> u32 {
+        let data = data & MASK;
+        (data << 12) | times_b(data)
+    }
+
+    /// The 12-bit syndrome of a 24-bit codeword, 0 when it's clean.
+    pub fn syndrome(&self, codeword: u32) -> u32 {
+        times_b((codeword >> 12) & MASK) ^ (codeword & MASK)
+    }
+
+    /// The error pattern of weight at most 3 explaining `codeword`, if there
+    /// is one.
+    fn error_pattern(&self, codeword: u32) -> Option<u32> {
+        let s = self.syndrome(codeword);
+        if s.count_ones() <= 3 {
+            return Some(s);
+        }
+        for (i, row) in B.iter().enumerate() {
+            if (s ^ row).count_ones() <= 2 {
+                return Some((1 << (23 - i)) | (s ^ row));
+            }
+        }
+
+        // the same again with the syndrome of the data half
+        let sb = times_b(s);
+        if sb.count_ones() <= 3 {
+            return Some(sb << 12);
+        }
+        for (i, row) in B.iter().enumerate() {
+            if (sb ^ row).count_ones() <= 2 {
+                return Some(((sb ^ row) << 12) | (1 << (11 - i)));
+            }
+        }
+
+        None
+    }
+
+    /// Corrects and decodes one 24-bit codeword, returning the data and the
+    /// error pattern, or `None` if there were more errors than can be
+    /// corrected.
+    pub fn decode_block(&self, codeword: u32) -> Option<(u32, u32)> {
+        let error = self.error_pattern(codeword)?;
+        Some((((codeword ^ error) >> 12) & MASK, error))
+    }
+
+    /// Decodes `codeword` and drops the padding, returning exactly
+    /// `original_bits` data bits.
+    pub fn decode_with_len(
+        &self,
+        codeword: &BitVec,
+        original_bits: usize,
+    ) -> Result<DecodeOutcome, HammingError> {
+        let DecodeOutcome { mut data, status } = self.decode(codeword)?;
+        if original_bits > data.len() || data.len() - original_bits >= Self::DATA_BITS {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+        data.retain(|i, _| i < original_bits);
+
+        Ok(DecodeOutcome { data, status })
+    }
+
+    fn block(&self, codeword: &BitVec, start: usize) -> Result<u32, HammingError> {
+        codeword
+            .to_uint_range(start..start + Self::CODE_BITS)
+            .map(|value| value as u32)
+            .map_err(|_| HammingError::UnexpectedOutOfBounds)
+    }
+
+    fn check_len(&self, codeword: &BitVec) -> Result<(), HammingError> {
+        if !codeword.len().is_multiple_of(Self::CODE_BITS) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+        Ok(())
+    }
+}
+
+impl HammingCode for Golay24 {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let blocks = data.len().div_ceil(Self::DATA_BITS);
+        let mut out = BitVec::with_capacity(blocks * Self::CODE_BITS);
+
+        for block in 0..blocks {
+            let start = block * Self::DATA_BITS;
+            let end = (start + Self::DATA_BITS).min(data.len());
+            let value = data
+                .to_uint_range(start..end)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?
+                << (Self::DATA_BITS - (end - start));
+
+            let codeword = self.encode_block(value as u32);
+            for i in (0..Self::CODE_BITS).rev() {
+                out.push((codeword >> i) & 1 == 1);
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        self.check_len(codeword)?;
+
+        let blocks = codeword.len() / Self::CODE_BITS;
+        let mut data = BitVec::with_capacity(blocks * Self::DATA_BITS);
+        let mut corrected = Vec::new();
+        let mut uncorrectable = false;
+
+        for start in (0..codeword.len()).step_by(Self::CODE_BITS) {
+            let received = self.block(codeword, start)?;
+            let value = match self.decode_block(received) {
+                Some((value, error)) => {
+                    corrected.extend(
+                        (0..Self::CODE_BITS)
+                            .filter(|i| error & (1 << (Self::CODE_BITS - 1 - i)) != 0)
+                            .map(|i| start + i),
+                    );
+                    value
+                }
+                None => {
+                    // hand back the data bits as received
+                    uncorrectable = true;
+                    received >> 12
+                }
+            };
+
+            for i in (0..Self::DATA_BITS).rev() {
+                data.push((value >> i) & 1 == 1);
+            }
+        }
+
+        let status = if uncorrectable {
+            DecodeStatus::Uncorrectable
+        } else {
+            DecodeStatus::corrected_many(corrected)
+        };
+
+        Ok(DecodeOutcome { data, status })
+    }
+
+    /// The syndrome of the first block that has one, 0 if every block is
+    /// clean. Unlike Hamming this is not an error position.
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        self.check_len(codeword)?;
+
+        for start in (0..codeword.len()).step_by(Self::CODE_BITS) {
+            let syndrome = self.syndrome(self.block(codeword, start)?);
+            if syndrome != 0 {
+                return Ok(syndrome as usize);
+            }
+        }
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng, seq::index::sample};
+
+    use super::*;
+
+    fn flip_all(codeword: &BitVec, flips: &[usize]) -> BitVec {
+        let mut corrupted = codeword.clone();
+        for &flip in flips {
+            corrupted.toggle(flip).unwrap();
+        }
+        corrupted
+    }
+
+    #[test]
+    fn test_b_is_self_inverse() {
+        for (i, row) in B.iter().enumerate() {
+            assert_eq!(times_b(*row), 1 << (11 - i));
+            // symmetric
+            for (j, other) in B.iter().enumerate() {
+                assert_eq!(row >> (11 - j) & 1, other >> (11 - i) & 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimum_distance() {
+        let min_weight = (1..1u32 << 12)
+            .map(|data| Golay24.encode_block(data).count_ones())
+            .min();
+        assert_eq!(min_weight, Some(8));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = rand::rng();
+        for len in [0, 1, 11, 12, 13, 100] {
+            let data = BitVec::random(len, &mut rng);
+            let codeword = Golay24.encode(&data).unwrap();
+            assert_eq!(codeword.len(), len.div_ceil(12) * 24);
+            assert!(Golay24.verify(&codeword).unwrap());
+
+            let outcome = Golay24.decode_with_len(&codeword, len).unwrap();
+            assert!(outcome.status.is_clean());
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+
+        assert!(Golay24.decode(&BitVec::zeros(23)).is_err());
+    }
+
+    #[test]
+    fn test_corrects_up_to_three_errors() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let data = BitVec::random(24, &mut rng);
+        let codeword = Golay24.encode(&data).unwrap();
+
+        let mut patterns: Vec<Vec<usize>> = (0..24).map(|i| vec![i]).collect();
+        for i in 0..24 {
+            for j in i + 1..24 {
+                patterns.push(vec![i, j]);
+            }
+        }
+        for _ in 0..500 {
+            let mut flips = sample(&mut rng, 24, 3).into_vec();
+            flips.sort_unstable();
+            patterns.push(flips);
+        }
+
+        for flips in patterns {
+            // hit the second block so the positions are offset
+            let global: Vec<usize> = flips.iter().map(|flip| flip + 24).collect();
+            let corrupted = flip_all(&codeword, &global);
+            assert!(!Golay24.verify(&corrupted).unwrap());
+
+            let outcome = Golay24.decode(&corrupted).unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+            assert_eq!(
+                outcome.status,
+                DecodeStatus::CorrectedMany {
+                    codeword_positions: global
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_flags_four_errors() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let data = BitVec::random(12, &mut rng);
+        let codeword = Golay24.encode(&data).unwrap();
+
+        for _ in 0..500 {
+            let flips = sample(&mut rng, 24, 4).into_vec();
+            let outcome = Golay24.decode(&flip_all(&codeword, &flips)).unwrap();
+            assert!(outcome.status.is_uncorrectable(), "{:?}", flips);
+        }
+
+        // one corrected block and one uncorrectable one
+        let data = BitVec::random(24, &mut rng);
+        let codeword = Golay24.encode(&data).unwrap();
+        let flip = rng.random_range(0..24);
+        let corrupted = flip_all(&codeword, &[flip, 24, 25, 26, 27]);
+        assert!(
+            Golay24
+                .decode(&corrupted)
+                .unwrap()
+                .status
+                .is_uncorrectable()
+        );
+    }
+}
@@ -1,31 +1,229 @@
-use std::fmt::{Display, Formatter};
+use crate::encoding::bitvec::{BitSlice, BitVec};
+use crate::encoding::layout::CodewordLayout;
+use crate::encoding::mapping;
+#[cfg(feature = "simd")]
+use crate::encoding::simd;
+use crate::encoding::source::BitSource;
 
-use crate::encoding::bitvec::BitVec;
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum HammingError {
+    #[error("Unexpected out of bounds error")]
     UnexpectedOutOfBounds,
+    /// A codeword whose length doesn't match the one its parity bits call for.
+    #[error("Invalid codeword length: got {got} bits, expected {expected_for_r}")]
+    InvalidCodewordLength { got: usize, expected_for_r: usize },
+    /// A codeword with more errors than the code can correct.
+    #[error("Uncorrectable codeword (syndrome {syndrome})")]
+    Uncorrectable { syndrome: usize },
+    /// Nothing to encode or decode where something was required.
+    #[error("Empty input")]
+    EmptyInput,
+    /// Unsupported number of parity bits per block.
+    #[error("Invalid block size: {0} parity bits")]
+    BlockSizeInvalid(usize),
+    /// Unsupported number of data bits for a shortened code.
+    #[error("Invalid number of data bits: {0}")]
+    DataBitsInvalid(usize),
+    /// Repetition factor that isn't odd and at least 3.
+    #[error("Invalid repetition factor: {0}")]
+    RepetitionFactorInvalid(usize),
+    /// More erased positions than the code can resolve.
+    #[error("Too many erasures: {erasures} (at most {max})")]
+    TooManyErasures { erasures: usize, max: usize },
+    /// An erased position outside of the codeword.
+    #[error("Erased position {position} is out of range for a {len} bit codeword")]
+    ErasureOutOfRange { position: usize, len: usize },
+    /// More padding than a chunk of data gets, or padding with no chunk.
+    #[error("Invalid padding: {padding} bits for {chunk_bits} bit chunks")]
+    InvalidPadding { padding: usize, chunk_bits: usize },
+}
+
+/// What happened while decoding a codeword.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeStatus {
+    /// The codeword had no errors.
+    Clean,
+    /// A single bit error was corrected.
+    Corrected {
+        /// 0-based index of the flipped bit in the codeword.
+        codeword_pos: usize,
+        /// Whether the flipped bit was a parity bit rather than data.
+        was_parity: bool,
+        /// 0-based index of the flipped bit in the decoded data, if it was a data bit.
+        data_pos: Option<usize>,
+    },
+    /// Several bit errors were corrected, e.g. one in each block of a block code.
+    CorrectedMany {
+        /// 0-based indices of the flipped bits in the codeword.
+        codeword_positions: Vec<usize>,
+    },
+    /// Errors were detected that the code can't correct, so the data is not
+    /// to be trusted.
+    Uncorrectable,
 }
-impl Display for HammingError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+
+impl DecodeStatus {
+    /// Status for a corrected error at `codeword_pos` of a Hamming codeword,
+    /// working out whether it hit a parity or a data bit.
+    pub fn corrected_at(codeword_pos: usize) -> Self {
+        let data_pos = mapping::codeword_to_data(codeword_pos);
+        Self::Corrected {
+            codeword_pos,
+            was_parity: data_pos.is_none(),
+            data_pos,
+        }
+    }
+
+    /// Status for a set of corrected positions, picking the right variant for
+    /// how many there are.
+    pub fn corrected_many(codeword_positions: Vec<usize>) -> Self {
+        match codeword_positions.len() {
+            0 => Self::Clean,
+            _ => Self::CorrectedMany { codeword_positions },
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        matches!(self, Self::Clean)
+    }
+
+    pub fn is_uncorrectable(&self) -> bool {
+        matches!(self, Self::Uncorrectable)
+    }
+
+    /// Index in the decoded data of the single corrected bit, if it was a data
+    /// bit.
+    pub fn data_pos(&self) -> Option<usize> {
+        match self {
+            Self::Corrected { data_pos, .. } => *data_pos,
+            _ => None,
+        }
+    }
+
+    /// Number of bits that were corrected.
+    pub fn corrections(&self) -> usize {
         match self {
-            HammingError::UnexpectedOutOfBounds => write!(f, "Unexpected out of bounds error"),
+            Self::Clean | Self::Uncorrectable => 0,
+            Self::Corrected { .. } => 1,
+            Self::CorrectedMany { codeword_positions } => codeword_positions.len(),
         }
     }
 }
 
+/// The decoded data together with what happened while decoding it.
+#[derive(Debug, Clone)]
+pub struct DecodeOutcome {
+    pub data: BitVec,
+    pub status: DecodeStatus,
+}
+
+/// What [`Hamming::decode_in_place`] found, without the extracted data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeInfo {
+    pub status: DecodeStatus,
+    /// Number of data bits in the codeword, i.e. what [`Hamming::extract_data`] returns.
+    pub data_len: usize,
+}
+
 /// Trait defining the interface for Hamming code implementations
+///
+/// None of the methods panic on bad input: any [`BitVec`], whatever its
+/// length or contents, either decodes or gives an `Err`.
 pub trait HammingCode {
     fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError>;
-    fn decode(&self, codeword: &BitVec) -> Result<(BitVec, usize), HammingError>;
+
+    /// Like [`HammingCode::encode`], but for any [`BitSource`], e.g. a `[bool]`
+    /// or a byte slice. By default the bits are copied into a [`BitVec`] first,
+    /// codecs that can read them directly override this.
+    fn encode_from<S: BitSource + ?Sized>(&self, data: &S) -> Result<BitVec, HammingError>
+    where
+        Self: Sized,
+    {
+        self.encode(&data.to_bitvec())
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError>;
     fn calculate_parity(&self, codeword: &BitVec, parity_mask: usize)
     -> Result<bool, HammingError>;
+
+    /// Computes the syndrome of `codeword` without correcting anything: the
+    /// 1-based position of a single flipped bit, or 0 if the codeword is clean.
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        let mut syndrome = 0;
+        for parity_mask in (0..usize::BITS).map(|i| 1usize << i) {
+            if parity_mask > codeword.len() {
+                break;
+            }
+            if self.calculate_parity(codeword, parity_mask)? {
+                syndrome |= parity_mask;
+            }
+        }
+        Ok(syndrome)
+    }
+
+    /// Checks whether `codeword` is consistent, without correcting it or
+    /// extracting the data.
+    fn verify(&self, codeword: &BitVec) -> Result<bool, HammingError> {
+        Ok(self.compute_syndrome(codeword)? == 0)
+    }
 }
 
 /// Base implementation containing shared functionality
 pub struct HammingCodeBase;
 
 impl HammingCodeBase {
+    /// 0-based indices of the parity bits in a codeword of `total_len` bits.
+    pub fn parity_positions(total_len: usize) -> Vec<usize> {
+        (0..usize::BITS)
+            .map(|i| 1usize << i)
+            .take_while(|&position| position <= total_len)
+            .map(|position| position - 1)
+            .collect()
+    }
+
+    /// Generator matrix G for `k` data bits, one row of `n` bits per data bit,
+    /// so that encoding is the XOR of the rows of the set data bits.
+    ///
+    /// Row `i` has the `i`-th data bit set at its codeword position, plus the
+    /// parity bits at the powers of two covering that position.
+    pub fn generator_matrix(k: usize) -> Vec<BitVec> {
+        let n = k + Self::calculate_parity_count(k);
+        (1..=n)
+            .filter(|position| !position.is_power_of_two())
+            .map(|position| {
+                BitVec::from_fn(n, |i| {
+                    let p = i + 1;
+                    p == position || (p.is_power_of_two() && position & p != 0)
+                })
+            })
+            .collect()
+    }
+
+    /// Parity-check matrix H for an `n` bit codeword, one row per parity bit.
+    ///
+    /// Row `j` covers every position whose 1-based index has bit `j` set, so
+    /// the column for position `p` is `p` written in binary and H·cᵀ is the
+    /// syndrome.
+    pub fn parity_check_matrix(n: usize) -> Vec<BitVec> {
+        Self::parity_positions(n)
+            .into_iter()
+            .map(|parity| BitVec::from_fn(n, |i| (i + 1) & (parity + 1) != 0))
+            .collect()
+    }
+
+    /// Whether H·cᵀ = 0 over GF(2), i.e. `codeword` has even parity with
+    /// every row of `h`.
+    pub fn is_codeword(h: &[BitVec], codeword: &BitVec) -> bool {
+        h.iter().all(|row| !Self::dot(row, codeword))
+    }
+
+    /// Dot product of two BitVecs over GF(2), over the shorter length.
+    fn dot(a: &BitVec, b: &BitVec) -> bool {
+        (0..a.len().min(b.len())).fold(false, |acc, i| {
+            acc ^ (a.get(i) == Some(true) && b.get(i) == Some(true))
+        })
+    }
+
     pub fn calculate_parity_count(data_len_bits: usize) -> usize {
         let mut parity_count = 0;
         while (1 << parity_count) < (data_len_bits + parity_count + 1) {
@@ -34,22 +232,103 @@ impl HammingCodeBase {
         parity_count
     }
 
+    /// Number of data bits in a codeword of `total_len` bits, if [`Hamming`]
+    /// could have produced one that long.
+    ///
+    /// Every power of two up to `total_len` is a parity position, and the data
+    /// bits left over must need exactly that many parity bits. Lengths that
+    /// don't add up (e.g. a truncated or extended codeword) give
+    /// [`HammingError::InvalidCodewordLength`] with the closest length that
+    /// does.
+    pub fn data_len(total_len: usize) -> Result<usize, HammingError> {
+        let parity_count = match total_len {
+            0 => 0,
+            n => n.ilog2() as usize + 1,
+        };
+        let data_len = total_len - parity_count;
+        let expected = data_len + Self::calculate_parity_count(data_len);
+        if expected != total_len {
+            return Err(HammingError::InvalidCodewordLength {
+                got: total_len,
+                expected_for_r: expected,
+            });
+        }
+        Ok(data_len)
+    }
+
+    /// Computes the parity of all codeword bits whose 1-based position shares a
+    /// bit with `parity_mask`.
+    ///
+    /// Works a byte at a time: within a byte the low 3 bits of the positions
+    /// follow the same pattern every time, and the higher bits only change
+    /// between the 7th and 8th bit, so the participating bits of a byte can be
+    /// masked out and popcounted at once.
     pub fn basic_compute_parity(
         codeword: &BitVec,
         parity_mask: usize,
     ) -> Result<bool, HammingError> {
-        let mut parity_sum = 0u32;
-        for i in 0..codeword.len() {
-            if ((i + 1) & parity_mask) != 0 {
-                if codeword.get(i).ok_or(HammingError::UnexpectedOutOfBounds)? {
-                    parity_sum += 1;
-                }
+        Self::slice_parity(Self::bitslice(codeword)?, parity_mask)
+    }
+
+    /// Borrows `codeword` as a [`BitSlice`], failing rather than panicking if
+    /// it holds fewer bytes than its length needs.
+    fn bitslice(codeword: &BitVec) -> Result<BitSlice<'_>, HammingError> {
+        BitSlice::new(&codeword.data, codeword.len())
+            .map_err(|_| HammingError::UnexpectedOutOfBounds)
+    }
+
+    /// [`HammingCodeBase::basic_compute_parity`] over bits that aren't in a
+    /// [`BitVec`] of their own.
+    pub fn slice_parity(codeword: BitSlice, parity_mask: usize) -> Result<bool, HammingError> {
+        let bytes = codeword.as_bytes();
+
+        // bit k of a byte (MSB first) is at position 8 * byte + k + 1
+        let low = parity_mask & 0b111;
+        let high = parity_mask & !0b111;
+        let low_pattern = (0..8).fold(0u8, |acc, k| {
+            acc | (u8::from((k + 1) & low != 0) << (7 - k))
+        });
+
+        // only the first `len % 8` bits of a partial last byte are part of the codeword
+        let last_byte_mask = match codeword.len() % 8 {
+            0 => 0xFF,
+            used => 0xFF << (8 - used),
+        };
+
+        // whole bytes only, the partial last byte gets masked below
+        #[cfg(feature = "simd")]
+        let (mut parity, start) = if codeword.len() >= simd::THRESHOLD_BITS {
+            simd::parity(&bytes[..codeword.len() / 8], parity_mask)
+        } else {
+            (0, 0)
+        };
+        #[cfg(not(feature = "simd"))]
+        let (mut parity, start) = (0u32, 0);
+
+        for (byte_index, &byte) in bytes.iter().enumerate().skip(start) {
+            let base = byte_index * 8;
+            let mut mask = low_pattern;
+            if base & high != 0 {
+                mask |= 0b1111_1110;
+            }
+            if (base + 8) & high != 0 {
+                mask |= 0b0000_0001;
+            }
+            if byte_index == bytes.len() - 1 {
+                mask &= last_byte_mask;
             }
+            parity ^= (byte & mask).count_ones();
         }
-        Ok(parity_sum % 2 == 1)
+
+        Ok(parity % 2 == 1)
     }
 }
 
+/// Classic single-error-correcting Hamming code over the whole input, with as
+/// many parity bits as it needs.
+///
+/// Any length goes: empty data encodes to an empty codeword and decodes back
+/// clean, and a single data bit gets two parity bits, a 3-bit codeword.
 pub struct Hamming;
 
 impl HammingCode for Hamming {
@@ -62,69 +341,1033 @@ impl HammingCode for Hamming {
     }
 
     fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
-        let data_len_bits = data.len();
-        let parity_count = HammingCodeBase::calculate_parity_count(data_len_bits);
-        let total_len = data_len_bits + parity_count;
-        let mut codeword = BitVec::zeros(total_len);
+        self.encode_from(data)
+    }
 
-        let mut data_index = 0;
+    fn encode_from<S: BitSource + ?Sized>(&self, data: &S) -> Result<BitVec, HammingError> {
+        let total_len = data.bit_len() + HammingCodeBase::calculate_parity_count(data.bit_len());
+        let mut codeword = BitVec::with_capacity(total_len);
+        self.encode_into(data, &mut codeword)?;
+        Ok(codeword)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let mut data = BitVec::with_capacity(codeword.len());
+        let DecodeInfo { status, .. } = self.decode_into(codeword, &mut data)?;
+        Ok(DecodeOutcome { data, status })
+    }
+}
+
+impl Hamming {
+    /// Like [`HammingCode::encode`], but writes the codeword into `out`,
+    /// replacing its contents and reusing its allocation. `out` only grows if
+    /// it's too small to hold the codeword. `out` can't alias `data`, so
+    /// re-encoding a buffer in place needs a second one.
+    pub fn encode_into<S: BitSource + ?Sized>(
+        &self,
+        data: &S,
+        out: &mut BitVec,
+    ) -> Result<(), HammingError> {
+        let parity_count = HammingCodeBase::calculate_parity_count(data.bit_len());
+        let total_len = data.bit_len() + parity_count;
+        if log::log_enabled!(log::Level::Trace) {
+            log::trace!(
+                "Codeword layout: {}",
+                CodewordLayout::for_data_len(data.bit_len())
+            );
+        }
+
+        // each set data bit flips every parity covering its position, so XORing
+        // the 1-based positions of the set bits gives all parities at once
+        out.clear();
+        let mut parities = 0;
         for i in 0..total_len {
-            if (i + 1).is_power_of_two() {
+            let Some(data_index) = mapping::codeword_to_data(i) else {
+                out.push(false);
                 continue;
+            };
+            let bit = data
+                .bit(data_index)
+                .ok_or(HammingError::UnexpectedOutOfBounds)?;
+            if bit {
+                parities ^= i + 1;
             }
-            codeword
-                .set(
-                    i,
-                    data.get(data_index)
-                        .ok_or(HammingError::UnexpectedOutOfBounds)?,
-                )
-                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
-            data_index += 1;
+            out.push(bit);
         }
 
         for i in 0..parity_count {
-            let parity_pos = (1 << i) - 1;
-            let parity = self.calculate_parity(&codeword, 1 << i)?;
+            if parities & (1 << i) != 0 {
+                out.set((1 << i) - 1, true)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The parity bits of the codeword for `data`, bit `i` being the one at
+    /// 1-based position `2^i`. Enough to lay the codeword out one bit at a
+    /// time without holding it all, the data bits just go in between.
+    pub fn parity_word<S: BitSource + ?Sized>(&self, data: &S) -> Result<usize, HammingError> {
+        // the same as in `encode_into`, without the codeword around it
+        let total_len = data.bit_len() + HammingCodeBase::calculate_parity_count(data.bit_len());
+        let mut parities = 0;
+        for i in 0..total_len {
+            let Some(data_index) = mapping::codeword_to_data(i) else {
+                continue;
+            };
+            let bit = data
+                .bit(data_index)
+                .ok_or(HammingError::UnexpectedOutOfBounds)?;
+            if bit {
+                parities ^= i + 1;
+            }
+        }
+        Ok(parities)
+    }
+
+    /// Like [`HammingCode::decode`], but writes the corrected data bits into
+    /// `out`, replacing its contents and reusing its allocation. The codeword
+    /// itself is left untouched, and `out` can't alias it.
+    pub fn decode_into(
+        &self,
+        codeword: &BitVec,
+        out: &mut BitVec,
+    ) -> Result<DecodeInfo, HammingError> {
+        self.decode_slice(HammingCodeBase::bitslice(codeword)?, out)
+    }
+
+    /// Like [`Hamming::decode_into`], for a codeword that's borrowed rather
+    /// than in a [`BitVec`], e.g. still in the buffer it was received in.
+    /// Only the corrected data bits are copied out.
+    pub fn decode_slice(
+        &self,
+        codeword: BitSlice,
+        out: &mut BitVec,
+    ) -> Result<DecodeInfo, HammingError> {
+        HammingCodeBase::data_len(codeword.len())?;
+        let error_pos = self.slice_syndrome(codeword)?;
+
+        let (status, flip) = if error_pos == 0 {
+            (DecodeStatus::Clean, None)
+        } else if error_pos - 1 < codeword.len() {
+            (
+                DecodeStatus::corrected_at(error_pos - 1),
+                Some(error_pos - 1),
+            )
+        } else {
+            // a syndrome pointing past the end can only come from multiple errors
+            (DecodeStatus::Uncorrectable, None)
+        };
+
+        out.clear();
+        for i in 0..codeword.len() {
+            if mapping::codeword_to_data(i).is_none() {
+                continue;
+            }
+            let bit = codeword.get(i).ok_or(HammingError::UnexpectedOutOfBounds)?;
+            out.push(bit ^ (flip == Some(i)));
+        }
+
+        Ok(DecodeInfo {
+            status,
+            data_len: out.len(),
+        })
+    }
+
+    /// Encodes a byte slice, taking the bits of each byte MSB first.
+    pub fn encode_bytes(&self, data: &[u8]) -> Result<BitVec, HammingError> {
+        self.encode_from(data)
+    }
+
+    /// Decodes a codeword produced by [`Hamming::encode_bytes`] back into the
+    /// `original_byte_len` bytes that were encoded.
+    ///
+    /// Unlike [`HammingCode::decode`] this fails with
+    /// [`HammingError::Uncorrectable`] instead of handing back bytes it knows
+    /// are wrong.
+    pub fn decode_bytes(
+        &self,
+        codeword: &BitVec,
+        original_byte_len: usize,
+    ) -> Result<(Vec<u8>, DecodeStatus), HammingError> {
+        let data_bits = original_byte_len * 8;
+        let expected = data_bits + HammingCodeBase::calculate_parity_count(data_bits);
+        if codeword.len() != expected {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: expected,
+            });
+        }
+
+        let DecodeOutcome { data, status } = self.decode(codeword)?;
+        if status.is_uncorrectable() {
+            return Err(HammingError::Uncorrectable {
+                syndrome: self.compute_syndrome(codeword)?,
+            });
+        }
+
+        let mut bytes = data.into_inner();
+        bytes.truncate(original_byte_len);
+        Ok((bytes, status))
+    }
+
+    /// Corrects a single bit error directly in `codeword`, leaving the parity
+    /// bits in place. Use [`Hamming::extract_data`] to get the payload out
+    /// afterwards.
+    ///
+    /// Like the other decoders, fails with [`HammingError::InvalidCodewordLength`]
+    /// if no codeword from [`Hamming`] can be that long.
+    pub fn decode_in_place(&self, codeword: &mut BitVec) -> Result<DecodeInfo, HammingError> {
+        let data_len = HammingCodeBase::data_len(codeword.len())?;
+        let error_pos = self.compute_syndrome(codeword)?;
+
+        let status = if error_pos == 0 {
+            DecodeStatus::Clean
+        } else if error_pos - 1 < codeword.len() {
             codeword
-                .set(parity_pos, parity)
+                .toggle(error_pos - 1)
                 .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            DecodeStatus::corrected_at(error_pos - 1)
+        } else {
+            // a syndrome pointing past the end can only come from multiple errors
+            DecodeStatus::Uncorrectable
+        };
+
+        Ok(DecodeInfo { status, data_len })
+    }
+
+    /// [`HammingCode::compute_syndrome`] over a borrowed codeword.
+    pub fn slice_syndrome(&self, codeword: BitSlice) -> Result<usize, HammingError> {
+        let mut syndrome = 0;
+        for parity_mask in (0..usize::BITS).map(|i| 1usize << i) {
+            if parity_mask > codeword.len() {
+                break;
+            }
+            if HammingCodeBase::slice_parity(codeword, parity_mask)? {
+                syndrome |= parity_mask;
+            }
         }
+        Ok(syndrome)
+    }
+
+    /// Copies everything that isn't a parity bit out of `codeword`.
+    pub fn extract_data(&self, codeword: &BitVec) -> BitVec {
+        codeword.filtered(|i, _| mapping::codeword_to_data(i).is_some())
+    }
+
+    /// Most erasures [`Hamming::decode_with_erasures`] can resolve. With a
+    /// minimum distance of 3, two unknown bits always have a single consistent
+    /// value, as long as no other bit is wrong.
+    pub const MAX_ERASURES: usize = 2;
+
+    /// Decodes `codeword` with the bits at `erasures` treated as unknown.
+    ///
+    /// Every combination of values for the erased bits is tried, and the one
+    /// that makes the codeword consistent is kept. The erased bits that ended
+    /// up different from what was received are reported as corrected. If no
+    /// combination works there were errors outside of the erasures as well,
+    /// which is [`DecodeStatus::Uncorrectable`].
+    pub fn decode_with_erasures(
+        &self,
+        codeword: &BitVec,
+        erasures: &[usize],
+    ) -> Result<DecodeOutcome, HammingError> {
+        let mut erasures = erasures.to_vec();
+        erasures.sort_unstable();
+        erasures.dedup();
+
+        if erasures.is_empty() {
+            return self.decode(codeword);
+        }
+        if erasures.len() > Self::MAX_ERASURES {
+            return Err(HammingError::TooManyErasures {
+                erasures: erasures.len(),
+                max: Self::MAX_ERASURES,
+            });
+        }
+        if let Some(&position) = erasures.iter().find(|&&pos| pos >= codeword.len()) {
+            return Err(HammingError::ErasureOutOfRange {
+                position,
+                len: codeword.len(),
+            });
+        }
+
+        let mut candidate = codeword.clone();
+        for values in 0..1usize << erasures.len() {
+            for (i, &position) in erasures.iter().enumerate() {
+                candidate
+                    .set(position, values & (1 << i) != 0)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            }
+            if self.compute_syndrome(&candidate)? != 0 {
+                continue;
+            }
+
+            let changed: Vec<usize> = erasures
+                .iter()
+                .copied()
+                .filter(|&pos| candidate.get(pos) != codeword.get(pos))
+                .collect();
+            let status = match changed.as_slice() {
+                [pos] => DecodeStatus::corrected_at(*pos),
+                _ => DecodeStatus::corrected_many(changed),
+            };
+
+            return Ok(DecodeOutcome {
+                data: self.extract_data(&candidate),
+                status,
+            });
+        }
+
+        Ok(DecodeOutcome {
+            data: self.extract_data(codeword),
+            status: DecodeStatus::Uncorrectable,
+        })
+    }
+}
+
+/// Extended Hamming code (SECDED): a regular Hamming codeword followed by an
+/// overall parity bit.
+///
+/// Corrects any single bit error and detects (but can't correct) any double
+/// bit error, which `decode` reports as [`DecodeStatus::Uncorrectable`]
+/// instead of claiming to have corrected it.
+pub struct ExtendedHamming;
+
+impl ExtendedHamming {
+    /// Splits the codeword into the inner Hamming codeword and the overall parity.
+    fn split(codeword: &BitVec) -> Result<(BitVec, bool), HammingError> {
+        let inner_len = codeword
+            .len()
+            .checked_sub(1)
+            .ok_or(HammingError::UnexpectedOutOfBounds)?;
+        let inner = codeword
+            .slice(0..inner_len)
+            .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+
+        let mut overall = false;
+        for i in 0..codeword.len() {
+            overall ^= codeword.get(i).ok_or(HammingError::UnexpectedOutOfBounds)?;
+        }
+
+        Ok((inner, overall))
+    }
+}
+
+impl HammingCode for ExtendedHamming {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        let (inner, _) = Self::split(codeword)?;
+        HammingCodeBase::basic_compute_parity(&inner, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let mut codeword = Hamming.encode(data)?;
+
+        let mut overall = false;
+        for i in 0..codeword.len() {
+            overall ^= codeword.get(i).ok_or(HammingError::UnexpectedOutOfBounds)?;
+        }
+        codeword.push(overall);
 
         Ok(codeword)
     }
 
-    fn decode(&self, codeword: &BitVec) -> Result<(BitVec, usize), HammingError> {
+    fn verify(&self, codeword: &BitVec) -> Result<bool, HammingError> {
+        let (inner, overall_odd) = Self::split(codeword)?;
+        Ok(!overall_odd && Hamming.verify(&inner)?)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let (inner, overall_odd) = Self::split(codeword)?;
+        let DecodeOutcome { data, status } = Hamming.decode(&inner)?;
+
+        let status = match (status, overall_odd) {
+            (DecodeStatus::Clean, false) => DecodeStatus::Clean,
+            // only the overall parity bit itself flipped
+            (DecodeStatus::Clean, true) => DecodeStatus::Corrected {
+                codeword_pos: inner.len(),
+                was_parity: true,
+                data_pos: None,
+            },
+            (status @ DecodeStatus::Corrected { .. }, true) => status,
+            // an even number of flips (or a bogus syndrome) can't be corrected
+            _ => DecodeStatus::Uncorrectable,
+        };
+
+        Ok(DecodeOutcome { data, status })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::Rng;
+
+    use super::*;
+    use crate::encoding::{
+        block::BlockHamming, golay::Golay24, matrix::MatrixHamming, prepared::PreparedHamming,
+        product::ProductHamming, repetition::Repetition, shortened::ShortenedHamming,
+        storage::Storage,
+    };
+    use crate::testing::{bitvec_strategy, codeword_strategy};
+
+    fn all_codecs() -> Vec<Box<dyn HammingCode>> {
+        vec![
+            Box::new(Hamming),
+            Box::new(ExtendedHamming),
+            Box::new(BlockHamming::hamming74()),
+            Box::new(Repetition::new(3).unwrap()),
+            Box::new(MatrixHamming::new(11)),
+            Box::new(Golay24),
+            Box::new(ShortenedHamming::new(8, 4).unwrap()),
+            Box::new(ProductHamming::new(4, 4).unwrap()),
+            Box::new(PreparedHamming::for_len(11)),
+        ]
+    }
+
+    /// Runs everything that reads a codeword, only caring that it returns.
+    fn poke(codec: &dyn HammingCode, codeword: &BitVec, parity_mask: usize) {
+        let _ = codec.decode(codeword);
+        let _ = codec.compute_syndrome(codeword);
+        let _ = codec.verify(codeword);
+        let _ = codec.calculate_parity(codeword, parity_mask);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(data in bitvec_strategy(512)) {
+            let codeword = Hamming.encode(&data).unwrap();
+            let outcome = Hamming.decode(&codeword).unwrap();
+
+            prop_assert_eq!(outcome.status, DecodeStatus::Clean);
+            prop_assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+
+        #[test]
+        fn prop_single_error_correction(
+            (data, codeword, flip) in (0usize..300).prop_flat_map(codeword_strategy)
+        ) {
+            let outcome = Hamming.decode(&codeword).unwrap();
+
+            let expected = flip.map_or(DecodeStatus::Clean, DecodeStatus::corrected_at);
+            prop_assert_eq!(outcome.status, expected);
+            prop_assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+
+        #[test]
+        fn prop_decode_never_panics(
+            bytes in proptest::collection::vec(any::<u8>(), 0..200),
+            bit_length in 0usize..1700,
+            parity_mask in any::<usize>(),
+        ) {
+            match BitVec::from_bytes(bytes.clone(), bit_length) {
+                Ok(codeword) => {
+                    for codec in all_codecs() {
+                        poke(codec.as_ref(), &codeword, parity_mask);
+                    }
+                    let mut codeword = codeword;
+                    let _ = Hamming.decode_in_place(&mut codeword);
+                    let _ = Hamming.decode_bytes(&codeword, bytes.len());
+                }
+                Err(_) => prop_assert!(bit_length > bytes.len() * 8),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_never_panics_exhaustive() {
+        // every codeword of up to 16 bits, with the stray bits past the end set,
+        // which BitVec::from_bytes would clear
+        let codecs = all_codecs();
+        for len in 0..=16 {
+            for value in 0..(1u32 << len) {
+                let bits = (value << (16 - len)) | ((1 << (16 - len)) - 1);
+                let codeword = BitVec {
+                    data: Storage::from_vec((bits as u16).to_be_bytes().to_vec()),
+                    len,
+                };
+                for codec in &codecs {
+                    poke(codec.as_ref(), &codeword, value as usize);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extended_round_trip() {
+        let data = BitVec::random(26, &mut rand::rng());
+        let codeword = ExtendedHamming.encode(&data).unwrap();
+        assert_eq!(codeword.len(), 32);
+
+        let outcome = ExtendedHamming.decode(&codeword).unwrap();
+        assert!(outcome.status.is_clean());
+        assert_eq!(outcome.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_extended_corrects_every_single_error() {
+        let data = BitVec::random(26, &mut rand::rng());
+        let codeword = ExtendedHamming.encode(&data).unwrap();
+
+        for i in 0..codeword.len() {
+            let mut corrupted = codeword.clone();
+            corrupted.toggle(i).unwrap();
+
+            let outcome = ExtendedHamming.decode(&corrupted).unwrap();
+            match outcome.status {
+                DecodeStatus::Corrected { codeword_pos, .. } => assert_eq!(codeword_pos, i),
+                status => panic!("expected a correction at {i}, got {status:?}"),
+            }
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_extended_detects_double_errors() {
+        let data = BitVec::random(11, &mut rand::rng());
+        let codeword = ExtendedHamming.encode(&data).unwrap();
+
+        for i in 0..codeword.len() {
+            for j in (i + 1)..codeword.len() {
+                let mut corrupted = codeword.clone();
+                corrupted.toggle(i).unwrap();
+                corrupted.toggle(j).unwrap();
+
+                let outcome = ExtendedHamming.decode(&corrupted).unwrap();
+                assert!(outcome.status.is_uncorrectable());
+            }
+        }
+    }
+
+    /// The encoder as it used to be: place the data, then a pass over the
+    /// codeword for every parity bit.
+    fn encode_per_parity(data: &BitVec) -> BitVec {
+        let parity_count = HammingCodeBase::calculate_parity_count(data.len());
+        let mut data_bits = data.to_vec().into_iter();
+        let mut codeword = BitVec::from_fn(data.len() + parity_count, |i| {
+            !(i + 1).is_power_of_two() && data_bits.next().unwrap()
+        });
+        for i in 0..parity_count {
+            let parity = HammingCodeBase::basic_compute_parity(&codeword, 1 << i).unwrap();
+            codeword.set((1 << i) - 1, parity).unwrap();
+        }
+        codeword
+    }
+
+    #[test]
+    fn test_single_pass_matches_per_parity() {
+        let mut rng = rand::rng();
+        for len in (0..=600).chain([1013, 2036, 4083, 65536]) {
+            let data = BitVec::random(len, &mut rng);
+            assert_eq!(
+                Hamming.encode(&data).unwrap().to_vec(),
+                encode_per_parity(&data).to_vec(),
+                "length {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_syndrome() {
+        // 12 data bits take 5 parity bits, so syndromes 18 to 31 don't point
+        // at any bit of the 17 bit codeword
+        let data = BitVec::random(12, &mut rand::rng());
+        let codeword = Hamming.encode(&data).unwrap();
         let n = codeword.len();
-        let r = (0..).find(|&r| (1 << r) >= n + 1).unwrap();
-        let mut error_pos = 0;
+        assert_eq!(n, 17);
+
+        let mut out_of_range = 0;
+        for i in 0..n {
+            for j in (i + 1)..n {
+                if ((i + 1) ^ (j + 1)) <= n {
+                    continue;
+                }
+                out_of_range += 1;
+
+                let mut corrupted = codeword.clone();
+                corrupted.toggle(i).unwrap();
+                corrupted.toggle(j).unwrap();
+
+                let outcome = Hamming.decode(&corrupted).unwrap();
+                assert!(outcome.status.is_uncorrectable(), "{i}, {j}");
+                assert_eq!(
+                    outcome.data.to_vec(),
+                    Hamming.extract_data(&corrupted).to_vec()
+                );
+
+                let mut in_place = corrupted.clone();
+                let info = Hamming.decode_in_place(&mut in_place).unwrap();
+                assert!(info.status.is_uncorrectable());
+                assert_eq!(in_place.to_vec(), corrupted.to_vec());
+            }
+        }
+        assert!(out_of_range > 0);
+    }
+
+    #[test]
+    fn test_syndrome() {
+        let data = BitVec::random(21, &mut rand::rng());
+        let codeword = Hamming.encode(&data).unwrap();
+        assert_eq!(codeword.len(), 26);
+        assert_eq!(Hamming.compute_syndrome(&codeword).unwrap(), 0);
+
+        for i in 0..codeword.len() {
+            let mut corrupted = codeword.clone();
+            corrupted.toggle(i).unwrap();
+            assert_eq!(Hamming.compute_syndrome(&corrupted).unwrap(), i + 1);
+        }
+
+        assert_eq!(Hamming.compute_syndrome(&BitVec::new()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_decode_in_place_matches_decode() {
+        let mut rng = rand::rng();
+        for len in [1, 4, 11, 26, 57, 200] {
+            let data = BitVec::random(len, &mut rng);
+            let codeword = Hamming.encode(&data).unwrap();
 
-        for i in 0..r {
-            if self.calculate_parity(codeword, 1 << i)? {
-                error_pos += 1 << i;
+            for flip in std::iter::once(None).chain((0..codeword.len()).map(Some)) {
+                let mut corrupted = codeword.clone();
+                if let Some(i) = flip {
+                    corrupted.toggle(i).unwrap();
+                }
+
+                let expected = Hamming.decode(&corrupted).unwrap();
+                let info = Hamming.decode_in_place(&mut corrupted).unwrap();
+
+                assert_eq!(corrupted.to_vec(), codeword.to_vec());
+                assert_eq!(info.status, expected.status);
+                assert_eq!(info.data_len, len);
+                assert_eq!(
+                    Hamming.extract_data(&corrupted).to_vec(),
+                    expected.data.to_vec()
+                );
             }
         }
+    }
 
-        let mut corrected = BitVec::clone(codeword);
-        if error_pos > 0 && error_pos - 1 < corrected.len() {
-            let current = corrected
-                .get(error_pos - 1)
-                .ok_or(HammingError::UnexpectedOutOfBounds)?;
-            corrected
-                .set(error_pos - 1, !current)
-                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+    #[test]
+    fn test_into_matches_allocating() {
+        let mut rng = rand::rng();
+        let mut codeword = BitVec::new();
+        let mut decoded = BitVec::new();
+
+        // reuse the same buffers across shrinking and growing sizes
+        for len in [200, 1, 26, 128, 4, 57, 300] {
+            let data = BitVec::random(len, &mut rng);
+            Hamming.encode_into(&data, &mut codeword).unwrap();
+            let expected = Hamming.encode(&data).unwrap();
+            assert_eq!(codeword.len(), expected.len());
+            assert_eq!(codeword.clone().into_inner(), expected.into_inner());
+
+            let flip = rng.random_range(0..codeword.len());
+            codeword.toggle(flip).unwrap();
+            let info = Hamming.decode_into(&codeword, &mut decoded).unwrap();
+            let expected = Hamming.decode(&codeword).unwrap();
+            assert_eq!(info.status, expected.status);
+            assert_eq!(info.data_len, len);
+            assert_eq!(decoded.to_vec(), data.to_vec());
+            assert_eq!(decoded.clone().into_inner(), expected.data.into_inner());
         }
+    }
 
-        let mut data = BitVec::with_capacity(n - r);
-        for i in 0..corrected.len() {
-            if !((i + 1).is_power_of_two()) {
-                data.push(
-                    corrected
-                        .get(i)
-                        .ok_or(HammingError::UnexpectedOutOfBounds)?,
+    #[test]
+    fn test_parity_word() {
+        let mut rng = rand::rng();
+        for len in [0, 1, 4, 57, 300] {
+            let data = BitVec::random(len, &mut rng);
+            let codeword = Hamming.encode(&data).unwrap();
+            let parities = Hamming.parity_word(&data).unwrap();
+            for position in HammingCodeBase::parity_positions(codeword.len()) {
+                assert_eq!(
+                    codeword.get(position).unwrap(),
+                    parities & (position + 1) != 0,
+                    "length {len}, position {position}"
                 );
             }
         }
+    }
+
+    #[test]
+    fn test_verify() {
+        let data = BitVec::random(21, &mut rand::rng());
+        for codec in [&Hamming as &dyn HammingCode, &ExtendedHamming] {
+            let codeword = codec.encode(&data).unwrap();
+            assert!(codec.verify(&codeword).unwrap());
+
+            for i in 0..codeword.len() {
+                let mut corrupted = codeword.clone();
+                corrupted.toggle(i).unwrap();
+                assert!(!codec.verify(&corrupted).unwrap());
+            }
+        }
+
+        assert!(Hamming.verify(&BitVec::new()).unwrap());
+        assert!(ExtendedHamming.verify(&BitVec::new()).is_err());
+    }
+
+    #[test]
+    fn test_tiny_inputs() {
+        assert_eq!(Hamming.encode(&BitVec::new()).unwrap().len(), 0);
+        let outcome = Hamming.decode(&BitVec::new()).unwrap();
+        assert!(outcome.data.is_empty());
+        assert!(outcome.status.is_clean());
+
+        for (len, codeword_len) in [(1, 3), (2, 5), (3, 6)] {
+            for value in 0..1u64 << len {
+                let data = BitVec::from_fn(len, |i| value >> i & 1 == 1);
+                let codeword = Hamming.encode(&data).unwrap();
+                assert_eq!(codeword.len(), codeword_len);
+
+                let outcome = Hamming.decode(&codeword).unwrap();
+                assert!(outcome.status.is_clean());
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+
+                for i in 0..codeword_len {
+                    let mut corrupted = codeword.clone();
+                    corrupted.toggle(i).unwrap();
+                    let outcome = Hamming.decode(&corrupted).unwrap();
+                    assert_eq!(outcome.status, DecodeStatus::corrected_at(i));
+                    assert_eq!(outcome.data.to_vec(), data.to_vec(), "{len} bits, flip {i}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut rng = rand::rng();
+        for len in [0, 1, 2, 3, 16, 100] {
+            let data: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+            let codeword = Hamming.encode_bytes(&data).unwrap();
+
+            let (decoded, status) = Hamming.decode_bytes(&codeword, len).unwrap();
+            assert_eq!(decoded, data);
+            assert!(status.is_clean());
+
+            assert!(Hamming.decode_bytes(&codeword, len + 1).is_err());
+        }
+    }
+
+    #[test]
+    fn test_bytes_corrects_error() {
+        let data = b"hamming".to_vec();
+        let mut codeword = Hamming.encode_bytes(&data).unwrap();
+        codeword.toggle(20).unwrap();
+
+        let (decoded, status) = Hamming.decode_bytes(&codeword, data.len()).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(status, DecodeStatus::corrected_at(20));
+    }
+
+    #[test]
+    fn test_codeword_length_validation() {
+        let mut rng = rand::rng();
+        // every length encode produces is accepted
+        for k in 0..=300 {
+            let codeword = Hamming.encode(&BitVec::random(k, &mut rng)).unwrap();
+            assert_eq!(HammingCodeBase::data_len(codeword.len()), Ok(k));
+            assert_eq!(Hamming.decode(&codeword).unwrap().data.len(), k);
+        }
+
+        // 27 data bits take 6 parity bits, so dropping the last bit leaves a
+        // codeword whose last position is a useless parity bit
+        let codeword = Hamming.encode(&BitVec::random(27, &mut rng)).unwrap();
+        assert_eq!(codeword.len(), 33);
+        let truncated = codeword.slice(0..32).unwrap();
+        let expected = HammingError::InvalidCodewordLength {
+            got: 32,
+            expected_for_r: 31,
+        };
+        assert_eq!(Hamming.decode(&truncated).unwrap_err(), expected);
+        assert_eq!(
+            Hamming.decode_in_place(&mut truncated.clone()).unwrap_err(),
+            expected
+        );
+
+        // 57 data bits fill up 63 bits, one more is a parity bit with no data
+        let codeword = Hamming.encode(&BitVec::random(57, &mut rng)).unwrap();
+        let extended = BitVec::concat_all(&[codeword, BitVec::zeros(1)]);
+        assert_eq!(
+            Hamming.decode(&extended).unwrap_err(),
+            HammingError::InvalidCodewordLength {
+                got: 64,
+                expected_for_r: 63,
+            }
+        );
+
+        for (n, expected_for_r) in [(1, 0), (2, 0), (4, 3), (8, 7), (16, 15), (1024, 1023)] {
+            assert_eq!(
+                HammingCodeBase::data_len(n),
+                Err(HammingError::InvalidCodewordLength {
+                    got: n,
+                    expected_for_r,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_variants() {
+        let data = b"hamming".to_vec();
+        let codeword = Hamming.encode_bytes(&data).unwrap();
+        assert_eq!(codeword.len(), 62);
+
+        assert_eq!(
+            Hamming.decode_bytes(&codeword, data.len() + 1),
+            Err(HammingError::InvalidCodewordLength {
+                got: 62,
+                expected_for_r: 71,
+            })
+        );
+
+        // positions 2 and 61 give syndrome 63, past the end of the codeword
+        let mut corrupted = codeword.clone();
+        corrupted.toggle(1).unwrap();
+        corrupted.toggle(60).unwrap();
+        assert_eq!(
+            Hamming.decode_bytes(&corrupted, data.len()),
+            Err(HammingError::Uncorrectable { syndrome: 63 })
+        );
+
+        assert_eq!(
+            HammingError::InvalidCodewordLength {
+                got: 62,
+                expected_for_r: 71
+            }
+            .to_string(),
+            "Invalid codeword length: got 62 bits, expected 71"
+        );
+        assert_eq!(
+            HammingError::Uncorrectable { syndrome: 63 }.to_string(),
+            "Uncorrectable codeword (syndrome 63)"
+        );
+    }
+
+    #[test]
+    fn test_erasures() {
+        let data = BitVec::random(26, &mut rand::rng());
+        let codeword = Hamming.encode(&data).unwrap();
+        let n = codeword.len();
+
+        // erased bits may arrive with either value
+        for i in 0..n {
+            let mut received = codeword.clone();
+            received.toggle(i).unwrap();
+            let outcome = Hamming.decode_with_erasures(&received, &[i]).unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+            assert_eq!(outcome.status, DecodeStatus::corrected_at(i));
+
+            let outcome = Hamming.decode_with_erasures(&codeword, &[i]).unwrap();
+            assert!(outcome.status.is_clean());
+
+            for j in i + 1..n {
+                let mut received = codeword.clone();
+                received.toggle(i).unwrap();
+                received.toggle(j).unwrap();
+                let outcome = Hamming.decode_with_erasures(&received, &[j, i]).unwrap();
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+                assert_eq!(
+                    outcome.status,
+                    DecodeStatus::CorrectedMany {
+                        codeword_positions: vec![i, j]
+                    }
+                );
+            }
+        }
+
+        // position 0 is a parity bit, 2 a data bit
+        for (parity, data_bit) in [(0, 2), (3, 4)] {
+            let mut received = codeword.clone();
+            received.toggle(parity).unwrap();
+            received.toggle(data_bit).unwrap();
+            let outcome = Hamming
+                .decode_with_erasures(&received, &[parity, data_bit])
+                .unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_erasure_errors() {
+        let codeword = Hamming
+            .encode(&BitVec::random(26, &mut rand::rng()))
+            .unwrap();
+
+        assert!(matches!(
+            Hamming.decode_with_erasures(&codeword, &[1, 2, 3]),
+            Err(HammingError::TooManyErasures {
+                erasures: 3,
+                max: 2
+            })
+        ));
+        assert!(matches!(
+            Hamming.decode_with_erasures(&codeword, &[1, 31]),
+            Err(HammingError::ErasureOutOfRange {
+                position: 31,
+                len: 31
+            })
+        ));
+
+        // an error outside of the erasures can't be resolved
+        let mut received = codeword.clone();
+        received.toggle(10).unwrap();
+        let outcome = Hamming.decode_with_erasures(&received, &[3, 20]).unwrap();
+        assert!(outcome.status.is_uncorrectable());
+    }
+
+    #[test]
+    fn test_matrices() {
+        let mut rng = rand::rng();
+        for k in [1, 4, 11, 26, 40] {
+            let g = HammingCodeBase::generator_matrix(k);
+            let n = k + HammingCodeBase::calculate_parity_count(k);
+            let h = HammingCodeBase::parity_check_matrix(n);
+            assert_eq!(g.len(), k);
+            assert_eq!(h.len(), n - k);
+
+            // G·Hᵀ = 0
+            for row in &g {
+                assert_eq!(row.len(), n);
+                assert!(HammingCodeBase::is_codeword(&h, row));
+            }
+
+            for _ in 0..20 {
+                let data = BitVec::random(k, &mut rng);
+                let codeword = Hamming.encode(&data).unwrap();
+                assert!(HammingCodeBase::is_codeword(&h, &codeword));
+
+                // m·G is the same codeword
+                let product = (0..k)
+                    .filter(|&i| data.get(i) == Some(true))
+                    .fold(BitVec::zeros(n), |acc, i| {
+                        BitVec::from_fn(n, |j| acc.get(j) != g[i].get(j))
+                    });
+                assert_eq!(product.to_vec(), codeword.to_vec());
+
+                let mut corrupted = codeword.clone();
+                corrupted.toggle(rng.random_range(0..n)).unwrap();
+                assert!(!HammingCodeBase::is_codeword(&h, &corrupted));
+            }
+        }
+
+        // Hamming(7,4) in this crate's layout: p1 p2 d1 p4 d2 d3 d4
+        let g: Vec<_> = HammingCodeBase::generator_matrix(4)
+            .iter()
+            .map(|row| row.to_uint().unwrap())
+            .collect();
+        assert_eq!(g, vec![0b111_0000, 0b100_1100, 0b010_1010, 0b110_1001]);
+        let h: Vec<_> = HammingCodeBase::parity_check_matrix(7)
+            .iter()
+            .map(|row| row.to_uint().unwrap())
+            .collect();
+        assert_eq!(h, vec![0b101_0101, 0b011_0011, 0b000_1111]);
+    }
+
+    #[test]
+    fn test_parity_positions() {
+        assert_eq!(HammingCodeBase::parity_positions(0), Vec::<usize>::new());
+        assert_eq!(HammingCodeBase::parity_positions(7), vec![0, 1, 3]);
+        assert_eq!(HammingCodeBase::parity_positions(26), vec![0, 1, 3, 7, 15]);
+    }
+
+    #[test]
+    fn test_corrected_positions() {
+        // p1 p2 d1 p4 d2 d3 d4 p8 d5
+        assert_eq!(
+            DecodeStatus::corrected_at(0),
+            DecodeStatus::Corrected {
+                codeword_pos: 0,
+                was_parity: true,
+                data_pos: None
+            }
+        );
+        let data_positions: Vec<_> = (0..9)
+            .map(|pos| match DecodeStatus::corrected_at(pos) {
+                DecodeStatus::Corrected { data_pos, .. } => data_pos,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            data_positions,
+            vec![
+                None,
+                None,
+                Some(0),
+                None,
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                Some(4)
+            ]
+        );
+
+        // flipping a data bit is reported at its place in the decoded data
+        let data = BitVec::zeros(11);
+        let mut codeword = Hamming.encode(&data).unwrap();
+        codeword.toggle(9).unwrap();
+        let outcome = Hamming.decode(&codeword).unwrap();
+        assert_eq!(outcome.status, DecodeStatus::corrected_at(9));
+        assert_eq!(outcome.status.corrections(), 1);
+        assert!(outcome.data.to_vec().iter().all(|&bit| !bit));
+    }
+
+    /// The original bit by bit parity computation, kept as a reference.
+    fn reference_parity(codeword: &BitVec, parity_mask: usize) -> bool {
+        (0..codeword.len())
+            .filter(|&i| ((i + 1) & parity_mask) != 0 && codeword.get(i).unwrap())
+            .count()
+            % 2
+            == 1
+    }
+
+    #[test]
+    fn test_parity_matches_reference() {
+        let mut rng = rand::rng();
+        let lengths = (1..=512).chain((0..100).map(|_| rng.random_range(513..=4096)));
+
+        for len in lengths.collect::<Vec<_>>() {
+            let codeword = BitVec::random(len, &mut rng);
+            for i in 0..=len.ilog2() {
+                assert_eq!(
+                    HammingCodeBase::basic_compute_parity(&codeword, 1 << i).unwrap(),
+                    reference_parity(&codeword, 1 << i),
+                    "length {len}, mask {}",
+                    1 << i
+                );
+            }
+            // arbitrary masks work too
+            let mask = rng.random_range(1..=len);
+            assert_eq!(
+                HammingCodeBase::basic_compute_parity(&codeword, mask).unwrap(),
+                reference_parity(&codeword, mask)
+            );
+        }
+    }
 
-        Ok((data, error_pos))
+    #[test]
+    fn test_parity_ignores_stored_tail() {
+        // BitVec::from_bytes would clear the tail
+        let codeword = BitVec {
+            data: Storage::from_vec(vec![0b1010_0111]),
+            len: 3,
+        };
+        assert_eq!(
+            HammingCodeBase::basic_compute_parity(&codeword, 1).unwrap(),
+            reference_parity(&codeword, 1)
+        );
+        // BitVec::from_bytes refuses these, but the parity shouldn't trust it
+        let short = BitVec {
+            data: Storage::new(),
+            len: 3,
+        };
+        assert!(HammingCodeBase::basic_compute_parity(&short, 1).is_err());
     }
 }
@@ -0,0 +1,229 @@
+use crate::encoding::{
+    bitvec::{BitVec, BitVecError},
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingError},
+};
+
+/// Block interleaver spreading adjacent bits over `depth` rows, see
+/// [`BitVec::interleave`] for the exact layout.
+///
+/// A burst of up to `depth` adjacent bit errors on the wire turns into single
+/// errors at least `len / depth` bits apart once deinterleaved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interleaver {
+    depth: usize,
+}
+
+impl Interleaver {
+    /// Creates an interleaver over `depth` rows, which must be at least 1.
+    pub fn new(depth: usize) -> Result<Self, BitVecError> {
+        if depth == 0 {
+            return Err(BitVecError::InvalidDepth);
+        }
+        Ok(Self { depth })
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn interleave(&self, bits: &BitVec) -> BitVec {
+        bits.interleave(self.depth)
+            .expect("depth is validated in Interleaver::new")
+    }
+
+    pub fn deinterleave(&self, bits: &BitVec) -> BitVec {
+        bits.deinterleave(self.depth)
+            .expect("depth is validated in Interleaver::new")
+    }
+
+    /// Where bit `index` of a `len` bit message ends up after interleaving.
+    pub fn position(&self, len: usize, index: usize) -> usize {
+        let cols = len.div_ceil(self.depth);
+        let (row, col) = (index / cols, index % cols);
+
+        // every column has `full_rows` cells, the first `rem` ones one more
+        let (full_rows, rem) = (len / cols, len % cols);
+        col * full_rows + col.min(rem) + row
+    }
+
+    /// Moves the positions `status` corrected in the deinterleaved `len` bit
+    /// codeword to where they were received.
+    pub fn map_status(&self, len: usize, status: DecodeStatus) -> DecodeStatus {
+        match status {
+            DecodeStatus::Corrected {
+                codeword_pos,
+                was_parity,
+                data_pos,
+            } => DecodeStatus::Corrected {
+                codeword_pos: self.position(len, codeword_pos),
+                was_parity,
+                data_pos,
+            },
+            DecodeStatus::CorrectedMany { codeword_positions } => {
+                let mut positions: Vec<usize> = codeword_positions
+                    .into_iter()
+                    .map(|pos| self.position(len, pos))
+                    .collect();
+                positions.sort_unstable();
+                DecodeStatus::CorrectedMany {
+                    codeword_positions: positions,
+                }
+            }
+            status => status,
+        }
+    }
+}
+
+/// Wraps a codec so its codewords are interleaved on the wire.
+///
+/// Encoding runs the inner codec and interleaves its output, decoding
+/// deinterleaves before handing the codeword to the inner codec. Positions in
+/// the returned [`DecodeStatus`] refer to the interleaved codeword as it was
+/// received.
+///
+/// Pairs best with a block code such as [`BlockHamming`](crate::encoding::block::BlockHamming),
+/// which can then correct a burst of up to `depth` bits as long as the
+/// deinterleaved errors land in different blocks.
+#[derive(Debug, Clone)]
+pub struct Interleaved<C: HammingCode> {
+    codec: C,
+    interleaver: Interleaver,
+}
+
+impl<C: HammingCode> Interleaved<C> {
+    pub fn new(codec: C, interleaver: Interleaver) -> Self {
+        Self { codec, interleaver }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn interleaver(&self) -> Interleaver {
+        self.interleaver
+    }
+}
+
+impl<C: HammingCode> HammingCode for Interleaved<C> {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        self.codec
+            .calculate_parity(&self.interleaver.deinterleave(codeword), parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        Ok(self.interleaver.interleave(&self.codec.encode(data)?))
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let DecodeOutcome { data, status } = self
+            .codec
+            .decode(&self.interleaver.deinterleave(codeword))?;
+
+        Ok(DecodeOutcome {
+            data,
+            status: self.interleaver.map_status(codeword.len(), status),
+        })
+    }
+
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        self.codec
+            .compute_syndrome(&self.interleaver.deinterleave(codeword))
+    }
+
+    fn verify(&self, codeword: &BitVec) -> Result<bool, HammingError> {
+        self.codec.verify(&self.interleaver.deinterleave(codeword))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::block::BlockHamming;
+
+    #[test]
+    fn test_interleaver_round_trip() {
+        let mut rng = rand::rng();
+        for depth in [1, 2, 3, 4, 7, 16] {
+            let interleaver = Interleaver::new(depth).unwrap();
+            for len in [0, 1, 5, 15, 16, 17, 100, 113] {
+                let bits = BitVec::random(len, &mut rng);
+                let interleaved = interleaver.interleave(&bits);
+                assert_eq!(interleaved.len(), len);
+                assert_eq!(
+                    interleaver.deinterleave(&interleaved).to_vec(),
+                    bits.to_vec()
+                );
+            }
+        }
+
+        assert!(Interleaver::new(0).is_err());
+    }
+
+    #[test]
+    fn test_position() {
+        for depth in [1, 2, 3, 4, 7] {
+            let interleaver = Interleaver::new(depth).unwrap();
+            for len in [1, 5, 16, 17, 100] {
+                for index in 0..len {
+                    let bits = BitVec::from_fn(len, |i| i == index);
+                    let interleaved = interleaver.interleave(&bits).to_vec();
+                    let expected = interleaved.iter().position(|&bit| bit).unwrap();
+                    assert_eq!(interleaver.position(len, index), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = rand::rng();
+        let codec = Interleaved::new(BlockHamming::hamming74(), Interleaver::new(5).unwrap());
+        for len in [0, 4, 9, 64, 101] {
+            let data = BitVec::random(len, &mut rng);
+            let outcome = codec.decode(&codec.encode(&data).unwrap()).unwrap();
+            assert!(outcome.status.is_clean());
+            assert_eq!(outcome.data.slice(0..len).unwrap().to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_corrects_burst() {
+        let data = BitVec::random(64, &mut rand::rng());
+        let plain = BlockHamming::hamming74();
+
+        for depth in [4, 8] {
+            let codec = Interleaved::new(plain, Interleaver::new(depth).unwrap());
+            let codeword = codec.encode(&data).unwrap();
+
+            for start in 0..codeword.len() - 4 {
+                let mut corrupted = codeword.clone();
+                for i in start..start + 4 {
+                    corrupted.toggle(i).unwrap();
+                }
+
+                let outcome = codec.decode(&corrupted).unwrap();
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+                assert_eq!(
+                    outcome.status,
+                    DecodeStatus::CorrectedMany {
+                        codeword_positions: (start..start + 4).collect()
+                    }
+                );
+            }
+        }
+
+        // the same burst inside a single block is too much for the plain codec
+        let mut corrupted = plain.encode(&data).unwrap();
+        for i in 0..4 {
+            corrupted.toggle(i).unwrap();
+        }
+        assert_ne!(
+            plain.decode(&corrupted).unwrap().data.to_vec(),
+            data.to_vec()
+        );
+    }
+}
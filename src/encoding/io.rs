@@ -0,0 +1,423 @@
+//! `std::io` adapters that Hamming-encode a byte stream on the fly.
+//!
+//! The stream is cut into chunks of `k` bytes (`k` being the codec's data bits
+//! per block), so every chunk encodes into exactly `n` whole bytes. The stream
+//! always ends with a trailer: the final partial chunk's bytes, zero padding,
+//! and then their count as a big-endian `u32`, filling the last one or two
+//! chunks. That's how the final partial chunk is recovered.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+};
+
+use crate::encoding::{
+    bitvec::BitVec,
+    block::BlockHamming,
+    hamming::{HammingCode, HammingError},
+};
+
+/// Bytes of the count that closes the trailer.
+const TRAILER_COUNT_BYTES: usize = 4;
+
+/// Most chunks a trailer takes: its count doesn't always fit after a partial
+/// chunk's bytes.
+const MAX_TRAILER_CHUNKS: usize = 2;
+
+fn to_io_error(error: HammingError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Sizes of a chunk before and after encoding, in bytes.
+fn chunk_sizes(codec: &BlockHamming) -> (usize, usize) {
+    (codec.data_bits_per_block(), codec.code_bits_per_block())
+}
+
+/// Counters collected by a [`HammingReader`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Number of chunks read, including the trailer.
+    pub chunks: usize,
+    /// Number of codec blocks that needed a correction.
+    pub corrected_blocks: usize,
+}
+
+/// Hamming-encodes everything written to it before passing it on to `W`.
+///
+/// [`HammingWriter::finish`] must be called to write the final partial chunk
+/// and the trailer. Dropping the writer does this too, but ignores any errors.
+pub struct HammingWriter<W: Write> {
+    inner: Option<W>,
+    codec: BlockHamming,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> HammingWriter<W> {
+    /// Creates a writer using Hamming(15,11) blocks.
+    pub fn new(inner: W) -> Self {
+        Self::with_codec(inner, BlockHamming::hamming1511())
+    }
+
+    pub fn with_codec(inner: W, codec: BlockHamming) -> Self {
+        let (chunk_bytes, _) = chunk_sizes(&codec);
+        Self {
+            inner: Some(inner),
+            codec,
+            pending: Vec::with_capacity(chunk_bytes),
+        }
+    }
+
+    /// Writes the remaining data and the trailer, returning the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_trailer()?;
+        let mut inner = self.inner.take().expect("inner writer is only taken once");
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    fn write_chunk(&mut self, chunk: Vec<u8>) -> io::Result<()> {
+        let codeword = self
+            .codec
+            .encode(&BitVec::from_whole_bytes(chunk))
+            .map_err(to_io_error)?;
+
+        self.inner
+            .as_mut()
+            .expect("writer used after finish")
+            .write_all(&codeword.into_inner())
+    }
+
+    fn write_trailer(&mut self) -> io::Result<()> {
+        let (chunk_bytes, _) = chunk_sizes(&self.codec);
+
+        let count = u32::try_from(self.pending.len()).expect("chunks are shorter than 4 GiB");
+        let mut trailer = std::mem::take(&mut self.pending);
+        // the count goes last, where the reader finds it however many chunks
+        // the trailer takes
+        let trailer_bytes = (trailer.len() + TRAILER_COUNT_BYTES).next_multiple_of(chunk_bytes);
+        trailer.resize(trailer_bytes - TRAILER_COUNT_BYTES, 0);
+        trailer.extend_from_slice(&count.to_be_bytes());
+
+        for chunk in trailer.chunks(chunk_bytes) {
+            self.write_chunk(chunk.to_vec())?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for HammingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (chunk_bytes, _) = chunk_sizes(&self.codec);
+
+        // whole chunks go out as soon as they're full, leaving the trailer
+        // less than a chunk of data
+        for &byte in buf {
+            self.pending.push(byte);
+            if self.pending.len() == chunk_bytes {
+                let chunk = std::mem::replace(&mut self.pending, Vec::with_capacity(chunk_bytes));
+                self.write_chunk(chunk)?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Flushes all complete chunks. The final partial chunk is only written by
+    /// [`HammingWriter::finish`].
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for HammingWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.write_trailer();
+            let _ = self.flush();
+        }
+    }
+}
+
+/// Decodes a stream produced by a [`HammingWriter`] using the same codec.
+pub struct HammingReader<R: Read> {
+    inner: R,
+    codec: BlockHamming,
+    /// Decoded bytes ready to be handed out.
+    ready: Vec<u8>,
+    position: usize,
+    /// The most recently decoded chunks, held back in case they're the
+    /// trailer.
+    held: VecDeque<Vec<u8>>,
+    finished: bool,
+    stats: StreamStats,
+}
+
+impl<R: Read> HammingReader<R> {
+    /// Creates a reader using Hamming(15,11) blocks.
+    pub fn new(inner: R) -> Self {
+        Self::with_codec(inner, BlockHamming::hamming1511())
+    }
+
+    pub fn with_codec(inner: R, codec: BlockHamming) -> Self {
+        Self {
+            inner,
+            codec,
+            ready: Vec::new(),
+            position: 0,
+            held: VecDeque::new(),
+            finished: false,
+            stats: StreamStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads up to one full encoded chunk, returning how many bytes were read.
+    fn read_encoded_chunk(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.inner.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(filled)
+    }
+
+    /// Decodes the next chunk, moving the held ones the trailer can't reach
+    /// anymore to `ready`.
+    fn fill(&mut self) -> io::Result<()> {
+        let (chunk_bytes, code_bytes) = chunk_sizes(&self.codec);
+        let mut encoded = vec![0; code_bytes];
+
+        match self.read_encoded_chunk(&mut encoded)? {
+            0 => {
+                // the held chunks end with the trailer
+                if self.held.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "missing stream trailer",
+                    ));
+                }
+                let held: Vec<u8> = self.held.drain(..).flatten().collect();
+                let (rest, count) = held.split_at(held.len() - TRAILER_COUNT_BYTES);
+                let valid = u32::from_be_bytes(count.try_into().expect("split off the count"));
+                let valid = valid as usize;
+                let invalid =
+                    || io::Error::new(io::ErrorKind::InvalidData, "invalid stream trailer");
+                if valid >= chunk_bytes {
+                    return Err(invalid());
+                }
+                let trailer_bytes = (valid + TRAILER_COUNT_BYTES).next_multiple_of(chunk_bytes);
+                // a one chunk trailer can have a whole chunk of data held ahead
+                // of it
+                let start = held.len().checked_sub(trailer_bytes).ok_or_else(invalid)?;
+                self.ready = [&rest[..start], &rest[start..start + valid]].concat();
+                self.finished = true;
+            }
+            read if read == code_bytes => {
+                let outcome = self
+                    .codec
+                    .decode(&BitVec::from_whole_bytes(encoded))
+                    .map_err(to_io_error)?;
+                self.stats.chunks += 1;
+                self.stats.corrected_blocks += outcome.status.corrections();
+
+                self.held.push_back(outcome.data.into_inner());
+                self.ready = match self.held.len() > MAX_TRAILER_CHUNKS {
+                    true => self.held.pop_front().unwrap_or_default(),
+                    false => Vec::new(),
+                };
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "stream ended in the middle of a chunk",
+                ));
+            }
+        }
+
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for HammingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.ready.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+
+        let available = &self.ready[self.position..];
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.position += read;
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    /// Writer that accepts at most 3 bytes per call.
+    struct ShortWriter(Vec<u8>);
+
+    impl Write for ShortWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = buf.len().min(3);
+            self.0.extend_from_slice(&buf[..written]);
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn round_trip(data: &[u8], codec: BlockHamming) -> Vec<u8> {
+        let mut writer = HammingWriter::with_codec(Vec::new(), codec);
+        writer.write_all(data).unwrap();
+        let encoded = writer.finish().unwrap();
+
+        let mut decoded = Vec::new();
+        HammingReader::with_codec(Cursor::new(encoded), codec)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        decoded
+    }
+
+    #[test]
+    fn test_round_trip_edge_lengths() {
+        for codec in [BlockHamming::hamming74(), BlockHamming::hamming1511()] {
+            for len in [0, 1, 3, 4, 10, 11, 12, 22, 100] {
+                let data: Vec<u8> = (0..len as u8).collect();
+                assert_eq!(round_trip(&data, codec), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_long_chunks() {
+        // 502 byte chunks, too long for a byte to count what's left of one
+        let codec = BlockHamming::new(9).unwrap();
+        for len in [300, 498, 499, 501, 502, 1003] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            assert_eq!(round_trip(&data, codec), data, "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_multi_megabyte_stream() {
+        let mut data = vec![0u8; 2 * 1024 * 1024 + 7];
+        StdRng::seed_from_u64(552).fill(data.as_mut_slice());
+
+        let mut encoded = Vec::new();
+        let mut writer = HammingWriter::new(&mut encoded);
+        io::copy(&mut Cursor::new(&data), &mut writer).unwrap();
+        writer.finish().unwrap();
+
+        // 11 data bytes become 15 code bytes, plus the trailer, two chunks
+        // here as the 9 bytes left over and their count don't fit in one
+        assert_eq!(encoded.len(), (data.len() / 11 + 2) * 15);
+
+        let mut reader = HammingReader::new(Cursor::new(encoded));
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+
+        assert_eq!(decoded, data);
+        assert_eq!(reader.stats().corrected_blocks, 0);
+    }
+
+    #[test]
+    fn test_corrections_are_counted() {
+        let data = b"teaching rocks to fix themselves".to_vec();
+        let mut writer = HammingWriter::new(Vec::new());
+        writer.write_all(&data).unwrap();
+        let mut encoded = writer.finish().unwrap();
+
+        // one flip in each of the first three 15-bit blocks
+        encoded[0] ^= 0b1000_0000;
+        encoded[2] ^= 0b0000_0100;
+        encoded[4] ^= 0b0000_0001;
+
+        let mut reader = HammingReader::new(Cursor::new(encoded));
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+
+        assert_eq!(decoded, data);
+        assert_eq!(reader.stats().corrected_blocks, 3);
+        // two of data, and a trailer taking two for the last 10 bytes
+        assert_eq!(reader.stats().chunks, 4);
+    }
+
+    #[test]
+    fn test_short_writes_downstream() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut writer = HammingWriter::new(ShortWriter(Vec::new()));
+        writer.write_all(&data).unwrap();
+        let encoded = writer.finish().unwrap().0;
+
+        let mut decoded = Vec::new();
+        HammingReader::new(Cursor::new(encoded))
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_truncated_streams() {
+        let mut writer = HammingWriter::new(Vec::new());
+        writer.write_all(&[1; 30]).unwrap();
+        let encoded = writer.finish().unwrap();
+
+        // ends in the middle of a chunk
+        let mut reader = HammingReader::new(Cursor::new(&encoded[..encoded.len() - 1]));
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // nothing at all, not even a trailer
+        let mut reader = HammingReader::new(Cursor::new(Vec::new()));
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let garbage: Vec<u8> = (0..15).map(|_| rng.random()).collect();
+        let mut reader = HammingReader::new(Cursor::new(garbage));
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_drop_finishes_the_stream() {
+        let mut encoded = Vec::new();
+        {
+            let mut writer = HammingWriter::new(&mut encoded);
+            writer.write_all(b"dropped").unwrap();
+        }
+
+        let mut decoded = Vec::new();
+        HammingReader::new(Cursor::new(encoded))
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, b"dropped");
+    }
+}
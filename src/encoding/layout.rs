@@ -0,0 +1,147 @@
+use std::fmt::{Display, Formatter};
+
+use crate::encoding::{hamming::HammingCodeBase, mapping};
+
+/// What a codeword position holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionKind {
+    /// A parity bit, covering every position whose 1-based index shares a bit
+    /// with `mask`. The mask is also its own 1-based position.
+    Parity { mask: usize },
+    /// The 0-based `index`-th data bit.
+    Data { index: usize },
+}
+
+/// Where the parity and data bits of a [`Hamming`](crate::encoding::hamming::Hamming)
+/// codeword go, for a given number of data bits.
+///
+/// Displays as an annotated map of the codeword with 1-based labels, e.g.
+/// `p1 p2 d1 p4 d2 d3 d4` for 4 data bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodewordLayout {
+    data_len: usize,
+    parity_len: usize,
+}
+
+impl CodewordLayout {
+    pub fn for_data_len(data_len: usize) -> Self {
+        Self {
+            data_len,
+            parity_len: HammingCodeBase::calculate_parity_count(data_len),
+        }
+    }
+
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    pub fn parity_len(&self) -> usize {
+        self.parity_len
+    }
+
+    pub fn total_len(&self) -> usize {
+        self.data_len + self.parity_len
+    }
+
+    /// 0-based positions of the parity bits.
+    pub fn parity_positions(&self) -> Vec<usize> {
+        (0..self.parity_len).map(|i| (1 << i) - 1).collect()
+    }
+
+    /// 0-based codeword position of the 0-based `index`-th data bit.
+    pub fn data_position(&self, index: usize) -> usize {
+        mapping::data_to_codeword(index)
+    }
+
+    /// What the 0-based `position` holds. Positions past the end are
+    /// classified as if the codeword went on.
+    pub fn position_kind(&self, position: usize) -> PositionKind {
+        match mapping::codeword_to_data(position) {
+            Some(index) => PositionKind::Data { index },
+            None => PositionKind::Parity { mask: position + 1 },
+        }
+    }
+}
+
+impl Display for CodewordLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for position in 0..self.total_len() {
+            if position > 0 {
+                write!(f, " ")?;
+            }
+            match self.position_kind(position) {
+                PositionKind::Parity { mask } => write!(f, "p{}", mask)?,
+                PositionKind::Data { index } => write!(f, "d{}", index + 1)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{
+        bitvec::BitVec,
+        hamming::{Hamming, HammingCode},
+    };
+
+    #[test]
+    fn test_textbook_layouts() {
+        let layout = CodewordLayout::for_data_len(4);
+        assert_eq!(layout.total_len(), 7);
+        assert_eq!(layout.parity_positions(), vec![0, 1, 3]);
+        assert_eq!(layout.to_string(), "p1 p2 d1 p4 d2 d3 d4");
+
+        let layout = CodewordLayout::for_data_len(11);
+        assert_eq!(layout.total_len(), 15);
+        assert_eq!(layout.parity_positions(), vec![0, 1, 3, 7]);
+        assert_eq!(
+            (0..11).map(|i| layout.data_position(i)).collect::<Vec<_>>(),
+            vec![2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14]
+        );
+        assert_eq!(layout.position_kind(7), PositionKind::Parity { mask: 8 });
+        assert_eq!(layout.position_kind(8), PositionKind::Data { index: 4 });
+        assert_eq!(
+            layout.to_string(),
+            "p1 p2 d1 p4 d2 d3 d4 p8 d5 d6 d7 d8 d9 d10 d11"
+        );
+
+        assert_eq!(CodewordLayout::for_data_len(0).to_string(), "");
+    }
+
+    #[test]
+    fn test_matches_encode() {
+        for k in (1..=80).chain([247, 1000]) {
+            let layout = CodewordLayout::for_data_len(k);
+            assert_eq!(
+                layout.parity_positions(),
+                HammingCodeBase::parity_positions(layout.total_len())
+            );
+
+            for i in 0..k {
+                let position = layout.data_position(i);
+                assert_eq!(
+                    layout.position_kind(position),
+                    PositionKind::Data { index: i }
+                );
+
+                // a lone data bit shows up where the layout says, along with
+                // exactly the parity bits covering it
+                let codeword = Hamming.encode(&BitVec::from_fn(k, |j| j == i)).unwrap();
+                assert_eq!(codeword.len(), layout.total_len());
+                for p in 0..codeword.len() {
+                    let expected = match layout.position_kind(p) {
+                        PositionKind::Parity { mask } => (position + 1) & mask != 0,
+                        PositionKind::Data { index } => index == i,
+                    };
+                    assert_eq!(
+                        codeword.get(p),
+                        Some(expected),
+                        "k {k}, bit {i}, position {p}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Line codes turning bits into the symbols actually put on the wire.
+
+use crate::encoding::bitvec::BitVec;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LineCodeError {
+    #[error("Line signal of {len} bits is not a whole number of symbols")]
+    OddLength { len: usize },
+    #[error("Invalid symbol at index {index}")]
+    InvalidSymbol { index: usize },
+}
+
+/// Which half-bit pair stands for which bit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ManchesterConvention {
+    /// IEEE 802.3: a 0 goes high to low (`10`), a 1 low to high (`01`).
+    #[default]
+    Ieee,
+    /// G. E. Thomas: the opposite, a 0 is `01` and a 1 is `10`.
+    Thomas,
+}
+
+/// Manchester line code: every bit becomes a pair of opposite half-bits, so
+/// there's a transition in the middle of every symbol to recover the clock
+/// from.
+///
+/// The pairs `00` and `11` never come out of the encoder. By default the
+/// decoder rejects the first one it sees, but it can be told to put up with a
+/// few, see [`Manchester::with_max_invalid`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Manchester {
+    convention: ManchesterConvention,
+    max_invalid: usize,
+}
+
+impl Manchester {
+    pub fn new(convention: ManchesterConvention) -> Self {
+        Self {
+            convention,
+            max_invalid: 0,
+        }
+    }
+
+    /// Lets the decoder carry on past up to `max_invalid` invalid symbols,
+    /// decoding each of them as a 0 and leaving it to the error correction
+    /// behind it.
+    pub fn with_max_invalid(mut self, max_invalid: usize) -> Self {
+        self.max_invalid = max_invalid;
+        self
+    }
+
+    pub fn convention(&self) -> ManchesterConvention {
+        self.convention
+    }
+
+    pub fn max_invalid(&self) -> usize {
+        self.max_invalid
+    }
+
+    /// First half-bit of the symbol for `bit`, the second is its opposite.
+    fn first_half(&self, bit: bool) -> bool {
+        match self.convention {
+            ManchesterConvention::Ieee => !bit,
+            ManchesterConvention::Thomas => bit,
+        }
+    }
+
+    pub fn encode(&self, bits: &BitVec) -> BitVec {
+        let mut line = BitVec::with_capacity(bits.len() * 2);
+        for i in 0..bits.len() {
+            let first = self.first_half(bits.get(i).unwrap_or(false));
+            line.push(first);
+            line.push(!first);
+        }
+        line
+    }
+
+    pub fn decode(&self, line: &BitVec) -> Result<BitVec, LineCodeError> {
+        self.decode_reporting(line).map(|(bits, _)| bits)
+    }
+
+    /// Like [`Manchester::decode`], but also hands back the indices of the
+    /// invalid symbols it put up with.
+    ///
+    /// Fails with [`LineCodeError::InvalidSymbol`] on the first invalid symbol
+    /// past the allowed number.
+    pub fn decode_reporting(&self, line: &BitVec) -> Result<(BitVec, Vec<usize>), LineCodeError> {
+        if !line.len().is_multiple_of(2) {
+            return Err(LineCodeError::OddLength { len: line.len() });
+        }
+
+        let mut bits = BitVec::with_capacity(line.len() / 2);
+        let mut invalid = Vec::new();
+        for index in 0..line.len() / 2 {
+            let first = line.get(2 * index).unwrap_or(false);
+            let second = line.get(2 * index + 1).unwrap_or(false);
+
+            if first == second {
+                if invalid.len() == self.max_invalid {
+                    return Err(LineCodeError::InvalidSymbol { index });
+                }
+                invalid.push(index);
+                bits.push(false);
+            } else {
+                bits.push(self.first_half(true) == first);
+            }
+        }
+
+        Ok((bits, invalid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::encoding::{
+        channel::{BinarySymmetricChannel, Channel},
+        hamming::{DecodeStatus, Hamming, HammingCode},
+    };
+    use crate::testing::bitvec_strategy;
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(data in bitvec_strategy(500), thomas in any::<bool>()) {
+            let convention = if thomas {
+                ManchesterConvention::Thomas
+            } else {
+                ManchesterConvention::Ieee
+            };
+            let manchester = Manchester::new(convention);
+            let line = manchester.encode(&data);
+            prop_assert_eq!(line.len(), data.len() * 2);
+            prop_assert_eq!(manchester.decode(&line).unwrap().to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_conventions() {
+        let bits = BitVec::from_vec(vec![false, true, true]);
+        assert_eq!(
+            Manchester::default().encode(&bits).to_vec(),
+            [true, false, false, true, false, true]
+        );
+        assert_eq!(
+            Manchester::new(ManchesterConvention::Thomas)
+                .encode(&bits)
+                .to_vec(),
+            [false, true, true, false, true, false]
+        );
+    }
+
+    #[test]
+    fn test_invalid_symbols() {
+        let line = Manchester::default().encode(&BitVec::ones(6));
+        let mut broken = line.clone();
+        broken.toggle(4).unwrap(); // symbol 2 becomes 11
+        broken.toggle(9).unwrap(); // symbol 4 becomes 00
+
+        assert_eq!(
+            Manchester::default().decode(&broken).unwrap_err(),
+            LineCodeError::InvalidSymbol { index: 2 }
+        );
+        assert_eq!(
+            Manchester::default()
+                .with_max_invalid(1)
+                .decode(&broken)
+                .unwrap_err(),
+            LineCodeError::InvalidSymbol { index: 4 }
+        );
+
+        let (bits, invalid) = Manchester::default()
+            .with_max_invalid(2)
+            .decode_reporting(&broken)
+            .unwrap();
+        assert_eq!(invalid, vec![2, 4]);
+        assert_eq!(bits.to_vec(), [true, true, false, true, false, true]);
+
+        let mut odd = line.clone();
+        odd.push(true);
+        assert_eq!(
+            Manchester::default().decode(&odd).unwrap_err(),
+            LineCodeError::OddLength { len: 13 }
+        );
+    }
+
+    #[test]
+    fn test_through_hamming_and_channel() {
+        let data = BitVec::random(120, &mut rand::rng());
+        let codeword = Hamming.encode(&data).unwrap();
+        let manchester = Manchester::default().with_max_invalid(1);
+        let line = manchester.encode(&codeword);
+
+        // a single hit on the wire is one invalid symbol, which Hamming cleans up
+        for i in 0..line.len() {
+            let mut received = line.clone();
+            received.toggle(i).unwrap();
+
+            let (bits, invalid) = manchester.decode_reporting(&received).unwrap();
+            assert_eq!(invalid, vec![i / 2]);
+            let outcome = Hamming.decode(&bits).unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+            if codeword.get(i / 2) == Some(true) {
+                assert_eq!(outcome.status, DecodeStatus::corrected_at(i / 2));
+            } else {
+                assert!(outcome.status.is_clean());
+            }
+        }
+
+        // and over a noisy channel, as long as it's at most one hit
+        let mut recovered = 0;
+        for seed in 0..50 {
+            let mut received = line.clone();
+            BinarySymmetricChannel::new(0.002, Some(seed)).transmit(&mut received);
+            if let Ok(bits) = manchester.decode(&received) {
+                let outcome = Hamming.decode(&bits).unwrap();
+                if outcome.data.to_vec() == data.to_vec() {
+                    recovered += 1;
+                }
+            }
+        }
+        assert!(recovered >= 40, "only recovered {recovered} of 50 frames");
+    }
+}
@@ -0,0 +1,98 @@
+//! Translating between codeword positions and data positions of a
+//! [`Hamming`](crate::encoding::hamming::Hamming) codeword.
+//!
+//! Parity bits sit at the 1-based powers of two, data bits fill in the rest in
+//! order. Both functions take and return 0-based positions, and don't depend
+//! on the length of the codeword.
+
+/// Index of the data bit at 0-based codeword position `pos`, or `None` if a
+/// parity bit sits there.
+pub fn codeword_to_data(pos: usize) -> Option<usize> {
+    let position = pos + 1;
+    // every power of two up to `position` is a parity bit before it
+    (!position.is_power_of_two()).then(|| position - position.ilog2() as usize - 2)
+}
+
+/// 0-based codeword position of the `pos`-th data bit.
+pub fn data_to_codeword(pos: usize) -> usize {
+    // skip over the parity bits before it, one power of two at a time
+    let mut position = pos + 1;
+    let mut parity = 1;
+    while parity <= position {
+        position += 1;
+        parity <<= 1;
+    }
+    position - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{
+        bitvec::BitVec,
+        hamming::{Hamming, HammingCode},
+    };
+
+    #[test]
+    fn test_first_positions() {
+        // p1 p2 d1 p4 d2 d3 d4 p8 d5
+        assert_eq!(
+            (0..9).map(codeword_to_data).collect::<Vec<_>>(),
+            vec![
+                None,
+                None,
+                Some(0),
+                None,
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                Some(4)
+            ]
+        );
+        assert_eq!(
+            (0..5).map(data_to_codeword).collect::<Vec<_>>(),
+            vec![2, 4, 5, 6, 8]
+        );
+    }
+
+    #[test]
+    fn test_inverses() {
+        let mut data_index = 0;
+        for pos in 0..1024 {
+            match codeword_to_data(pos) {
+                Some(index) => {
+                    // data positions are numbered in order, no gaps
+                    assert_eq!(index, data_index, "position {pos}");
+                    assert_eq!(data_to_codeword(index), pos);
+                    data_index += 1;
+                }
+                None => assert!((pos + 1).is_power_of_two(), "position {pos}"),
+            }
+        }
+        for index in 0..1024 {
+            assert_eq!(codeword_to_data(data_to_codeword(index)), Some(index));
+        }
+    }
+
+    #[test]
+    fn test_matches_encode() {
+        // 1014 data bits take 11 parity bits, covering all the positions above
+        let k = 1014;
+        let data = BitVec::random(k, &mut rand::rng());
+        let codeword = Hamming.encode(&data).unwrap();
+        assert_eq!(codeword.len(), 1025);
+        for index in 0..k {
+            assert_eq!(codeword.get(data_to_codeword(index)), data.get(index));
+        }
+
+        // and a corrected data bit is reported at the matching data position
+        for pos in (0..1025).step_by(37) {
+            let mut corrupted = codeword.clone();
+            corrupted.toggle(pos).unwrap();
+            let outcome = Hamming.decode(&corrupted).unwrap();
+            assert_eq!(outcome.status.data_pos(), codeword_to_data(pos));
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+    }
+}
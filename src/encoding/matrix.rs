@@ -0,0 +1,109 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, Hamming, HammingCode, HammingCodeBase, HammingError},
+};
+
+/// Hamming encoder for a fixed data length that multiplies by the generator
+/// matrix instead of placing and computing bits one by one.
+///
+/// The rows of [`HammingCodeBase::generator_matrix`] are computed once up
+/// front, encoding XORs together the rows of the set data bits. The codewords
+/// are bit for bit the same as [`Hamming`]'s, and decoding goes through
+/// [`Hamming`] as well.
+#[derive(Debug, Clone)]
+pub struct MatrixHamming {
+    data_bits: usize,
+    code_bits: usize,
+    generator: Vec<BitVec>,
+}
+
+impl MatrixHamming {
+    pub fn new(data_bits: usize) -> Self {
+        Self {
+            data_bits,
+            code_bits: data_bits + HammingCodeBase::calculate_parity_count(data_bits),
+            generator: HammingCodeBase::generator_matrix(data_bits),
+        }
+    }
+
+    pub fn data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    pub fn code_bits(&self) -> usize {
+        self.code_bits
+    }
+}
+
+impl HammingCode for MatrixHamming {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        if data.len() != self.data_bits {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+
+        let mut codeword = BitVec::zeros(self.code_bits);
+        for (i, row) in self.generator.iter().enumerate() {
+            if data.get(i) == Some(true) {
+                codeword
+                    .xor_assign(row)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            }
+        }
+        Ok(codeword)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        if codeword.len() != self.code_bits {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: self.code_bits,
+            });
+        }
+        Hamming.decode(codeword)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_hamming() {
+        let mut rng = rand::rng();
+        for k in (0..=70).chain([100, 247, 256, 500]) {
+            let codec = MatrixHamming::new(k);
+            for _ in 0..10 {
+                let data = BitVec::random(k, &mut rng);
+                let codeword = codec.encode(&data).unwrap();
+                let expected = Hamming.encode(&data).unwrap();
+                assert_eq!(codeword.len(), codec.code_bits());
+                assert_eq!(codeword.clone().into_inner(), expected.into_inner());
+
+                let outcome = codec.decode(&codeword).unwrap();
+                assert!(outcome.status.is_clean());
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_lengths() {
+        let codec = MatrixHamming::new(11);
+        assert!(codec.encode(&BitVec::zeros(10)).is_err());
+        assert_eq!(
+            codec.decode(&BitVec::zeros(14)).unwrap_err(),
+            HammingError::InvalidCodewordLength {
+                got: 14,
+                expected_for_r: 15
+            }
+        );
+    }
+}
@@ -1,2 +1,37 @@
 pub mod bitvec;
+pub mod block;
+pub mod channel;
+pub mod checksum;
+pub mod chunked;
+pub mod corruption;
+pub mod golay;
 pub mod hamming;
+pub mod interleave;
+pub mod io;
+pub mod layout;
+pub mod line;
+pub mod mapping;
+pub mod matrix;
+pub mod prepared;
+pub mod product;
+pub mod repetition;
+pub mod scrambler;
+pub mod secded;
+mod select;
+pub mod shortened;
+#[cfg(feature = "simd")]
+mod simd;
+mod simulation;
+pub mod source;
+pub mod stats;
+mod storage;
+pub mod stream;
+pub mod syndrome;
+mod table;
+
+pub use select::{
+    CodeChoice, DEFAULT_MAX_OVERHEAD, DEFAULT_MIN_CORRECTION_DENSITY, auto_codec, select_code,
+};
+pub use simulation::{
+    ChannelModel, CodecKind, SimulationConfig, SimulationPoint, SimulationReport, simulate,
+};
@@ -0,0 +1,212 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingCodeBase, HammingError},
+    source::BitSource,
+};
+
+/// Hamming codec for one fixed data length, with everything that only depends
+/// on the length worked out up front.
+///
+/// [`Hamming`](crate::encoding::hamming::Hamming) recomputes the parity count
+/// and bit layout on every call, which adds up when encoding many frames of
+/// the same size. This keeps the codeword index of every data bit and, per
+/// parity bit, a mask of the codeword bits it covers, so a parity is a masked
+/// popcount over whole bytes. Codewords are bit for bit the same as
+/// `Hamming`'s.
+#[derive(Debug, Clone)]
+pub struct PreparedHamming {
+    data_bits: usize,
+    code_bits: usize,
+    /// 0-based codeword index of every data bit.
+    data_positions: Vec<usize>,
+    /// One mask per parity bit, set at every codeword bit it covers.
+    masks: Vec<BitVec>,
+}
+
+impl PreparedHamming {
+    pub fn for_len(data_bits: usize) -> Self {
+        let code_bits = data_bits + HammingCodeBase::calculate_parity_count(data_bits);
+        let data_positions = (0..code_bits)
+            .filter(|i| !(i + 1).is_power_of_two())
+            .collect();
+        let masks = HammingCodeBase::parity_check_matrix(code_bits);
+
+        Self {
+            data_bits,
+            code_bits,
+            data_positions,
+            masks,
+        }
+    }
+
+    pub fn data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    pub fn code_bits(&self) -> usize {
+        self.code_bits
+    }
+
+    pub fn parity_bits(&self) -> usize {
+        self.masks.len()
+    }
+
+    /// Parity of the bits of `codeword` under `mask`. Anything past the mask,
+    /// like stray bits in the last byte, is ignored.
+    fn masked_parity(codeword: &BitVec, mask: &BitVec) -> bool {
+        let ones = codeword
+            .data
+            .iter()
+            .zip(mask.data.iter())
+            .fold(0, |acc, (byte, mask)| acc ^ (byte & mask).count_ones());
+        ones % 2 == 1
+    }
+
+    fn check_len(&self, codeword: &BitVec) -> Result<(), HammingError> {
+        if codeword.len() != self.code_bits {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: self.code_bits,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl HammingCode for PreparedHamming {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        self.encode_from(data)
+    }
+
+    fn encode_from<S: BitSource + ?Sized>(&self, data: &S) -> Result<BitVec, HammingError> {
+        if data.bit_len() != self.data_bits {
+            return Err(HammingError::DataBitsInvalid(data.bit_len()));
+        }
+
+        let mut codeword = BitVec::zeros(self.code_bits);
+        for (i, &position) in self.data_positions.iter().enumerate() {
+            if data.bit(i) == Some(true) {
+                codeword
+                    .set(position, true)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            }
+        }
+
+        // the parity bits are still 0, so they don't count towards themselves
+        for (i, mask) in self.masks.iter().enumerate() {
+            if Self::masked_parity(&codeword, mask) {
+                codeword
+                    .set((1 << i) - 1, true)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            }
+        }
+
+        Ok(codeword)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let error_pos = self.compute_syndrome(codeword)?;
+
+        let (status, flip) = if error_pos == 0 {
+            (DecodeStatus::Clean, None)
+        } else if error_pos <= self.code_bits {
+            (
+                DecodeStatus::corrected_at(error_pos - 1),
+                Some(error_pos - 1),
+            )
+        } else {
+            // a syndrome pointing past the end can only come from multiple errors
+            (DecodeStatus::Uncorrectable, None)
+        };
+
+        let mut data = BitVec::with_capacity(self.data_bits);
+        for &position in &self.data_positions {
+            let bit = codeword
+                .get(position)
+                .ok_or(HammingError::UnexpectedOutOfBounds)?;
+            data.push(bit ^ (flip == Some(position)));
+        }
+
+        Ok(DecodeOutcome { data, status })
+    }
+
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        self.check_len(codeword)?;
+        Ok(self
+            .masks
+            .iter()
+            .enumerate()
+            .filter(|(_, mask)| Self::masked_parity(codeword, mask))
+            .fold(0, |syndrome, (i, _)| syndrome | (1 << i)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::hamming::Hamming;
+
+    #[test]
+    fn test_matches_hamming() {
+        let mut rng = rand::rng();
+        for k in (0..=130).chain([247, 500, 1013]) {
+            let codec = PreparedHamming::for_len(k);
+            for _ in 0..5 {
+                let data = BitVec::random(k, &mut rng);
+                let codeword = codec.encode(&data).unwrap();
+                assert_eq!(codeword.len(), codec.code_bits());
+                assert_eq!(codeword.to_vec(), Hamming.encode(&data).unwrap().to_vec());
+
+                let outcome = codec.decode(&codeword).unwrap();
+                assert!(outcome.status.is_clean());
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+
+                for i in 0..codeword.len() {
+                    let mut corrupted = codeword.clone();
+                    corrupted.toggle(i).unwrap();
+                    let outcome = codec.decode(&corrupted).unwrap();
+                    let expected = Hamming.decode(&corrupted).unwrap();
+                    assert_eq!(outcome.status, expected.status);
+                    assert_eq!(outcome.data.to_vec(), data.to_vec());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_syndrome() {
+        let codec = PreparedHamming::for_len(12);
+        let codeword = codec.encode(&BitVec::zeros(12)).unwrap();
+        let mut corrupted = codeword.clone();
+        // positions 2 and 17 give syndrome 19 in a 17 bit codeword
+        corrupted.toggle(1).unwrap();
+        corrupted.toggle(16).unwrap();
+        assert_eq!(codec.compute_syndrome(&corrupted), Ok(19));
+        assert!(codec.decode(&corrupted).unwrap().status.is_uncorrectable());
+    }
+
+    #[test]
+    fn test_wrong_lengths() {
+        let codec = PreparedHamming::for_len(11);
+        assert_eq!(codec.parity_bits(), 4);
+        assert_eq!(
+            codec.encode(&BitVec::zeros(10)).unwrap_err(),
+            HammingError::DataBitsInvalid(10)
+        );
+        assert_eq!(
+            codec.decode(&BitVec::zeros(14)).unwrap_err(),
+            HammingError::InvalidCodewordLength {
+                got: 14,
+                expected_for_r: 15
+            }
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, Hamming, HammingCode, HammingCodeBase, HammingError},
+};
+
+/// Two-dimensional product Hamming code.
+///
+/// Each block of `rows * cols` data bits is laid out row by row into a matrix,
+/// every row is Hamming encoded and then every column of the result, so a
+/// block is sent as `code_rows() * code_cols()` bits, again row by row.
+///
+/// Decoding alternates between correcting every row and every column until a
+/// pass changes nothing or `max_passes` is reached. An error missed or even
+/// introduced by a row can still be fixed by its column and the other way
+/// round, so many patterns of two or more errors per block are corrected, e.g.
+/// any two errors, or three in an L shape.
+///
+/// Data is padded to whole blocks with zeros like
+/// [`BlockHamming`](crate::encoding::block::BlockHamming), and `decode`
+/// hands the padding back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductHamming {
+    rows: usize,
+    cols: usize,
+    max_passes: usize,
+}
+
+/// Decode result of a [`ProductHamming`] along with how many bits each
+/// row/column pass corrected.
+#[derive(Debug, Clone)]
+pub struct ProductDecodeOutcome {
+    pub data: BitVec,
+    pub status: DecodeStatus,
+    /// Corrections made in each pass, summed over all blocks. A pass corrects
+    /// the rows and then the columns.
+    pub passes: Vec<usize>,
+}
+
+impl ProductHamming {
+    pub const DEFAULT_MAX_PASSES: usize = 4;
+
+    /// Creates a product code over a `rows` by `cols` data matrix.
+    pub fn new(rows: usize, cols: usize) -> Result<Self, HammingError> {
+        if rows == 0 || cols == 0 {
+            return Err(HammingError::DataBitsInvalid(rows * cols));
+        }
+        Ok(Self {
+            rows,
+            cols,
+            max_passes: Self::DEFAULT_MAX_PASSES,
+        })
+    }
+
+    /// Limits decoding to `max_passes` row/column passes, at least one.
+    pub fn with_max_passes(mut self, max_passes: usize) -> Self {
+        self.max_passes = max_passes.max(1);
+        self
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of rows of an encoded block.
+    pub fn code_rows(&self) -> usize {
+        self.rows + HammingCodeBase::calculate_parity_count(self.rows)
+    }
+
+    /// Number of columns of an encoded block.
+    pub fn code_cols(&self) -> usize {
+        self.cols + HammingCodeBase::calculate_parity_count(self.cols)
+    }
+
+    pub fn data_bits_per_block(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn code_bits_per_block(&self) -> usize {
+        self.code_rows() * self.code_cols()
+    }
+
+    fn column(&self, matrix: &BitVec, col: usize) -> BitVec {
+        let cols = self.code_cols();
+        BitVec::from_fn(self.code_rows(), |row| {
+            matrix.get(row * cols + col).unwrap_or(false)
+        })
+    }
+
+    fn encode_block(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let (code_rows, code_cols) = (self.code_rows(), self.code_cols());
+
+        let mut rows = Vec::with_capacity(self.rows);
+        for row in 0..self.rows {
+            let start = row * self.cols;
+            let bits = data
+                .slice(start..start + self.cols)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            rows.push(Hamming.encode(&bits)?);
+        }
+        let rows = BitVec::concat_all(&rows);
+
+        let mut block = BitVec::zeros(code_rows * code_cols);
+        for col in 0..code_cols {
+            let bits = BitVec::from_fn(self.rows, |row| {
+                rows.get(row * code_cols + col).unwrap_or(false)
+            });
+            let column = Hamming.encode(&bits)?;
+            for row in 0..code_rows {
+                block
+                    .set(
+                        row * code_cols + col,
+                        column.get(row).ok_or(HammingError::UnexpectedOutOfBounds)?,
+                    )
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            }
+        }
+
+        Ok(block)
+    }
+
+    /// Corrects every row and then every column once, returning how many bits
+    /// were flipped.
+    fn correction_pass(&self, block: &mut BitVec) -> Result<usize, HammingError> {
+        let (code_rows, code_cols) = (self.code_rows(), self.code_cols());
+        let mut corrected = 0;
+
+        for row in 0..code_rows {
+            let start = row * code_cols;
+            let bits = block
+                .slice(start..start + code_cols)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            let syndrome = Hamming.compute_syndrome(&bits)?;
+            // a syndrome past the end of the row is left to the columns
+            if syndrome != 0 && syndrome <= code_cols {
+                block
+                    .toggle(start + syndrome - 1)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+                corrected += 1;
+            }
+        }
+
+        for col in 0..code_cols {
+            let syndrome = Hamming.compute_syndrome(&self.column(block, col))?;
+            if syndrome != 0 && syndrome <= code_rows {
+                block
+                    .toggle((syndrome - 1) * code_cols + col)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+                corrected += 1;
+            }
+        }
+
+        Ok(corrected)
+    }
+
+    fn is_consistent(&self, block: &BitVec) -> Result<bool, HammingError> {
+        let code_cols = self.code_cols();
+        for row in 0..self.code_rows() {
+            let start = row * code_cols;
+            let bits = block
+                .slice(start..start + code_cols)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            if !Hamming.verify(&bits)? {
+                return Ok(false);
+            }
+        }
+        for col in 0..code_cols {
+            if !Hamming.verify(&self.column(block, col))? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Like [`HammingCode::decode`], but also reports the corrections made in
+    /// every pass.
+    pub fn decode_with_passes(
+        &self,
+        codeword: &BitVec,
+    ) -> Result<ProductDecodeOutcome, HammingError> {
+        let n = self.code_bits_per_block();
+        if !codeword.len().is_multiple_of(n) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+
+        let code_cols = self.code_cols();
+        let mut data = BitVec::with_capacity(codeword.len() / n * self.data_bits_per_block());
+        let mut corrected = Vec::new();
+        let mut passes: Vec<usize> = Vec::new();
+        let mut uncorrectable = false;
+
+        for start in (0..codeword.len()).step_by(n) {
+            let received = codeword
+                .slice(start..start + n)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            let mut block = received.clone();
+
+            for pass in 0..self.max_passes {
+                let count = self.correction_pass(&mut block)?;
+                if pass == passes.len() {
+                    passes.push(0);
+                }
+                passes[pass] += count;
+                if count == 0 {
+                    break;
+                }
+            }
+
+            uncorrectable |= !self.is_consistent(&block)?;
+            corrected.extend(
+                (0..n)
+                    .filter(|&i| block.get(i) != received.get(i))
+                    .map(|i| start + i),
+            );
+
+            for row in (0..self.code_rows()).filter(|row| !(row + 1).is_power_of_two()) {
+                let bits = block
+                    .slice(row * code_cols..(row + 1) * code_cols)
+                    .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+                let row_data = Hamming.extract_data(&bits);
+                for i in 0..row_data.len() {
+                    data.push(row_data.get(i).ok_or(HammingError::UnexpectedOutOfBounds)?);
+                }
+            }
+        }
+
+        let status = if uncorrectable {
+            DecodeStatus::Uncorrectable
+        } else {
+            DecodeStatus::corrected_many(corrected)
+        };
+
+        Ok(ProductDecodeOutcome {
+            data,
+            status,
+            passes,
+        })
+    }
+}
+
+impl HammingCode for ProductHamming {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let k = self.data_bits_per_block();
+        let blocks = data.len().div_ceil(k);
+        let mut codewords = Vec::with_capacity(blocks);
+
+        for block in 0..blocks {
+            let start = block * k;
+            let chunk = BitVec::from_fn(k, |i| data.get(start + i).unwrap_or(false));
+            codewords.push(self.encode_block(&chunk)?);
+        }
+
+        Ok(BitVec::concat_all(&codewords))
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let ProductDecodeOutcome { data, status, .. } = self.decode_with_passes(codeword)?;
+        Ok(DecodeOutcome { data, status })
+    }
+
+    fn verify(&self, codeword: &BitVec) -> Result<bool, HammingError> {
+        let n = self.code_bits_per_block();
+        if !codeword.len().is_multiple_of(n) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+
+        for start in (0..codeword.len()).step_by(n) {
+            let block = codeword
+                .slice(start..start + n)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+            if !self.is_consistent(&block)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::testing::bitvec_strategy;
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(data in bitvec_strategy(300), rows in 1usize..12, cols in 1usize..12) {
+            let codec = ProductHamming::new(rows, cols).unwrap();
+            let codeword = codec.encode(&data).unwrap();
+            prop_assert!(codec.verify(&codeword).unwrap());
+
+            let outcome = codec.decode_with_passes(&codeword).unwrap();
+            prop_assert_eq!(outcome.status, DecodeStatus::Clean);
+            prop_assert!(outcome.passes.iter().all(|&count| count == 0));
+            prop_assert_eq!(
+                outcome.data.slice(0..data.len()).unwrap().to_vec(),
+                data.to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let codec = ProductHamming::new(8, 11).unwrap();
+        assert_eq!((codec.code_rows(), codec.code_cols()), (12, 15));
+        assert_eq!(codec.data_bits_per_block(), 88);
+        assert_eq!(codec.code_bits_per_block(), 180);
+
+        assert!(ProductHamming::new(0, 4).is_err());
+        assert!(ProductHamming::new(4, 0).is_err());
+
+        let codec = ProductHamming::new(2, 2).unwrap();
+        assert!(codec.decode(&BitVec::zeros(10)).is_err());
+    }
+
+    #[test]
+    fn test_corrects_error_patterns() {
+        let codec = ProductHamming::new(8, 8).unwrap();
+        let cols = codec.code_cols();
+        let at = |row: usize, col: usize| row * cols + col;
+
+        let patterns: [&[usize]; 6] = [
+            // same row
+            &[at(2, 2), at(2, 5)],
+            // same column
+            &[at(1, 4), at(6, 4)],
+            // diagonal
+            &[at(3, 3), at(4, 4)],
+            &[at(2, 2), at(5, 5), at(9, 9)],
+            // L shape
+            &[at(2, 2), at(2, 6), at(9, 2)],
+            &[at(0, 0), at(0, 10), at(10, 10)],
+        ];
+
+        let mut rng = rand::rng();
+        for flips in patterns {
+            let data = BitVec::random(64, &mut rng);
+            let mut codeword = codec.encode(&data).unwrap();
+            for &flip in flips {
+                codeword.toggle(flip).unwrap();
+            }
+
+            let outcome = codec.decode_with_passes(&codeword).unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec(), "{:?}", flips);
+            let mut expected = flips.to_vec();
+            expected.sort_unstable();
+            assert_eq!(
+                outcome.status,
+                DecodeStatus::CorrectedMany {
+                    codeword_positions: expected
+                }
+            );
+            assert!(outcome.passes[0] >= flips.len());
+
+            // the same number of errors is too much for a single Hamming codeword
+            let mut plain = Hamming.encode(&data).unwrap();
+            for &flip in &flips[..2] {
+                plain.toggle(flip % plain.len()).unwrap();
+            }
+            assert_ne!(Hamming.decode(&plain).unwrap().data.to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_flags_uncorrectable() {
+        let codec = ProductHamming::new(8, 8).unwrap();
+        let cols = codec.code_cols();
+        let data = BitVec::random(64, &mut rand::rng());
+        let mut codeword = codec.encode(&data).unwrap();
+
+        // a square with two errors in every affected row and column, whose
+        // syndromes point past the end of the 12 bit rows and columns
+        for (row, col) in [(3, 3), (3, 8), (8, 3), (8, 8)] {
+            codeword.toggle(row * cols + col).unwrap();
+        }
+
+        let outcome = codec.decode_with_passes(&codeword).unwrap();
+        assert!(outcome.status.is_uncorrectable());
+        assert_eq!(outcome.passes, vec![0]);
+        assert!(!codec.verify(&codeword).unwrap());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingCodeBase, HammingError},
+};
+
+/// Repetition code: every data bit is sent `factor` times and decoded by
+/// majority vote, so `factor = 3` is triple modular redundancy.
+///
+/// Corrects up to `factor / 2` flips within each repeated symbol. Beyond that
+/// the vote silently picks the wrong bit, there is no way to tell it apart
+/// from a correction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repetition {
+    factor: usize,
+}
+
+impl Repetition {
+    /// Creates a repetition code, `factor` must be odd and at least 3 so the
+    /// vote can't tie.
+    pub fn new(factor: usize) -> Result<Self, HammingError> {
+        if factor < 3 || factor.is_multiple_of(2) {
+            return Err(HammingError::RepetitionFactorInvalid(factor));
+        }
+        Ok(Self { factor })
+    }
+
+    /// Triple modular redundancy.
+    pub fn tmr() -> Self {
+        Self { factor: 3 }
+    }
+
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Majority vote over the symbol starting at `start`, along with the
+    /// number of its bits that disagree.
+    fn vote(&self, codeword: &BitVec, start: usize) -> Result<(bool, usize), HammingError> {
+        let ones = (start..start + self.factor)
+            .map(|i| codeword.get(i).ok_or(HammingError::UnexpectedOutOfBounds))
+            .try_fold(0, |acc, bit| bit.map(|bit| acc + usize::from(bit)))?;
+
+        let majority = ones > self.factor / 2;
+        let minority = if majority { self.factor - ones } else { ones };
+        Ok((majority, minority))
+    }
+
+    fn check_len(&self, codeword: &BitVec) -> Result<(), HammingError> {
+        if !codeword.len().is_multiple_of(self.factor) {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+        Ok(())
+    }
+}
+
+impl HammingCode for Repetition {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        let mut codeword = BitVec::with_capacity(data.len() * self.factor);
+        for i in 0..data.len() {
+            let bit = data.get(i).ok_or(HammingError::UnexpectedOutOfBounds)?;
+            for _ in 0..self.factor {
+                codeword.push(bit);
+            }
+        }
+        Ok(codeword)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        self.check_len(codeword)?;
+
+        let mut data = BitVec::with_capacity(codeword.len() / self.factor);
+        let mut corrected = Vec::new();
+        for start in (0..codeword.len()).step_by(self.factor) {
+            let (bit, minority) = self.vote(codeword, start)?;
+            if minority > 0 {
+                for i in start..start + self.factor {
+                    if codeword.get(i) != Some(bit) {
+                        corrected.push(i);
+                    }
+                }
+            }
+            data.push(bit);
+        }
+
+        Ok(DecodeOutcome {
+            data,
+            status: DecodeStatus::corrected_many(corrected),
+        })
+    }
+
+    /// 1-based position of the first bit outvoted within its symbol, 0 if
+    /// every symbol is unanimous.
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        self.check_len(codeword)?;
+
+        for start in (0..codeword.len()).step_by(self.factor) {
+            let (bit, minority) = self.vote(codeword, start)?;
+            if minority > 0 {
+                let position = (start..start + self.factor)
+                    .find(|&i| codeword.get(i) != Some(bit))
+                    .ok_or(HammingError::UnexpectedOutOfBounds)?;
+                return Ok(position + 1);
+            }
+        }
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Repetition::new(3).unwrap(), Repetition::tmr());
+        assert_eq!(Repetition::new(7).unwrap().factor(), 7);
+
+        for factor in [0, 1, 2, 4, 10] {
+            assert!(matches!(
+                Repetition::new(factor),
+                Err(HammingError::RepetitionFactorInvalid(f)) if f == factor
+            ));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = rand::rng();
+        for factor in [3, 5, 7] {
+            let codec = Repetition::new(factor).unwrap();
+            for len in [0, 1, 8, 33] {
+                let data = BitVec::random(len, &mut rng);
+                let codeword = codec.encode(&data).unwrap();
+                assert_eq!(codeword.len(), len * factor);
+                assert!(codec.verify(&codeword).unwrap());
+
+                let outcome = codec.decode(&codeword).unwrap();
+                assert!(outcome.status.is_clean());
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+            }
+        }
+
+        assert!(Repetition::tmr().decode(&BitVec::zeros(4)).is_err());
+    }
+
+    #[test]
+    fn test_corrects_up_to_half() {
+        let data = BitVec::from_vec(vec![true, false, true]);
+        for factor in [3, 5, 7] {
+            let codec = Repetition::new(factor).unwrap();
+            let codeword = codec.encode(&data).unwrap();
+
+            // flip bits in the middle symbol
+            let mut corrupted = codeword.clone();
+            let flips: Vec<usize> = (factor..factor + factor / 2).collect();
+            for &flip in &flips {
+                corrupted.toggle(flip).unwrap();
+            }
+            assert_eq!(codec.compute_syndrome(&corrupted).unwrap(), factor + 1);
+
+            let outcome = codec.decode(&corrupted).unwrap();
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+            assert_eq!(
+                outcome.status,
+                DecodeStatus::CorrectedMany {
+                    codeword_positions: flips
+                }
+            );
+
+            // one more flip and the vote goes the wrong way
+            corrupted.toggle(factor + factor / 2).unwrap();
+            let outcome = codec.decode(&corrupted).unwrap();
+            assert_eq!(outcome.data.to_vec(), vec![true, true, true]);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, HammingCode, HammingError},
+};
+
+/// Additive (synchronous) scrambler driven by a Fibonacci LFSR.
+///
+/// `polynomial` has bit `i` set for every `x^i` term, so the default
+/// x^7 + x^4 + 1 (the 802.11 whitening polynomial) is `0x91`, and its degree
+/// is the width of the register. `seed` is the starting register contents,
+/// truncated to that width.
+///
+/// Scrambling XORs the data with the LFSR output, restarted from `seed` every
+/// time, so descrambling is the very same operation and works on any length.
+/// Unlike a self-synchronizing scrambler a flipped bit stays a single flipped
+/// bit, which keeps it correctable by the code underneath, but both sides need
+/// the same seed. A seed of 0 never leaves the all-zero state and scrambles
+/// nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lfsr {
+    pub polynomial: u32,
+    pub seed: u32,
+}
+
+impl Default for Lfsr {
+    fn default() -> Self {
+        Self {
+            polynomial: 0x91,
+            seed: 0x7F,
+        }
+    }
+}
+
+impl Lfsr {
+    pub fn new(polynomial: u32, seed: u32) -> Self {
+        Self { polynomial, seed }
+    }
+
+    /// Width of the register, i.e. the degree of the polynomial.
+    pub fn degree(&self) -> u32 {
+        self.polynomial.checked_ilog2().unwrap_or(0)
+    }
+
+    /// The first `len` output bits, starting from `seed`.
+    pub fn keystream(&self, len: usize) -> BitVec {
+        let mask = 1u32
+            .checked_shl(self.degree())
+            .map_or(u32::MAX, |bit| bit - 1);
+        // the x^k term taps register bit k - 1, x^0 is the feedback itself
+        let taps = self.polynomial >> 1;
+
+        let mut state = self.seed & mask;
+        BitVec::from_fn(len, |_| {
+            let feedback = (state & taps).count_ones() % 2 == 1;
+            state = ((state << 1) | u32::from(feedback)) & mask;
+            feedback
+        })
+    }
+
+    pub fn scramble(&self, bits: &BitVec) -> BitVec {
+        let mut scrambled = bits.clone();
+        scrambled
+            .xor_assign(&self.keystream(bits.len()))
+            .expect("keystream is as long as the data");
+        scrambled
+    }
+
+    /// Undoes [`Lfsr::scramble`], which for an additive scrambler is the same
+    /// operation.
+    pub fn descramble(&self, bits: &BitVec) -> BitVec {
+        self.scramble(bits)
+    }
+}
+
+/// Wraps a codec so the data is scrambled before it's encoded, breaking up
+/// long runs of identical bits on the wire.
+///
+/// The codeword layout and any reported positions are the inner codec's, the
+/// scrambler only touches the data. Padding added by the inner codec comes back
+/// descrambled along with the data, so it isn't necessarily zero anymore, and
+/// should be cut off by length.
+#[derive(Debug, Clone)]
+pub struct Scrambled<C: HammingCode> {
+    codec: C,
+    lfsr: Lfsr,
+}
+
+impl<C: HammingCode> Scrambled<C> {
+    pub fn new(codec: C, lfsr: Lfsr) -> Self {
+        Self { codec, lfsr }
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    pub fn lfsr(&self) -> Lfsr {
+        self.lfsr
+    }
+}
+
+impl<C: HammingCode> HammingCode for Scrambled<C> {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        self.codec.calculate_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        self.codec.encode(&self.lfsr.scramble(data))
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let DecodeOutcome { data, status } = self.codec.decode(codeword)?;
+        Ok(DecodeOutcome {
+            data: self.lfsr.descramble(&data),
+            status,
+        })
+    }
+
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        self.codec.compute_syndrome(codeword)
+    }
+
+    fn verify(&self, codeword: &BitVec) -> Result<bool, HammingError> {
+        self.codec.verify(codeword)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::encoding::{
+        block::BlockHamming,
+        hamming::{DecodeStatus, Hamming},
+    };
+    use crate::testing::{bitvec_strategy, nonempty_bitvec_strategy};
+
+    fn ones(bits: &BitVec) -> usize {
+        bits.to_vec().into_iter().filter(|&bit| bit).count()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_scramble_round_trip(
+            data in bitvec_strategy(700),
+            polynomial in 2u32..,
+            seed in any::<u32>(),
+        ) {
+            let lfsr = Lfsr::new(polynomial, seed);
+            let scrambled = lfsr.scramble(&data);
+            prop_assert_eq!(scrambled.len(), data.len());
+            prop_assert_eq!(lfsr.descramble(&scrambled).to_vec(), data.to_vec());
+        }
+
+        #[test]
+        fn prop_scrambled_round_trip(data in nonempty_bitvec_strategy(500), flip in any::<prop::sample::Index>()) {
+            let codec = Scrambled::new(Hamming, Lfsr::default());
+            let mut codeword = codec.encode(&data).unwrap();
+            let flip = flip.index(codeword.len());
+            codeword.toggle(flip).unwrap();
+
+            let outcome = codec.decode(&codeword).unwrap();
+            prop_assert_eq!(outcome.status, DecodeStatus::corrected_at(flip));
+            prop_assert_eq!(outcome.data.to_vec(), data.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_default_sequence() {
+        // x^7 + x^4 + 1 is primitive, so the all-ones seed comes back after
+        // 127 bits, 64 of them set
+        let lfsr = Lfsr::default();
+        assert_eq!(lfsr.degree(), 7);
+        let keystream = lfsr.keystream(254);
+        assert_eq!(ones(&keystream.slice(0..127).unwrap()), 64);
+        assert_eq!(
+            keystream.slice(0..127).unwrap().to_vec(),
+            keystream.slice(127..254).unwrap().to_vec()
+        );
+        assert_eq!(
+            keystream.slice(0..8).unwrap().to_vec(),
+            [false, false, false, false, true, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_whitens_zeros() {
+        let lfsr = Lfsr::default();
+        for len in [100, 1000, 4003] {
+            let scrambled = lfsr.scramble(&BitVec::zeros(len));
+            let ratio = ones(&scrambled) as f64 / len as f64;
+            assert!((0.45..0.55).contains(&ratio), "{len} bits, {ratio} set");
+            // and no long runs left either
+            assert!(scrambled.windows(8).all(|window| ones(&window) > 0));
+        }
+
+        let codec = Scrambled::new(BlockHamming::hamming74(), lfsr);
+        let codeword = codec.encode(&BitVec::zeros(400)).unwrap();
+        let ratio = ones(&codeword) as f64 / codeword.len() as f64;
+        assert!((0.4..0.6).contains(&ratio));
+    }
+
+    #[test]
+    fn test_zero_seed() {
+        let data = BitVec::random(50, &mut rand::rng());
+        let lfsr = Lfsr::new(0x91, 0);
+        assert_eq!(lfsr.scramble(&data).to_vec(), data.to_vec());
+        // degenerate polynomials don't scramble anything either
+        assert_eq!(Lfsr::new(1, 5).scramble(&data).to_vec(), data.to_vec());
+        assert_eq!(Lfsr::new(0, 5).scramble(&data).to_vec(), data.to_vec());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::encoding::hamming::HammingError;
+
+/// What decoding a single 8-bit [`Secded84`] block found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    Clean,
+    /// One bit was flipped and has been corrected. `bit` is its 0-based index
+    /// in the block, MSB first.
+    Corrected {
+        bit: u8,
+    },
+    /// Two bits were flipped. The nibble is handed back as received.
+    Uncorrectable,
+}
+
+/// 1-based positions (MSB first) of the data bits of a Hamming(7,4) codeword.
+const DATA_POSITIONS: [u8; 4] = [3, 5, 6, 7];
+
+/// Bit of the block for 1-based position `position`, position 8 being the
+/// overall parity bit in the LSB.
+const fn bit(position: u8) -> u8 {
+    1 << (8 - position)
+}
+
+const fn build_encode() -> [u8; 16] {
+    let mut table = [0; 16];
+    let mut nibble = 0;
+    while nibble < 16 {
+        let mut block = 0u8;
+        let mut syndrome = 0;
+        let mut i = 0;
+        while i < 4 {
+            if nibble & (0b1000 >> i) != 0 {
+                block |= bit(DATA_POSITIONS[i]);
+                syndrome ^= DATA_POSITIONS[i];
+            }
+            i += 1;
+        }
+        // parity bits 1, 2 and 4 sit at their own positions
+        let mut parity = 1;
+        while parity <= 4 {
+            if syndrome & parity != 0 {
+                block |= bit(parity);
+            }
+            parity <<= 1;
+        }
+        if block.count_ones() % 2 == 1 {
+            block |= bit(8);
+        }
+        table[nibble] = block;
+        nibble += 1;
+    }
+    table
+}
+
+/// Extracts the data nibble of a block, MSB first.
+const fn nibble(block: u8) -> u8 {
+    let mut value = 0;
+    let mut i = 0;
+    while i < 4 {
+        if block & bit(DATA_POSITIONS[i]) != 0 {
+            value |= 0b1000 >> i;
+        }
+        i += 1;
+    }
+    value
+}
+
+/// Per received block: the corrected nibble in the low 4 bits, and in the
+/// high 4 bits 0 for clean, 1-8 for the corrected position, or 0xF when
+/// uncorrectable.
+const fn build_decode() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut block = 0;
+    while block < 256 {
+        let received = block as u8;
+        let mut syndrome = 0;
+        let mut position = 1;
+        while position <= 7 {
+            if received & bit(position) != 0 {
+                syndrome ^= position;
+            }
+            position += 1;
+        }
+        let overall_odd = received.count_ones() % 2 == 1;
+
+        table[block] = match (syndrome, overall_odd) {
+            (0, false) => nibble(received),
+            // a lone flip of the overall parity bit
+            (0, true) => (8 << 4) | nibble(received),
+            (_, true) => (syndrome << 4) | nibble(received ^ bit(syndrome)),
+            (_, false) => 0xF0 | nibble(received),
+        };
+        block += 1;
+    }
+    table
+}
+
+const ENCODE: [u8; 16] = build_encode();
+const DECODE: [u8; 256] = build_decode();
+
+/// Byte-oriented extended Hamming(8,4) code (SECDED).
+///
+/// Every byte is split into two nibbles, high one first, and each nibble is
+/// encoded into one byte: the Hamming(7,4) codeword `p1 p2 d1 p4 d2 d3 d4`
+/// followed by an overall parity bit, MSB first like
+/// [`ExtendedHamming`](crate::encoding::hamming::ExtendedHamming). The output
+/// is exactly twice the input, and every block corrects one flipped bit and
+/// detects two.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Secded84;
+
+impl Secded84 {
+    pub fn encode_byte(&self, byte: u8) -> [u8; 2] {
+        [ENCODE[(byte >> 4) as usize], ENCODE[(byte & 0xF) as usize]]
+    }
+
+    /// Corrects and decodes one block into its nibble.
+    pub fn decode_block(&self, block: u8) -> (u8, BlockStatus) {
+        let entry = DECODE[block as usize];
+        let status = match entry >> 4 {
+            0 => BlockStatus::Clean,
+            0xF => BlockStatus::Uncorrectable,
+            position => BlockStatus::Corrected { bit: position - 1 },
+        };
+        (entry & 0xF, status)
+    }
+
+    pub fn encode_bytes(&self, data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .flat_map(|&byte| self.encode_byte(byte))
+            .collect()
+    }
+
+    /// Decodes pairs of blocks back into bytes, along with the status of every
+    /// block. Uncorrectable blocks don't fail the whole decode, check the
+    /// statuses.
+    pub fn decode_bytes(
+        &self,
+        encoded: &[u8],
+    ) -> Result<(Vec<u8>, Vec<BlockStatus>), HammingError> {
+        if !encoded.len().is_multiple_of(2) {
+            return Err(HammingError::InvalidCodewordLength {
+                got: encoded.len() * 8,
+                expected_for_r: (encoded.len() + 1) * 8,
+            });
+        }
+
+        let mut data = Vec::with_capacity(encoded.len() / 2);
+        let mut statuses = Vec::with_capacity(encoded.len());
+        for pair in encoded.chunks_exact(2) {
+            let (high, high_status) = self.decode_block(pair[0]);
+            let (low, low_status) = self.decode_block(pair[1]);
+            data.push((high << 4) | low);
+            statuses.push(high_status);
+            statuses.push(low_status);
+        }
+
+        Ok((data, statuses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{
+        bitvec::BitVec,
+        hamming::{ExtendedHamming, HammingCode},
+    };
+
+    #[test]
+    fn test_matches_extended_hamming() {
+        for nibble in 0..16u8 {
+            let data = BitVec::from_uint(nibble as u64, 4).unwrap();
+            let codeword = ExtendedHamming.encode(&data).unwrap();
+            assert_eq!(codeword.to_uint().unwrap() as u8, ENCODE[nibble as usize]);
+        }
+        assert_eq!(ENCODE[0b1011], 0b0110_0110);
+    }
+
+    #[test]
+    fn test_round_trip_all_bytes() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = Secded84.encode_bytes(&data);
+        assert_eq!(encoded.len(), 512);
+
+        let (decoded, statuses) = Secded84.decode_bytes(&encoded).unwrap();
+        assert_eq!(decoded, data);
+        assert!(statuses.iter().all(|&status| status == BlockStatus::Clean));
+    }
+
+    #[test]
+    fn test_corrects_single_flips() {
+        for byte in 0..=255u8 {
+            let encoded = Secded84.encode_bytes(&[byte]);
+            for block in 0..2 {
+                for bit in 0..8 {
+                    let mut corrupted = encoded.clone();
+                    corrupted[block] ^= 0x80 >> bit;
+
+                    let (decoded, statuses) = Secded84.decode_bytes(&corrupted).unwrap();
+                    assert_eq!(decoded, vec![byte]);
+                    assert_eq!(statuses[block], BlockStatus::Corrected { bit });
+                    assert_eq!(statuses[1 - block], BlockStatus::Clean);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_detects_double_flips() {
+        for byte in 0..=255u8 {
+            let encoded = Secded84.encode_bytes(&[byte]);
+            for block in 0..2 {
+                for i in 0..8 {
+                    for j in (i + 1)..8 {
+                        let mut corrupted = encoded.clone();
+                        corrupted[block] ^= (0x80 >> i) | (0x80 >> j);
+
+                        let (_, statuses) = Secded84.decode_bytes(&corrupted).unwrap();
+                        assert_eq!(statuses[block], BlockStatus::Uncorrectable);
+                        assert_eq!(statuses[1 - block], BlockStatus::Clean);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_odd_length() {
+        assert_eq!(
+            Secded84.decode_bytes(&[0, 0, 0]),
+            Err(HammingError::InvalidCodewordLength {
+                got: 24,
+                expected_for_r: 32
+            })
+        );
+        assert_eq!(Secded84.decode_bytes(&[]), Ok((vec![], vec![])));
+    }
+}
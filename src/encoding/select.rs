@@ -0,0 +1,239 @@
+//! Picking a block size for a payload.
+
+use crate::encoding::{block::BlockHamming, hamming::HammingCode};
+
+/// Overhead [`auto_codec`] is willing to pay: a quarter more bits on the wire.
+pub const DEFAULT_MAX_OVERHEAD: f64 = 0.25;
+
+/// Protection [`auto_codec`] insists on: at least one correctable error every
+/// 64 bits.
+pub const DEFAULT_MIN_CORRECTION_DENSITY: f64 = 1.0 / 64.0;
+
+/// Largest `r` [`select_code`] considers: past it blocks are too long to ever
+/// be worth it.
+const MAX_PARITY_BITS: usize = 16;
+
+/// A [`BlockHamming`] block size, together with what it costs for the payload
+/// it was picked for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeChoice {
+    pub parity_bits: usize,
+    /// Bits per block on the wire, `2^r - 1`.
+    pub code_bits: usize,
+    /// Payload bits per block, `2^r - 1 - r`.
+    pub data_bits: usize,
+    pub blocks: usize,
+    /// Total encoded size, padding of the last block included.
+    pub encoded_bits: usize,
+    /// Extra bits sent per payload bit, `(encoded - payload) / payload`, or 0
+    /// for an empty payload.
+    pub overhead_ratio: f64,
+    /// Correctable errors per encoded bit, one per block, so `1 / code_bits`.
+    pub correction_density: f64,
+    /// Whether both limits passed to [`select_code`] are met. If not, this is
+    /// the closest thing it could find.
+    pub meets_constraints: bool,
+}
+
+impl CodeChoice {
+    /// `None` if the encoded size doesn't fit in a `usize`.
+    fn for_block(parity_bits: usize, payload_bits: usize) -> Option<Self> {
+        let code_bits = (1 << parity_bits) - 1;
+        let data_bits = code_bits - parity_bits;
+        let blocks = payload_bits.div_ceil(data_bits);
+        let encoded_bits = blocks.checked_mul(code_bits)?;
+        let overhead_ratio = match payload_bits {
+            0 => 0.0,
+            _ => (encoded_bits - payload_bits) as f64 / payload_bits as f64,
+        };
+
+        Some(Self {
+            parity_bits,
+            code_bits,
+            data_bits,
+            blocks,
+            encoded_bits,
+            overhead_ratio,
+            correction_density: 1.0 / code_bits as f64,
+            meets_constraints: false,
+        })
+    }
+
+    /// Correctable errors per `bits` encoded bits.
+    pub fn correctable_per(&self, bits: usize) -> f64 {
+        self.correction_density * bits as f64
+    }
+
+    pub fn codec(&self) -> BlockHamming {
+        BlockHamming::new(self.parity_bits).expect("only valid block sizes are chosen")
+    }
+}
+
+/// Recommends a [`BlockHamming`] block size for `payload_bits` bits.
+///
+/// Bigger blocks cost less, but correct fewer errors per bit, and for small
+/// payloads the padding of the last block can outweigh the parity savings. Of
+/// the block sizes sending at most `max_overhead_ratio` extra bits per payload
+/// bit and correcting at least `min_correction_density` errors per bit, this
+/// picks the cheapest one. If none meets both, it stays within the overhead
+/// budget with the best protection it can get, and failing that just goes for
+/// the lowest overhead. Either way [`CodeChoice::meets_constraints`] is unset.
+///
+/// Block sizes the payload would be too big for once encoded, its size
+/// overflowing a `usize`, are left out, and if that's all of them there's
+/// nothing to recommend.
+pub fn select_code(
+    payload_bits: usize,
+    max_overhead_ratio: f64,
+    min_correction_density: f64,
+) -> Option<CodeChoice> {
+    let candidates: Vec<CodeChoice> = (BlockHamming::MIN_PARITY_BITS..=MAX_PARITY_BITS)
+        .filter_map(|parity_bits| CodeChoice::for_block(parity_bits, payload_bits))
+        .collect();
+    let cheapest = |choices: &mut dyn Iterator<Item = &CodeChoice>| {
+        choices
+            .min_by(|a, b| a.overhead_ratio.total_cmp(&b.overhead_ratio))
+            .copied()
+    };
+
+    let within_budget = |choice: &&CodeChoice| choice.overhead_ratio <= max_overhead_ratio;
+    let protective = |choice: &&CodeChoice| choice.correction_density >= min_correction_density;
+
+    if let Some(mut choice) =
+        cheapest(&mut candidates.iter().filter(within_budget).filter(protective))
+    {
+        choice.meets_constraints = true;
+        return Some(choice);
+    }
+    if let Some(choice) = candidates
+        .iter()
+        .filter(within_budget)
+        .max_by(|a, b| a.correction_density.total_cmp(&b.correction_density))
+    {
+        return Some(*choice);
+    }
+    cheapest(&mut candidates.iter())
+}
+
+/// The codec [`select_code`] recommends for `payload_bits` bits with
+/// [`DEFAULT_MAX_OVERHEAD`] and [`DEFAULT_MIN_CORRECTION_DENSITY`]. A payload
+/// too big for it to recommend anything gets the longest block it considers,
+/// which can't encode it either.
+pub fn auto_codec(payload_bits: usize) -> Box<dyn HammingCode> {
+    let choice = select_code(
+        payload_bits,
+        DEFAULT_MAX_OVERHEAD,
+        DEFAULT_MIN_CORRECTION_DENSITY,
+    );
+    Box::new(match choice {
+        Some(choice) => choice.codec(),
+        None => BlockHamming::new(MAX_PARITY_BITS).expect("a valid block size"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitvec::BitVec;
+
+    fn defaults(payload_bits: usize) -> CodeChoice {
+        select_code(
+            payload_bits,
+            DEFAULT_MAX_OVERHEAD,
+            DEFAULT_MIN_CORRECTION_DENSITY,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_representative_sizes() {
+        // too small for any block to stay within budget, (7,4) pads the least
+        let choice = defaults(13);
+        assert_eq!((choice.code_bits, choice.data_bits), (7, 4));
+        assert_eq!((choice.blocks, choice.encoded_bits), (4, 28));
+        assert!((choice.overhead_ratio - 15.0 / 13.0).abs() < 1e-12);
+        assert!(!choice.meets_constraints);
+
+        // 1 KiB
+        let choice = defaults(8192);
+        assert_eq!((choice.code_bits, choice.data_bits), (63, 57));
+        assert_eq!((choice.blocks, choice.encoded_bits), (144, 9072));
+        assert!((choice.overhead_ratio - 880.0 / 8192.0).abs() < 1e-12);
+        assert!(choice.meets_constraints);
+
+        // 1 MiB
+        let choice = defaults(8 << 20);
+        assert_eq!((choice.code_bits, choice.data_bits), (63, 57));
+        assert_eq!(choice.blocks, 147_169);
+        assert_eq!(choice.encoded_bits, 147_169 * 63);
+        assert!(choice.meets_constraints);
+    }
+
+    #[test]
+    fn test_constraints() {
+        // insisting on (7,4) level protection
+        let choice = select_code(8192, 1.0, 1.0 / 7.0).unwrap();
+        assert_eq!(choice.code_bits, 7);
+        assert!(choice.meets_constraints);
+
+        // a tight budget only bigger blocks fit in, as protective as they get
+        let choice = select_code(8192, 0.07, 1.0 / 64.0).unwrap();
+        assert_eq!(choice.code_bits, 127);
+        assert!((choice.overhead_ratio - (69.0 * 127.0 - 8192.0) / 8192.0).abs() < 1e-12);
+        assert!(!choice.meets_constraints);
+
+        // anything goes: the cheapest block there is, which for 1 MiB isn't
+        // the biggest because of the padding
+        let choice = select_code(8 << 20, f64::INFINITY, 0.0).unwrap();
+        assert_eq!(choice.parity_bits, 13);
+        assert!(choice.meets_constraints);
+
+        let choice = defaults(0);
+        assert_eq!((choice.blocks, choice.encoded_bits), (0, 0));
+        assert_eq!(choice.overhead_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_huge_payloads() {
+        // up to (31,26) the encoded size overflows, so those aren't candidates
+        let choice = defaults(usize::MAX / 8 * 7);
+        assert_eq!(choice.code_bits, 63);
+        assert!(choice.meets_constraints);
+        assert!(CodeChoice::for_block(5, usize::MAX / 8 * 7).is_none());
+
+        // and here nothing fits
+        assert_eq!(select_code(usize::MAX, f64::INFINITY, 0.0), None);
+        let codec = auto_codec(usize::MAX);
+        assert_eq!(
+            codec.encode(&BitVec::zeros(1)).unwrap().len(),
+            (1 << 16) - 1
+        );
+    }
+
+    #[test]
+    fn test_overhead_math() {
+        for payload_bits in [1, 4, 13, 100, 1000, 8192] {
+            for parity_bits in 3..=10 {
+                let choice = CodeChoice::for_block(parity_bits, payload_bits).unwrap();
+                let codec = choice.codec();
+                let encoded = codec.encode(&BitVec::zeros(payload_bits)).unwrap();
+                assert_eq!(encoded.len(), choice.encoded_bits);
+                assert_eq!(
+                    choice.overhead_ratio,
+                    (encoded.len() - payload_bits) as f64 / payload_bits as f64
+                );
+                assert_eq!(choice.correctable_per(choice.code_bits), 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_codec() {
+        let data = BitVec::random(8192, &mut rand::rng());
+        let codec = auto_codec(data.len());
+        let codeword = codec.encode(&data).unwrap();
+        assert_eq!(codeword.len(), 9072);
+        let outcome = codec.decode(&codeword).unwrap();
+        assert_eq!(outcome.data.slice(0..8192).unwrap().to_vec(), data.to_vec());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus, Hamming, HammingCode, HammingCodeBase, HammingError},
+};
+
+/// Shortened Hamming code: a Hamming(2^r − 1, 2^r − 1 − r) parent code whose
+/// trailing data bits are fixed to zero and never transmitted.
+///
+/// Encodes exactly `data_bits` bits into [`ShortenedHamming::code_len`] bits,
+/// which is tighter than padding the data up to a full parent block. Decoding
+/// puts the implicit zeros back before computing the syndrome, and a syndrome
+/// pointing at one of them is reported as [`DecodeStatus::Uncorrectable`].
+///
+/// With `data_bits` equal to the parent's data length the codewords are the
+/// same as the unshortened code's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortenedHamming {
+    data_bits: usize,
+    parity_bits: usize,
+    /// 0-based positions of the transmitted bits in the parent codeword.
+    kept: Vec<usize>,
+}
+
+impl ShortenedHamming {
+    pub const MIN_PARITY_BITS: usize = 2;
+    pub const MAX_PARITY_BITS: usize = 16;
+
+    /// Shortens the parent code with `parent_r` parity bits to `data_bits`
+    /// data bits, which must be between 1 and the parent's data length.
+    pub fn new(data_bits: usize, parent_r: usize) -> Result<Self, HammingError> {
+        if !(Self::MIN_PARITY_BITS..=Self::MAX_PARITY_BITS).contains(&parent_r) {
+            return Err(HammingError::BlockSizeInvalid(parent_r));
+        }
+
+        let parent_len = (1 << parent_r) - 1;
+        if data_bits == 0 || data_bits > parent_len - parent_r {
+            return Err(HammingError::DataBitsInvalid(data_bits));
+        }
+
+        // all the parity bits and the first `data_bits` data bits
+        let mut data_seen = 0;
+        let kept = (0..parent_len)
+            .filter(|i| {
+                if (i + 1).is_power_of_two() {
+                    return true;
+                }
+                data_seen += 1;
+                data_seen <= data_bits
+            })
+            .collect();
+
+        Ok(Self {
+            data_bits,
+            parity_bits: parent_r,
+            kept,
+        })
+    }
+
+    pub fn data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    pub fn parity_bits(&self) -> usize {
+        self.parity_bits
+    }
+
+    /// Number of bits actually transmitted per codeword.
+    pub fn code_len(&self) -> usize {
+        self.data_bits + self.parity_bits
+    }
+
+    fn parent_len(&self) -> usize {
+        (1 << self.parity_bits) - 1
+    }
+
+    /// Rebuilds the parent codeword, with the omitted bits set to zero.
+    fn expand(&self, codeword: &BitVec) -> Result<BitVec, HammingError> {
+        if codeword.len() != self.code_len() {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: self.code_len(),
+            });
+        }
+
+        let mut parent = BitVec::zeros(self.parent_len());
+        for (i, &position) in self.kept.iter().enumerate() {
+            parent
+                .set(
+                    position,
+                    codeword.get(i).ok_or(HammingError::UnexpectedOutOfBounds)?,
+                )
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+        }
+        Ok(parent)
+    }
+}
+
+impl HammingCode for ShortenedHamming {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(&self.expand(codeword)?, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        if data.len() != self.data_bits {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+
+        let padding = self.parent_len() - self.parity_bits - self.data_bits;
+        let parent_data = BitVec::concat_all(&[data.clone(), BitVec::zeros(padding)]);
+        let parent = Hamming.encode(&parent_data)?;
+
+        let mut codeword = BitVec::with_capacity(self.code_len());
+        for &position in &self.kept {
+            codeword.push(
+                parent
+                    .get(position)
+                    .ok_or(HammingError::UnexpectedOutOfBounds)?,
+            );
+        }
+        Ok(codeword)
+    }
+
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let mut parent = self.expand(codeword)?;
+        let status = match Hamming.decode_in_place(&mut parent)?.status {
+            DecodeStatus::Corrected {
+                codeword_pos,
+                was_parity,
+                data_pos,
+            } => match self.kept.binary_search(&codeword_pos) {
+                Ok(codeword_pos) => DecodeStatus::Corrected {
+                    codeword_pos,
+                    was_parity,
+                    data_pos,
+                },
+                // none of the omitted bits can have flipped on the wire
+                Err(_) => DecodeStatus::Uncorrectable,
+            },
+            status => status,
+        };
+
+        let data = Hamming
+            .extract_data(&parent)
+            .slice(0..self.data_bits)
+            .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+
+        Ok(DecodeOutcome { data, status })
+    }
+
+    fn compute_syndrome(&self, codeword: &BitVec) -> Result<usize, HammingError> {
+        Hamming.compute_syndrome(&self.expand(codeword)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::block::BlockHamming;
+
+    const SHAPES: [(usize, usize); 6] = [(1, 2), (1, 3), (2, 3), (5, 4), (8, 4), (20, 5)];
+
+    #[test]
+    fn test_new() {
+        assert_eq!(ShortenedHamming::new(5, 4).unwrap().code_len(), 9);
+        assert_eq!(ShortenedHamming::new(11, 4).unwrap().code_len(), 15);
+
+        assert!(ShortenedHamming::new(0, 4).is_err());
+        assert!(ShortenedHamming::new(12, 4).is_err());
+        assert!(ShortenedHamming::new(1, 1).is_err());
+        assert!(ShortenedHamming::new(1, 17).is_err());
+    }
+
+    #[test]
+    fn test_corrects_every_position() {
+        let mut rng = rand::rng();
+        for (data_bits, r) in SHAPES {
+            let codec = ShortenedHamming::new(data_bits, r).unwrap();
+            let data = BitVec::random(data_bits, &mut rng);
+            let codeword = codec.encode(&data).unwrap();
+            assert_eq!(codeword.len(), codec.code_len());
+
+            let outcome = codec.decode(&codeword).unwrap();
+            assert!(outcome.status.is_clean());
+            assert_eq!(outcome.data.to_vec(), data.to_vec());
+
+            for i in 0..codeword.len() {
+                let mut corrupted = codeword.clone();
+                corrupted.toggle(i).unwrap();
+                assert_ne!(codec.compute_syndrome(&corrupted).unwrap(), 0);
+
+                let outcome = codec.decode(&corrupted).unwrap();
+                assert!(matches!(
+                    outcome.status,
+                    DecodeStatus::Corrected { codeword_pos, .. } if codeword_pos == i
+                ));
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_unshortened() {
+        let mut rng = rand::rng();
+        for r in [3, 4, 5] {
+            let parent = BlockHamming::new(r).unwrap();
+            let codec = ShortenedHamming::new(parent.data_bits_per_block(), r).unwrap();
+            let data = BitVec::random(parent.data_bits_per_block(), &mut rng);
+
+            let codeword = codec.encode(&data).unwrap();
+            assert_eq!(codeword.to_vec(), parent.encode(&data).unwrap().to_vec());
+            assert_eq!(codeword.to_vec(), Hamming.encode(&data).unwrap().to_vec());
+        }
+    }
+
+    #[test]
+    fn test_wrong_lengths() {
+        let codec = ShortenedHamming::new(5, 4).unwrap();
+        assert!(codec.encode(&BitVec::zeros(4)).is_err());
+        assert_eq!(
+            codec.decode(&BitVec::zeros(10)).unwrap_err(),
+            HammingError::InvalidCodewordLength {
+                got: 10,
+                expected_for_r: 9
+            }
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Wide-word kernels for long BitVecs, enabled by the `simd` feature.
+//!
+//! Bytes are processed 32 at a time as four `u64`s, an unrolled loop that the
+//! compiler can turn into 256-bit operations where the target has them,
+//! without needing nightly `std::simd`. Callers only switch over above
+//! [`THRESHOLD_BITS`] and handle whatever is left after the last full chunk
+//! themselves, so results are bit for bit the same as the byte-wise paths.
+
+/// BitVecs shorter than this stay on the byte-wise paths.
+pub(crate) const THRESHOLD_BITS: usize = 1024;
+
+const CHUNK_BYTES: usize = 32;
+const CHUNK_BITS: usize = CHUNK_BYTES * 8;
+
+type Chunk = [u64; 4];
+
+/// Loads 32 bytes MSB first, so bit `i` of the chunk is bit `63 - i % 64` of
+/// word `i / 64`.
+fn load(bytes: &[u8]) -> Chunk {
+    let mut words = [0; 4];
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+        let mut array = [0; 8];
+        array.copy_from_slice(bytes);
+        *word = u64::from_be_bytes(array);
+    }
+    words
+}
+
+fn store(words: Chunk, bytes: &mut [u8]) {
+    for (word, bytes) in words.iter().zip(bytes.chunks_exact_mut(8)) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+}
+
+/// XORs `src` into `dst` a chunk at a time, returning how many bytes were
+/// done. The rest (less than a chunk) is left to the caller.
+pub(crate) fn xor(dst: &mut [u8], src: &[u8]) -> usize {
+    let mut done = 0;
+    for (dst, src) in dst
+        .chunks_exact_mut(CHUNK_BYTES)
+        .zip(src.chunks_exact(CHUNK_BYTES))
+    {
+        let (a, b) = (load(dst), load(src));
+        store([a[0] ^ b[0], a[1] ^ b[1], a[2] ^ b[2], a[3] ^ b[3]], dst);
+        done += CHUNK_BYTES;
+    }
+    done
+}
+
+/// Parity of the bits of `bytes` whose 1-based position (counting from the
+/// first byte) shares a bit with `parity_mask`, over whole chunks only.
+/// Returns the number of set participating bits and how many bytes were done.
+///
+/// Chunk `c` holds positions `256c + 1` to `256c + 256`. The low 8 bits of the
+/// mask pick the same bits in every chunk, while the higher bits are the same
+/// for every position but the last one, which is the only multiple of 256.
+pub(crate) fn parity(bytes: &[u8], parity_mask: usize) -> (u32, usize) {
+    let low = parity_mask & 0xFF;
+    let high = parity_mask & !0xFF;
+
+    let mut low_bytes = [0u8; CHUNK_BYTES];
+    for i in 0..CHUNK_BITS {
+        if (i + 1) & low != 0 {
+            low_bytes[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    let low_mask = load(&low_bytes);
+    let all_but_last = [u64::MAX, u64::MAX, u64::MAX, u64::MAX - 1];
+    let last = [0, 0, 0, 1];
+
+    let mut count = 0;
+    let mut done = 0;
+    for (c, bytes) in bytes.chunks_exact(CHUNK_BYTES).enumerate() {
+        let base = c * CHUNK_BITS;
+        let mut mask = low_mask;
+        if base & high != 0 {
+            for (mask, extra) in mask.iter_mut().zip(all_but_last) {
+                *mask |= extra;
+            }
+        }
+        if (base + CHUNK_BITS) & high != 0 {
+            for (mask, extra) in mask.iter_mut().zip(last) {
+                *mask |= extra;
+            }
+        }
+
+        let words = load(bytes);
+        count += (words[0] & mask[0]).count_ones()
+            + (words[1] & mask[1]).count_ones()
+            + (words[2] & mask[2]).count_ones()
+            + (words[3] & mask[3]).count_ones();
+        done += CHUNK_BYTES;
+    }
+    (count, done)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::encoding::{bitvec::BitVec, hamming::HammingCodeBase};
+
+    #[test]
+    fn test_parity_matches_bitwise() {
+        let mut rng = StdRng::seed_from_u64(578);
+        for _ in 0..200 {
+            let len = rng.random_range(THRESHOLD_BITS..20_000);
+            let bits = BitVec::random(len, &mut rng);
+            let masks = [1 << rng.random_range(0..15), rng.random_range(1..1 << 15)];
+            for mask in masks {
+                let expected = (0..len)
+                    .filter(|&i| (i + 1) & mask != 0 && bits.get(i) == Some(true))
+                    .count()
+                    % 2
+                    == 1;
+                assert_eq!(
+                    HammingCodeBase::basic_compute_parity(&bits, mask).unwrap(),
+                    expected,
+                    "len {len}, mask {mask}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_xor_matches_bitwise() {
+        let mut rng = StdRng::seed_from_u64(578);
+        for _ in 0..200 {
+            let len = rng.random_range(THRESHOLD_BITS..20_000);
+            let a = BitVec::random(len, &mut rng);
+            let b = BitVec::random(len, &mut rng);
+
+            let mut xored = a.clone();
+            xored.xor_assign(&b).unwrap();
+            let expected: Vec<bool> = (0..len).map(|i| a.get(i) != b.get(i)).collect();
+            assert_eq!(xored.to_vec(), expected);
+        }
+    }
+
+    #[test]
+    fn test_partial_chunks() {
+        let bytes = [0xFFu8; 40];
+        // one full chunk, the last 8 bytes are left over
+        assert_eq!(parity(&bytes, 1), (128, 32));
+        // every position but 256 has a bit below 256 set
+        assert_eq!(parity(&bytes, 0xFF), (255, 32));
+        assert_eq!(parity(&bytes, 256), (1, 32));
+
+        let mut dst = [0u8; 40];
+        assert_eq!(xor(&mut dst, &bytes), 32);
+        assert!(dst[..32].iter().all(|&byte| byte == 0xFF));
+        assert!(dst[32..].iter().all(|&byte| byte == 0));
+    }
+}
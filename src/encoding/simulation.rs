@@ -0,0 +1,259 @@
+//! Monte Carlo simulation of a codec over a noisy channel.
+
+use rand::{SeedableRng, rngs::StdRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::encoding::{
+    bitvec::BitVec,
+    block::BlockHamming,
+    channel::{BinarySymmetricChannel, BurstChannel, Channel},
+    golay::Golay24,
+    hamming::{ExtendedHamming, Hamming, HammingCode, HammingError},
+    repetition::Repetition,
+    stats::CodecStats,
+};
+
+/// Which codec to simulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecKind {
+    /// [`Hamming`] over the whole frame.
+    Hamming,
+    /// [`ExtendedHamming`] over the whole frame.
+    ExtendedHamming,
+    /// [`BlockHamming`] with the given number of parity bits per block.
+    Block(usize),
+    Golay24,
+    /// [`Repetition`] with the given factor.
+    Repetition(usize),
+}
+
+impl CodecKind {
+    fn build(self) -> Result<Box<dyn HammingCode + Send + Sync>, HammingError> {
+        Ok(match self {
+            CodecKind::Hamming => Box::new(Hamming),
+            CodecKind::ExtendedHamming => Box::new(ExtendedHamming),
+            CodecKind::Block(parity_bits) => Box::new(BlockHamming::new(parity_bits)?),
+            CodecKind::Golay24 => Box::new(Golay24),
+            CodecKind::Repetition(factor) => Box::new(Repetition::new(factor)?),
+        })
+    }
+}
+
+/// How the channel corrupts frames. The probability comes from
+/// [`SimulationConfig::probabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelModel {
+    /// [`BinarySymmetricChannel`], the probability is per bit.
+    BinarySymmetric,
+    /// [`BurstChannel`], the probability is for a burst starting at each bit.
+    Burst { min_len: usize, max_len: usize },
+}
+
+impl ChannelModel {
+    fn build(self, probability: f64, seed: u64) -> Box<dyn Channel> {
+        match self {
+            ChannelModel::BinarySymmetric => {
+                Box::new(BinarySymmetricChannel::new(probability, Some(seed)))
+            }
+            ChannelModel::Burst { min_len, max_len } => {
+                Box::new(BurstChannel::new(probability, min_len, max_len, Some(seed)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    pub codec: CodecKind,
+    pub channel: ChannelModel,
+    /// Channel probabilities to simulate, each gets its own [`CodecStats`].
+    pub probabilities: Vec<f64>,
+    /// Data bits per frame.
+    pub frame_bits: usize,
+    /// Frames per probability.
+    pub frames: u64,
+    pub seed: u64,
+}
+
+/// Results for a single channel probability.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationPoint {
+    pub probability: f64,
+    pub stats: CodecStats,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub points: Vec<SimulationPoint>,
+}
+
+/// SplitMix64 finalizer, to turn counters into well mixed seeds.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn run_frame(
+    config: &SimulationConfig,
+    codec: &(dyn HammingCode + Send + Sync),
+    point: usize,
+    frame: u64,
+) -> Result<CodecStats, HammingError> {
+    // every frame gets its own seed, so the order frames run in doesn't matter
+    let seed = mix(mix(config.seed ^ point as u64) ^ frame);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let data = BitVec::random(config.frame_bits, &mut rng);
+    let sent = codec.encode(&data)?;
+    let mut received = sent.clone();
+    config
+        .channel
+        .build(config.probabilities[point], mix(seed))
+        .transmit(&mut received);
+    let outcome = codec.decode(&received)?;
+
+    let mut stats = CodecStats::new();
+    stats.observe(&data, &sent, &received, &outcome);
+    Ok(stats)
+}
+
+fn run_point(
+    config: &SimulationConfig,
+    codec: &(dyn HammingCode + Send + Sync),
+    point: usize,
+) -> Result<CodecStats, HammingError> {
+    let merge = |mut a: CodecStats, b: CodecStats| {
+        a.merge(&b);
+        a
+    };
+
+    #[cfg(feature = "parallel")]
+    let stats = (0..config.frames)
+        .into_par_iter()
+        .map(|frame| run_frame(config, codec, point, frame))
+        .try_reduce(CodecStats::new, |a, b| Ok(merge(a, b)));
+
+    #[cfg(not(feature = "parallel"))]
+    let stats = (0..config.frames).try_fold(CodecStats::new(), |stats, frame| {
+        Ok(merge(stats, run_frame(config, codec, point, frame)?))
+    });
+
+    stats
+}
+
+/// Sends `config.frames` random frames through the codec and channel for
+/// every probability and collects the stats.
+///
+/// The results only depend on the config, including the seed, so runs are
+/// comparable. With the `parallel` feature the frames are spread over the
+/// rayon thread pool, which gives the same results.
+pub fn simulate(config: SimulationConfig) -> Result<SimulationReport, HammingError> {
+    let codec = config.codec.build()?;
+
+    let points = (0..config.probabilities.len())
+        .map(|point| {
+            Ok(SimulationPoint {
+                probability: config.probabilities[point],
+                stats: run_point(&config, codec.as_ref(), point)?,
+            })
+        })
+        .collect::<Result<_, HammingError>>()?;
+
+    Ok(SimulationReport { points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(
+        codec: CodecKind,
+        probabilities: Vec<f64>,
+        frame_bits: usize,
+        frames: u64,
+    ) -> SimulationConfig {
+        SimulationConfig {
+            codec,
+            channel: ChannelModel::BinarySymmetric,
+            probabilities,
+            frame_bits,
+            frames,
+            seed: 1234,
+        }
+    }
+
+    #[test]
+    fn test_fixed_seed() {
+        let report = simulate(config(CodecKind::Block(4), vec![0.01, 0.05], 44, 50)).unwrap();
+        assert_eq!(
+            report,
+            SimulationReport {
+                points: vec![
+                    SimulationPoint {
+                        probability: 0.01,
+                        stats: CodecStats {
+                            frames: 50,
+                            bits_transmitted: 3000,
+                            raw_bit_errors: 34,
+                            corrected_bits: 29,
+                            uncorrectable_frames: 0,
+                            data_bits: 2200,
+                            residual_bit_errors: 12,
+                            frame_errors: 5,
+                        },
+                    },
+                    SimulationPoint {
+                        probability: 0.05,
+                        stats: CodecStats {
+                            frames: 50,
+                            bits_transmitted: 3000,
+                            raw_bit_errors: 140,
+                            corrected_bits: 105,
+                            uncorrectable_frames: 0,
+                            data_bits: 2200,
+                            residual_bit_errors: 57,
+                            frame_errors: 20,
+                        },
+                    },
+                ],
+            }
+        );
+
+        let again = simulate(config(CodecKind::Block(4), vec![0.01, 0.05], 44, 50)).unwrap();
+        assert_eq!(again, report);
+    }
+
+    #[test]
+    fn test_no_errors() {
+        for codec in [
+            CodecKind::Hamming,
+            CodecKind::Golay24,
+            CodecKind::Repetition(3),
+        ] {
+            let report = simulate(config(codec, vec![0.0], 100, 20)).unwrap();
+            let stats = report.points[0].stats;
+            assert_eq!(stats.frames, 20);
+            assert_eq!(stats.raw_bit_errors, 0);
+            assert_eq!(stats.residual_bit_errors, 0);
+            assert_eq!(stats.frame_error_rate(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_coin_flip_channel() {
+        let report = simulate(config(CodecKind::Block(3), vec![0.5], 1000, 20)).unwrap();
+        assert!(report.points[0].stats.frame_error_rate() > 0.99);
+        assert!((report.points[0].stats.raw_ber() - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_invalid_codec() {
+        assert!(matches!(
+            simulate(config(CodecKind::Repetition(2), vec![0.1], 10, 1)),
+            Err(HammingError::RepetitionFactorInvalid(2))
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+//! Read-only bit access over the different shapes data comes in, so it can be
+//! encoded without converting it to a [`BitVec`] first.
+
+use crate::encoding::bitvec::{BitSlice, BitVec};
+
+/// Anything that can hand out a fixed number of bits by index.
+///
+/// Implemented for [`BitVec`], [`BitSlice`], `[bool]` and `[u8]`, the latter
+/// read MSB first like [`BitVec::from_bytes`].
+pub trait BitSource {
+    /// Number of bits.
+    fn bit_len(&self) -> usize;
+
+    /// The bit at `index`, or `None` past the end.
+    fn bit(&self, index: usize) -> Option<bool>;
+
+    fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.bit_len()).filter_map(|i| self.bit(i))
+    }
+
+    fn to_bitvec(&self) -> BitVec {
+        BitVec::from_fn(self.bit_len(), |i| self.bit(i).unwrap_or(false))
+    }
+}
+
+impl BitSource for BitVec {
+    fn bit_len(&self) -> usize {
+        self.len()
+    }
+
+    fn bit(&self, index: usize) -> Option<bool> {
+        self.get(index)
+    }
+
+    fn to_bitvec(&self) -> BitVec {
+        self.clone()
+    }
+}
+
+impl BitSource for BitSlice<'_> {
+    fn bit_len(&self) -> usize {
+        self.len()
+    }
+
+    fn bit(&self, index: usize) -> Option<bool> {
+        self.get(index)
+    }
+
+    fn to_bitvec(&self) -> BitVec {
+        BitSlice::to_bitvec(self)
+    }
+}
+
+impl BitSource for [bool] {
+    fn bit_len(&self) -> usize {
+        self.len()
+    }
+
+    fn bit(&self, index: usize) -> Option<bool> {
+        self.get(index).copied()
+    }
+
+    fn to_bitvec(&self) -> BitVec {
+        BitVec::from_vec(self.to_vec())
+    }
+}
+
+impl BitSource for [u8] {
+    fn bit_len(&self) -> usize {
+        self.len() * 8
+    }
+
+    fn bit(&self, index: usize) -> Option<bool> {
+        let byte = self.get(index / 8)?;
+        Some(byte & (0x80 >> (index % 8)) != 0)
+    }
+
+    fn to_bitvec(&self) -> BitVec {
+        BitVec::from_whole_bytes(self.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::{
+        block::BlockHamming,
+        hamming::{Hamming, HammingCode},
+    };
+
+    #[test]
+    fn test_sources_agree() {
+        let bytes = [0b1011_0010u8, 0b0111_1101, 0b1000_0001];
+        let bools: Vec<bool> = (0..24)
+            .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+            .collect();
+        let bitvec = BitVec::from_whole_bytes(bytes.to_vec());
+
+        assert_eq!(bytes[..].bits().collect::<Vec<_>>(), bools);
+        assert_eq!(bitvec.bits().collect::<Vec<_>>(), bools);
+        assert_eq!(bools[..].to_bitvec().to_vec(), bools);
+        assert_eq!(bytes[..].to_bitvec().to_vec(), bools);
+        assert_eq!(bytes[..].bit(24), None);
+        assert_eq!(bools[..].bit(24), None);
+    }
+
+    #[test]
+    fn test_identical_codewords() {
+        let mut rng = rand::rng();
+        for len in [0, 1, 4, 11, 64, 300] {
+            let bitvec = BitVec::random(len * 8, &mut rng);
+            let bytes = bitvec.clone().into_inner();
+            let bools = bitvec.to_vec();
+
+            let expected = Hamming.encode(&bitvec).unwrap().to_vec();
+            assert_eq!(Hamming.encode_from(&bitvec).unwrap().to_vec(), expected);
+            assert_eq!(Hamming.encode_from(&bools[..]).unwrap().to_vec(), expected);
+            assert_eq!(Hamming.encode_from(&bytes[..]).unwrap().to_vec(), expected);
+
+            // codecs without their own encode_from go through a BitVec
+            let block = BlockHamming::hamming74();
+            let expected = block.encode(&bitvec).unwrap().to_vec();
+            assert_eq!(block.encode_from(&bools[..]).unwrap().to_vec(), expected);
+            assert_eq!(block.encode_from(&bytes[..]).unwrap().to_vec(), expected);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::{Display, Formatter};
+
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{DecodeOutcome, DecodeStatus},
+};
+
+/// Running totals over many encoded, transmitted and decoded frames.
+///
+/// Counters saturate instead of overflowing, and stats collected by separate
+/// workers can be combined with [`CodecStats::merge`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodecStats {
+    /// Frames observed.
+    pub frames: u64,
+    /// Codeword bits sent over the channel.
+    pub bits_transmitted: u64,
+    /// Codeword bits the channel flipped.
+    pub raw_bit_errors: u64,
+    /// Bits the decoder reported as corrected.
+    pub corrected_bits: u64,
+    /// Frames the decoder reported as uncorrectable.
+    pub uncorrectable_frames: u64,
+    /// Data bits handed to the encoder.
+    pub data_bits: u64,
+    /// Data bits that were still wrong after decoding.
+    pub residual_bit_errors: u64,
+    /// Frames with at least one residual bit error.
+    pub frame_errors: u64,
+}
+
+/// Number of positions where `a` and `b` differ, counting every bit of the
+/// longer one past the end of the shorter one as different.
+fn bit_errors(a: &BitVec, b: &BitVec) -> u64 {
+    let common = a.len().min(b.len());
+    let differing = (0..common).filter(|&i| a.get(i) != b.get(i)).count();
+    (differing + a.len().max(b.len()) - common) as u64
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+impl CodecStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one frame: the `original` data, the codeword as it was `sent`,
+    /// the codeword as it was `received` and what decoding it gave.
+    ///
+    /// Decoded data longer than the original (padding from a block code) is
+    /// only compared over the original's length.
+    pub fn observe(
+        &mut self,
+        original: &BitVec,
+        sent: &BitVec,
+        received: &BitVec,
+        outcome: &DecodeOutcome,
+    ) {
+        let decoded = if outcome.data.len() > original.len() {
+            outcome.data.slice(0..original.len()).ok()
+        } else {
+            None
+        };
+        let residual = bit_errors(original, decoded.as_ref().unwrap_or(&outcome.data));
+
+        self.frames = self.frames.saturating_add(1);
+        self.bits_transmitted = self.bits_transmitted.saturating_add(sent.len() as u64);
+        self.raw_bit_errors = self
+            .raw_bit_errors
+            .saturating_add(bit_errors(sent, received));
+        self.corrected_bits = self
+            .corrected_bits
+            .saturating_add(outcome.status.corrections() as u64);
+        if outcome.status == DecodeStatus::Uncorrectable {
+            self.uncorrectable_frames = self.uncorrectable_frames.saturating_add(1);
+        }
+        self.data_bits = self.data_bits.saturating_add(original.len() as u64);
+        self.residual_bit_errors = self.residual_bit_errors.saturating_add(residual);
+        if residual > 0 {
+            self.frame_errors = self.frame_errors.saturating_add(1);
+        }
+    }
+
+    /// Adds the counts of `other`, e.g. from another worker.
+    pub fn merge(&mut self, other: &CodecStats) {
+        self.frames = self.frames.saturating_add(other.frames);
+        self.bits_transmitted = self.bits_transmitted.saturating_add(other.bits_transmitted);
+        self.raw_bit_errors = self.raw_bit_errors.saturating_add(other.raw_bit_errors);
+        self.corrected_bits = self.corrected_bits.saturating_add(other.corrected_bits);
+        self.uncorrectable_frames = self
+            .uncorrectable_frames
+            .saturating_add(other.uncorrectable_frames);
+        self.data_bits = self.data_bits.saturating_add(other.data_bits);
+        self.residual_bit_errors = self
+            .residual_bit_errors
+            .saturating_add(other.residual_bit_errors);
+        self.frame_errors = self.frame_errors.saturating_add(other.frame_errors);
+    }
+
+    /// Bit error rate of the channel, before decoding.
+    pub fn raw_ber(&self) -> f64 {
+        ratio(self.raw_bit_errors, self.bits_transmitted)
+    }
+
+    /// Bit error rate of the data, after decoding.
+    pub fn residual_ber(&self) -> f64 {
+        ratio(self.residual_bit_errors, self.data_bits)
+    }
+
+    /// Share of frames that had any error left after decoding.
+    pub fn frame_error_rate(&self) -> f64 {
+        ratio(self.frame_errors, self.frames)
+    }
+}
+
+impl Display for CodecStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "frames:               {}", self.frames)?;
+        writeln!(f, "bits transmitted:     {}", self.bits_transmitted)?;
+        writeln!(
+            f,
+            "raw bit errors:       {} (BER {:e})",
+            self.raw_bit_errors,
+            self.raw_ber()
+        )?;
+        writeln!(f, "corrected bits:       {}", self.corrected_bits)?;
+        writeln!(f, "uncorrectable frames: {}", self.uncorrectable_frames)?;
+        writeln!(
+            f,
+            "residual bit errors:  {} (BER {:e})",
+            self.residual_bit_errors,
+            self.residual_ber()
+        )?;
+        write!(
+            f,
+            "frame errors:         {} (FER {:e})",
+            self.frame_errors,
+            self.frame_error_rate()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::hamming::{Hamming, HammingCode};
+
+    fn frame(stats: &mut CodecStats, data: &BitVec, flips: &[usize]) {
+        let sent = Hamming.encode(data).unwrap();
+        let mut received = sent.clone();
+        for &flip in flips {
+            received.toggle(flip).unwrap();
+        }
+        let outcome = Hamming.decode(&received).unwrap();
+        stats.observe(data, &sent, &received, &outcome);
+    }
+
+    #[test]
+    fn test_observe() {
+        let data = BitVec::from_uint(0b1011, 4).unwrap();
+        let mut stats = CodecStats::new();
+
+        // clean, one corrected error, and two errors that get "corrected" into
+        // a third wrong bit
+        frame(&mut stats, &data, &[]);
+        frame(&mut stats, &data, &[2]);
+        frame(&mut stats, &data, &[2, 4]);
+
+        assert_eq!(
+            stats,
+            CodecStats {
+                frames: 3,
+                bits_transmitted: 21,
+                raw_bit_errors: 3,
+                corrected_bits: 2,
+                uncorrectable_frames: 0,
+                data_bits: 12,
+                // positions 3 and 5 are data bits, the miscorrected 6 is too
+                residual_bit_errors: 3,
+                frame_errors: 1,
+            }
+        );
+        assert_eq!(stats.raw_ber(), 3.0 / 21.0);
+        assert_eq!(stats.residual_ber(), 0.25);
+        assert_eq!(stats.frame_error_rate(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_uncorrectable_and_padding() {
+        let data = BitVec::ones(5);
+        let outcome = DecodeOutcome {
+            data: BitVec::from_vec(vec![true, true, true, true, false, false]),
+            status: DecodeStatus::Uncorrectable,
+        };
+
+        let mut stats = CodecStats::new();
+        stats.observe(&data, &BitVec::zeros(10), &BitVec::ones(10), &outcome);
+        assert_eq!(stats.uncorrectable_frames, 1);
+        assert_eq!(stats.raw_bit_errors, 10);
+        // the padding bit isn't counted
+        assert_eq!(stats.residual_bit_errors, 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let data = BitVec::from_uint(0b1011, 4).unwrap();
+        let (mut a, mut b, mut all) = (CodecStats::new(), CodecStats::new(), CodecStats::new());
+        frame(&mut a, &data, &[1]);
+        frame(&mut b, &data, &[0, 6]);
+        frame(&mut all, &data, &[1]);
+        frame(&mut all, &data, &[0, 6]);
+
+        a.merge(&b);
+        assert_eq!(a, all);
+
+        let mut full = CodecStats {
+            frames: u64::MAX,
+            ..CodecStats::default()
+        };
+        full.merge(&a);
+        assert_eq!(full.frames, u64::MAX);
+        assert_eq!(full.raw_bit_errors, a.raw_bit_errors);
+    }
+
+    #[test]
+    fn test_empty_rates() {
+        let stats = CodecStats::new();
+        assert_eq!(stats.raw_ber(), 0.0);
+        assert_eq!(stats.residual_ber(), 0.0);
+        assert_eq!(stats.frame_error_rate(), 0.0);
+        assert!(stats.to_string().contains("frames:               0"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let data = BitVec::from_uint(0b1011, 4).unwrap();
+        let mut stats = CodecStats::new();
+        frame(&mut stats, &data, &[3]);
+
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.contains("\"corrected_bits\":1"));
+        assert_eq!(serde_json::from_str::<CodecStats>(&json).unwrap(), stats);
+    }
+}
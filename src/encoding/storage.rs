@@ -0,0 +1,218 @@
+use std::ops::{Deref, DerefMut};
+
+/// Number of bytes a [`Storage`] can hold before spilling to the heap (128 bits).
+pub(crate) const INLINE_BYTES: usize = 16;
+
+/// Byte storage backing a BitVec.
+///
+/// Small BitVecs (up to 128 bits) keep their bytes inline, and only spill to a
+/// heap allocated `Vec<u8>` once they grow past that. Derefs to `[u8]`, so it
+/// can be indexed just like the `Vec<u8>` it replaces.
+#[derive(Clone)]
+pub(crate) enum Storage {
+    Inline {
+        bytes: [u8; INLINE_BYTES],
+        len: usize,
+    },
+    Heap(Vec<u8>),
+}
+
+impl Storage {
+    pub fn new() -> Self {
+        Self::Inline {
+            bytes: [0; INLINE_BYTES],
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= INLINE_BYTES {
+            Self::new()
+        } else {
+            Self::Heap(Vec::with_capacity(capacity))
+        }
+    }
+
+    pub fn from_vec(vec: Vec<u8>) -> Self {
+        if vec.len() <= INLINE_BYTES {
+            let mut bytes = [0; INLINE_BYTES];
+            bytes[..vec.len()].copy_from_slice(&vec);
+            Self::Inline {
+                bytes,
+                len: vec.len(),
+            }
+        } else {
+            Self::Heap(vec)
+        }
+    }
+
+    /// A zeroed storage of `len` bytes.
+    pub fn zeroed(len: usize) -> Self {
+        if len <= INLINE_BYTES {
+            Self::Inline {
+                bytes: [0; INLINE_BYTES],
+                len,
+            }
+        } else {
+            Self::Heap(vec![0; len])
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        match self {
+            Self::Inline { bytes, len } => bytes[..len].to_vec(),
+            Self::Heap(vec) => vec,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn capacity(&self) -> usize {
+        match self {
+            Self::Inline { .. } => INLINE_BYTES,
+            Self::Heap(vec) => vec.capacity(),
+        }
+    }
+
+    /// Whether the bytes have been moved to the heap.
+    #[cfg(test)]
+    pub fn spilled(&self) -> bool {
+        matches!(self, Self::Heap(_))
+    }
+
+    pub fn push(&mut self, byte: u8) {
+        match self {
+            Self::Inline { bytes, len } if *len < INLINE_BYTES => {
+                bytes[*len] = byte;
+                *len += 1;
+            }
+            Self::Inline { .. } => {
+                self.spill(INLINE_BYTES * 2);
+                self.push(byte);
+            }
+            Self::Heap(vec) => vec.push(byte),
+        }
+    }
+
+    pub fn extend_from_slice(&mut self, other: &[u8]) {
+        match self {
+            Self::Inline { bytes, len } if *len + other.len() <= INLINE_BYTES => {
+                bytes[*len..*len + other.len()].copy_from_slice(other);
+                *len += other.len();
+            }
+            Self::Inline { len, .. } => {
+                let needed = *len + other.len();
+                self.spill(needed);
+                self.extend_from_slice(other);
+            }
+            Self::Heap(vec) => vec.extend_from_slice(other),
+        }
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        match self {
+            Self::Inline { bytes, len } => {
+                if new_len < *len {
+                    // keep the unused bytes zeroed
+                    bytes[new_len..*len].fill(0);
+                    *len = new_len;
+                }
+            }
+            Self::Heap(vec) => vec.truncate(new_len),
+        }
+    }
+
+    /// Moves inline bytes to a heap allocation of at least `capacity` bytes.
+    fn spill(&mut self, capacity: usize) {
+        if let Self::Inline { bytes, len } = self {
+            let mut vec = Vec::with_capacity(capacity.max(*len));
+            vec.extend_from_slice(&bytes[..*len]);
+            *self = Self::Heap(vec);
+        }
+    }
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Inline { bytes, len } => &bytes[..*len],
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl DerefMut for Storage {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Self::Inline { bytes, len } => &mut bytes[..*len],
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl std::fmt::Debug for Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for Storage {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl PartialEq<Vec<u8>> for Storage {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        **self == **other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_on_push() {
+        let mut storage = Storage::new();
+        for i in 0..INLINE_BYTES as u8 {
+            storage.push(i);
+        }
+        assert!(!storage.spilled());
+
+        storage.push(0xFF);
+        assert!(storage.spilled());
+        assert_eq!(storage.len(), INLINE_BYTES + 1);
+        assert_eq!(storage[INLINE_BYTES], 0xFF);
+        assert_eq!(storage[..3], [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_spill_on_extend() {
+        let mut storage = Storage::from_vec(vec![1; 10]);
+        storage.extend_from_slice(&[2; 6]);
+        assert!(!storage.spilled());
+
+        storage.extend_from_slice(&[3; 5]);
+        assert!(storage.spilled());
+        assert_eq!(
+            storage.into_vec(),
+            [[1; 10].as_slice(), &[2; 6], &[3; 5]].concat()
+        );
+    }
+
+    #[test]
+    fn test_truncate_keeps_inline_tail_zeroed() {
+        let mut storage = Storage::from_vec(vec![0xFF; 4]);
+        storage.truncate(1);
+        storage.push(0);
+        assert_eq!(storage, vec![0xFF, 0]);
+    }
+}
@@ -0,0 +1,321 @@
+//! Push-based [`BlockHamming`] encoding and decoding, for bits that trickle in
+//! a few at a time.
+//!
+//! Unlike the [`io`](crate::encoding::io) adapters these don't touch
+//! `std::io` and don't frame anything: the output is exactly what the batch
+//! codec produces for everything pushed so far, handed out a block at a time as
+//! soon as each block is complete.
+
+use crate::encoding::{
+    bitvec::BitVec,
+    block::BlockHamming,
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingError},
+};
+
+/// Encodes the bits pushed into it block by block.
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    codec: BlockHamming,
+    pending: BitVec,
+    output: BitVec,
+}
+
+impl Default for Encoder {
+    /// Encodes Hamming(15,11) blocks.
+    fn default() -> Self {
+        Self::new(BlockHamming::hamming1511())
+    }
+}
+
+impl Encoder {
+    pub fn new(codec: BlockHamming) -> Self {
+        Self {
+            pending: BitVec::with_capacity(codec.data_bits_per_block()),
+            output: BitVec::new(),
+            codec,
+        }
+    }
+
+    pub fn codec(&self) -> &BlockHamming {
+        &self.codec
+    }
+
+    /// Number of bits pushed that don't make up a whole block yet.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn push_bit(&mut self, bit: bool) {
+        self.pending.push(bit);
+        if self.pending.len() == self.codec.data_bits_per_block() {
+            self.encode_pending();
+        }
+    }
+
+    pub fn push_bits(&mut self, bits: &BitVec) {
+        for i in 0..bits.len() {
+            self.push_bit(bits.get(i).unwrap_or(false));
+        }
+    }
+
+    /// Takes the codewords of the blocks completed so far.
+    pub fn take_output(&mut self) -> BitVec {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Takes whatever output is left, with the pending bits zero-padded into a
+    /// final block like [`BlockHamming`] pads them.
+    pub fn finish(mut self) -> BitVec {
+        if !self.pending.is_empty() {
+            self.encode_pending();
+        }
+        self.output
+    }
+
+    fn encode_pending(&mut self) {
+        let codeword = self
+            .codec
+            .encode(&self.pending)
+            .expect("at most a block always encodes");
+        self.output.append(&codeword);
+        self.pending.clear();
+    }
+}
+
+/// Decodes the codeword bits pushed into it block by block.
+///
+/// The padding of the last block comes out along with the data, as it does
+/// from [`BlockHamming`], so it's up to the caller to cut it off by length.
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    codec: BlockHamming,
+    pending: BitVec,
+    output: BitVec,
+    /// Blocks decoded so far.
+    blocks: usize,
+    corrected: Vec<usize>,
+}
+
+impl Default for Decoder {
+    /// Decodes Hamming(15,11) blocks.
+    fn default() -> Self {
+        Self::new(BlockHamming::hamming1511())
+    }
+}
+
+impl Decoder {
+    pub fn new(codec: BlockHamming) -> Self {
+        Self {
+            pending: BitVec::with_capacity(codec.code_bits_per_block()),
+            output: BitVec::new(),
+            blocks: 0,
+            corrected: Vec::new(),
+            codec,
+        }
+    }
+
+    pub fn codec(&self) -> &BlockHamming {
+        &self.codec
+    }
+
+    /// Number of bits pushed that don't make up a whole block yet.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn push_bit(&mut self, bit: bool) {
+        self.pending.push(bit);
+        if self.pending.len() == self.codec.code_bits_per_block() {
+            self.decode_pending();
+        }
+    }
+
+    pub fn push_bits(&mut self, bits: &BitVec) {
+        for i in 0..bits.len() {
+            self.push_bit(bits.get(i).unwrap_or(false));
+        }
+    }
+
+    /// Takes the data of the blocks decoded so far.
+    pub fn take_output(&mut self) -> BitVec {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Positions corrected so far, counted from the first bit pushed.
+    pub fn corrected_so_far(&self) -> &[usize] {
+        &self.corrected
+    }
+
+    /// What decoding everything pushed so far came to, in the shape
+    /// [`BlockHamming::decode`] reports it.
+    pub fn status(&self) -> DecodeStatus {
+        DecodeStatus::corrected_many(self.corrected.clone())
+    }
+
+    /// Takes whatever output is left, failing with
+    /// [`HammingError::UnexpectedOutOfBounds`] if the bits pushed end partway
+    /// through a block, like [`BlockHamming::decode`] does.
+    pub fn finish(self) -> Result<DecodeOutcome, HammingError> {
+        if !self.pending.is_empty() {
+            return Err(HammingError::UnexpectedOutOfBounds);
+        }
+        Ok(DecodeOutcome {
+            status: DecodeStatus::corrected_many(self.corrected),
+            data: self.output,
+        })
+    }
+
+    fn decode_pending(&mut self) {
+        let DecodeOutcome { data, status } = self
+            .codec
+            .decode(&self.pending)
+            .expect("a whole block always decodes");
+        // every syndrome of a perfect code points at a bit, so a block is
+        // either clean or corrected
+        if let DecodeStatus::CorrectedMany { codeword_positions } = status {
+            let start = self.blocks * self.codec.code_bits_per_block();
+            self.corrected
+                .extend(codeword_positions.into_iter().map(|pos| start + pos));
+        }
+        self.output.append(&data);
+        self.pending.clear();
+        self.blocks += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::Rng;
+
+    use super::*;
+    use crate::testing::bitvec_strategy;
+
+    /// Feeds `bits` to `push` in bursts of random sizes, `max` at most.
+    fn in_bursts(bits: &BitVec, max: usize, mut push: impl FnMut(&BitVec)) {
+        let mut rng = rand::rng();
+        let mut start = 0;
+        while start < bits.len() {
+            let end = (start + rng.random_range(0..=max)).min(bits.len());
+            push(&bits.slice(start..end).unwrap());
+            start = end;
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_batch_encode(data in bitvec_strategy(300), r in 3usize..=7) {
+            let codec = BlockHamming::new(r).unwrap();
+            let expected = codec.encode(&data).unwrap().to_vec();
+
+            let mut encoder = Encoder::new(codec);
+            let mut one_by_one = BitVec::new();
+            for i in 0..data.len() {
+                encoder.push_bit(data.get(i).unwrap());
+                one_by_one.append(&encoder.take_output());
+            }
+            one_by_one.append(&encoder.finish());
+            prop_assert_eq!(one_by_one.to_vec(), expected.clone());
+
+            let mut encoder = Encoder::new(codec);
+            let mut bursts = BitVec::new();
+            in_bursts(&data, 40, |bits| {
+                encoder.push_bits(bits);
+                bursts.append(&encoder.take_output());
+            });
+            bursts.append(&encoder.finish());
+            prop_assert_eq!(bursts.to_vec(), expected.clone());
+
+            let mut encoder = Encoder::new(codec);
+            encoder.push_bits(&data);
+            prop_assert_eq!(encoder.finish().to_vec(), expected);
+        }
+
+        #[test]
+        fn prop_matches_batch_decode(
+            data in bitvec_strategy(300),
+            r in 3usize..=7,
+            flips in prop::collection::vec(any::<prop::sample::Index>(), 0..4),
+        ) {
+            let codec = BlockHamming::new(r).unwrap();
+            let mut codeword = codec.encode(&data).unwrap();
+            if !codeword.is_empty() {
+                for flip in &flips {
+                    let i = flip.index(codeword.len());
+                    codeword.toggle(i).unwrap();
+                }
+            }
+            let expected = codec.decode(&codeword).unwrap();
+
+            let mut decoder = Decoder::new(codec);
+            let mut one_by_one = BitVec::new();
+            for i in 0..codeword.len() {
+                decoder.push_bit(codeword.get(i).unwrap());
+                one_by_one.append(&decoder.take_output());
+            }
+            let outcome = decoder.finish().unwrap();
+            one_by_one.append(&outcome.data);
+            prop_assert_eq!(one_by_one.to_vec(), expected.data.to_vec());
+            prop_assert_eq!(&outcome.status, &expected.status);
+
+            let mut decoder = Decoder::new(codec);
+            let mut bursts = BitVec::new();
+            in_bursts(&codeword, 40, |bits| {
+                decoder.push_bits(bits);
+                bursts.append(&decoder.take_output());
+            });
+            prop_assert_eq!(decoder.status(), expected.status.clone());
+            bursts.append(&decoder.finish().unwrap().data);
+            prop_assert_eq!(bursts.to_vec(), expected.data.to_vec());
+
+            let mut decoder = Decoder::new(codec);
+            decoder.push_bits(&codeword);
+            let outcome = decoder.finish().unwrap();
+            prop_assert_eq!(outcome.data.to_vec(), expected.data.to_vec());
+            prop_assert_eq!(outcome.status, expected.status);
+        }
+    }
+
+    #[test]
+    fn test_output_per_block() {
+        let mut encoder = Encoder::default();
+        encoder.push_bits(&BitVec::ones(10));
+        assert!(encoder.take_output().is_empty());
+        assert_eq!(encoder.pending_len(), 10);
+
+        // the 11th bit completes a block, which comes out straight away
+        encoder.push_bit(true);
+        assert_eq!(encoder.take_output().len(), 15);
+        assert!(encoder.take_output().is_empty());
+        assert_eq!(encoder.pending_len(), 0);
+
+        encoder.push_bit(true);
+        let codeword = encoder.finish();
+        assert_eq!(codeword.len(), 15);
+
+        let mut decoder = Decoder::default();
+        let mut corrupted = codeword.clone();
+        corrupted.toggle(3).unwrap();
+        decoder.push_bits(&codeword);
+        decoder.push_bits(&corrupted);
+        assert_eq!(decoder.corrected_so_far(), &[18]);
+        assert_eq!(decoder.take_output().len(), 22);
+        assert!(decoder.finish().unwrap().data.is_empty());
+    }
+
+    #[test]
+    fn test_empty_and_partial() {
+        assert!(Encoder::default().finish().is_empty());
+        let outcome = Decoder::default().finish().unwrap();
+        assert!(outcome.data.is_empty());
+        assert!(outcome.status.is_clean());
+
+        let mut decoder = Decoder::default();
+        decoder.push_bits(&BitVec::zeros(20));
+        assert_eq!(decoder.pending_len(), 5);
+        assert_eq!(
+            decoder.finish().unwrap_err(),
+            HammingError::UnexpectedOutOfBounds
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::LazyLock;
+
+use crate::encoding::{
+    bitvec::BitVec,
+    block::BlockHamming,
+    hamming::{DecodeOutcome, DecodeStatus, HammingCode, HammingCodeBase, HammingError},
+};
+
+/// What to do with a block, by syndrome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Clean,
+    /// Flip the bit at this 0-based position of the block.
+    Flip(usize),
+    Uncorrectable,
+}
+
+/// Block decoder for Hamming(2^r − 1, 2^r − 1 − r) driven by a syndrome table.
+///
+/// Every block fits in a `u64`, so its syndrome is one masked popcount per
+/// parity bit, and the table says straight away which bit to flip, if any.
+/// Blocks are laid out like [`BlockHamming`]'s, which also does the encoding.
+///
+/// The table has an entry for every `r` bit syndrome: 0 is clean, the ones
+/// pointing into the block flip that bit, anything else is uncorrectable. For
+/// these perfect codes every non-zero syndrome points into the block, but the
+/// table doesn't rely on it.
+#[derive(Debug, Clone)]
+pub struct SyndromeTableDecoder {
+    block: BlockHamming,
+    /// Per parity bit, the block bits it covers, MSB-first like
+    /// [`BitVec::to_uint_range`].
+    parity_masks: Vec<u64>,
+    /// Per block bit (MSB-first), whether it carries data.
+    data_positions: Vec<usize>,
+    table: Vec<Action>,
+}
+
+static HAMMING_3126: LazyLock<SyndromeTableDecoder> =
+    LazyLock::new(|| SyndromeTableDecoder::for_block(5).expect("5 parity bits have a table"));
+static HAMMING_6357: LazyLock<SyndromeTableDecoder> =
+    LazyLock::new(|| SyndromeTableDecoder::for_block(6).expect("6 parity bits have a table"));
+
+impl SyndromeTableDecoder {
+    /// Largest `r` a table is built for, keeping blocks within 63 bits.
+    pub const MAX_PARITY_BITS: usize = 6;
+
+    /// Builds the table for blocks with `r` parity bits, failing with
+    /// [`HammingError::BlockSizeInvalid`] unless `r` is between
+    /// [`BlockHamming::MIN_PARITY_BITS`] and [`Self::MAX_PARITY_BITS`].
+    pub fn for_block(r: usize) -> Result<Self, HammingError> {
+        if r > Self::MAX_PARITY_BITS {
+            return Err(HammingError::BlockSizeInvalid(r));
+        }
+        let block = BlockHamming::new(r)?;
+        let n = block.code_bits_per_block();
+        // 1-based position p is bit `n - p` of the block value
+        let bit = |position: usize| 1u64 << (n - position);
+
+        let parity_masks = HammingCodeBase::parity_positions(n)
+            .into_iter()
+            .map(|parity| {
+                (1..=n)
+                    .filter(|position| position & (parity + 1) != 0)
+                    .fold(0, |acc, position| acc | bit(position))
+            })
+            .collect();
+        let data_positions = (0..n).filter(|i| !(i + 1).is_power_of_two()).collect();
+        let table = (0..1usize << r)
+            .map(|syndrome| match syndrome {
+                0 => Action::Clean,
+                s if s <= n => Action::Flip(s - 1),
+                _ => Action::Uncorrectable,
+            })
+            .collect();
+
+        Ok(Self {
+            block,
+            parity_masks,
+            data_positions,
+            table,
+        })
+    }
+
+    /// The shared decoder for the block sizes [`BlockHamming`] decodes
+    /// through one, built the first time it's asked for.
+    pub(crate) fn for_parity_bits(parity_bits: usize) -> Option<&'static Self> {
+        match parity_bits {
+            5 => Some(&HAMMING_3126),
+            6 => Some(&HAMMING_6357),
+            _ => None,
+        }
+    }
+
+    pub fn parity_bits(&self) -> usize {
+        self.block.parity_bits()
+    }
+
+    pub fn code_bits_per_block(&self) -> usize {
+        self.block.code_bits_per_block()
+    }
+
+    pub fn data_bits_per_block(&self) -> usize {
+        self.block.data_bits_per_block()
+    }
+
+    /// Syndrome of a single block value.
+    fn syndrome(&self, block: u64) -> usize {
+        self.parity_masks
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, mask)| {
+                acc | (((block & mask).count_ones() as usize & 1) << i)
+            })
+    }
+}
+
+impl HammingCode for SyndromeTableDecoder {
+    fn calculate_parity(
+        &self,
+        codeword: &BitVec,
+        parity_mask: usize,
+    ) -> Result<bool, HammingError> {
+        HammingCodeBase::basic_compute_parity(codeword, parity_mask)
+    }
+
+    fn encode(&self, data: &BitVec) -> Result<BitVec, HammingError> {
+        self.block.encode(data)
+    }
+
+    /// Decodes whole blocks like [`BlockHamming::decode`], padding included.
+    /// Any uncorrectable block makes the whole outcome uncorrectable.
+    fn decode(&self, codeword: &BitVec) -> Result<DecodeOutcome, HammingError> {
+        let n = self.code_bits_per_block();
+        if !codeword.len().is_multiple_of(n) {
+            return Err(HammingError::InvalidCodewordLength {
+                got: codeword.len(),
+                expected_for_r: codeword.len().next_multiple_of(n),
+            });
+        }
+
+        let blocks = codeword.len() / n;
+        let mut data = BitVec::with_capacity(blocks * self.data_bits_per_block());
+        let mut corrected = Vec::new();
+        let mut uncorrectable = false;
+
+        for start in (0..codeword.len()).step_by(n) {
+            let mut value = codeword
+                .to_uint_range(start..start + n)
+                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+
+            match self.table[self.syndrome(value)] {
+                Action::Clean => {}
+                Action::Flip(position) => {
+                    value ^= 1 << (n - 1 - position);
+                    corrected.push(start + position);
+                }
+                Action::Uncorrectable => uncorrectable = true,
+            }
+
+            for &position in &self.data_positions {
+                data.push(value & (1 << (n - 1 - position)) != 0);
+            }
+        }
+
+        let status = if uncorrectable {
+            DecodeStatus::Uncorrectable
+        } else {
+            DecodeStatus::corrected_many(corrected)
+        };
+        Ok(DecodeOutcome { data, status })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_sizes() {
+        for r in 3..=6 {
+            let decoder = SyndromeTableDecoder::for_block(r).unwrap();
+            let n = (1 << r) - 1;
+            assert_eq!(decoder.table.len(), 1 << r);
+            assert_eq!(decoder.table[0], Action::Clean);
+            assert!(
+                (1..=n).all(|s| decoder.table[s] == Action::Flip(s - 1)),
+                "r = {r}"
+            );
+        }
+
+        for r in [0, 1, 2, 7, 16] {
+            assert_eq!(
+                SyndromeTableDecoder::for_block(r).unwrap_err(),
+                HammingError::BlockSizeInvalid(r)
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches_generic_decoder() {
+        let mut rng = rand::rng();
+        for r in 3..=6 {
+            let decoder = SyndromeTableDecoder::for_block(r).unwrap();
+            let block = BlockHamming::new(r).unwrap();
+            let k = block.data_bits_per_block();
+
+            let zeros = BitVec::zeros(2 * k);
+            let random = BitVec::random(3 * k, &mut rng);
+            for data in [zeros, random] {
+                let codeword = decoder.encode(&data).unwrap();
+                let outcome = decoder.decode(&codeword).unwrap();
+                assert!(outcome.status.is_clean());
+                assert_eq!(outcome.data.to_vec(), data.to_vec());
+
+                for i in 0..codeword.len() {
+                    let mut corrupted = codeword.clone();
+                    corrupted.toggle(i).unwrap();
+
+                    let outcome = decoder.decode(&corrupted).unwrap();
+                    let expected = block.decode_generic(&corrupted).unwrap();
+                    assert_eq!(outcome.status, expected.status, "r = {r}, bit {i}");
+                    assert_eq!(outcome.data.to_vec(), expected.data.to_vec());
+                    assert_eq!(outcome.data.to_vec(), data.to_vec());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_decoders() {
+        for r in [5, 6] {
+            let shared = SyndromeTableDecoder::for_parity_bits(r).unwrap();
+            assert_eq!(shared.parity_bits(), r);
+            // built once, handed out every time after
+            assert!(std::ptr::eq(
+                shared,
+                SyndromeTableDecoder::for_parity_bits(r).unwrap()
+            ));
+        }
+        // (7,4) and (15,11) have their own tables
+        for r in [3, 4, 7] {
+            assert!(SyndromeTableDecoder::for_parity_bits(r).is_none());
+        }
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        let decoder = SyndromeTableDecoder::for_block(5).unwrap();
+        assert_eq!(
+            decoder.decode(&BitVec::zeros(30)).unwrap_err(),
+            HammingError::InvalidCodewordLength {
+                got: 30,
+                expected_for_r: 31
+            }
+        );
+        assert!(decoder.decode(&BitVec::new()).unwrap().data.is_empty());
+    }
+}
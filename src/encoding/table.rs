@@ -0,0 +1,154 @@
+//! Precomputed tables for the small block codes, Hamming(7,4) and Hamming(15,11).
+
+use std::sync::LazyLock;
+
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{Hamming, HammingCode},
+};
+
+/// Lookup tables for a single block code with `parity_bits` parity bits.
+pub(crate) struct CodeTable {
+    pub data_bits: usize,
+    pub code_bits: usize,
+    /// Data value -> codeword, both MSB-first.
+    encode: Vec<u32>,
+    /// Codeword -> data value, with the parity positions dropped.
+    extract: Vec<u32>,
+    /// Per parity bit, the codeword positions it covers (MSB-first mask).
+    parity_masks: Vec<u32>,
+    /// Syndrome -> codeword bit to flip (as a mask), 0 when clean.
+    syndrome_flips: Vec<u32>,
+}
+
+static HAMMING_74: LazyLock<CodeTable> = LazyLock::new(|| CodeTable::build(3));
+static HAMMING_1511: LazyLock<CodeTable> = LazyLock::new(|| CodeTable::build(4));
+
+impl CodeTable {
+    /// The table for the given block size, if there is one.
+    pub fn for_parity_bits(parity_bits: usize) -> Option<&'static CodeTable> {
+        match parity_bits {
+            3 => Some(&HAMMING_74),
+            4 => Some(&HAMMING_1511),
+            _ => None,
+        }
+    }
+
+    fn build(parity_bits: usize) -> Self {
+        let code_bits = (1 << parity_bits) - 1;
+        let data_bits = code_bits - parity_bits;
+        // position p (1-based) is bit `code_bits - p` of the MSB-first value
+        let bit = |position: usize| 1u32 << (code_bits - position);
+
+        let encode = (0..1u64 << data_bits)
+            .map(|value| {
+                let data = BitVec::from_uint(value, data_bits).expect("value fits in data_bits");
+                let codeword = Hamming.encode(&data).expect("encoding a full block");
+                codeword.to_uint().expect("codeword fits in a u64") as u32
+            })
+            .collect();
+
+        let extract = (0..1u32 << code_bits)
+            .map(|codeword| {
+                (1..=code_bits)
+                    .filter(|position| !position.is_power_of_two())
+                    .fold(0, |acc, position| {
+                        (acc << 1) | u32::from(codeword & bit(position) != 0)
+                    })
+            })
+            .collect();
+
+        let parity_masks = (0..parity_bits)
+            .map(|i| {
+                (1..=code_bits)
+                    .filter(|position| position & (1 << i) != 0)
+                    .fold(0, |acc, position| acc | bit(position))
+            })
+            .collect();
+
+        // every syndrome of a perfect Hamming code points at a bit
+        let syndrome_flips = (0..=code_bits)
+            .map(|syndrome| if syndrome == 0 { 0 } else { bit(syndrome) })
+            .collect();
+
+        Self {
+            data_bits,
+            code_bits,
+            encode,
+            extract,
+            parity_masks,
+            syndrome_flips,
+        }
+    }
+
+    pub fn encode_block(&self, value: u32) -> u32 {
+        self.encode[value as usize]
+    }
+
+    /// The syndrome of one codeword, 0 when it's clean.
+    pub fn syndrome(&self, codeword: u32) -> usize {
+        self.parity_masks
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, mask)| {
+                acc | (((codeword & mask).count_ones() as usize & 1) << i)
+            })
+    }
+
+    /// Corrects and decodes one codeword, returning the data and the 0-based
+    /// index of the flipped bit, if any.
+    pub fn decode_block(&self, codeword: u32) -> (u32, Option<usize>) {
+        let syndrome = self.syndrome(codeword);
+        let flip = self.syndrome_flips[syndrome];
+        (
+            self.extract[(codeword ^ flip) as usize],
+            syndrome.checked_sub(1),
+        )
+    }
+
+    /// Encodes `data` block by block, zero padding the last block.
+    pub fn encode(&self, data: &BitVec) -> BitVec {
+        let blocks = data.len().div_ceil(self.data_bits);
+        let mut out = BitVec::with_capacity(blocks * self.code_bits);
+
+        for block in 0..blocks {
+            let start = block * self.data_bits;
+            let end = (start + self.data_bits).min(data.len());
+            let value = data
+                .to_uint_range(start..end)
+                .expect("block is within the data and at most 11 bits")
+                << (self.data_bits - (end - start));
+
+            push_uint(&mut out, self.encode_block(value as u32), self.code_bits);
+        }
+
+        out
+    }
+
+    /// Decodes whole blocks, returning the data and the positions of the
+    /// corrected bits. `codeword` must be a multiple of `code_bits` long.
+    pub fn decode(&self, codeword: &BitVec) -> (BitVec, Vec<usize>) {
+        let blocks = codeword.len() / self.code_bits;
+        let mut out = BitVec::with_capacity(blocks * self.data_bits);
+        let mut corrected = Vec::new();
+
+        for block in 0..blocks {
+            let start = block * self.code_bits;
+            let value = codeword
+                .to_uint_range(start..start + self.code_bits)
+                .expect("block is within the codeword and at most 15 bits");
+
+            let (data, flipped) = self.decode_block(value as u32);
+            corrected.extend(flipped.map(|pos| start + pos));
+            push_uint(&mut out, data, self.data_bits);
+        }
+
+        (out, corrected)
+    }
+}
+
+fn push_uint(bits: &mut BitVec, value: u32, width: usize) {
+    for i in (0..width).rev() {
+        bits.push((value >> i) & 1 == 1);
+    }
+}
@@ -1 +1,5 @@
 pub mod encoding;
+pub mod proto;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
@@ -1,84 +1,598 @@
-use anyhow::anyhow;
+use anyhow::{Context, anyhow};
 use clap::Parser;
 use cli::enums::DataType;
-use encoding::bitvec::BitVec;
-use std::io::{Read, Write};
-use utils::misc::{bits_to_bytestring, bits_to_string, bytestring_to_bitvec, string_to_bits};
+#[cfg(unix)]
+use hamming_rust::proto::transport::UnixTransport;
+use hamming_rust::{
+    encoding::{bitvec::BitVec, channel::BinarySymmetricChannel, mapping, source::BitSource},
+    proto::{
+        self, Capabilities, GUSProtocol, Heartbeat, IdleMonitor, Latency, PacketType,
+        ProtocolError, Reassembler, Reassembly, SequenceTracker, Sequenced,
+        transport::{StdioTransport, TcpTransport, Transport, UdpTransport},
+    },
+};
+use std::{
+    fs::File,
+    io::{BufWriter, IsTerminal, Read, Write},
+    net::TcpListener,
+    num::NonZeroUsize,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use utils::misc::{
+    base64_to_bytes, bits_to_bytestring, bytes_to_base64, bytestring_to_bitvec, hex_preview,
+};
 
 mod cli;
-mod encoding;
-mod proto;
 mod utils;
 
+/// The code in the error frame the sender sends when it fails.
+const SENDER_FAILED: u16 = 1;
+
+/// How many bytes of data the receiver shows the start of, when it doesn't
+/// show all of it.
+const PREVIEW_BYTES: usize = 32;
+
+/// What the receiver exits with when the sender's been idle too long, with
+/// `--exit-on-idle`.
+const SENDER_IDLE: i32 = 2;
+
 fn main() {
     let args = cli::Args::parse();
     utils::log::Logger::init(&args);
 
     match args.command {
         cli::Subcommands::Sender(sender_args) => {
-            sender(sender_args, args.type_data).unwrap_or_else(|e| {
-                std::io::stdout()
-                    .write_all(b"ERR")
-                    .expect("Failed to write to stdout");
-                log::error!("Error: {}", e);
-                std::process::exit(1);
+            let data_type = args.type_data.unwrap_or(DataType::Binary);
+            // before there's anywhere to send to, so a file that can't be read
+            // doesn't leave so much as an error frame behind
+            let data = sender_data(&sender_args, &data_type).unwrap_or_else(|e| exit_with(e));
+            let mut transport = sender_transport(&sender_args).unwrap_or_else(|e| exit_with(e));
+            sender(sender_args, data, data_type, &mut transport).unwrap_or_else(|e| {
+                // so the receiver knows it wasn't just an empty message
+                let frame = proto::GUSProtocol::error(SENDER_FAILED)
+                    .encode()
+                    .expect("an error frame always encodes");
+                if let Err(send_error) = transport.send(&frame) {
+                    log::warn!("Couldn't tell the receiver: {send_error}");
+                }
+                exit_with(e);
             });
         }
         cli::Subcommands::Receiver(receiver_args) => {
-            receiver(receiver_args, args.type_data).unwrap_or_else(|e| {
-                log::error!("Error: {}", e);
-                std::process::exit(1);
-            });
+            // before waiting on a sender that would only be turned away
+            let output = receiver_args.output.as_deref().map(open_output);
+            let output = output.transpose().unwrap_or_else(|e| exit_with(e));
+            let mut transport = receiver_transport(&receiver_args).unwrap_or_else(|e| exit_with(e));
+            receiver(receiver_args, args.type_data, output, &mut transport)
+                .unwrap_or_else(|e| exit_with(e));
         }
     }
 }
 
-fn sender(args: cli::SenderArgs, data_type: DataType) -> Result<(), anyhow::Error> {
-    let data: BitVec = match data_type {
-        DataType::Binary => BitVec::from_vec(bytestring_to_bitvec(&args.data)?),
-        DataType::Text => BitVec::from_vec(string_to_bits(&args.data)),
+fn exit_with(e: anyhow::Error) -> ! {
+    log::error!("Error: {:#}", e);
+    std::process::exit(1);
+}
+
+/// Where the sender's frames go, stdout unless told otherwise.
+fn sender_transport(args: &cli::SenderArgs) -> Result<Box<dyn Transport>, anyhow::Error> {
+    #[cfg(unix)]
+    if let Some(path) = &args.socket {
+        return Ok(Box::new(UnixTransport::connect(path).with_context(
+            || format!("Error connecting to {}", path.display()),
+        )?));
+    }
+
+    Ok(match &args.connect {
+        Some(addr) if args.udp => Box::new(
+            UdpTransport::connect(addr.as_str())
+                .with_context(|| format!("Error connecting to {addr}"))?,
+        ),
+        Some(addr) => Box::new(
+            TcpTransport::connect(addr.as_str(), None)
+                .with_context(|| format!("Error connecting to {addr}"))?,
+        ),
+        None => Box::new(StdioTransport::new()),
+    })
+}
+
+/// Where the receiver's frames come from, stdin unless told otherwise.
+fn receiver_transport(args: &cli::ReceiverArgs) -> Result<Box<dyn Transport>, anyhow::Error> {
+    #[cfg(unix)]
+    if let Some(path) = &args.socket {
+        let listener = UnixTransport::listen(path)
+            .with_context(|| format!("Error listening on {}", path.display()))?;
+        log::info!("Waiting for a sender on {}...", path.display());
+        let transport = UnixTransport::accept(&listener).context("Error accepting a sender")?;
+        // nobody else gets to connect, same as once a TCP listener is gone
+        if let Err(e) = std::fs::remove_file(path) {
+            log::debug!("Couldn't remove {}: {e}", path.display());
+        }
+        return Ok(Box::new(transport));
+    }
+
+    Ok(match &args.listen {
+        Some(addr) if args.udp => {
+            let transport = UdpTransport::bind(addr.as_str())
+                .with_context(|| format!("Error listening on {addr}"))?;
+            log::info!("Waiting for datagrams on {}...", transport.local_addr()?);
+            Box::new(transport)
+        }
+        Some(addr) => {
+            let listener = TcpListener::bind(addr.as_str())
+                .with_context(|| format!("Error listening on {addr}"))?;
+            log::info!("Waiting for a sender on {}...", listener.local_addr()?);
+            Box::new(TcpTransport::accept(&listener).context("Error accepting a sender")?)
+        }
+        None => Box::new(StdioTransport::new()),
+    })
+}
+
+/// The data to send, off the command line, out of `--file`, or otherwise
+/// read off stdin to the end. Files and stdin are bytes, whatever the type.
+fn sender_data(args: &cli::SenderArgs, data_type: &DataType) -> Result<BitVec, anyhow::Error> {
+    if let Some(data) = &args.data {
+        return Ok(match data_type {
+            DataType::Binary => bytestring_to_bitvec(data)?[..].to_bitvec(),
+            DataType::Text => data.as_bytes().to_bitvec(),
+            DataType::Bytes => BitVec::from_whole_bytes(data.as_bytes().to_vec()),
+            DataType::Hex => BitVec::from_hex(data).context("Error reading the data as hex")?,
+            DataType::Base64 => BitVec::from_whole_bytes(
+                base64_to_bytes(data).context("Error reading the data as base64")?,
+            ),
+        });
+    }
+
+    let (bytes, source) = match &args.file {
+        Some(path) => (
+            std::fs::read(path).with_context(|| format!("Error reading {}", path.display()))?,
+            path.display().to_string(),
+        ),
+        None => {
+            let mut stdin = std::io::stdin();
+            // there's no telling someone at a terminal what it's waiting for
+            if stdin.is_terminal() {
+                return Err(anyhow!(
+                    "Nothing to send, give it with --data or --file, or pipe it in"
+                ));
+            }
+            let mut bytes = Vec::new();
+            stdin
+                .read_to_end(&mut bytes)
+                .context("Error reading stdin")?;
+            (bytes, "stdin".to_string())
+        }
+    };
+    if let DataType::Text = data_type {
+        std::str::from_utf8(&bytes).with_context(|| format!("{source} is not valid UTF-8"))?;
+    }
+    Ok(BitVec::from_whole_bytes(bytes))
+}
+
+fn sender(
+    args: cli::SenderArgs,
+    data: BitVec,
+    data_type: DataType,
+    transport: &mut impl Transport,
+) -> Result<(), anyhow::Error> {
+    let mut max_fragment_bits = args.max_fragment_bits.map_or(usize::MAX, NonZeroUsize::get);
+    let mut channel = BinarySymmetricChannel::new(args.flip_probability, args.seed);
+
+    let session = match args.handshake {
+        true => {
+            needs_answers(&args, "--handshake")?;
+            let session = Capabilities::default()
+                .negotiate(transport)
+                .context("Error negotiating with the receiver")?;
+            log::info!(
+                "Agreed on version {} and codec {}, frames of at most {} bytes",
+                session.version,
+                session.codec,
+                session.max_frame_bytes
+            );
+            max_fragment_bits = max_fragment_bits.min(session.max_fragment_bits());
+            Some(session)
+        }
+        false => None,
     };
 
-    let packet =
-        proto::GUSProtocol::new(data).map_err(|e| anyhow!("Error creating GUSProtocol: {}", e))?;
-    let encoded = packet
-        .encode()
-        .map_err(|e| anyhow!("Error encoding GUSProtocol: {}", e))?;
+    let mut reliable = match args.retries {
+        Some(max_retries) => {
+            needs_answers(&args, "--retries")?;
+            Some(proto::ReliableSender::new(proto::ArqConfig {
+                max_retries,
+                ..proto::ArqConfig::default()
+            }))
+        }
+        None => None,
+    };
+
+    let packets = proto::GUSProtocol::fragment(data, max_fragment_bits);
+    let mut heartbeat = args
+        .heartbeat
+        .map(|seconds| Heartbeat::new(Duration::from_secs(seconds.get()), Instant::now()));
+    for (sequence, packet) in (0..).zip(packets) {
+        let mut packet = packet.with_content_type((&data_type).into());
+        packet.set_sequence(sequence)?;
+        if let Some(session) = &session {
+            packet = session.apply(packet);
+        }
+        #[cfg(feature = "compress")]
+        if args.compress {
+            packet.flags.insert(proto::Flags::COMPRESSED);
+        }
+        if args.timestamp {
+            packet.set_sent_at(proto::now_micros())?;
+        }
+        if let Some(reliable) = &mut reliable {
+            let message_id = packet.fragment.message_id;
+            let attempts = reliable
+                .send(transport, &packet)
+                .context("Error sending a frame")?;
+            if attempts > 1 {
+                log::info!("Message {message_id} got through on attempt {attempts}");
+            }
+            if let Some(heartbeat) = &mut heartbeat {
+                heartbeat.sent(Instant::now());
+            }
+            continue;
+        }
+        let frame = match args.flip_probability > 0.0 {
+            true => packet.encode_over(&mut channel),
+            false => packet.encode(),
+        }
+        .context("Error encoding GUSProtocol")?;
+        transport.send(&frame).context("Error sending a frame")?;
+        if let Some(heartbeat) = &mut heartbeat {
+            heartbeat.sent(Instant::now());
+        }
+    }
 
-    std::io::stdout().write_all(encoded.as_slice())?;
+    if let Some(heartbeat) = heartbeat {
+        send_heartbeats(heartbeat, transport);
+    }
 
     Ok(())
 }
 
-fn receiver(_: cli::ReceiverArgs, data_type: DataType) -> Result<(), anyhow::Error> {
+/// Sends a heartbeat whenever one's due, until sending one fails, which is
+/// how a sender finds out the receiver's gone.
+fn send_heartbeats(mut heartbeat: Heartbeat, transport: &mut impl Transport) {
+    log::info!("Sent everything, sending heartbeats until the receiver goes away");
+    loop {
+        thread::sleep(heartbeat.until_due(Instant::now()));
+        let Some(beat) = heartbeat.due(Instant::now()) else {
+            continue;
+        };
+        let sent = beat.encode().and_then(|frame| transport.send(&frame));
+        if let Err(e) = sent {
+            log::info!("Stopped sending heartbeats: {e}");
+            return;
+        }
+    }
+}
+
+/// Fails unless the sender's frames go somewhere that can answer, for the
+/// options that wait on the receiver.
+fn needs_answers(args: &cli::SenderArgs, option: &str) -> Result<(), anyhow::Error> {
+    #[cfg(unix)]
+    let connected = args.connect.is_some() || args.socket.is_some();
+    #[cfg(not(unix))]
+    let connected = args.connect.is_some();
+    match connected {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "{option} needs --connect or --socket, stdout can't answer"
+        )),
+    }
+}
+
+fn receiver(
+    args: cli::ReceiverArgs,
+    data_type: Option<DataType>,
+    mut output: Option<Box<dyn Write>>,
+    transport: &mut impl Transport,
+) -> Result<(), anyhow::Error> {
     log::info!("Receiving data...");
 
-    // receive from stdin until EOF
-    let mut buffer = Vec::new();
-    std::io::stdin()
-        .read_to_end(&mut buffer)
-        .expect("Failed to read from stdin");
+    let mut reassembler = Reassembler::new();
+    let mut tracker = SequenceTracker::new(args.reorder_window);
+    let monitor = args
+        .idle_timeout
+        .map(|seconds| watch_sender(Duration::from_secs(seconds.get()), args.exit_on_idle));
+    let mut frames = 0;
+    loop {
+        // frames are decoded as they come in, until the sender is done
+        let (packet, report) = match args.reliable {
+            true => match proto::recv_reliable(transport) {
+                Ok(decoded) => {
+                    frames += 1;
+                    decoded
+                }
+                Err(ProtocolError::EndOfStream) if frames > 0 => break,
+                Err(e) => return Err(e).context("Error receiving a frame"),
+            },
+            false => {
+                let frame = match transport.recv() {
+                    Ok(frame) => frame,
+                    Err(ProtocolError::EndOfStream) if frames > 0 => break,
+                    Err(e @ ProtocolError::DatagramTooLarge { .. }) if args.udp => {
+                        log::error!("Skipping a datagram: {:#}", e);
+                        continue;
+                    }
+                    Err(e) => return Err(e).context("Error receiving a frame"),
+                };
+                frames += 1;
+                match proto::GUSProtocol::decode(frame) {
+                    Ok(decoded) => decoded,
+                    // every datagram stands on its own, a bad one doesn't
+                    // throw off the next
+                    Err(e) if args.udp => {
+                        log::error!("Skipping frame {}: {:#}", frames, e);
+                        continue;
+                    }
+                    Err(e) => return Err(e).context("Error decoding GUSProtocol"),
+                }
+            }
+        };
+        log_report(&report);
+        if let Some(monitor) = &monitor {
+            let mut monitor = monitor.lock().expect("monitor lock poisoned");
+            monitor.observe(&packet, Instant::now());
+        }
+        for (ext_type, value) in packet.extensions.iter() {
+            log::debug!("Header extension {ext_type:?}: {value:02x?}");
+        }
+        match packet.packet_type {
+            PacketType::Data => {}
+            PacketType::Error { code } => {
+                return Err(anyhow!("Sender errored with code {code}, exiting..."));
+            }
+            PacketType::Hello(theirs) => {
+                let session = Capabilities::default()
+                    .answer(&theirs, transport)
+                    .context("Error answering the sender's hello")?;
+                log::info!(
+                    "Agreed on version {} and codec {}",
+                    session.version,
+                    session.codec
+                );
+                continue;
+            }
+            PacketType::Heartbeat {
+                uptime_ms,
+                frames_sent,
+            } => {
+                log::debug!(
+                    "Heartbeat: the sender's been up for {:?} and sent {frames_sent} frames",
+                    Duration::from_millis(uptime_ms)
+                );
+                continue;
+            }
+            control => {
+                log::warn!("Ignoring a {control:?} frame, nothing here asked for one");
+                continue;
+            }
+        }
+
+        // frames from senders that don't number them go straight through
+        let released = match packet.sequence() {
+            Some(sequence) => tracker.push(sequence, packet),
+            None => vec![Sequenced::Frame(packet)],
+        };
+        deliver(&mut reassembler, released, &data_type, &mut output)?;
+    }
+    deliver(&mut reassembler, tracker.finish(), &data_type, &mut output)?;
+    if let Some(output) = &mut output {
+        output.flush().context("Error writing the data")?;
+    }
+
+    let heartbeat = monitor.and_then(|monitor| {
+        let monitor = monitor.lock().expect("monitor lock poisoned");
+        monitor.last_heartbeat()
+    });
+    if let Some(status) = heartbeat {
+        log::info!(
+            "The sender's last heartbeat had it up for {:?}, with {} frames sent",
+            status.uptime,
+            status.frames_sent
+        );
+    }
+
+    let stats = tracker.stats();
+    if stats.received > 0 {
+        log::info!(
+            "Received {} numbered frames: {} duplicates, {} out of order, {} lost",
+            stats.received,
+            stats.duplicates,
+            stats.reordered,
+            stats.lost
+        );
+    }
+
+    if let Some(message_id) = reassembler.pending().next() {
+        return Err(anyhow!(
+            "Message {} is missing fragments {:?}",
+            message_id,
+            reassembler.missing(message_id).unwrap_or_default()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Where `--output` writes the data: the file at `path`, created or emptied
+/// first, or stdout for `-`.
+fn open_output(path: &Path) -> Result<Box<dyn Write>, anyhow::Error> {
+    if path == Path::new("-") {
+        return Ok(Box::new(std::io::stdout()));
+    }
+    let file = File::create(path).with_context(|| format!("Error creating {}", path.display()))?;
+    Ok(Box::new(BufWriter::new(file)))
+}
+
+/// Keeps an eye on the sender from a thread of its own, since the receiver's
+/// own is stuck waiting on the next frame: warns once it's been quiet for
+/// `timeout`, or exits if `exit` says to.
+fn watch_sender(timeout: Duration, exit: bool) -> Arc<Mutex<IdleMonitor>> {
+    let monitor = Arc::new(Mutex::new(IdleMonitor::new(timeout, Instant::now())));
+    let watched = monitor.clone();
+    thread::spawn(move || {
+        loop {
+            thread::sleep((timeout / 10).max(Duration::from_millis(10)));
+            let idle = watched
+                .lock()
+                .expect("monitor lock poisoned")
+                .check(Instant::now());
+            let Some(idle) = idle else {
+                continue;
+            };
+            match exit {
+                true => {
+                    log::error!("Nothing from the sender for {idle:?}, exiting...");
+                    std::process::exit(SENDER_IDLE);
+                }
+                false => log::warn!("Nothing from the sender for {idle:?}, is it still there?"),
+            }
+        }
+    });
+    monitor
+}
 
-    if buffer.starts_with(b"ERR") {
-        return Err(anyhow!("Sender errored, exiting..."));
+/// Puts the data frames let out by the sequence tracker back into their
+/// messages, showing each as it's complete, or writing it to `output`.
+fn deliver(
+    reassembler: &mut Reassembler,
+    released: Vec<Sequenced<GUSProtocol>>,
+    data_type: &Option<DataType>,
+    output: &mut Option<Box<dyn Write>>,
+) -> Result<(), anyhow::Error> {
+    for released in released {
+        let packet = match released {
+            Sequenced::Frame(packet) => packet,
+            Sequenced::Lost(sequences) => {
+                log::warn!(
+                    "Frames {} to {} never came",
+                    sequences.start(),
+                    sequences.end()
+                );
+                continue;
+            }
+        };
+        let content_type = packet.content_type;
+        let fragment = packet.fragment;
+        if !fragment.is_whole() {
+            log::debug!(
+                "Received fragment {} of {} of message {}",
+                fragment.index,
+                fragment.count,
+                fragment.message_id
+            );
+        }
+        match reassembler.push(packet)? {
+            Reassembly::Complete(data) => match output {
+                Some(output) => write_data(data, output)?,
+                None => show(data, content_type, data_type.clone())?,
+            },
+            Reassembly::Pending => {}
+            Reassembly::Duplicate => log::warn!(
+                "Dropped a duplicate of fragment {} of message {}",
+                fragment.index,
+                fragment.message_id
+            ),
+        }
     }
+    Ok(())
+}
 
-    log::debug!("Buffer:\n{:?}", buffer);
+/// Logs what decoding a frame corrected.
+fn log_report(report: &proto::DecodeReport) {
+    log::debug!(
+        "Decoded a version {} frame of {} bytes, {} bits of data ({:?})",
+        report.version,
+        report.frame_bytes,
+        report.payload_bits,
+        report.integrity
+    );
+    match report.latency {
+        Some(Latency::InFlight { micros }) => {
+            log::info!("Frame spent {:?} in flight", Duration::from_micros(micros))
+        }
+        Some(Latency::ClockSkew { micros }) => log::warn!(
+            "Frame says it was sent {:?} after it arrived, clock skew between sender and receiver",
+            Duration::from_micros(micros)
+        ),
+        None => {}
+    }
+    let Some(corrected) = &report.corrected else {
+        return;
+    };
 
-    // decode the packet
-    let (packet, errored) = proto::GUSProtocol::decode(buffer)
-        .map_err(|e| anyhow!("Error decoding GUSProtocol: {}", e))?;
+    if !corrected.header.is_empty() {
+        log::warn!(
+            "Corrected errors in the packet header at positions {:?}",
+            corrected.header
+        );
+    }
+    for &position in &corrected.payload {
+        match mapping::codeword_to_data(position) {
+            Some(data_pos) => log::warn!(
+                "Corrected an error at position {} (data bit {})",
+                position,
+                data_pos
+            ),
+            None => log::warn!("Corrected an error at position {} (parity bit)", position),
+        }
+    }
+}
 
-    if errored {
-        log::warn!("Correctable error detected in received data");
+/// Writes a whole message to `output` as it is, whatever its type.
+fn write_data(data: BitVec, output: &mut impl Write) -> Result<(), anyhow::Error> {
+    let bits = data.len();
+    if !bits.is_multiple_of(8) {
+        log::warn!("Received {bits} bits, padding the last byte with zeros");
     }
+    let bytes = data.into_inner();
+    output.write_all(&bytes).context("Error writing the data")?;
+    log::info!(
+        "Wrote {} bytes ({bits} bits): {}",
+        bytes.len(),
+        hex_preview(&bytes, PREVIEW_BYTES)
+    );
+    Ok(())
+}
 
-    log::info!("Length (bits): {:?}", packet.data.len());
+/// Prints a whole message, as `data_type` or whatever its frames said it is.
+fn show(
+    data: BitVec,
+    content_type: proto::ContentType,
+    data_type: Option<DataType>,
+) -> Result<(), anyhow::Error> {
+    log::info!("Length (bits): {:?}", data.len());
 
-    let bitvec = packet.data.to_vec();
-    let data = match data_type {
-        DataType::Binary => bits_to_bytestring(&bitvec),
-        DataType::Text => bits_to_string(&bitvec),
+    if data.is_empty() {
+        log::info!("Received an empty payload");
+        return Ok(());
+    }
+
+    let data = match DataType::for_payload(data_type, content_type) {
+        DataType::Bytes => {
+            let bytes = data.into_inner();
+            log::info!(
+                "Received {} bytes: {}",
+                bytes.len(),
+                hex_preview(&bytes, PREVIEW_BYTES)
+            );
+            return Ok(());
+        }
+        DataType::Binary => bits_to_bytestring(&data.to_vec()),
+        DataType::Hex => data.to_hex(),
+        DataType::Base64 => bytes_to_base64(&data.into_inner()),
+        DataType::Text => String::from_utf8(data.into_inner())
+            .map_err(|_| anyhow!("Received data is not valid UTF-8"))?,
     };
 
     log::info!("Received data:\n{}", data);
@@ -0,0 +1,300 @@
+//! Sending over a link that answers: the receiver acks every frame that
+//! decodes and nacks every one that doesn't, and the sender sends it again
+//! until it gets through or it's tried enough times.
+//!
+//! One frame is in flight at a time. The sender waits for its answer before
+//! sending the next, so an answer is always about the frame just sent or one
+//! still kept from before it.
+
+use std::collections::VecDeque;
+
+use crate::proto::{
+    DecodeReport, GUSProtocol, NackReason, PacketType, ProtocolError, header::FrameHeader,
+    transport::Transport,
+};
+
+/// The message id a receiver nacks a frame with when even its header didn't
+/// decode, so there's no telling which message it was. The sender takes it as
+/// being about the frame it's waiting on.
+pub const UNKNOWN_MESSAGE: u32 = u32::MAX;
+
+/// How hard [`send_reliable`] tries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArqConfig {
+    /// Times a frame is sent again after the first, before giving up.
+    pub max_retries: u32,
+    /// Frames kept after they were acked, newest first, for a late nack to
+    /// still find.
+    pub history: usize,
+}
+
+impl Default for ArqConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            history: 16,
+        }
+    }
+}
+
+/// The sending end of the link, keeping the last [`ArqConfig::history`]
+/// frames it sent by their message id.
+#[derive(Debug, Clone, Default)]
+pub struct ReliableSender {
+    config: ArqConfig,
+    sent: VecDeque<(u32, Vec<u8>)>,
+}
+
+impl ReliableSender {
+    pub fn new(config: ArqConfig) -> Self {
+        Self {
+            config,
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Sends `packet` until the receiver acks it, returning how many times it
+    /// took. Fails with [`ProtocolError::RetriesExhausted`] once it's been
+    /// nacked more than [`ArqConfig::max_retries`] times.
+    ///
+    /// An answer that doesn't decode is taken as a nack, and a nack for an
+    /// earlier frame still kept sends that one again without counting
+    /// against this one.
+    pub fn send(
+        &mut self,
+        transport: &mut impl Transport,
+        packet: &GUSProtocol,
+    ) -> Result<u32, ProtocolError> {
+        let message_id = packet.fragment.message_id;
+        let frame = packet.clone().encode()?;
+        let mut attempts = 1;
+        transport.send(&frame)?;
+
+        loop {
+            let reason = match GUSProtocol::decode(transport.recv()?) {
+                Ok((answer, _)) => match answer.packet_type {
+                    PacketType::Ack { message_id: acked } if acked == message_id => break,
+                    // a late ack of something already done with
+                    PacketType::Ack { .. } => continue,
+                    PacketType::Nack {
+                        message_id: nacked,
+                        reason,
+                    } if nacked == message_id || nacked == UNKNOWN_MESSAGE => reason,
+                    PacketType::Nack {
+                        message_id: nacked, ..
+                    } => {
+                        match self.sent.iter().find(|(id, _)| *id == nacked) {
+                            Some((_, earlier)) => transport.send(earlier)?,
+                            None => log::warn!("Nack for message {nacked}, long gone"),
+                        }
+                        continue;
+                    }
+                    got => {
+                        return Err(ProtocolError::UnexpectedPacket {
+                            expected: "ack or nack",
+                            got,
+                        });
+                    }
+                },
+                Err(e) => {
+                    log::debug!("Answer to message {message_id} doesn't decode: {e}");
+                    NackReason::Uncorrectable
+                }
+            };
+
+            if attempts > self.config.max_retries {
+                return Err(ProtocolError::RetriesExhausted {
+                    message_id,
+                    attempts,
+                    reason,
+                });
+            }
+            log::debug!("Message {message_id} nacked ({reason:?}), sending it again");
+            attempts += 1;
+            transport.send(&frame)?;
+        }
+
+        self.sent.push_front((message_id, frame));
+        self.sent.truncate(self.config.history);
+        Ok(attempts)
+    }
+}
+
+/// Sends one `packet` on `transport` until the receiver acks it, see
+/// [`ReliableSender::send`].
+pub fn send_reliable(
+    transport: &mut impl Transport,
+    packet: &GUSProtocol,
+    config: &ArqConfig,
+) -> Result<u32, ProtocolError> {
+    ReliableSender::new(config.clone()).send(transport, packet)
+}
+
+/// The receiving end of [`send_reliable`]: waits for the next frame that
+/// decodes, acking it if it's data, and nacks every one before it that
+/// doesn't. Control frames come back as they are, unanswered.
+///
+/// A frame that doesn't decode is nacked with the message id in its header,
+/// or [`UNKNOWN_MESSAGE`] if even that's gone.
+pub fn recv_reliable(
+    transport: &mut impl Transport,
+) -> Result<(GUSProtocol, DecodeReport), ProtocolError> {
+    loop {
+        let frame = transport.recv()?;
+        let error = match GUSProtocol::decode(frame.clone()) {
+            Ok((packet, report)) => {
+                if packet.packet_type == PacketType::Data {
+                    transport.send(&GUSProtocol::ack(packet.fragment.message_id).encode()?)?;
+                }
+                return Ok((packet, report));
+            }
+            Err(error) => error,
+        };
+
+        let message_id = FrameHeader::decode(&frame).map_or(UNKNOWN_MESSAGE, |decoded| {
+            decoded.header.fragment.message_id
+        });
+        let reason = match error {
+            ProtocolError::Integrity { .. }
+            | ProtocolError::MissingChecksum { .. }
+            | ProtocolError::HeaderChecksum { .. } => NackReason::Integrity,
+            ProtocolError::UnsupportedVersion(_)
+            | ProtocolError::UnsupportedFlags(_)
+            | ProtocolError::UnknownCodec(_)
+            | ProtocolError::UnknownPacketType(_) => NackReason::Unsupported,
+            _ => NackReason::Uncorrectable,
+        };
+        log::debug!("Nacking message {message_id}: {error}");
+        transport.send(&GUSProtocol::nack(message_id, reason).encode()?)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{Fragment, transport::ChannelTransport},
+    };
+
+    /// Sends through `inner`, wrecking the first `corrupt` frames past what
+    /// any codec fixes.
+    struct Scripted {
+        inner: ChannelTransport,
+        corrupt: usize,
+        sent: usize,
+    }
+
+    impl Transport for Scripted {
+        fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+            self.sent += 1;
+            match self.sent <= self.corrupt {
+                true => {
+                    let mut frame = frame.to_vec();
+                    let len = frame.len();
+                    for byte in &mut frame[len - 4..] {
+                        *byte ^= 0xff;
+                    }
+                    self.inner.send(&frame)
+                }
+                false => self.inner.send(frame),
+            }
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+            self.inner.recv()
+        }
+    }
+
+    fn packet(message_id: u32) -> GUSProtocol {
+        GUSProtocol {
+            fragment: Fragment {
+                message_id,
+                index: 0,
+                count: 1,
+            },
+            ..GUSProtocol::data(BitVec::random(200, &mut rand::rng()))
+        }
+    }
+
+    #[test]
+    fn test_retransmits() {
+        let (near, mut far) = ChannelTransport::pair();
+        let mut near = Scripted {
+            inner: near,
+            corrupt: 2,
+            sent: 0,
+        };
+        let sent = packet(42);
+        let data = sent.data.to_vec();
+        let receiver = thread::spawn(move || recv_reliable(&mut far).unwrap());
+
+        let attempts = send_reliable(&mut near, &sent, &ArqConfig::default()).unwrap();
+        assert_eq!(attempts, 3);
+        let (received, _) = receiver.join().unwrap();
+        assert_eq!(received.data.to_vec(), data);
+        assert_eq!(received.fragment.message_id, 42);
+    }
+
+    #[test]
+    fn test_retry_limit() {
+        let (near, mut far) = ChannelTransport::pair();
+        let mut near = Scripted {
+            inner: near,
+            corrupt: usize::MAX,
+            sent: 0,
+        };
+        let config = ArqConfig {
+            max_retries: 2,
+            ..ArqConfig::default()
+        };
+        let receiver = thread::spawn(move || {
+            // until the sender gives up and goes away
+            assert!(matches!(
+                recv_reliable(&mut far),
+                Err(ProtocolError::EndOfStream)
+            ));
+        });
+
+        assert!(matches!(
+            send_reliable(&mut near, &packet(7), &config),
+            Err(ProtocolError::RetriesExhausted {
+                message_id: 7,
+                attempts: 3,
+                ..
+            })
+        ));
+        assert_eq!(near.sent, 3);
+        drop(near);
+        receiver.join().unwrap();
+    }
+
+    #[test]
+    fn test_late_nack() {
+        let (mut near, mut far) = ChannelTransport::pair();
+        let mut sender = ReliableSender::new(ArqConfig::default());
+        let first = packet(1);
+        let frame = first.clone().encode().unwrap();
+
+        // acks the first, then asks for it again before acking the second
+        let receiver = thread::spawn(move || {
+            assert_eq!(recv_reliable(&mut far).unwrap().0.fragment.message_id, 1);
+            far.recv().unwrap();
+            far.send(
+                &GUSProtocol::nack(1, NackReason::Integrity)
+                    .encode()
+                    .unwrap(),
+            )
+            .unwrap();
+            let again = far.recv().unwrap();
+            far.send(&GUSProtocol::ack(2).encode().unwrap()).unwrap();
+            again
+        });
+
+        assert_eq!(sender.send(&mut near, &first).unwrap(), 1);
+        assert_eq!(sender.send(&mut near, &packet(2)).unwrap(), 1);
+        assert_eq!(receiver.join().unwrap(), frame);
+    }
+}
@@ -0,0 +1,448 @@
+//! Putting a packet together an option at a time.
+
+use crate::{
+    encoding::bitvec::BitVec,
+    proto::{
+        CodecId, ContentType, Endianness, ExtType, Flags, Fragment, FrameVersion, GUSProtocol,
+        HeaderExtensions, MAX_INTERLEAVE_DEPTH, ProtocolError, now_micros,
+    },
+};
+
+/// Builds a [`GUSProtocol`], see [`GUSProtocol::builder`]. Whatever isn't set
+/// is what [`GUSProtocol::new`] would have picked.
+///
+/// Unlike [`GUSProtocol::encode_as`], which quietly leaves out what an older
+/// version has no room for, [`GUSProtocolBuilder::build`] refuses options the
+/// chosen version can't carry.
+#[derive(Debug, Clone, Default)]
+pub struct GUSProtocolBuilder {
+    data: BitVec,
+    version: Option<FrameVersion>,
+    content_type: Option<ContentType>,
+    fragment: Option<Fragment>,
+    checksum: Option<bool>,
+    endianness: Option<Endianness>,
+    codec: Option<CodecId>,
+    #[cfg(feature = "compress")]
+    compress: bool,
+    extensions: Vec<(ExtType, Vec<u8>)>,
+    interleave_depth: u8,
+    scramble_seed: Option<u32>,
+}
+
+impl GUSProtocol {
+    /// A packet to be built an option at a time, with no data yet.
+    pub fn builder() -> GUSProtocolBuilder {
+        GUSProtocolBuilder::default()
+    }
+}
+
+impl GUSProtocolBuilder {
+    pub fn data(mut self, data: BitVec) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// The frame version to send, [`FrameVersion::CURRENT`] by default.
+    pub fn version(mut self, version: FrameVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Needs version 4.
+    pub fn content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Needs version 5, and an index below the count.
+    pub fn fragment(mut self, fragment: Fragment) -> Self {
+        self.fragment = Some(fragment);
+        self
+    }
+
+    /// Whether a CRC-32 of the data goes along with it. On by default for
+    /// versions that have one, needs version 6.
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// The byte order of the header's numbers, little-endian by default.
+    /// Needs version 6 for big-endian.
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = Some(endianness);
+        self
+    }
+
+    /// Needs version 7 for anything but [`CodecId::Hamming`].
+    pub fn codec(mut self, codec: CodecId) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    /// Whether the data goes out deflated, when that makes it shorter. Off by
+    /// default, needs version 6.
+    #[cfg(feature = "compress")]
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Interleaves the payload's codeword over `depth` rows, so a burst of up
+    /// to `depth` flipped bits lands in as many blocks of the codec. 0 or 1,
+    /// the default, for none. Needs version 10, and at most
+    /// [`MAX_INTERLEAVE_DEPTH`].
+    pub fn interleave(mut self, depth: u8) -> Self {
+        self.interleave_depth = depth;
+        self
+    }
+
+    /// Scrambles the payload, checksum and all, before it's encoded, starting
+    /// from `seed`, which goes along in the header for the receiver to undo
+    /// it. Breaks up long runs of the same bit, like a payload of zeros
+    /// would be. Only the low 31 bits of the seed count, and a seed without
+    /// any leaves the payload as it is. Needs version 11.
+    pub fn scramble(mut self, seed: u32) -> Self {
+        self.scramble_seed = Some(seed);
+        self
+    }
+
+    /// Adds a header extension, or replaces the one of the same type. Needs
+    /// version 9, and all of them to fit in
+    /// [`HeaderExtensions::MAX_SIZE`].
+    pub fn extension(mut self, ext_type: ExtType, value: impl Into<Vec<u8>>) -> Self {
+        self.extensions.push((ext_type, value.into()));
+        self
+    }
+
+    /// Stamps the packet as sent at `micros` microseconds since the Unix
+    /// epoch, for the receiver to tell how long it took, see
+    /// [`GUSProtocol::sent_at`]. Needs version 9.
+    pub fn timestamp(self, micros: u64) -> Self {
+        self.extension(ExtType::SentAt, micros.to_le_bytes())
+    }
+
+    /// Stamps the packet as sent now, by this host's clock. Best left until
+    /// right before it's sent. Needs version 9.
+    pub fn timestamp_now(self) -> Self {
+        self.timestamp(now_micros())
+    }
+
+    /// Numbers the packet as its sender's `sequence`th data frame, see
+    /// [`GUSProtocol::sequence`]. Needs version 9.
+    pub fn sequence(self, sequence: u32) -> Self {
+        self.extension(ExtType::Sequence, sequence.to_le_bytes())
+    }
+
+    /// The packet, if its version can carry every option it was given.
+    pub fn build(self) -> Result<GUSProtocol, ProtocolError> {
+        let version = self.version.unwrap_or(FrameVersion::CURRENT);
+        let needs =
+            |option: &'static str, since: FrameVersion, set: bool| match set && version < since {
+                true => Err(ProtocolError::UnsupportedOption { option, version }),
+                false => Ok(()),
+            };
+
+        let content_type = self.content_type.unwrap_or_default();
+        needs(
+            "A content type",
+            FrameVersion::V4,
+            content_type != ContentType::default(),
+        )?;
+
+        let fragment = self.fragment.unwrap_or_default();
+        needs(
+            "Fragment fields",
+            FrameVersion::V5,
+            fragment != Fragment::default(),
+        )?;
+        if fragment.index >= fragment.count {
+            return Err(ProtocolError::FragmentOutOfRange {
+                message_id: fragment.message_id,
+                index: fragment.index,
+                count: fragment.count,
+            });
+        }
+
+        let checksum = self.checksum.unwrap_or(version >= FrameVersion::V6);
+        needs("A checksum", FrameVersion::V6, checksum)?;
+
+        let endianness = self.endianness.unwrap_or_default();
+        needs(
+            "A big-endian header",
+            FrameVersion::V6,
+            endianness == Endianness::Big,
+        )?;
+        let mut flags = match checksum {
+            true => Flags::CRC32,
+            false => Flags::empty(),
+        };
+        if endianness == Endianness::Big {
+            flags.insert(Flags::BIG_ENDIAN);
+        }
+        #[cfg(feature = "compress")]
+        {
+            needs("Compression", FrameVersion::V6, self.compress)?;
+            if self.compress {
+                flags.insert(Flags::COMPRESSED);
+            }
+        }
+
+        let codec = self.codec.unwrap_or_default();
+        needs("A codec", FrameVersion::V7, codec != CodecId::Hamming)?;
+
+        needs(
+            "Header extensions",
+            FrameVersion::V9,
+            !self.extensions.is_empty(),
+        )?;
+        let mut extensions = HeaderExtensions::new();
+        for (ext_type, value) in self.extensions {
+            extensions.insert(ext_type, value)?;
+        }
+
+        needs("Interleaving", FrameVersion::V10, self.interleave_depth > 1)?;
+        if self.interleave_depth > MAX_INTERLEAVE_DEPTH {
+            return Err(ProtocolError::InterleaveTooDeep {
+                depth: self.interleave_depth,
+                max: MAX_INTERLEAVE_DEPTH,
+            });
+        }
+
+        needs(
+            "Scrambling",
+            FrameVersion::V11,
+            self.scramble_seed.is_some(),
+        )?;
+
+        Ok(GUSProtocol {
+            version,
+            content_type,
+            fragment,
+            flags,
+            codec,
+            extensions,
+            interleave_depth: self.interleave_depth,
+            scramble_seed: self.scramble_seed,
+            ..GUSProtocol::new(self.data)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::IntegrityStatus;
+
+    /// Builds `builder` with `data` and reads it back off the wire.
+    fn round_trip(builder: GUSProtocolBuilder, data: &BitVec) -> GUSProtocol {
+        let encoded = builder
+            .data(data.clone())
+            .build()
+            .unwrap()
+            .encode()
+            .unwrap();
+        let (decoded, _) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        decoded
+    }
+
+    #[test]
+    fn test_defaults() {
+        let data = BitVec::random(100, &mut rand::rng());
+        let built = GUSProtocol::builder().data(data.clone()).build().unwrap();
+        let new = GUSProtocol::new(data).unwrap();
+        assert_eq!(built.clone().encode().unwrap(), new.encode().unwrap());
+
+        // an older version gets what it has room for without being told
+        let built = GUSProtocol::builder()
+            .version(FrameVersion::V3)
+            .build()
+            .unwrap();
+        assert_eq!(built.flags, Flags::empty());
+    }
+
+    #[test]
+    fn test_options() {
+        let data = BitVec::random(100, &mut rand::rng());
+
+        for version in FrameVersion::ALL {
+            let decoded = round_trip(GUSProtocol::builder().version(version), &data);
+            assert_eq!(decoded.version, version);
+        }
+
+        let decoded = round_trip(
+            GUSProtocol::builder().content_type(ContentType::Text),
+            &data,
+        );
+        assert_eq!(decoded.content_type, ContentType::Text);
+
+        let fragment = Fragment {
+            message_id: 7,
+            index: 2,
+            count: 3,
+        };
+        let decoded = round_trip(GUSProtocol::builder().fragment(fragment), &data);
+        assert_eq!(decoded.fragment, fragment);
+
+        let packet = GUSProtocol::builder()
+            .data(data.clone())
+            .checksum(false)
+            .build()
+            .unwrap();
+        let (decoded, report) = GUSProtocol::decode(packet.encode().unwrap()).unwrap();
+        assert_eq!(decoded.flags, Flags::empty());
+        assert_eq!(report.integrity, IntegrityStatus::Unchecked);
+
+        for codec in CodecId::ALL {
+            let decoded = round_trip(GUSProtocol::builder().codec(codec), &data);
+            assert_eq!(decoded.codec, codec);
+        }
+
+        let decoded = round_trip(
+            GUSProtocol::builder()
+                .extension(ExtType::Filename, "old.txt")
+                .extension(ExtType::Origin, [7])
+                .extension(ExtType::Filename, "notes.txt"),
+            &data,
+        );
+        assert_eq!(decoded.extensions.len(), 2);
+        assert_eq!(
+            decoded.extensions.get(ExtType::Filename),
+            Some(&b"notes.txt"[..])
+        );
+        assert_eq!(decoded.extensions.get(ExtType::Origin), Some(&[7][..]));
+
+        let decoded = round_trip(
+            GUSProtocol::builder().timestamp(1_700_000_000_000_000),
+            &data,
+        );
+        assert_eq!(decoded.sent_at(), Some(1_700_000_000_000_000));
+        let before = now_micros();
+        let decoded = round_trip(GUSProtocol::builder().timestamp_now(), &data);
+        assert!(decoded.sent_at().unwrap() >= before);
+        let decoded = round_trip(GUSProtocol::builder().sequence(41), &data);
+        assert_eq!(decoded.sequence(), Some(41));
+
+        let decoded = round_trip(GUSProtocol::builder().interleave(4), &data);
+        assert_eq!(decoded.interleave_depth, 4);
+
+        let decoded = round_trip(GUSProtocol::builder().scramble(0xbeef), &data);
+        assert_eq!(decoded.scramble_seed, Some(0xbeef));
+    }
+
+    #[test]
+    fn test_rejects() {
+        let error = |builder: GUSProtocolBuilder| builder.build().unwrap_err();
+
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .version(FrameVersion::V9)
+                    .interleave(2)
+            ),
+            ProtocolError::UnsupportedOption {
+                option: "Interleaving",
+                ..
+            }
+        ));
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .version(FrameVersion::V10)
+                    .scramble(1)
+            ),
+            ProtocolError::UnsupportedOption {
+                option: "Scrambling",
+                ..
+            }
+        ));
+        assert!(matches!(
+            error(GUSProtocol::builder().interleave(MAX_INTERLEAVE_DEPTH + 1)),
+            ProtocolError::InterleaveTooDeep { depth: 65, .. }
+        ));
+
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .version(FrameVersion::V6)
+                    .codec(CodecId::Golay24)
+            ),
+            ProtocolError::UnsupportedOption {
+                option: "A codec",
+                version: FrameVersion::V6
+            }
+        ));
+        let checksum = error(
+            GUSProtocol::builder()
+                .version(FrameVersion::V5)
+                .checksum(true),
+        );
+        assert_eq!(
+            checksum.to_string(),
+            "A checksum can't go in a version 5 frame"
+        );
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .version(FrameVersion::V4)
+                    .fragment(Fragment {
+                        message_id: 1,
+                        index: 0,
+                        count: 2,
+                    })
+            ),
+            ProtocolError::UnsupportedOption { .. }
+        ));
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .content_type(ContentType::Text)
+                    .version(FrameVersion::V2)
+            ),
+            ProtocolError::UnsupportedOption { .. }
+        ));
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .version(FrameVersion::V5)
+                    .endianness(Endianness::Big)
+            ),
+            ProtocolError::UnsupportedOption {
+                option: "A big-endian header",
+                ..
+            }
+        ));
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .version(FrameVersion::V8)
+                    .extension(ExtType::Origin, [1])
+            ),
+            ProtocolError::UnsupportedOption {
+                option: "Header extensions",
+                ..
+            }
+        ));
+        assert!(matches!(
+            error(
+                GUSProtocol::builder()
+                    .extension(ExtType::Origin, vec![0; HeaderExtensions::MAX_SIZE])
+            ),
+            ProtocolError::ExtensionsTooLong { .. }
+        ));
+        assert!(matches!(
+            error(GUSProtocol::builder().fragment(Fragment {
+                message_id: 1,
+                index: 3,
+                count: 3,
+            })),
+            ProtocolError::FragmentOutOfRange {
+                message_id: 1,
+                index: 3,
+                count: 3
+            }
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! Frames stuffed with COBS (Consistent Overhead Byte Stuffing), so none of
+//! their bytes is 0 and a 0 can end each one. For byte streams with no other
+//! way to tell where a frame starts, e.g. a serial line, a receiver that lost
+//! track picks up again at the next 0.
+
+use std::io::BufRead;
+
+use crate::proto::{DecodeReport, GUSProtocol, ProtocolError};
+
+/// What ends every stuffed frame.
+const DELIMITER: u8 = 0;
+
+/// The longest run of non-zero bytes one code byte covers.
+const MAX_RUN: usize = 0xfe;
+
+/// Stuffs `bytes`, leaving no 0 in them. Each 0 becomes a code byte saying how
+/// far away the next one is, runs longer than [`MAX_RUN`] get a code byte of
+/// their own, so it costs a byte per 254 at most.
+fn stuff(bytes: &[u8]) -> Vec<u8> {
+    let mut stuffed = Vec::with_capacity(bytes.len() + bytes.len() / MAX_RUN + 2);
+    let mut code = stuffed.len();
+    stuffed.push(0);
+    for &byte in bytes {
+        if byte != DELIMITER {
+            stuffed.push(byte);
+        }
+        let run = stuffed.len() - code;
+        if byte == DELIMITER || run > MAX_RUN {
+            stuffed[code] = run as u8;
+            code = stuffed.len();
+            stuffed.push(0);
+        }
+    }
+    stuffed[code] = (stuffed.len() - code) as u8;
+    stuffed
+}
+
+/// Undoes [`stuff`], or `None` if `block` isn't something it could have made:
+/// a 0 in it, or a code byte pointing past its end.
+fn unstuff(block: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(block.len());
+    let mut rest = block;
+    while let Some((&code, after)) = rest.split_first() {
+        let run = after.get(..usize::from(code).checked_sub(1)?)?;
+        if run.contains(&DELIMITER) {
+            return None;
+        }
+        bytes.extend_from_slice(run);
+        rest = &after[run.len()..];
+        // a full run carries no 0 after it, and neither does the last one
+        if usize::from(code) <= MAX_RUN && !rest.is_empty() {
+            bytes.push(DELIMITER);
+        }
+    }
+    Some(bytes)
+}
+
+impl GUSProtocol {
+    /// Encodes the packet, then stuffs the frame so it has no 0 bytes and ends
+    /// it with one. Read it back with [`GUSProtocol::decode_cobs_stream`].
+    pub fn encode_cobs(self) -> Result<Vec<u8>, ProtocolError> {
+        let mut stuffed = stuff(&self.encode()?);
+        stuffed.push(DELIMITER);
+        Ok(stuffed)
+    }
+
+    /// Reads up to the next 0 off `reader` and decodes the stuffed frame
+    /// before it, leaving the reader just past the 0.
+    ///
+    /// Empty blocks, and ones that can't have been stuffed, e.g. because a 0
+    /// got corrupted into something else and two frames ran together, are
+    /// skipped. A block that unstuffs but doesn't decode comes back as its
+    /// error, the reader is at the next frame all the same. A stream that ends
+    /// without a 0 has whatever came last read as if it had one.
+    pub fn decode_cobs_stream(
+        reader: &mut impl BufRead,
+    ) -> Result<(Self, DecodeReport), ProtocolError> {
+        let mut block = Vec::new();
+        loop {
+            block.clear();
+            if reader.read_until(DELIMITER, &mut block)? == 0 {
+                return Err(ProtocolError::EndOfStream);
+            }
+            if block.last() == Some(&DELIMITER) {
+                block.pop();
+            }
+            if block.is_empty() {
+                continue;
+            }
+
+            match unstuff(&block) {
+                Some(frame) => return Self::decode(frame),
+                None => log::warn!("Skipping a malformed COBS block of {} bytes", block.len()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::encoding::bitvec::BitVec;
+
+    #[test]
+    fn test_stuffing() {
+        let cases: [(&[u8], &[u8]); 6] = [
+            (b"", b"\x01"),
+            (b"\0", b"\x01\x01"),
+            (b"\0\0", b"\x01\x01\x01"),
+            (b"\x11\x22\0\x33", b"\x03\x11\x22\x02\x33"),
+            (b"\x11\x22\x33\x44", b"\x05\x11\x22\x33\x44"),
+            (b"\x11\0\0\0", b"\x02\x11\x01\x01\x01"),
+        ];
+        for (bytes, stuffed) in cases {
+            assert_eq!(stuff(bytes), stuffed, "{bytes:?}");
+            assert_eq!(unstuff(stuffed).unwrap(), bytes, "{bytes:?}");
+        }
+
+        // runs longer than a code byte covers
+        for len in [253, 254, 255, 508, 1000] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i % 255 + 1) as u8).collect();
+            let stuffed = stuff(&bytes);
+            assert!(!stuffed.contains(&DELIMITER));
+            assert_eq!(stuffed.len(), len + len / MAX_RUN + 1, "{len}");
+            assert_eq!(unstuff(&stuffed).unwrap(), bytes, "{len}");
+        }
+
+        assert_eq!(unstuff(b"\x05\x11\x22"), None);
+        assert_eq!(unstuff(b"\x03\x11\0"), None);
+        assert_eq!(unstuff(b"\0"), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = BitVec::random(1000, &mut rand::rng());
+        let encoded = GUSProtocol::new(data.clone())
+            .unwrap()
+            .encode_cobs()
+            .unwrap();
+        assert_eq!(
+            encoded.iter().position(|&byte| byte == DELIMITER),
+            Some(encoded.len() - 1)
+        );
+
+        let mut reader = Cursor::new(encoded);
+        let (packet, _) = GUSProtocol::decode_cobs_stream(&mut reader).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+        assert!(matches!(
+            GUSProtocol::decode_cobs_stream(&mut reader),
+            Err(ProtocolError::EndOfStream)
+        ));
+    }
+
+    #[test]
+    fn test_zero_bytes() {
+        // mostly 0s, in the payload as in the header's lengths
+        let data = BitVec::zeros(2000);
+        let frame = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+        assert!(frame.iter().filter(|&&byte| byte == DELIMITER).count() > 100);
+
+        let encoded = GUSProtocol::new(data.clone())
+            .unwrap()
+            .encode_cobs()
+            .unwrap();
+        let (packet, _) = GUSProtocol::decode_cobs_stream(&mut Cursor::new(encoded)).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_back_to_back() {
+        let mut rng = rand::rng();
+        let first = BitVec::random(100, &mut rng);
+        let second = BitVec::random(7, &mut rng);
+        // a leading 0 is how a sender makes sure the line is clear
+        let mut stream = vec![DELIMITER];
+        stream.extend(
+            GUSProtocol::new(first.clone())
+                .unwrap()
+                .encode_cobs()
+                .unwrap(),
+        );
+        stream.extend(
+            GUSProtocol::new(second.clone())
+                .unwrap()
+                .encode_cobs()
+                .unwrap(),
+        );
+        let mut reader = Cursor::new(stream);
+
+        let (packet, _) = GUSProtocol::decode_cobs_stream(&mut reader).unwrap();
+        assert_eq!(packet.data.to_vec(), first.to_vec());
+        let (packet, _) = GUSProtocol::decode_cobs_stream(&mut reader).unwrap();
+        assert_eq!(packet.data.to_vec(), second.to_vec());
+        assert!(matches!(
+            GUSProtocol::decode_cobs_stream(&mut reader),
+            Err(ProtocolError::EndOfStream)
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_frame() {
+        let mut rng = rand::rng();
+        let data = BitVec::random(100, &mut rng);
+        let good = GUSProtocol::new(data.clone())
+            .unwrap()
+            .encode_cobs()
+            .unwrap();
+
+        // a code byte pointing past the end of its block
+        let mut malformed = GUSProtocol::new(BitVec::random(100, &mut rng))
+            .unwrap()
+            .encode_cobs()
+            .unwrap();
+        malformed[0] = 0xff;
+        // and a block that unstuffs fine, into something that's no frame
+        let mut garbled = stuff(&[0xa5; 60]);
+        garbled.push(DELIMITER);
+
+        let mut stream = malformed;
+        stream.extend(&garbled);
+        stream.extend(&good);
+        let mut reader = Cursor::new(stream);
+        assert!(GUSProtocol::decode_cobs_stream(&mut reader).is_err());
+        let (packet, _) = GUSProtocol::decode_cobs_stream(&mut reader).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+
+        // a bit flipped on the way is corrected as ever
+        let mut frame = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+        frame[40] ^= 0x10;
+        let mut flipped = stuff(&frame);
+        flipped.push(DELIMITER);
+        let (packet, report) = GUSProtocol::decode_cobs_stream(&mut Cursor::new(flipped)).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+        assert!(report.corrected.is_some());
+    }
+}
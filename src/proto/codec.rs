@@ -0,0 +1,143 @@
+//! The codes a frame's payload can be sent with, and the ids they go by on
+//! the wire.
+
+use crate::{
+    encoding::{
+        block::BlockHamming,
+        golay::Golay24,
+        hamming::{ExtendedHamming, Hamming, HammingCode, HammingCodeBase},
+    },
+    proto::ProtocolError,
+};
+
+/// Which code protects a frame's payload. Sent as a byte from version 7 on,
+/// frames of earlier versions are always [`CodecId::Hamming`].
+///
+/// Block codes pad the payload out to whole blocks, the header says by how
+/// much so the receiver can drop the padding again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CodecId {
+    /// [`Hamming`] over the whole payload.
+    #[default]
+    Hamming = 0,
+    /// [`ExtendedHamming`] over the whole payload, SECDED: double errors are
+    /// caught rather than miscorrected.
+    ExtendedHamming = 1,
+    /// [`BlockHamming::hamming74`], an error corrected in every 7 bits.
+    Hamming74 = 2,
+    /// [`BlockHamming::hamming1511`], an error corrected in every 15 bits.
+    Hamming1511 = 3,
+    /// [`Golay24`], three errors corrected in every 24 bits.
+    Golay24 = 4,
+}
+
+impl CodecId {
+    /// Every registered codec.
+    pub const ALL: [Self; 5] = [
+        Self::Hamming,
+        Self::ExtendedHamming,
+        Self::Hamming74,
+        Self::Hamming1511,
+        Self::Golay24,
+    ];
+
+    /// The codec id byte on the wire.
+    pub fn byte(self) -> u8 {
+        self as u8
+    }
+
+    /// A fresh instance of the codec.
+    pub fn build(self) -> Box<dyn HammingCode + Send + Sync> {
+        match self {
+            CodecId::Hamming => Box::new(Hamming),
+            CodecId::ExtendedHamming => Box::new(ExtendedHamming),
+            CodecId::Hamming74 => Box::new(BlockHamming::hamming74()),
+            CodecId::Hamming1511 => Box::new(BlockHamming::hamming1511()),
+            CodecId::Golay24 => Box::new(Golay24),
+        }
+    }
+
+    /// Data and code bits per block, for the codes that work in blocks.
+    fn block(self) -> Option<(usize, usize)> {
+        match self {
+            CodecId::Hamming | CodecId::ExtendedHamming => None,
+            CodecId::Hamming74 => Some((4, 7)),
+            CodecId::Hamming1511 => Some((11, 15)),
+            CodecId::Golay24 => Some((Golay24::DATA_BITS, Golay24::CODE_BITS)),
+        }
+    }
+
+    /// Bits of the codeword for `data_bits` bits of payload.
+    pub fn encoded_bits(self, data_bits: usize) -> usize {
+        let parity_bits = HammingCodeBase::calculate_parity_count(data_bits);
+        match self.block() {
+            Some((data, code)) => data_bits.div_ceil(data) * code,
+            None if self == CodecId::ExtendedHamming => data_bits + parity_bits + 1,
+            None => data_bits + parity_bits,
+        }
+    }
+
+    /// Bits of padding the codec puts after `data_bits` bits of payload.
+    pub fn padding(self, data_bits: usize) -> usize {
+        self.block()
+            .map_or(0, |(data, _)| data_bits.next_multiple_of(data) - data_bits)
+    }
+
+    /// The most padding the codec ever puts after a payload, short of a
+    /// whole block. 0 for the codecs that don't work in blocks.
+    pub fn max_padding(self) -> usize {
+        self.block().map_or(0, |(data, _)| data - 1)
+    }
+}
+
+impl TryFrom<u8> for CodecId {
+    type Error = ProtocolError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|codec| codec.byte() == byte)
+            .ok_or(ProtocolError::UnknownCodec(byte))
+    }
+}
+
+impl std::fmt::Display for CodecId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CodecId::Hamming => "hamming",
+            CodecId::ExtendedHamming => "extended-hamming",
+            CodecId::Hamming74 => "hamming-7-4",
+            CodecId::Hamming1511 => "hamming-15-11",
+            CodecId::Golay24 => "golay-24",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::bitvec::BitVec;
+
+    #[test]
+    fn test_registry() {
+        for codec in CodecId::ALL {
+            assert_eq!(CodecId::try_from(codec.byte()).unwrap(), codec);
+
+            // the sizes worked out up front are the ones the codec comes up with
+            for len in [1, 11, 12, 45, 100] {
+                let data = BitVec::random(len, &mut rand::rng());
+                let codeword = codec.build().encode(&data).unwrap();
+                assert_eq!(codec.encoded_bits(len), codeword.len(), "{codec}, {len}");
+                let decoded = codec.build().decode(&codeword).unwrap().data;
+                assert_eq!(decoded.len(), len + codec.padding(len), "{codec}, {len}");
+            }
+        }
+
+        assert!(matches!(
+            CodecId::try_from(5),
+            Err(ProtocolError::UnknownCodec(5))
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+//! What can go wrong framing a packet, or reading one back.
+
+use crate::{
+    encoding::hamming::HammingError,
+    proto::{CodecId, FrameVersion, NackReason, PacketType},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProtocolError {
+    /// Fewer bytes than the frame, or the part of it being read, takes up.
+    #[error("Frame is too short: got {got} bytes, expected {needed}")]
+    TooShort { needed: usize, got: usize },
+    /// A stream ended cleanly, before the first byte of another frame.
+    #[error("The stream ended before the next frame")]
+    EndOfStream,
+    /// A header that came through fine, but isn't ours.
+    #[error("Invalid protocol name {:?}", String::from_utf8_lossy(.0))]
+    BadMagic([u8; 3]),
+    #[error(
+        "Unsupported version {0}, supported versions are {first} to {last}",
+        first = FrameVersion::ALL[0],
+        last = FrameVersion::ALL[FrameVersion::ALL.len() - 1]
+    )]
+    UnsupportedVersion(u8),
+    /// Flags this version doesn't know, and so can't tell what they change
+    /// about the frame.
+    #[error("Unsupported flags {0:#04x}")]
+    UnsupportedFlags(u8),
+    /// An option for a packet whose version has no room for it.
+    #[error("{option} can't go in a version {version} frame")]
+    UnsupportedOption {
+        option: &'static str,
+        version: FrameVersion,
+    },
+    /// A codec id that isn't registered, see [`CodecId`](crate::proto::CodecId).
+    #[error("Unknown codec {0}")]
+    UnknownCodec(u8),
+    /// A packet type byte that isn't one of [`PacketType`](crate::proto::PacketType)'s.
+    #[error("Unknown packet type {0}")]
+    UnknownPacketType(u8),
+    /// More padding than the codec ever adds, which would eat into the data.
+    #[error("Header says {codec} padded the payload with {padding} bits, it pads at most {max}")]
+    BadPadding {
+        padding: u8,
+        codec: CodecId,
+        max: usize,
+    },
+    /// An interleaving depth deeper than a frame is allowed to ask for.
+    #[error("Interleaving depth {depth} is deeper than the {max} a frame can ask for")]
+    InterleaveTooDeep { depth: u8, max: u8 },
+    /// A control frame whose payload isn't the size its packet type's is.
+    #[error("Control frame of type {packet_type} carries {bits} bits, expected {expected}")]
+    ControlPayload {
+        packet_type: u8,
+        expected: usize,
+        bits: usize,
+    },
+    /// A header whose codewords decoded, but not to what was sent: more
+    /// errors than they could tell were there.
+    #[error("Header checksum {actual:#04x} doesn't match {expected:#04x}")]
+    HeaderChecksum { expected: u8, actual: u8 },
+    /// A header with more errors than its codewords can correct.
+    #[error("Packet header is corrupted beyond repair")]
+    CorruptedHeader,
+    /// A payload with more errors than its codec can correct, for codecs that
+    /// don't say more than that.
+    #[error("Packet is corrupted beyond repair")]
+    CorruptedPayload,
+    /// A header declaring more bits than its bytes hold.
+    #[error("Header declares {bits_length} bits in {data_length} bytes")]
+    InconsistentLengths {
+        data_length: usize,
+        bits_length: usize,
+    },
+    /// A length that doesn't fit in a `usize` on this host.
+    #[error("Length {0} is too large for this platform")]
+    LengthOverflow(u64),
+    /// Header extensions that don't fit in the room a header has for them.
+    #[error("Header extensions of {len} bytes are longer than the {max} a header holds")]
+    ExtensionsTooLong { len: usize, max: usize },
+    /// A header extension running past the end of the extensions.
+    #[error("Header extension at byte {offset} runs past the end of the extensions")]
+    MalformedExtensions { offset: usize },
+    /// Two ends of a handshake with nothing in common on `what`.
+    #[error("Can't agree on {what}: this end supports {ours}, the other {theirs}")]
+    Incompatible {
+        what: &'static str,
+        ours: String,
+        theirs: String,
+    },
+    /// A frame other than the one a handshake was waiting for.
+    #[error("Expected a {expected} frame, got {got:?}")]
+    UnexpectedPacket {
+        expected: &'static str,
+        got: PacketType,
+    },
+    #[error("Fragment {index} of message {message_id} is out of range, it has {count}")]
+    FragmentOutOfRange {
+        message_id: u32,
+        index: u32,
+        count: u32,
+    },
+    /// A fragment that disagrees with the ones before it on how many
+    /// fragments its message has.
+    #[error(
+        "Fragment {index} of message {message_id} says it has {count} fragments, earlier ones said {expected}"
+    )]
+    FragmentCountMismatch {
+        message_id: u32,
+        index: u32,
+        count: u32,
+        expected: u32,
+    },
+    /// The data decoded fine but doesn't match its checksum, so the codec
+    /// "corrected" it into something else.
+    #[error("Payload checksum {actual:#010x} doesn't match {expected:#010x}")]
+    Integrity {
+        /// The checksum sent along with the data.
+        expected: u32,
+        /// The checksum of the data as decoded.
+        actual: u32,
+    },
+    /// A payload flagged as checksummed with fewer bits than the checksum.
+    #[error("Payload of {bits} bits is too short to hold its checksum")]
+    MissingChecksum { bits: usize },
+    /// A payload flagged as compressed that doesn't inflate, e.g. after more
+    /// errors than the codec could tell were there.
+    #[error("Compressed payload doesn't decompress")]
+    Decompression(#[source] std::io::Error),
+    /// A frame in a buffer of them that doesn't decode.
+    #[error("Frame at byte {offset} doesn't decode")]
+    BadFrame {
+        offset: usize,
+        source: Box<ProtocolError>,
+    },
+    /// Nothing in a buffer decoded as a frame.
+    #[error("No frame found in {0} bytes")]
+    NoFrame(usize),
+    /// A frame nacked more times than the sender was set up to send it
+    /// again.
+    #[error(
+        "Message {message_id} didn't get through in {attempts} attempts, last nacked as {reason:?}"
+    )]
+    RetriesExhausted {
+        message_id: u32,
+        attempts: u32,
+        reason: NackReason,
+    },
+    /// The other end stopped sending before the last frame of a file.
+    #[error("Transfer ended after {got} bytes, before its last frame")]
+    TransferIncomplete { got: u64 },
+    /// Frames of a file skipped over, that never came.
+    #[error("Frames {first} to {last} of the transfer never came")]
+    TransferGap { first: u32, last: u32 },
+    /// The last frame of a file says it was a different size than what came.
+    #[error("Transfer says it was {expected} bytes, but {got} came")]
+    TransferSize { expected: u64, got: u64 },
+    /// Nothing listening at the address a transport tried to connect to.
+    #[error("Connection to {0} refused, is the receiver listening?")]
+    ConnectionRefused(String),
+    /// The other end went away partway through a frame.
+    #[error("Connection lost {got} bytes into a {needed} byte frame")]
+    ConnectionLost { needed: usize, got: usize },
+    /// A transport waited longer for a frame than it was set up to.
+    #[error("Timed out waiting for a frame")]
+    Timeout,
+    /// A frame bigger than a transport takes, either way.
+    #[error("Frame of {len} bytes is larger than the {max} bytes the transport takes")]
+    FrameTooLarge { len: usize, max: usize },
+    /// A frame that doesn't fit in a datagram.
+    #[error(
+        "Frame of {len} bytes doesn't fit in a {max} byte datagram, split the data into smaller fragments"
+    )]
+    DatagramTooLarge { len: usize, max: usize },
+    /// A Unix socket path longer than the platform's socket addresses hold.
+    #[error("Socket path {path} is {len} bytes long, at most {max} fit in a socket address")]
+    SocketPathTooLong {
+        path: String,
+        len: usize,
+        max: usize,
+    },
+    /// A live socket already at the path a transport tried to listen on.
+    #[error("Something is already listening on {0}")]
+    SocketInUse(String),
+    #[error(transparent)]
+    Codec(#[from] HammingError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,296 @@
+//! Small metadata riding along in the header, from version 9 on.
+//!
+//! The extensions go right after the fixed header, as entries of a type byte,
+//! a `u16` length and that many bytes of value, numbers in the header's
+//! [`Endianness`]. The fields before them say how many bytes they take up in
+//! all, 0 for a frame without any, in which case nothing goes after the fixed
+//! header at all.
+
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::proto::{Endianness, GUSProtocol, ProtocolError};
+
+/// Bytes of an entry before its value: the type and the length.
+const ENTRY_HEADER_SIZE: usize = 1 + std::mem::size_of::<u16>();
+
+/// What a header extension holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ExtType {
+    /// The name of the file the data came from, UTF-8.
+    Filename,
+    /// Whatever the sender goes by, for telling senders apart.
+    Origin,
+    /// When the frame was sent, in microseconds since the Unix epoch, as a
+    /// little-endian `u64`. See [`GUSProtocol::sent_at`].
+    SentAt,
+    /// Where the frame comes among the data frames its sender sent, counting
+    /// up from 0, as a little-endian `u32`. See [`GUSProtocol::sequence`].
+    Sequence,
+    /// Bytes in a whole file transfer, on its last frame only, as a
+    /// little-endian `u64`. See [`transfer`](crate::proto::transfer).
+    TransferSize,
+    /// An extension this version doesn't know about. Kept as it came, so it
+    /// goes out again if the packet is re-encoded.
+    Unknown(u8),
+}
+
+impl From<u8> for ExtType {
+    fn from(byte: u8) -> Self {
+        match byte {
+            1 => Self::Filename,
+            2 => Self::Origin,
+            3 => Self::SentAt,
+            4 => Self::Sequence,
+            5 => Self::TransferSize,
+            byte => Self::Unknown(byte),
+        }
+    }
+}
+
+impl From<ExtType> for u8 {
+    fn from(ext_type: ExtType) -> Self {
+        match ext_type {
+            ExtType::Filename => 1,
+            ExtType::Origin => 2,
+            ExtType::SentAt => 3,
+            ExtType::Sequence => 4,
+            ExtType::TransferSize => 5,
+            ExtType::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// The extensions of a header, a value per type. They go out in order of
+/// their type byte.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderExtensions {
+    entries: BTreeMap<u8, Vec<u8>>,
+}
+
+impl HeaderExtensions {
+    /// The most bytes the extensions can take up on the wire, entries and
+    /// all.
+    pub const MAX_SIZE: usize = u16::MAX as usize;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of `ext_type`, returning the one it had. Fails, leaving
+    /// the extensions as they were, if they would no longer fit in
+    /// [`HeaderExtensions::MAX_SIZE`].
+    pub fn insert(
+        &mut self,
+        ext_type: ExtType,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, ProtocolError> {
+        let value = value.into();
+        let key = u8::from(ext_type);
+        let replaced = self
+            .entries
+            .get(&key)
+            .map_or(0, |old| ENTRY_HEADER_SIZE + old.len());
+        let len = self.encoded_len() - replaced + ENTRY_HEADER_SIZE + value.len();
+        if len > Self::MAX_SIZE {
+            return Err(ProtocolError::ExtensionsTooLong {
+                len,
+                max: Self::MAX_SIZE,
+            });
+        }
+        Ok(self.entries.insert(key, value))
+    }
+
+    pub fn get(&self, ext_type: ExtType) -> Option<&[u8]> {
+        self.entries.get(&u8::from(ext_type)).map(Vec::as_slice)
+    }
+
+    pub fn remove(&mut self, ext_type: ExtType) -> Option<Vec<u8>> {
+        self.entries.remove(&u8::from(ext_type))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ExtType, &[u8])> {
+        self.entries
+            .iter()
+            .map(|(&key, value)| (ExtType::from(key), value.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The [`ExtType::SentAt`] timestamp, if there is one 8 bytes long.
+    pub fn sent_at(&self) -> Option<u64> {
+        let micros = self.get(ExtType::SentAt)?.try_into().ok()?;
+        Some(u64::from_le_bytes(micros))
+    }
+
+    /// The [`ExtType::Sequence`] number, if there is one 4 bytes long.
+    pub fn sequence(&self) -> Option<u32> {
+        let sequence = self.get(ExtType::Sequence)?.try_into().ok()?;
+        Some(u32::from_le_bytes(sequence))
+    }
+
+    /// The [`ExtType::TransferSize`], if there is one 8 bytes long.
+    pub fn transfer_size(&self) -> Option<u64> {
+        let size = self.get(ExtType::TransferSize)?.try_into().ok()?;
+        Some(u64::from_le_bytes(size))
+    }
+
+    /// Bytes the entries take up on the wire, before they're protected.
+    pub fn encoded_len(&self) -> usize {
+        self.entries
+            .values()
+            .map(|value| ENTRY_HEADER_SIZE + value.len())
+            .sum()
+    }
+
+    pub(super) fn to_bytes(&self, endianness: Endianness) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        for (&key, value) in &self.entries {
+            let len = u16::try_from(value.len()).expect("insert keeps values under u16::MAX");
+            bytes.push(key);
+            bytes.extend(endianness.u16_bytes(len));
+            bytes.extend(value);
+        }
+        bytes
+    }
+
+    /// Reads the entries back, failing at the first one that runs past the
+    /// end of `bytes`. A type that turns up twice keeps its last value.
+    pub(super) fn from_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, ProtocolError> {
+        let mut entries = BTreeMap::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let value = bytes
+                .get(offset..offset + ENTRY_HEADER_SIZE)
+                .map(|entry| {
+                    let len = endianness.u16_from([entry[1], entry[2]]) as usize;
+                    offset + ENTRY_HEADER_SIZE..offset + ENTRY_HEADER_SIZE + len
+                })
+                .filter(|value| value.end <= bytes.len())
+                .ok_or(ProtocolError::MalformedExtensions { offset })?;
+            entries.insert(bytes[offset], bytes[value.clone()].to_vec());
+            offset = value.end;
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Microseconds since the Unix epoch, by this host's clock. 0 for a clock set
+/// before it.
+pub fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_micros() as u64)
+}
+
+impl GUSProtocol {
+    /// When the sender says the frame was sent, in microseconds since the
+    /// Unix epoch, if it said. A [`ExtType::SentAt`] extension that isn't 8
+    /// bytes long is taken not to say.
+    pub fn sent_at(&self) -> Option<u64> {
+        self.extensions.sent_at()
+    }
+
+    /// Stamps the packet as sent at `micros` microseconds since the Unix
+    /// epoch, see [`now_micros`]. Only goes out from version 9 on.
+    pub fn set_sent_at(&mut self, micros: u64) -> Result<(), ProtocolError> {
+        self.extensions
+            .insert(ExtType::SentAt, micros.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Where the frame comes among its sender's data frames, if it said. A
+    /// [`ExtType::Sequence`] extension that isn't 4 bytes long is taken not
+    /// to say.
+    pub fn sequence(&self) -> Option<u32> {
+        self.extensions.sequence()
+    }
+
+    /// Numbers the packet as its sender's `sequence`th data frame, for the
+    /// receiver to put frames back in order, see
+    /// [`SequenceTracker`](crate::proto::SequenceTracker). Only goes out from
+    /// version 9 on.
+    pub fn set_sequence(&mut self, sequence: u32) -> Result<(), ProtocolError> {
+        self.extensions
+            .insert(ExtType::Sequence, sequence.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries() {
+        let mut extensions = HeaderExtensions::new();
+        assert_eq!(extensions.encoded_len(), 0);
+        assert_eq!(extensions.insert(ExtType::Filename, "a.txt").unwrap(), None);
+        extensions.insert(ExtType::Unknown(0x80), [1, 2]).unwrap();
+        assert_eq!(
+            extensions.insert(ExtType::Filename, "b.txt").unwrap(),
+            Some(b"a.txt".to_vec())
+        );
+        assert_eq!(extensions.get(ExtType::Filename), Some(&b"b.txt"[..]));
+        assert_eq!(extensions.get(ExtType::Origin), None);
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions.encoded_len(), 3 + 5 + 3 + 2);
+
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let bytes = extensions.to_bytes(endianness);
+            assert_eq!(bytes.len(), extensions.encoded_len());
+            let read = HeaderExtensions::from_bytes(&bytes, endianness).unwrap();
+            assert_eq!(read, extensions);
+        }
+        assert_eq!(
+            extensions.to_bytes(Endianness::Big),
+            b"\x01\0\x05b.txt\x80\0\x02\x01\x02"
+        );
+        assert_eq!(
+            extensions.iter().collect::<Vec<_>>(),
+            [
+                (ExtType::Filename, &b"b.txt"[..]),
+                (ExtType::Unknown(0x80), &[1, 2][..])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_too_long() {
+        let mut extensions = HeaderExtensions::new();
+        let max = HeaderExtensions::MAX_SIZE - ENTRY_HEADER_SIZE;
+        extensions.insert(ExtType::Origin, vec![0; max]).unwrap();
+        assert!(matches!(
+            extensions.insert(ExtType::Filename, []),
+            Err(ProtocolError::ExtensionsTooLong { len, .. }) if len == HeaderExtensions::MAX_SIZE + 3
+        ));
+        assert_eq!(extensions.len(), 1);
+        // replacing a value only counts the new one
+        extensions.insert(ExtType::Origin, vec![1; max]).unwrap();
+        assert_eq!(extensions.encoded_len(), HeaderExtensions::MAX_SIZE);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let bytes = b"\x01\x05\0hello\x02\x04\0ab";
+        assert!(matches!(
+            HeaderExtensions::from_bytes(bytes, Endianness::Little),
+            Err(ProtocolError::MalformedExtensions { offset: 8 })
+        ));
+        assert!(matches!(
+            HeaderExtensions::from_bytes(&bytes[..9], Endianness::Little),
+            Err(ProtocolError::MalformedExtensions { offset: 8 })
+        ));
+        let read = HeaderExtensions::from_bytes(&bytes[..8], Endianness::Little).unwrap();
+        assert_eq!(read.get(ExtType::Filename), Some(&b"hello"[..]));
+    }
+}
@@ -0,0 +1,255 @@
+//! Decoding a frame without copying it out of the buffer it's in.
+
+use crate::{
+    encoding::{
+        bitvec::{BitSlice, BitVec},
+        hamming::HammingError,
+    },
+    proto::{
+        CodecId, ContentType, CorrectionInfo, DecodeReport, Flags, Fragment, FrameVersion,
+        GUSProtocol, HeaderExtensions, IntegrityStatus, Latency, PacketType, ProtocolError,
+        header::{FrameHeader, PROTOCOL_NAME},
+        now_micros,
+        payload::Payload,
+    },
+};
+
+/// A decoded frame whose payload, as it was received, is still in the buffer
+/// it was decoded from. Only the corrected data is a copy.
+#[derive(Debug)]
+pub struct GusFrameRef<'a> {
+    pub version: FrameVersion,
+    pub content_type: ContentType,
+    pub fragment: Fragment,
+    pub flags: Flags,
+    pub codec: CodecId,
+    pub packet_type: PacketType,
+    pub extensions: HeaderExtensions,
+    pub interleave_depth: u8,
+    pub scramble_seed: Option<u32>,
+    /// The data, corrected, as [`GUSProtocol::data`] would have it.
+    pub data: BitVec,
+    payload: BitSlice<'a>,
+}
+
+impl<'a> GusFrameRef<'a> {
+    /// The payload's codeword as it came in, errors and all, borrowed from the
+    /// buffer the frame was decoded from.
+    pub fn payload(&self) -> BitSlice<'a> {
+        self.payload
+    }
+
+    /// The frame as a packet of its own, for when it has to outlive the
+    /// buffer.
+    pub fn to_owned(&self) -> GUSProtocol {
+        GUSProtocol {
+            extensions: self.extensions.clone(),
+            data: self.data.clone(),
+            ..self.packet()
+        }
+    }
+
+    /// Like [`GusFrameRef::to_owned`], without copying the data.
+    pub fn into_owned(self) -> GUSProtocol {
+        let packet = self.packet();
+        GUSProtocol {
+            extensions: self.extensions,
+            data: self.data,
+            ..packet
+        }
+    }
+
+    /// The packet, but for its extensions and data.
+    fn packet(&self) -> GUSProtocol {
+        GUSProtocol {
+            protocol_name: PROTOCOL_NAME.to_vec(),
+            version: self.version,
+            content_type: self.content_type,
+            fragment: self.fragment,
+            flags: self.flags,
+            codec: self.codec,
+            packet_type: self.packet_type,
+            extensions: HeaderExtensions::new(),
+            interleave_depth: self.interleave_depth,
+            scramble_seed: self.scramble_seed,
+            data: BitVec::new(),
+        }
+    }
+}
+
+impl GUSProtocol {
+    /// Decodes a frame like [`GUSProtocol::decode`], but reads the payload
+    /// where it is in `encoded` instead of copying it out first. For large
+    /// frames that halves the memory decoding takes, when the codec is
+    /// [`CodecId::Hamming`]; the other codecs still work on a copy.
+    pub fn decode_ref(encoded: &[u8]) -> Result<(GusFrameRef<'_>, DecodeReport), ProtocolError> {
+        Self::decode_ref_at(encoded, now_micros)
+    }
+
+    /// Decodes a frame like [`GUSProtocol::decode_ref`], taking the time it
+    /// arrived from `now` for its latency.
+    pub(super) fn decode_ref_at(
+        encoded: &[u8],
+        now: impl FnOnce() -> u64,
+    ) -> Result<(GusFrameRef<'_>, DecodeReport), ProtocolError> {
+        if encoded.is_empty() {
+            return Err(HammingError::EmptyInput.into());
+        }
+        let decoded = FrameHeader::decode(encoded)?;
+        let frame_size = decoded.frame_size()?;
+        let FrameHeader {
+            version,
+            content_type,
+            length,
+            fragment,
+            flags,
+            codec,
+            padding,
+            packet_type,
+            extensions,
+            interleave_depth,
+            scramble_seed,
+        } = decoded.header;
+        if let ContentType::Unknown(byte) = content_type {
+            log::warn!("Unknown content type {byte}, decoding the payload anyway");
+        }
+
+        let payload = encoded
+            .get(decoded.size..frame_size)
+            .ok_or(ProtocolError::TooShort {
+                needed: frame_size,
+                got: encoded.len(),
+            })?;
+        let payload = BitSlice::new(payload, length.bits_length)
+            .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+        let (mut data, status) = Self::decode_payload(codec, padding, interleave_depth, payload)?;
+        if let Some(seed) = scramble_seed {
+            data = Payload::descramble(&data, seed);
+        }
+
+        // the codec can "correct" two errors into a third, the checksum can
+        // tell
+        let integrity = match flags.contains(Flags::CRC32) {
+            true => {
+                data = Payload::verify(data)?;
+                IntegrityStatus::Verified
+            }
+            false => IntegrityStatus::Unchecked,
+        };
+        if flags.contains(Flags::COMPRESSED) {
+            data = Payload::inflate(data)?;
+        }
+        let packet_type = PacketType::from_payload(packet_type, &data)?;
+
+        let report = DecodeReport {
+            corrected: CorrectionInfo::new(&decoded.status, &status),
+            payload_bits: data.len(),
+            frame_bytes: frame_size,
+            version,
+            integrity,
+            latency: extensions
+                .sent_at()
+                .map(|sent_at| Latency::between(sent_at, now())),
+        };
+        Ok((
+            GusFrameRef {
+                version,
+                content_type,
+                fragment,
+                flags,
+                codec,
+                packet_type,
+                extensions,
+                interleave_depth,
+                scramble_seed,
+                data,
+                payload,
+            },
+            report,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::hamming::HammingCodeBase,
+        proto::{Length, NackReason},
+    };
+
+    #[test]
+    fn test_matches_decode() {
+        let mut rng = rand::rng();
+        let packets = [
+            GUSProtocol::new(BitVec::random(1000, &mut rng)).unwrap(),
+            GUSProtocol::new_with_codec(BitVec::random(100, &mut rng), CodecId::Golay24).unwrap(),
+            GUSProtocol::nack(3, NackReason::Integrity),
+        ];
+        for packet in packets {
+            let mut encoded = packet.clone().encode().unwrap();
+            let last = encoded.len() - 1;
+            encoded[last] ^= 0x80;
+
+            let (frame, report) = GUSProtocol::decode_ref(&encoded).unwrap();
+            assert!(report.corrected.is_some());
+            assert_eq!(frame.data.to_vec(), packet.data.to_vec());
+            assert_eq!(frame.packet_type, packet.packet_type);
+            assert_eq!(frame.codec, packet.codec);
+            // the payload as received, flipped bit and all
+            let payload = frame.payload().as_bytes();
+            assert_eq!(payload, &encoded[encoded.len() - payload.len()..]);
+
+            let owned = frame.to_owned();
+            let (decoded, _) = GUSProtocol::decode(encoded.clone()).unwrap();
+            assert_eq!(owned.data.to_vec(), decoded.data.to_vec());
+            assert_eq!(owned.fragment, decoded.fragment);
+            assert_eq!(owned.flags, decoded.flags);
+            assert_eq!(
+                frame.into_owned().encode().unwrap(),
+                packet.encode().unwrap()
+            );
+        }
+
+        assert!(matches!(
+            GUSProtocol::decode_ref(&[]),
+            Err(ProtocolError::Codec(HammingError::EmptyInput))
+        ));
+    }
+
+    #[test]
+    fn test_large_frame_is_borrowed() {
+        // 4 KiB of data, all zeros so the codeword is too and is valid
+        // without encoding it
+        let data_bits = 4 << 13;
+        let codeword_bits = data_bits + HammingCodeBase::calculate_parity_count(data_bits);
+        let packet = GUSProtocol::builder()
+            .data(BitVec::zeros(data_bits))
+            .checksum(false)
+            .build()
+            .unwrap();
+        let mut frame = packet
+            .header(
+                &packet.payload(),
+                Length {
+                    data_length: codeword_bits.div_ceil(8),
+                    bits_length: codeword_bits,
+                },
+            )
+            .encode(PROTOCOL_NAME);
+        let header_size = frame.len();
+        frame.resize(header_size + codeword_bits.div_ceil(8), 0);
+        // a data bit deep into the payload
+        frame[header_size + 1_000] = 0x01;
+
+        let (decoded, report) = GUSProtocol::decode_ref(&frame).unwrap();
+        assert!(std::ptr::eq(
+            decoded.payload().as_bytes(),
+            &frame[header_size..]
+        ));
+        assert_eq!(decoded.payload().len(), codeword_bits);
+        assert_eq!(decoded.data.len(), data_bits);
+        assert!(report.corrected.is_some());
+        assert!(decoded.data.into_inner().iter().all(|&byte| byte == 0));
+    }
+}
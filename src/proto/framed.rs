@@ -0,0 +1,217 @@
+//! A [`tokio_util::codec`] codec, to read and write frames on anything async
+//! with `Framed`.
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::proto::{
+    DecodeReport, GUSProtocol, ProtocolError, header::FrameHeader,
+    transport::DEFAULT_MAX_FRAME_BYTES,
+};
+
+/// Frames back to back, as [`GUSProtocol::encode`] makes them and
+/// [`GUSProtocol::read_frame`] reads them: each header says how long the
+/// payload after it is, so there's nothing around them.
+///
+/// A frame that doesn't decode ends the stream with its error, as there's no
+/// telling where the next one starts after a header that can't be read.
+#[derive(Debug, Clone)]
+pub struct GusCodec {
+    max_frame_bytes: usize,
+}
+
+impl GusCodec {
+    pub fn new() -> Self {
+        Self {
+            max_frame_bytes: DEFAULT_MAX_FRAME_BYTES,
+        }
+    }
+
+    /// The largest frame read or written, so a corrupted or hostile length
+    /// can't make the decoder buffer whatever it says.
+    /// [`DEFAULT_MAX_FRAME_BYTES`] unless set.
+    pub fn with_max_frame_bytes(mut self, max_frame_bytes: usize) -> Self {
+        self.max_frame_bytes = max_frame_bytes;
+        self
+    }
+
+    /// How many bytes the frame at the start of `src` takes up, once there
+    /// are enough of them to tell.
+    fn frame_size(&self, src: &[u8]) -> Result<Option<usize>, ProtocolError> {
+        if src.len() < FrameHeader::MIN_SIZE {
+            return Ok(None);
+        }
+        let header_size = FrameHeader::size_hint(src)?;
+        if src.len() < header_size {
+            return Ok(None);
+        }
+
+        let frame_size = FrameHeader::decode(&src[..header_size])?.frame_size()?;
+        if frame_size > self.max_frame_bytes {
+            return Err(ProtocolError::FrameTooLarge {
+                len: frame_size,
+                max: self.max_frame_bytes,
+            });
+        }
+        Ok(Some(frame_size))
+    }
+}
+
+impl Default for GusCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for GusCodec {
+    type Item = (GUSProtocol, DecodeReport);
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, ProtocolError> {
+        match self.frame_size(src)? {
+            Some(frame_size) if src.len() >= frame_size => {
+                let frame = src.split_to(frame_size);
+                GUSProtocol::decode(frame.to_vec()).map(Some)
+            }
+            Some(frame_size) => {
+                src.reserve(frame_size - src.len());
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl Encoder<GUSProtocol> for GusCodec {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, packet: GUSProtocol, dst: &mut BytesMut) -> Result<(), ProtocolError> {
+        let frame = packet.encode()?;
+        if frame.len() > self.max_frame_bytes {
+            return Err(ProtocolError::FrameTooLarge {
+                len: frame.len(),
+                max: self.max_frame_bytes,
+            });
+        }
+        dst.extend_from_slice(&frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{FrameVersion, PacketType},
+    };
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut rng = rand::rng();
+        let sent: Vec<BitVec> = [500, 7, 2000]
+            .into_iter()
+            .map(|len| BitVec::random(len, &mut rng))
+            .collect();
+
+        let packets: Vec<GUSProtocol> = sent.iter().cloned().map(GUSProtocol::data).collect();
+        let writer = tokio::spawn(async move {
+            let mut framed = FramedWrite::new(client, GusCodec::new());
+            for packet in packets {
+                framed.send(packet).await.unwrap();
+            }
+            framed.send(GUSProtocol::ack(9)).await.unwrap();
+        });
+
+        let mut framed = FramedRead::new(server, GusCodec::new());
+        for data in &sent {
+            let (packet, _) = framed.next().await.unwrap().unwrap();
+            assert_eq!(packet.data.to_vec(), data.to_vec());
+        }
+        let (packet, _) = framed.next().await.unwrap().unwrap();
+        assert_eq!(packet.packet_type, PacketType::Ack { message_id: 9 });
+        writer.await.unwrap();
+        assert!(framed.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_byte_at_a_time() {
+        let data = BitVec::random(300, &mut rand::rng());
+        let mut stream = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+        // and a legacy frame after it, with a header of another size
+        stream.extend(
+            GUSProtocol::new(data.clone())
+                .unwrap()
+                .encode_as(FrameVersion::V2)
+                .unwrap(),
+        );
+
+        let (mut client, server) = tokio::io::duplex(1);
+        let writer = tokio::spawn(async move {
+            for byte in stream {
+                client.write_all(&[byte]).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let mut framed = FramedRead::new(server, GusCodec::new());
+        for _ in 0..2 {
+            let (packet, _) = framed.next().await.unwrap().unwrap();
+            assert_eq!(packet.data.to_vec(), data.to_vec());
+        }
+        writer.await.unwrap();
+        assert!(framed.next().await.is_none());
+    }
+
+    #[test]
+    fn test_partial() {
+        let frame = GUSProtocol::new(BitVec::random(100, &mut rand::rng()))
+            .unwrap()
+            .encode()
+            .unwrap();
+        let mut codec = GusCodec::new();
+        let mut src = BytesMut::new();
+        for &byte in &frame[..frame.len() - 1] {
+            src.extend_from_slice(&[byte]);
+            assert!(codec.decode(&mut src).unwrap().is_none());
+        }
+        // nothing is taken off until the frame is whole
+        assert_eq!(src.len(), frame.len() - 1);
+
+        src.extend_from_slice(&frame[frame.len() - 1..]);
+        src.extend_from_slice(&frame[..5]);
+        assert!(codec.decode(&mut src).unwrap().is_some());
+        assert_eq!(&src[..], &frame[..5]);
+    }
+
+    #[test]
+    fn test_max_frame_bytes() {
+        let packet = GUSProtocol::new(BitVec::random(1000, &mut rand::rng())).unwrap();
+        let frame = packet.clone().encode().unwrap();
+
+        // refused from the header alone, before the payload arrives
+        let mut codec = GusCodec::new().with_max_frame_bytes(100);
+        let mut src = BytesMut::from(&frame[..FrameHeader::size(FrameVersion::CURRENT)]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(ProtocolError::FrameTooLarge { len, max: 100 }) if len == frame.len()
+        ));
+
+        let mut dst = BytesMut::new();
+        assert!(matches!(
+            codec.encode(packet.clone(), &mut dst),
+            Err(ProtocolError::FrameTooLarge { max: 100, .. })
+        ));
+        assert!(dst.is_empty());
+
+        let mut codec = GusCodec::new().with_max_frame_bytes(frame.len());
+        codec.encode(packet, &mut dst).unwrap();
+        assert_eq!(&dst[..], &frame[..]);
+        assert!(codec.decode(&mut dst).unwrap().is_some());
+    }
+}
@@ -0,0 +1,403 @@
+//! Agreeing on how to send frames before sending any, with a
+//! [`PacketType::Hello`] one way and a [`PacketType::HelloAck`] back.
+
+use crate::proto::{
+    CodecId, FrameVersion, GUSProtocol, PacketType, ProtocolError,
+    header::FrameHeader,
+    transport::{DEFAULT_MAX_FRAME_BYTES, Transport},
+};
+
+/// Bits of the checksum going along with the data, for working out how much
+/// data fits in a frame.
+const CHECKSUM_BITS: usize = 32;
+
+/// What one end can read and write, as sent in a hello.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Capabilities {
+    /// The oldest version byte this end reads and writes.
+    pub min_version: u8,
+    /// The newest. The other end's can be past anything this one knows.
+    pub max_version: u8,
+    /// A bit per codec id this end decodes, bit `n` for id `n`.
+    pub codecs: u16,
+    /// The largest frame this end takes.
+    pub max_frame_bytes: u32,
+}
+
+impl Default for Capabilities {
+    /// Every version and codec this implementation knows, and frames as
+    /// large as the transports take by default.
+    fn default() -> Self {
+        Self::new(
+            FrameVersion::ALL[0],
+            FrameVersion::CURRENT,
+            &CodecId::ALL,
+            DEFAULT_MAX_FRAME_BYTES,
+        )
+    }
+}
+
+impl Capabilities {
+    /// Size of the serialized capabilities in bytes.
+    pub const SIZE: usize = 8;
+
+    /// Frames as large as `max_frame_bytes`, or as `u32::MAX` goes.
+    pub fn new(
+        min_version: FrameVersion,
+        max_version: FrameVersion,
+        codecs: &[CodecId],
+        max_frame_bytes: usize,
+    ) -> Self {
+        Self {
+            min_version: min_version.byte(),
+            max_version: max_version.byte(),
+            codecs: codecs
+                .iter()
+                .fold(0, |codecs, codec| codecs | 1 << codec.byte()),
+            max_frame_bytes: u32::try_from(max_frame_bytes).unwrap_or(u32::MAX),
+        }
+    }
+
+    pub fn supports(&self, codec: CodecId) -> bool {
+        self.codecs & 1 << codec.byte() != 0
+    }
+
+    /// The little-endian fields, in the order they're declared.
+    pub(super) fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0] = self.min_version;
+        bytes[1] = self.max_version;
+        bytes[2..4].copy_from_slice(&self.codecs.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.max_frame_bytes.to_le_bytes());
+        bytes
+    }
+
+    pub(super) fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        Self {
+            min_version: bytes[0],
+            max_version: bytes[1],
+            codecs: u16::from_le_bytes([bytes[2], bytes[3]]),
+            max_frame_bytes: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        }
+    }
+
+    /// What this end and one with `theirs` can both do, or which of it they
+    /// can't agree on.
+    ///
+    /// The same whichever end works it out: the newest version they share,
+    /// the first codec of [`CodecId::ALL`] they share, and the smaller of
+    /// their largest frames.
+    pub fn agree(&self, theirs: &Capabilities) -> Result<SessionParams, ProtocolError> {
+        let version = FrameVersion::ALL
+            .into_iter()
+            .rev()
+            .find(|version| {
+                let byte = version.byte();
+                [self, theirs]
+                    .iter()
+                    .all(|caps| (caps.min_version..=caps.max_version).contains(&byte))
+            })
+            .ok_or_else(|| ProtocolError::Incompatible {
+                what: "a version",
+                ours: format!("{} to {}", self.min_version, self.max_version),
+                theirs: format!("{} to {}", theirs.min_version, theirs.max_version),
+            })?;
+        let codec = CodecId::ALL
+            .into_iter()
+            .find(|&codec| self.supports(codec) && theirs.supports(codec))
+            .ok_or_else(|| ProtocolError::Incompatible {
+                what: "a codec",
+                ours: self.codec_names(),
+                theirs: theirs.codec_names(),
+            })?;
+
+        let max_frame_bytes = self.max_frame_bytes.min(theirs.max_frame_bytes) as usize;
+        let params = SessionParams {
+            version,
+            codec,
+            max_frame_bytes,
+        };
+        if params.max_fragment_bits() == 0 {
+            return Err(ProtocolError::Incompatible {
+                what: "a frame size",
+                ours: format!("{} bytes", self.max_frame_bytes),
+                theirs: format!("{} bytes", theirs.max_frame_bytes),
+            });
+        }
+        Ok(params)
+    }
+
+    /// The codecs this end supports, by name, for telling what's missing.
+    fn codec_names(&self) -> String {
+        let names: Vec<String> = CodecId::ALL
+            .into_iter()
+            .filter(|&codec| self.supports(codec))
+            .map(|codec| codec.to_string())
+            .collect();
+        match names.is_empty() {
+            true => "no codecs".to_string(),
+            false => names.join(", "),
+        }
+    }
+
+    /// Starts the handshake on `transport`: sends a hello with these
+    /// capabilities and waits for the other end's answer, see
+    /// [`Capabilities::respond`].
+    pub fn negotiate(
+        &self,
+        transport: &mut impl Transport,
+    ) -> Result<SessionParams, ProtocolError> {
+        transport.send(&GUSProtocol::hello(*self).encode_as(HANDSHAKE_VERSION)?)?;
+        match GUSProtocol::decode(transport.recv()?)?.0.packet_type {
+            PacketType::HelloAck(theirs) => self.agree(&theirs),
+            got => Err(ProtocolError::UnexpectedPacket {
+                expected: "hello ack",
+                got,
+            }),
+        }
+    }
+
+    /// Answers the other end's [`Capabilities::negotiate`] on `transport`,
+    /// waiting for its hello and answering with these capabilities. They go
+    /// back even if the two ends can't agree, so both can tell why.
+    pub fn respond(&self, transport: &mut impl Transport) -> Result<SessionParams, ProtocolError> {
+        let theirs = match GUSProtocol::decode(transport.recv()?)?.0.packet_type {
+            PacketType::Hello(theirs) => theirs,
+            got => {
+                return Err(ProtocolError::UnexpectedPacket {
+                    expected: "hello",
+                    got,
+                });
+            }
+        };
+        self.answer(&theirs, transport)
+    }
+
+    /// Answers a hello with `theirs` that already came in on `transport`, as
+    /// [`Capabilities::respond`] does.
+    pub fn answer(
+        &self,
+        theirs: &Capabilities,
+        transport: &mut impl Transport,
+    ) -> Result<SessionParams, ProtocolError> {
+        transport.send(&GUSProtocol::hello_ack(*self).encode_as(HANDSHAKE_VERSION)?)?;
+        self.agree(theirs)
+    }
+}
+
+/// The version hellos go out as: the oldest one with packet types, so an end
+/// that hasn't caught up with the newest can still read them.
+const HANDSHAKE_VERSION: FrameVersion = FrameVersion::V8;
+
+/// What both ends of a link agreed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SessionParams {
+    pub version: FrameVersion,
+    pub codec: CodecId,
+    /// The largest frame either end takes.
+    pub max_frame_bytes: usize,
+}
+
+impl SessionParams {
+    /// The most bits of data a frame of these parameters holds, checksum
+    /// included, without header extensions. 0 if not even a bit fits.
+    pub fn max_fragment_bits(&self) -> usize {
+        let checksum = match self.version >= FrameVersion::V6 {
+            true => CHECKSUM_BITS,
+            false => 0,
+        };
+        let Some(payload_bytes) = self
+            .max_frame_bytes
+            .checked_sub(FrameHeader::size(self.version))
+        else {
+            return 0;
+        };
+        let fits = |data_bits: usize| {
+            self.codec.encoded_bits(data_bits + checksum).div_ceil(8) <= payload_bytes
+        };
+
+        // the most that fits, between none and more than could
+        let (mut low, mut high) = (0, payload_bytes * 8 + 1);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            match fits(mid) {
+                true => low = mid,
+                false => high = mid,
+            }
+        }
+        low
+    }
+
+    /// `packet` as it goes out in this session, with the agreed codec.
+    pub fn apply(&self, packet: GUSProtocol) -> GUSProtocol {
+        GUSProtocol {
+            version: self.version,
+            codec: self.codec,
+            ..packet
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{encoding::bitvec::BitVec, proto::transport::ChannelTransport};
+
+    /// Runs the handshake between an end with `ours` and one with `theirs`,
+    /// and what each came out with.
+    fn handshake(
+        ours: Capabilities,
+        theirs: Capabilities,
+    ) -> (
+        Result<SessionParams, ProtocolError>,
+        Result<SessionParams, ProtocolError>,
+    ) {
+        let (mut near, mut far) = ChannelTransport::pair();
+        let responder = thread::spawn(move || theirs.respond(&mut far));
+        let negotiated = ours.negotiate(&mut near);
+        (negotiated, responder.join().unwrap())
+    }
+
+    #[test]
+    fn test_matching() {
+        let (ours, theirs) = handshake(Capabilities::default(), Capabilities::default());
+        let params = ours.unwrap();
+        assert_eq!(params, theirs.unwrap());
+        assert_eq!(params.version, FrameVersion::CURRENT);
+        assert_eq!(params.codec, CodecId::Hamming);
+        assert_eq!(params.max_frame_bytes, DEFAULT_MAX_FRAME_BYTES);
+
+        assert_eq!(
+            Capabilities::from_bytes(Capabilities::default().to_bytes()),
+            Capabilities::default()
+        );
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let ours = Capabilities::new(
+            FrameVersion::V5,
+            FrameVersion::V8,
+            &[CodecId::Hamming74, CodecId::Golay24],
+            4096,
+        );
+        // a newer end, with versions this one doesn't know
+        let theirs = Capabilities {
+            max_version: 12,
+            ..Capabilities::new(
+                FrameVersion::V7,
+                FrameVersion::CURRENT,
+                &[CodecId::Hamming, CodecId::Golay24],
+                1 << 20,
+            )
+        };
+        let (negotiated, responded) = handshake(ours, theirs);
+        let params = negotiated.unwrap();
+        assert_eq!(params, responded.unwrap());
+        assert_eq!(
+            params,
+            SessionParams {
+                version: FrameVersion::V8,
+                codec: CodecId::Golay24,
+                max_frame_bytes: 4096,
+            }
+        );
+
+        // and what it makes of the frames after it
+        let data = BitVec::random(params.max_fragment_bits(), &mut rand::rng());
+        let frame = params.apply(GUSProtocol::data(data)).encode().unwrap();
+        assert!(frame.len() <= params.max_frame_bytes);
+        assert!(frame.len() > params.max_frame_bytes - 3);
+        let (decoded, _) = GUSProtocol::decode(frame).unwrap();
+        assert_eq!(decoded.version, FrameVersion::V8);
+        assert_eq!(decoded.codec, CodecId::Golay24);
+    }
+
+    #[test]
+    fn test_disjoint() {
+        let old = Capabilities::new(FrameVersion::V1, FrameVersion::V4, &CodecId::ALL, 4096);
+        let new = Capabilities::new(FrameVersion::V8, FrameVersion::CURRENT, &CodecId::ALL, 4096);
+        let (negotiated, responded) = handshake(new, old);
+        let error = negotiated.unwrap_err();
+        assert!(matches!(
+            error,
+            ProtocolError::Incompatible {
+                what: "a version",
+                ..
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Can't agree on a version: this end supports 8 to 12, the other 1 to 4"
+        );
+        assert!(matches!(
+            responded,
+            Err(ProtocolError::Incompatible {
+                what: "a version",
+                ..
+            })
+        ));
+
+        let hamming = Capabilities::new(
+            FrameVersion::V8,
+            FrameVersion::CURRENT,
+            &[CodecId::Hamming],
+            4096,
+        );
+        let golay = Capabilities::new(
+            FrameVersion::V8,
+            FrameVersion::CURRENT,
+            &[CodecId::Golay24, CodecId::Hamming74],
+            4096,
+        );
+        let (negotiated, _) = handshake(hamming, golay);
+        assert_eq!(
+            negotiated.unwrap_err().to_string(),
+            "Can't agree on a codec: this end supports hamming, the other hamming-7-4, golay-24"
+        );
+
+        let tiny = Capabilities {
+            max_frame_bytes: 40,
+            ..Capabilities::default()
+        };
+        assert!(matches!(
+            Capabilities::default().agree(&tiny),
+            Err(ProtocolError::Incompatible {
+                what: "a frame size",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_unexpected() {
+        let (mut near, mut far) = ChannelTransport::pair();
+        near.send(&GUSProtocol::ack(1).encode().unwrap()).unwrap();
+        assert!(matches!(
+            Capabilities::default().respond(&mut far),
+            Err(ProtocolError::UnexpectedPacket {
+                expected: "hello",
+                got: PacketType::Ack { message_id: 1 }
+            })
+        ));
+
+        // a data frame where the answer should be
+        let responder = thread::spawn(move || {
+            far.recv().unwrap();
+            far.send(&GUSProtocol::data(BitVec::zeros(8)).encode().unwrap())
+                .unwrap();
+        });
+        assert!(matches!(
+            Capabilities::default().negotiate(&mut near),
+            Err(ProtocolError::UnexpectedPacket {
+                expected: "hello ack",
+                got: PacketType::Data
+            })
+        ));
+        responder.join().unwrap();
+    }
+}
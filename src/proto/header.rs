@@ -0,0 +1,1657 @@
+//! The frame header and how each protocol version puts it on the wire.
+//!
+//! Versions 1 and 2 send the header as is, 20 bytes: the magic, the version and
+//! the two lengths. Version 3 sends those same 20 bytes as one
+//! [`ExtendedHamming`] codeword. From version 4 on the magic and version go
+//! first in a codeword of their own, the lead, so the version is known before
+//! the fields after it, which get a second codeword sized for that version.
+//! Version 5 adds the fragment fields to those, version 6 the flags, version 7
+//! the codec, version 8 the packet type, version 9 the length of the
+//! [extensions](HeaderExtensions) in a third codeword after the fixed header,
+//! if there are any, version 10 the interleaving depth, version 11 the seed
+//! of a scrambled payload in a codeword of its own before the extensions,
+//! version 12 a checksum of the lead and the fields, at the end of them.
+
+use crate::{
+    encoding::{
+        bitvec::BitVec,
+        checksum::{Checksum, Crc8},
+        hamming::{
+            DecodeOutcome, DecodeStatus, ExtendedHamming, HammingCode, HammingCodeBase,
+            HammingError,
+        },
+    },
+    proto::{CodecId, HeaderExtensions, PacketType, ProtocolError},
+};
+
+pub(super) const PROTOCOL_NAME: &[u8; 3] = b"GUS";
+
+/// A protocol version this implementation reads and writes.
+///
+/// Version 12 added the header checksum. Version 11 added scrambling.
+/// Version 10 added the interleaving depth.
+/// Version 9 added the header
+/// extensions. Version 8 added the packet type.
+/// Version 7 added the codec. Version 6 added the flags. Version 5 added the
+/// fragment fields. Version 4 added the content type. Version 3 protects the
+/// header. Version 2 fixed the length fields at 64 bits. Version 1 used
+/// `usize`, so only frames from 64-bit hosts can still be read, and are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "u8", try_from = "u8")
+)]
+pub enum FrameVersion {
+    V1 = 1,
+    V2 = 2,
+    V3 = 3,
+    V4 = 4,
+    V5 = 5,
+    V6 = 6,
+    V7 = 7,
+    V8 = 8,
+    V9 = 9,
+    V10 = 10,
+    V11 = 11,
+    #[default]
+    V12 = 12,
+}
+
+impl FrameVersion {
+    /// What gets sent unless asked otherwise.
+    pub const CURRENT: Self = Self::V12;
+
+    /// Every supported version, oldest first.
+    pub const ALL: [Self; 12] = [
+        Self::V1,
+        Self::V2,
+        Self::V3,
+        Self::V4,
+        Self::V5,
+        Self::V6,
+        Self::V7,
+        Self::V8,
+        Self::V9,
+        Self::V10,
+        Self::V11,
+        Self::V12,
+    ];
+
+    /// The version byte on the wire.
+    pub fn byte(self) -> u8 {
+        self as u8
+    }
+}
+
+impl TryFrom<u8> for FrameVersion {
+    type Error = ProtocolError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|version| version.byte() == byte)
+            .ok_or(ProtocolError::UnsupportedVersion(byte))
+    }
+}
+
+impl From<FrameVersion> for u8 {
+    fn from(version: FrameVersion) -> Self {
+        version.byte()
+    }
+}
+
+impl std::fmt::Display for FrameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.byte())
+    }
+}
+
+/// Size of a length field on the wire, the same on every architecture.
+const LENGTH_FIELD_SIZE: usize = std::mem::size_of::<u64>();
+
+/// Magic, version and the two length fields, the whole header up to version 3.
+const PLAIN_HEADER_SIZE: usize = 4 + Length::SIZE;
+
+/// Magic and version.
+const LEAD_SIZE: usize = 4;
+
+/// Content type and the two length fields, the rest of a version 4 header.
+const FIELDS_SIZE: usize = 1 + Length::SIZE;
+
+/// The codec id and the bits of padding it added.
+const CODEC_SIZE: usize = 2;
+
+/// The packet type.
+const PACKET_TYPE_SIZE: usize = 1;
+
+/// The length of the extensions.
+const EXTENSIONS_LEN_SIZE: usize = std::mem::size_of::<u16>();
+
+/// Where the length of the extensions starts in the fields.
+const EXTENSIONS_LEN_OFFSET: usize =
+    FIELDS_SIZE + Fragment::SIZE + Flags::SIZE + CODEC_SIZE + PACKET_TYPE_SIZE;
+
+/// The interleaving depth.
+const INTERLEAVE_SIZE: usize = 1;
+
+/// The CRC-8 of the lead and the fields before it.
+const HEADER_CHECKSUM_SIZE: usize = 1;
+
+/// The scrambler seed, when there is one.
+const SEED_SIZE: usize = std::mem::size_of::<u32>();
+
+/// The deepest interleaving a frame can ask for. Deeper buys nothing the
+/// codecs can use, and a receiver deinterleaving a frame a bit a row is
+/// more likely looking at a corrupted depth.
+pub const MAX_INTERLEAVE_DEPTH: u8 = 64;
+
+/// Bytes of the version 4 fields plus whatever later versions added to them.
+fn fields_size(version: FrameVersion) -> usize {
+    match version {
+        FrameVersion::V12 => fields_size(FrameVersion::V11) + HEADER_CHECKSUM_SIZE,
+        FrameVersion::V10 | FrameVersion::V11 => {
+            EXTENSIONS_LEN_OFFSET + EXTENSIONS_LEN_SIZE + INTERLEAVE_SIZE
+        }
+        FrameVersion::V9 => {
+            FIELDS_SIZE
+                + Fragment::SIZE
+                + Flags::SIZE
+                + CODEC_SIZE
+                + PACKET_TYPE_SIZE
+                + EXTENSIONS_LEN_SIZE
+        }
+        FrameVersion::V8 => {
+            FIELDS_SIZE + Fragment::SIZE + Flags::SIZE + CODEC_SIZE + PACKET_TYPE_SIZE
+        }
+        FrameVersion::V7 => FIELDS_SIZE + Fragment::SIZE + Flags::SIZE + CODEC_SIZE,
+        FrameVersion::V6 => FIELDS_SIZE + Fragment::SIZE + Flags::SIZE,
+        FrameVersion::V5 => FIELDS_SIZE + Fragment::SIZE,
+        _ => FIELDS_SIZE,
+    }
+}
+
+/// The byte order of the numbers in a header, little-endian unless the
+/// [`Flags::BIG_ENDIAN`] flag says otherwise. Only the header's fields follow
+/// it, what's in the payload is up to the payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Endianness {
+    #[default]
+    Little,
+    /// Network byte order.
+    Big,
+}
+
+impl Endianness {
+    pub(super) fn u16_bytes(self, value: u16) -> [u8; 2] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+
+    pub(super) fn u16_from(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn u64_bytes(self, value: u64) -> [u8; 8] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn u64_from(self, bytes: [u8; 8]) -> u64 {
+        match self {
+            Endianness::Little => u64::from_le_bytes(bytes),
+            Endianness::Big => u64::from_be_bytes(bytes),
+        }
+    }
+
+    fn u32_bytes(self, value: u32) -> [u8; 4] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn u32_from(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// Length structure to hold the length of data and bits
+///
+/// Both are sent as `u64`s, 16 bytes in all, whatever the width of `usize` on
+/// either end. Little-endian ones, unless the frame says otherwise, which also
+/// happens to be how version 1 frames built on 64-bit hosts laid them out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Length {
+    pub data_length: usize,
+    pub bits_length: usize,
+}
+
+impl Length {
+    /// Size of the serialized lengths in bytes.
+    pub const SIZE: usize = LENGTH_FIELD_SIZE * 2;
+
+    pub fn to_le_bytes(&self) -> [u8; Self::SIZE] {
+        self.to_bytes(Endianness::Little)
+    }
+
+    pub fn to_bytes(&self, endianness: Endianness) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..LENGTH_FIELD_SIZE].copy_from_slice(&endianness.u64_bytes(self.data_length as u64));
+        bytes[LENGTH_FIELD_SIZE..Self::SIZE]
+            .copy_from_slice(&endianness.u64_bytes(self.bits_length as u64));
+        bytes
+    }
+
+    pub fn from_le_bytes(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        Self::from_bytes(bytes, Endianness::Little)
+    }
+
+    /// Reads the lengths back, failing if there aren't enough bytes or a
+    /// length doesn't fit in a `usize` on this host.
+    pub fn from_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, ProtocolError> {
+        if bytes.len() < Self::SIZE {
+            return Err(ProtocolError::TooShort {
+                needed: Self::SIZE,
+                got: bytes.len(),
+            });
+        }
+        let field = |range: std::ops::Range<usize>| -> Result<usize, ProtocolError> {
+            let value = endianness.u64_from(bytes[range].try_into().expect("a field is 8 bytes"));
+            usize::try_from(value).map_err(|_| ProtocolError::LengthOverflow(value))
+        };
+
+        Ok(Self {
+            data_length: field(0..LENGTH_FIELD_SIZE)?,
+            bits_length: field(LENGTH_FIELD_SIZE..Self::SIZE)?,
+        })
+    }
+
+    /// Whether the byte length is the one the bit length takes up, as it is
+    /// in every header ever sent.
+    pub fn adds_up(&self) -> bool {
+        self.bits_length.div_ceil(8) == self.data_length
+    }
+}
+
+/// Where a frame's payload goes in the message it's a piece of.
+///
+/// Sent as three `u32`s from version 5 on, in the header's [`Endianness`]. Frames of earlier
+/// versions always hold a whole message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Fragment {
+    /// The same for every fragment of a message.
+    pub message_id: u32,
+    /// 0-based position of this fragment in the message.
+    pub index: u32,
+    /// Number of fragments the message was split into, at least 1.
+    pub count: u32,
+}
+
+impl Default for Fragment {
+    /// The one and only fragment of message 0.
+    fn default() -> Self {
+        Self {
+            message_id: 0,
+            index: 0,
+            count: 1,
+        }
+    }
+}
+
+impl Fragment {
+    /// Size of the serialized fields in bytes.
+    pub const SIZE: usize = 3 * std::mem::size_of::<u32>();
+
+    /// Whether the message fits in this one frame.
+    pub fn is_whole(&self) -> bool {
+        self.count == 1
+    }
+
+    pub fn to_le_bytes(&self) -> [u8; Self::SIZE] {
+        self.to_bytes(Endianness::Little)
+    }
+
+    pub fn to_bytes(&self, endianness: Endianness) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..4].copy_from_slice(&endianness.u32_bytes(self.message_id));
+        bytes[4..8].copy_from_slice(&endianness.u32_bytes(self.index));
+        bytes[8..12].copy_from_slice(&endianness.u32_bytes(self.count));
+        bytes
+    }
+
+    pub fn from_le_bytes(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        Self::from_bytes(bytes, Endianness::Little)
+    }
+
+    /// Reads the fields back, failing if there aren't enough bytes or the
+    /// index doesn't fall within the count.
+    pub fn from_bytes(bytes: &[u8], endianness: Endianness) -> Result<Self, ProtocolError> {
+        if bytes.len() < Self::SIZE {
+            return Err(ProtocolError::TooShort {
+                needed: Self::SIZE,
+                got: bytes.len(),
+            });
+        }
+        let field = |start: usize| {
+            endianness.u32_from(
+                bytes[start..start + 4]
+                    .try_into()
+                    .expect("a field is 4 bytes"),
+            )
+        };
+
+        let fragment = Self {
+            message_id: field(0),
+            index: field(4),
+            count: field(8),
+        };
+        if fragment.index >= fragment.count {
+            return Err(ProtocolError::FragmentOutOfRange {
+                message_id: fragment.message_id,
+                index: fragment.index,
+                count: fragment.count,
+            });
+        }
+        Ok(fragment)
+    }
+}
+
+/// Optional features a frame uses, a bit each. Sent as a byte from version 6
+/// on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct Flags(u8);
+
+impl Flags {
+    /// A CRC-32 of the payload follows it, inside the codeword.
+    pub const CRC32: Self = Self(0x01);
+    /// The header's numbers are big-endian, see [`Endianness`].
+    pub const BIG_ENDIAN: Self = Self(0x02);
+    /// The data is deflated, and inflated again once it's been checked. Only
+    /// read and written with the `compress` feature, see
+    /// [`GUSProtocolBuilder::compress`](crate::proto::GUSProtocolBuilder).
+    pub const COMPRESSED: Self = Self(0x04);
+    /// The payload went into the codec scrambled, with the seed that goes
+    /// after the fixed header. Only from version 11 on, see
+    /// [`GUSProtocolBuilder::scramble`](crate::proto::GUSProtocolBuilder).
+    pub const SCRAMBLED: Self = Self(0x08);
+
+    /// Size of the serialized flags in bytes.
+    pub const SIZE: usize = 1;
+
+    /// Every flag this version knows about.
+    #[cfg(feature = "compress")]
+    const KNOWN: Self =
+        Self(Self::CRC32.0 | Self::BIG_ENDIAN.0 | Self::COMPRESSED.0 | Self::SCRAMBLED.0);
+    #[cfg(not(feature = "compress"))]
+    const KNOWN: Self = Self(Self::CRC32.0 | Self::BIG_ENDIAN.0 | Self::SCRAMBLED.0);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Reads the flags back, failing if any isn't known, since there's no
+    /// telling what it would have changed about the frame.
+    pub fn from_bits(bits: u8) -> Result<Self, ProtocolError> {
+        match bits & !Self::KNOWN.0 {
+            0 => Ok(Self(bits)),
+            unknown => Err(ProtocolError::UnsupportedFlags(unknown)),
+        }
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// The byte order these flags say the header's numbers are in.
+    pub const fn endianness(self) -> Endianness {
+        match self.contains(Self::BIG_ENDIAN) {
+            true => Endianness::Big,
+            false => Endianness::Little,
+        }
+    }
+}
+
+/// What the payload holds, so the receiver knows how to show it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ContentType {
+    /// Arbitrary bits. Frames from before version 4 are taken to be binary.
+    #[default]
+    Binary,
+    /// UTF-8 text.
+    Text,
+    /// Bits given as hex, to be shown the same way.
+    Hex,
+    /// Bytes given as base64, to be shown the same way.
+    Base64,
+    /// Bytes that are never read as text.
+    Bytes,
+    /// A content type this version doesn't know about.
+    Unknown(u8),
+}
+
+impl From<u8> for ContentType {
+    fn from(byte: u8) -> Self {
+        match byte {
+            0 => Self::Binary,
+            1 => Self::Text,
+            2 => Self::Hex,
+            3 => Self::Base64,
+            4 => Self::Bytes,
+            byte => Self::Unknown(byte),
+        }
+    }
+}
+
+impl From<ContentType> for u8 {
+    fn from(content_type: ContentType) -> Self {
+        match content_type {
+            ContentType::Binary => 0,
+            ContentType::Text => 1,
+            ContentType::Hex => 2,
+            ContentType::Base64 => 3,
+            ContentType::Bytes => 4,
+            ContentType::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// Everything the header says, whatever the version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FrameHeader {
+    pub version: FrameVersion,
+    pub content_type: ContentType,
+    pub length: Length,
+    /// Always whole for frames before version 5.
+    pub fragment: Fragment,
+    /// Always empty for frames before version 6.
+    pub flags: Flags,
+    /// Always Hamming for frames before version 7.
+    pub codec: CodecId,
+    /// Bits of padding after the payload, to fill the codec's last block.
+    pub padding: u8,
+    /// The byte of a [`PacketType`], one this version knows. Always data for
+    /// frames before version 8, whose payload is needed to tell the rest.
+    pub packet_type: u8,
+    /// Always empty for frames before version 9.
+    pub extensions: HeaderExtensions,
+    /// Rows the payload's codeword was interleaved over, 0 or 1 for none.
+    /// Always 0 for frames before version 10.
+    pub interleave_depth: u8,
+    /// What the scrambler started from, if the payload was scrambled; the
+    /// [`Flags::SCRAMBLED`] flag goes by it. Always `None` for frames before
+    /// version 11.
+    pub scramble_seed: Option<u32>,
+}
+
+/// A header read off the front of a frame.
+pub(super) struct DecodedHeader {
+    pub header: FrameHeader,
+    /// Bits corrected in the header, counted from the start of the frame.
+    /// Always clean for legacy headers, which aren't protected.
+    pub status: DecodeStatus,
+    /// Bytes the header took up, extensions and all, i.e. where the payload
+    /// starts.
+    pub size: usize,
+}
+
+impl DecodedHeader {
+    /// Bytes the whole frame takes up, header and payload, once the lengths
+    /// are checked to make sense together: a hostile or corrupted header can
+    /// say anything.
+    pub fn frame_size(&self) -> Result<usize, ProtocolError> {
+        let Length {
+            data_length,
+            bits_length,
+        } = self.header.length;
+        if bits_length > data_length.saturating_mul(8) {
+            return Err(ProtocolError::InconsistentLengths {
+                data_length,
+                bits_length,
+            });
+        }
+        self.size
+            .checked_add(data_length)
+            .ok_or(ProtocolError::LengthOverflow(data_length as u64))
+    }
+}
+
+/// Bits of the [`ExtendedHamming`] codeword protecting `bytes` bytes.
+fn protected_bits(bytes: usize) -> usize {
+    bytes * 8 + HammingCodeBase::calculate_parity_count(bytes * 8) + 1
+}
+
+/// Bytes of the [`ExtendedHamming`] codeword protecting `bytes` bytes, padded
+/// to whole bytes.
+fn protected_size(bytes: usize) -> usize {
+    protected_bits(bytes).div_ceil(8)
+}
+
+fn protect(bytes: Vec<u8>) -> Vec<u8> {
+    ExtendedHamming
+        .encode(&BitVec::from_whole_bytes(bytes))
+        .expect("extended Hamming encodes any non-empty input")
+        .into_inner()
+}
+
+/// Reads back the `bytes` bytes protected at the start of `encoded`. The
+/// outcome can still be uncorrectable, it's up to the caller what to make of
+/// that.
+fn unprotect(encoded: &[u8], bytes: usize) -> Result<DecodeOutcome, ProtocolError> {
+    let codeword = encoded
+        .get(..protected_size(bytes))
+        .ok_or(ProtocolError::TooShort {
+            needed: protected_size(bytes),
+            got: encoded.len(),
+        })?;
+    let codeword = BitVec::from_bytes(codeword.to_vec(), protected_bits(bytes))
+        .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+    Ok(ExtendedHamming.decode(&codeword)?)
+}
+
+/// The CRC-8 of a header's `lead` and the `fields` after it, up to the
+/// checksum itself.
+fn header_checksum(lead: &[u8], fields: &[u8]) -> u8 {
+    let bytes = [lead, fields].concat();
+    Crc8.compute(&BitVec::from_whole_bytes(bytes)) as u8
+}
+
+/// Bytes the codeword of the scrambler seed takes up, for a header of
+/// `version` with `flags`.
+fn seed_block(version: FrameVersion, flags: Flags) -> usize {
+    match version >= FrameVersion::V11 && flags.contains(Flags::SCRAMBLED) {
+        true => protected_size(SEED_SIZE),
+        false => 0,
+    }
+}
+
+/// Bytes the codeword of `len` bytes of extensions takes up, none at all for
+/// none.
+fn extensions_block(len: usize) -> usize {
+    match len {
+        0 => 0,
+        len => protected_size(len),
+    }
+}
+
+/// Bit positions `status` corrected, moved along by `offset`.
+fn corrected_positions(status: &DecodeStatus, offset: usize) -> Vec<usize> {
+    match status {
+        DecodeStatus::Corrected { codeword_pos, .. } => vec![offset + codeword_pos],
+        DecodeStatus::CorrectedMany { codeword_positions } => codeword_positions
+            .iter()
+            .map(|position| offset + position)
+            .collect(),
+        DecodeStatus::Clean | DecodeStatus::Uncorrectable => Vec::new(),
+    }
+}
+
+impl FrameHeader {
+    /// The smallest header of any version.
+    pub const MIN_SIZE: usize = PLAIN_HEADER_SIZE;
+
+    /// Bytes a header of `version` takes up on the wire.
+    pub fn size(version: FrameVersion) -> usize {
+        match version {
+            FrameVersion::V1 | FrameVersion::V2 => PLAIN_HEADER_SIZE,
+            FrameVersion::V3 => protected_size(PLAIN_HEADER_SIZE),
+            FrameVersion::V4
+            | FrameVersion::V5
+            | FrameVersion::V6
+            | FrameVersion::V7
+            | FrameVersion::V8
+            | FrameVersion::V9
+            | FrameVersion::V10
+            | FrameVersion::V11
+            | FrameVersion::V12 => protected_size(LEAD_SIZE) + protected_size(fields_size(version)),
+        }
+    }
+
+    /// Bytes the codeword of the scrambler seed takes up after a version 11
+    /// header, when there is one.
+    pub fn seed_size() -> usize {
+        protected_size(SEED_SIZE)
+    }
+
+    /// Bytes the codeword of `extensions` takes up after a version 9 header,
+    /// none if there aren't any.
+    pub fn extensions_size(extensions: &HeaderExtensions) -> usize {
+        extensions_block(extensions.encoded_len())
+    }
+
+    /// The header as it goes on the wire for its version. `protocol_name`
+    /// normally is [`PROTOCOL_NAME`].
+    pub fn encode(&self, protocol_name: &[u8]) -> Vec<u8> {
+        let mut lead = Vec::with_capacity(PLAIN_HEADER_SIZE);
+        lead.extend(protocol_name);
+        lead.push(self.version.byte());
+
+        match self.version {
+            FrameVersion::V1 | FrameVersion::V2 => {
+                lead.extend(self.length.to_le_bytes());
+                lead
+            }
+            FrameVersion::V3 => {
+                lead.extend(self.length.to_le_bytes());
+                protect(lead)
+            }
+            FrameVersion::V4
+            | FrameVersion::V5
+            | FrameVersion::V6
+            | FrameVersion::V7
+            | FrameVersion::V8
+            | FrameVersion::V9
+            | FrameVersion::V10
+            | FrameVersion::V11
+            | FrameVersion::V12 => {
+                let mut fields = self.fields();
+                if self.version >= FrameVersion::V12 {
+                    fields.push(header_checksum(&lead, &fields));
+                }
+                let mut header = protect(lead);
+                header.extend(protect(fields));
+                if let Some(seed) = self.wire_seed() {
+                    header.extend(protect(self.endianness().u32_bytes(seed).to_vec()));
+                }
+                if self.version >= FrameVersion::V9 && !self.extensions.is_empty() {
+                    header.extend(protect(self.extensions.to_bytes(self.endianness())));
+                }
+                header
+            }
+        }
+    }
+
+    /// The fields after the lead, as they go in their codeword from version 4
+    /// on, but for the checksum at their end from version 12 on.
+    fn fields(&self) -> Vec<u8> {
+        let endianness = self.endianness();
+        let mut fields = Vec::with_capacity(fields_size(self.version));
+        fields.push(self.content_type.into());
+        fields.extend(self.length.to_bytes(endianness));
+        if self.version >= FrameVersion::V5 {
+            fields.extend(self.fragment.to_bytes(endianness));
+        }
+        if self.version >= FrameVersion::V6 {
+            let mut flags = self.flags;
+            flags.remove(Flags::SCRAMBLED);
+            if self.wire_seed().is_some() {
+                flags.insert(Flags::SCRAMBLED);
+            }
+            fields.push(flags.bits());
+        }
+        if self.version >= FrameVersion::V7 {
+            fields.push(self.codec.byte());
+            fields.push(self.padding);
+        }
+        if self.version >= FrameVersion::V8 {
+            fields.push(self.packet_type);
+        }
+        if self.version >= FrameVersion::V9 {
+            let len = u16::try_from(self.extensions.encoded_len())
+                .expect("extensions are kept under u16::MAX bytes");
+            fields.extend(endianness.u16_bytes(len));
+        }
+        if self.version >= FrameVersion::V10 {
+            fields.push(self.interleave_depth);
+        }
+        fields
+    }
+
+    /// The scrambler seed, if the header's version has room for it.
+    fn wire_seed(&self) -> Option<u32> {
+        self.scramble_seed
+            .filter(|_| self.version >= FrameVersion::V11)
+    }
+
+    /// The byte order of the header's numbers.
+    fn endianness(&self) -> Endianness {
+        // only versions with flags can say they aren't little-endian
+        match self.version >= FrameVersion::V6 {
+            true => self.flags.endianness(),
+            false => Endianness::Little,
+        }
+    }
+
+    /// Bytes the header at the start of `encoded` takes up, going by the
+    /// version it seems to be, without reading more of it than the lead. Right
+    /// unless the header is too corrupted to decode anyway.
+    ///
+    /// Needs the first [`FrameHeader::MIN_SIZE`] bytes. From version 9 on the
+    /// extensions (and from version 11 on the scrambler seed) after the fixed
+    /// header only count once `encoded` holds all of it, so the hint can grow
+    /// as more of the header comes in; it's final once it's no more than
+    /// `encoded` holds.
+    pub fn size_hint(encoded: &[u8]) -> Result<usize, ProtocolError> {
+        if let Some(version) = Self::legacy_version(encoded) {
+            return Ok(Self::size(version));
+        }
+        let version = match Self::read_lead(encoded)?.1.map(FrameVersion::try_from) {
+            Some(Ok(version)) if version >= FrameVersion::V4 => version,
+            _ => FrameVersion::V3,
+        };
+        let size = Self::size(version);
+        if version < FrameVersion::V9 || encoded.len() < size {
+            return Ok(size);
+        }
+
+        // a corrupted length is for decoding to find out about
+        let fields = unprotect(&encoded[protected_size(LEAD_SIZE)..], fields_size(version))?;
+        if fields.status.is_uncorrectable() {
+            return Ok(size);
+        }
+        let fields_bytes = fields.data.into_inner();
+        let flags = Flags(fields_bytes[FIELDS_SIZE + Fragment::SIZE]);
+        Ok(size
+            + seed_block(version, flags)
+            + extensions_block(Self::extensions_len(&fields_bytes, flags.endianness())))
+    }
+
+    /// Bytes of extensions after the fixed header, going by the length in
+    /// its `fields_bytes`.
+    fn extensions_len(fields_bytes: &[u8], endianness: Endianness) -> usize {
+        let start = EXTENSIONS_LEN_OFFSET;
+        endianness
+            .u16_from([fields_bytes[start], fields_bytes[start + 1]])
+            .into()
+    }
+
+    /// The version of the unprotected header `encoded` starts with, if it
+    /// starts with one.
+    fn legacy_version(encoded: &[u8]) -> Option<FrameVersion> {
+        // a protected header never starts like this, not even with an error or
+        // two, its first bits are interleaved with parity
+        if !encoded.starts_with(PROTOCOL_NAME) {
+            return None;
+        }
+        match encoded.get(3).map(|&byte| FrameVersion::try_from(byte)) {
+            Some(Ok(version @ (FrameVersion::V1 | FrameVersion::V2))) => Some(version),
+            _ => None,
+        }
+    }
+
+    /// Decodes the lead of a protected header, along with the version byte in
+    /// it if it came through with the magic. A version 3 header has no lead,
+    /// but what passes for one at its start carries the same data bits.
+    fn read_lead(encoded: &[u8]) -> Result<(DecodeOutcome, Option<u8>), ProtocolError> {
+        let lead = unprotect(encoded, LEAD_SIZE)?;
+        let lead_bytes = lead.data.clone().into_inner();
+        let claimed = (!lead.status.is_uncorrectable() && lead_bytes.starts_with(PROTOCOL_NAME))
+            .then_some(lead_bytes[3]);
+        Ok((lead, claimed))
+    }
+
+    /// Reads the header off the front of `encoded` with the parser for the
+    /// version it carries.
+    pub fn decode(encoded: &[u8]) -> Result<DecodedHeader, ProtocolError> {
+        match Self::legacy_version(encoded) {
+            Some(FrameVersion::V1) => return Self::decode_v1(encoded),
+            Some(_) => return Self::decode_v2(encoded),
+            None => {}
+        }
+
+        let (lead, claimed) = Self::read_lead(encoded)?;
+        let lead_bytes = lead.data.clone().into_inner();
+        let parsed = match claimed.map(FrameVersion::try_from) {
+            Some(Ok(FrameVersion::V4)) => Self::decode_v4(encoded, &lead.status),
+            Some(Ok(FrameVersion::V5)) => Self::decode_v5(encoded, &lead.status),
+            Some(Ok(FrameVersion::V6)) => Self::decode_v6(encoded, &lead.status),
+            Some(Ok(FrameVersion::V7)) => Self::decode_v7(encoded, &lead.status),
+            Some(Ok(FrameVersion::V8)) => Self::decode_v8(encoded, &lead.status),
+            Some(Ok(FrameVersion::V9)) => Self::decode_v9(encoded, &lead.status),
+            Some(Ok(FrameVersion::V10)) => Self::decode_v10(encoded, &lead.status),
+            Some(Ok(FrameVersion::V11)) => Self::decode_v11(encoded, &lead.status),
+            Some(Ok(FrameVersion::V12)) => Self::decode_v12(encoded, &lead.status),
+            Some(Ok(FrameVersion::V3)) | None => Self::decode_v3(encoded),
+            // those are never protected
+            Some(Ok(FrameVersion::V1 | FrameVersion::V2)) => Err(ProtocolError::CorruptedHeader),
+            Some(Err(error)) => Err(error),
+        };
+        if parsed.is_ok() || claimed == Some(FrameVersion::V3.byte()) {
+            return parsed;
+        }
+
+        // a version 3 header starts out with the same data bits as a lead, so
+        // a lead that looks alright isn't proof of anything else
+        if claimed.is_some()
+            && let Ok(header) = Self::decode_v3(encoded)
+        {
+            return Ok(header);
+        }
+
+        // only go by the lead if it came through untouched, or it got as far
+        // as the fields
+        if lead.status.is_clean() && !lead_bytes.starts_with(PROTOCOL_NAME) {
+            return Err(ProtocolError::BadMagic(
+                lead_bytes[..3]
+                    .try_into()
+                    .expect("the lead starts with the magic"),
+            ));
+        }
+        // a corrected lead claiming more fields than there are bytes for is
+        // more likely misread than cut short
+        let got_to_fields = matches!(
+            claimed.map(FrameVersion::try_from),
+            Some(Ok(version)) if version >= FrameVersion::V4
+        ) && !matches!(parsed, Err(ProtocolError::TooShort { .. }));
+        match lead.status.is_clean() || got_to_fields {
+            true => parsed,
+            false => Err(ProtocolError::CorruptedHeader),
+        }
+    }
+
+    /// Reads a version 1 header, the same as a version 2 one. Its lengths
+    /// were `usize`s, so this only works for frames from 64-bit hosts.
+    fn decode_v1(encoded: &[u8]) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_plain(encoded, FrameVersion::V1)
+    }
+
+    fn decode_v2(encoded: &[u8]) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_plain(encoded, FrameVersion::V2)
+    }
+
+    /// Reads an unprotected header of `version`.
+    fn decode_plain(encoded: &[u8], version: FrameVersion) -> Result<DecodedHeader, ProtocolError> {
+        let header = encoded
+            .get(..PLAIN_HEADER_SIZE)
+            .ok_or(ProtocolError::TooShort {
+                needed: PLAIN_HEADER_SIZE,
+                got: encoded.len(),
+            })?;
+        Ok(DecodedHeader {
+            header: Self {
+                version,
+                content_type: ContentType::Binary,
+                length: Length::from_le_bytes(&header[4..])?,
+                fragment: Fragment::default(),
+                flags: Flags::empty(),
+                codec: CodecId::Hamming,
+                padding: 0,
+                packet_type: PacketType::Data.byte(),
+                extensions: HeaderExtensions::new(),
+                interleave_depth: 0,
+                scramble_seed: None,
+            },
+            status: DecodeStatus::Clean,
+            size: Self::size(version),
+        })
+    }
+
+    /// Reads a version 3 header, the whole thing in one codeword.
+    fn decode_v3(encoded: &[u8]) -> Result<DecodedHeader, ProtocolError> {
+        let header = unprotect(encoded, PLAIN_HEADER_SIZE)?;
+        let header_bytes = header.data.into_inner();
+        let version = FrameVersion::V3;
+        if header.status.is_uncorrectable()
+            || !header_bytes.starts_with(PROTOCOL_NAME)
+            || header_bytes[3] != version.byte()
+        {
+            return Err(ProtocolError::CorruptedHeader);
+        }
+        // a later header with errors in its lead can pass for a version 3 one
+        // with an error in the version byte, but not with lengths that add up
+        let length = Length::from_le_bytes(&header_bytes[4..])?;
+        if !header.status.is_clean() && !length.adds_up() {
+            return Err(ProtocolError::CorruptedHeader);
+        }
+
+        Ok(DecodedHeader {
+            header: Self {
+                version,
+                content_type: ContentType::Binary,
+                length,
+                fragment: Fragment::default(),
+                flags: Flags::empty(),
+                codec: CodecId::Hamming,
+                padding: 0,
+                packet_type: PacketType::Data.byte(),
+                extensions: HeaderExtensions::new(),
+                interleave_depth: 0,
+                scramble_seed: None,
+            },
+            status: DecodeStatus::corrected_many(corrected_positions(&header.status, 0)),
+            size: Self::size(version),
+        })
+    }
+
+    /// Reads the fields after a version 4 lead that came out as `lead_status`.
+    fn decode_v4(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V4)
+    }
+
+    /// Reads the fields after a version 5 lead, the fragment fields included.
+    fn decode_v5(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V5)
+    }
+
+    /// Reads the fields after a version 6 lead, the flags included.
+    fn decode_v6(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V6)
+    }
+
+    /// Reads the fields after a version 7 lead, the codec included.
+    fn decode_v7(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V7)
+    }
+
+    /// Reads the fields after a version 8 lead, the packet type included.
+    fn decode_v8(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V8)
+    }
+
+    /// Reads the fields after a version 9 lead, and the extensions after
+    /// them.
+    fn decode_v9(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V9)
+    }
+
+    /// Reads the fields after a version 10 lead, the interleaving depth
+    /// included.
+    fn decode_v10(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V10)
+    }
+
+    /// Reads the fields after a version 11 lead, and the scrambler seed after
+    /// them if the flags say there is one.
+    fn decode_v11(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V11)
+    }
+
+    /// Reads the fields after a version 12 lead, once their checksum says
+    /// they're what was sent.
+    fn decode_v12(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        Self::decode_fields(encoded, lead_status, FrameVersion::V12)
+    }
+
+    /// Reads the fields codeword of a `version` header after the lead.
+    fn decode_fields(
+        encoded: &[u8],
+        lead_status: &DecodeStatus,
+        version: FrameVersion,
+    ) -> Result<DecodedHeader, ProtocolError> {
+        let offset = protected_size(LEAD_SIZE);
+        let fields = unprotect(&encoded[offset..], fields_size(version))?;
+        if fields.status.is_uncorrectable() {
+            return Err(ProtocolError::CorruptedHeader);
+        }
+        let fields_bytes = fields.data.into_inner();
+        // the codewords only know about the bits, not whether they make sense
+        if version >= FrameVersion::V12 {
+            let (checked, &[expected]) = fields_bytes.split_at(fields_bytes.len() - 1) else {
+                unreachable!("the checksum is the last byte");
+            };
+            let mut lead = PROTOCOL_NAME.to_vec();
+            lead.push(version.byte());
+            let actual = header_checksum(&lead, checked);
+            if actual != expected {
+                return Err(ProtocolError::HeaderChecksum { expected, actual });
+            }
+        }
+        // the flags come after the numbers they say the byte order of
+        let flags = match version {
+            FrameVersion::V6
+            | FrameVersion::V7
+            | FrameVersion::V8
+            | FrameVersion::V9
+            | FrameVersion::V10
+            | FrameVersion::V11
+            | FrameVersion::V12 => Flags::from_bits(fields_bytes[FIELDS_SIZE + Fragment::SIZE])?,
+            _ => Flags::empty(),
+        };
+        // there's no seed to go with it before version 11
+        if version < FrameVersion::V11 && flags.contains(Flags::SCRAMBLED) {
+            return Err(ProtocolError::UnsupportedFlags(Flags::SCRAMBLED.bits()));
+        }
+        let endianness = flags.endianness();
+        let fragment = match version {
+            FrameVersion::V5
+            | FrameVersion::V6
+            | FrameVersion::V7
+            | FrameVersion::V8
+            | FrameVersion::V9
+            | FrameVersion::V10
+            | FrameVersion::V11
+            | FrameVersion::V12 => Fragment::from_bytes(
+                &fields_bytes[FIELDS_SIZE..FIELDS_SIZE + Fragment::SIZE],
+                endianness,
+            )?,
+            _ => Fragment::default(),
+        };
+        let codec = FIELDS_SIZE + Fragment::SIZE + Flags::SIZE;
+        let (codec, padding) = match version {
+            FrameVersion::V7
+            | FrameVersion::V8
+            | FrameVersion::V9
+            | FrameVersion::V10
+            | FrameVersion::V11
+            | FrameVersion::V12 => (
+                CodecId::try_from(fields_bytes[codec])?,
+                fields_bytes[codec + 1],
+            ),
+            _ => (CodecId::Hamming, 0),
+        };
+        if usize::from(padding) > codec.max_padding() {
+            return Err(ProtocolError::BadPadding {
+                padding,
+                codec,
+                max: codec.max_padding(),
+            });
+        }
+        let packet_type = match version {
+            FrameVersion::V8
+            | FrameVersion::V9
+            | FrameVersion::V10
+            | FrameVersion::V11
+            | FrameVersion::V12 => PacketType::check(
+                fields_bytes[FIELDS_SIZE + Fragment::SIZE + Flags::SIZE + CODEC_SIZE],
+            )?,
+            _ => PacketType::Data.byte(),
+        };
+        let interleave_depth = match version {
+            FrameVersion::V10 | FrameVersion::V11 | FrameVersion::V12 => {
+                fields_bytes[EXTENSIONS_LEN_OFFSET + EXTENSIONS_LEN_SIZE]
+            }
+            _ => 0,
+        };
+        if interleave_depth > MAX_INTERLEAVE_DEPTH {
+            return Err(ProtocolError::InterleaveTooDeep {
+                depth: interleave_depth,
+                max: MAX_INTERLEAVE_DEPTH,
+            });
+        }
+
+        let mut positions = corrected_positions(lead_status, 0);
+        positions.extend(corrected_positions(&fields.status, offset * 8));
+        let mut size = Self::size(version);
+        let scramble_seed = match seed_block(version, flags) {
+            0 => None,
+            block => {
+                if encoded.len() < size + block {
+                    return Err(ProtocolError::TooShort {
+                        needed: size + block,
+                        got: encoded.len(),
+                    });
+                }
+                let decoded = unprotect(&encoded[size..], SEED_SIZE)?;
+                if decoded.status.is_uncorrectable() {
+                    return Err(ProtocolError::CorruptedHeader);
+                }
+                positions.extend(corrected_positions(&decoded.status, size * 8));
+                size += block;
+                let bytes = decoded.data.into_inner();
+                Some(endianness.u32_from(bytes[..SEED_SIZE].try_into().expect("a seed is 4 bytes")))
+            }
+        };
+        let extensions = match version {
+            FrameVersion::V9 | FrameVersion::V10 | FrameVersion::V11 | FrameVersion::V12 => {
+                let len = Self::extensions_len(&fields_bytes, endianness);
+                let block = extensions_block(len);
+                if encoded.len() < size + block {
+                    return Err(ProtocolError::TooShort {
+                        needed: size + block,
+                        got: encoded.len(),
+                    });
+                }
+                let extensions = match len {
+                    0 => HeaderExtensions::new(),
+                    len => {
+                        let decoded = unprotect(&encoded[size..], len)?;
+                        if decoded.status.is_uncorrectable() {
+                            return Err(ProtocolError::CorruptedHeader);
+                        }
+                        positions.extend(corrected_positions(&decoded.status, size * 8));
+                        HeaderExtensions::from_bytes(&decoded.data.into_inner(), endianness)?
+                    }
+                };
+                size += block;
+                extensions
+            }
+            _ => HeaderExtensions::new(),
+        };
+
+        Ok(DecodedHeader {
+            header: Self {
+                version,
+                content_type: ContentType::from(fields_bytes[0]),
+                length: Length::from_bytes(&fields_bytes[1..FIELDS_SIZE], endianness)?,
+                fragment,
+                flags,
+                codec,
+                padding,
+                packet_type,
+                extensions,
+                interleave_depth,
+                scramble_seed,
+            },
+            status: DecodeStatus::corrected_many(positions),
+            size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::ExtType;
+
+    fn header(version: FrameVersion) -> FrameHeader {
+        FrameHeader {
+            version,
+            content_type: ContentType::Text,
+            length: Length {
+                data_length: 14,
+                bits_length: 107,
+            },
+            fragment: Fragment {
+                message_id: 0xdead,
+                index: 2,
+                count: 3,
+            },
+            flags: Flags(Flags::CRC32.0 | Flags::SCRAMBLED.0),
+            codec: CodecId::Golay24,
+            padding: 5,
+            packet_type: PacketType::Error { code: 1 }.byte(),
+            extensions: extensions(),
+            interleave_depth: 8,
+            scramble_seed: Some(0x1234_5678),
+        }
+    }
+
+    fn extensions() -> HeaderExtensions {
+        let mut extensions = HeaderExtensions::new();
+        extensions.insert(ExtType::Filename, "frame.bin").unwrap();
+        extensions
+            .insert(ExtType::Unknown(0x42), [1, 2, 3])
+            .unwrap();
+        extensions
+    }
+
+    /// Bit ranges of the codewords making up a header of `version`.
+    fn codewords(version: FrameVersion) -> Vec<std::ops::Range<usize>> {
+        match version {
+            FrameVersion::V3 => {
+                let header = 0..protected_bits(PLAIN_HEADER_SIZE);
+                vec![header]
+            }
+            _ => {
+                let fields = protected_size(LEAD_SIZE) * 8;
+                let mut codewords = vec![
+                    0..protected_bits(LEAD_SIZE),
+                    fields..fields + protected_bits(fields_size(version)),
+                ];
+                let mut extensions = FrameHeader::size(version) * 8;
+                if version >= FrameVersion::V11 {
+                    codewords.push(extensions..extensions + protected_bits(SEED_SIZE));
+                    extensions += protected_size(SEED_SIZE) * 8;
+                }
+                if version >= FrameVersion::V9 {
+                    let bits = protected_bits(self::extensions().encoded_len());
+                    codewords.push(extensions..extensions + bits);
+                }
+                codewords
+            }
+        }
+    }
+
+    fn flip(bytes: &mut [u8], bit: usize) {
+        bytes[bit / 8] ^= 0x80 >> (bit % 8);
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(FrameHeader::size(FrameVersion::V1), 20);
+        assert_eq!(FrameHeader::size(FrameVersion::V2), 20);
+        assert_eq!(FrameHeader::size(FrameVersion::V3), 22);
+        assert_eq!(FrameHeader::size(FrameVersion::V4), 24);
+        assert_eq!(FrameHeader::size(FrameVersion::V5), 36);
+        assert_eq!(FrameHeader::size(FrameVersion::V6), 37);
+        assert_eq!(FrameHeader::size(FrameVersion::V7), 39);
+        assert_eq!(FrameHeader::size(FrameVersion::V8), 40);
+        assert_eq!(FrameHeader::size(FrameVersion::V9), 42);
+        assert_eq!(FrameHeader::size(FrameVersion::V10), 43);
+        assert_eq!(FrameHeader::size(FrameVersion::V11), 43);
+        assert_eq!(FrameHeader::size(FrameVersion::V12), 44);
+        for version in FrameVersion::ALL {
+            let extensions = match version >= FrameVersion::V9 {
+                true => FrameHeader::extensions_size(&extensions()),
+                false => 0,
+            };
+            // the seed in a 39 bit codeword
+            let seed = match version >= FrameVersion::V11 {
+                true => 5,
+                false => 0,
+            };
+            assert_eq!(
+                header(version).encode(PROTOCOL_NAME).len(),
+                FrameHeader::size(version) + seed + extensions
+            );
+        }
+        // entries of 3 + 9 and 3 + 3 bytes in a 153 bit codeword
+        assert_eq!(FrameHeader::extensions_size(&extensions()), 20);
+        assert_eq!(FrameHeader::extensions_size(&HeaderExtensions::new()), 0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for version in FrameVersion::ALL {
+            let encoded = header(version).encode(PROTOCOL_NAME);
+            let decoded = FrameHeader::decode(&encoded).unwrap();
+            // whatever the version has no room for comes back as the default
+            let full = header(version);
+            let expected = FrameHeader {
+                content_type: match version >= FrameVersion::V4 {
+                    true => full.content_type,
+                    false => ContentType::Binary,
+                },
+                fragment: match version >= FrameVersion::V5 {
+                    true => full.fragment,
+                    false => Fragment::default(),
+                },
+                flags: match version {
+                    version if version >= FrameVersion::V11 => full.flags,
+                    version if version >= FrameVersion::V6 => Flags::CRC32,
+                    _ => Flags::empty(),
+                },
+                codec: match version >= FrameVersion::V7 {
+                    true => full.codec,
+                    false => CodecId::Hamming,
+                },
+                padding: match version >= FrameVersion::V7 {
+                    true => full.padding,
+                    false => 0,
+                },
+                packet_type: match version >= FrameVersion::V8 {
+                    true => full.packet_type,
+                    false => 0,
+                },
+                extensions: match version >= FrameVersion::V9 {
+                    true => full.extensions.clone(),
+                    false => HeaderExtensions::new(),
+                },
+                interleave_depth: match version >= FrameVersion::V10 {
+                    true => full.interleave_depth,
+                    false => 0,
+                },
+                scramble_seed: full.scramble_seed.filter(|_| version >= FrameVersion::V11),
+                ..full
+            };
+            assert_eq!(decoded.header, expected);
+            assert!(decoded.status.is_clean());
+            assert_eq!(decoded.size, encoded.len());
+        }
+    }
+
+    #[test]
+    fn test_single_errors_corrected() {
+        for version in [
+            FrameVersion::V3,
+            FrameVersion::V4,
+            FrameVersion::V5,
+            FrameVersion::V6,
+            FrameVersion::V7,
+            FrameVersion::V8,
+            FrameVersion::V9,
+            FrameVersion::V10,
+            FrameVersion::V11,
+            FrameVersion::V12,
+        ] {
+            let encoded = header(version).encode(PROTOCOL_NAME);
+            for bit in codewords(version).into_iter().flatten() {
+                let mut corrupted = encoded.clone();
+                flip(&mut corrupted, bit);
+                let decoded = FrameHeader::decode(&corrupted).unwrap();
+                assert_eq!(decoded.header.version, version, "bit {bit}");
+                assert_eq!(decoded.header.length, header(version).length);
+                assert_eq!(
+                    decoded.status,
+                    DecodeStatus::corrected_many(vec![bit]),
+                    "version {version}, bit {bit}"
+                );
+            }
+
+            // the padding after each codeword doesn't matter
+            let mut padded = encoded.clone();
+            for bit in codewords(version).iter().map(|codeword| codeword.end) {
+                for bit in bit..bit.next_multiple_of(8) {
+                    flip(&mut padded, bit);
+                }
+            }
+            assert!(FrameHeader::decode(&padded).unwrap().status.is_clean());
+        }
+    }
+
+    #[test]
+    fn test_double_errors_detected() {
+        // every kind of codeword there is, in version 3's layout and the
+        // current one's
+        for version in [FrameVersion::V3, FrameVersion::CURRENT] {
+            let encoded = header(version).encode(PROTOCOL_NAME);
+            for codeword in codewords(version) {
+                for first in codeword.clone() {
+                    for second in first + 1..codeword.end {
+                        let mut corrupted = encoded.clone();
+                        flip(&mut corrupted, first);
+                        flip(&mut corrupted, second);
+                        assert!(
+                            matches!(
+                                FrameHeader::decode(&corrupted),
+                                Err(ProtocolError::CorruptedHeader)
+                            ),
+                            "version {version}, bits {first} and {second}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects() {
+        let error = |bytes: &[u8]| FrameHeader::decode(bytes).err().unwrap();
+
+        // a lead for a version that doesn't exist yet
+        let mut future = header(FrameVersion::CURRENT).encode(PROTOCOL_NAME);
+        future[..protected_size(LEAD_SIZE)].copy_from_slice(&protect(b"GUS\x0d".to_vec()));
+        assert!(matches!(
+            error(&future),
+            ProtocolError::UnsupportedVersion(13)
+        ));
+        assert_eq!(
+            error(&future).to_string(),
+            "Unsupported version 13, supported versions are 1 to 12"
+        );
+        assert!(matches!(
+            FrameVersion::try_from(0),
+            Err(ProtocolError::UnsupportedVersion(0))
+        ));
+
+        let current = header(FrameVersion::CURRENT);
+        assert!(matches!(
+            error(&current.encode(b"GUT")),
+            ProtocolError::BadMagic(magic) if &magic == b"GUT"
+        ));
+        assert!(matches!(
+            error(&current.encode(PROTOCOL_NAME)[..39]),
+            ProtocolError::TooShort {
+                needed: 39,
+                got: 34
+            }
+        ));
+
+        let out_of_range = FrameHeader {
+            fragment: Fragment {
+                message_id: 1,
+                index: 3,
+                count: 3,
+            },
+            ..header(FrameVersion::V5)
+        };
+        assert!(matches!(
+            error(&out_of_range.encode(PROTOCOL_NAME)),
+            ProtocolError::FragmentOutOfRange {
+                message_id: 1,
+                index: 3,
+                count: 3
+            }
+        ));
+
+        // scrambled, with nowhere to say what with
+        let mut fields = header(FrameVersion::V10).fields();
+        fields[FIELDS_SIZE + Fragment::SIZE] |= Flags::SCRAMBLED.bits();
+        let mut scrambled = protect(b"GUS\x0a".to_vec());
+        scrambled.extend(protect(fields));
+        assert!(matches!(
+            error(&scrambled),
+            ProtocolError::UnsupportedFlags(0x08)
+        ));
+
+        let unknown_flags = FrameHeader {
+            flags: Flags(0xc1),
+            ..header(FrameVersion::V6)
+        };
+        assert!(matches!(
+            error(&unknown_flags.encode(PROTOCOL_NAME)),
+            ProtocolError::UnsupportedFlags(0xc0)
+        ));
+        // compressed data can't be read without the feature to inflate it
+        #[cfg(not(feature = "compress"))]
+        assert!(matches!(
+            Flags::from_bits(0x05),
+            Err(ProtocolError::UnsupportedFlags(0x04))
+        ));
+
+        let current = header(FrameVersion::V7);
+        let mut fields = current.fields();
+        fields[fields_size(FrameVersion::V7) - 2] = 9;
+        let mut unknown_codec = protect(b"GUS\x07".to_vec());
+        unknown_codec.extend(protect(fields));
+        assert!(matches!(
+            error(&unknown_codec),
+            ProtocolError::UnknownCodec(9)
+        ));
+
+        // a whole block of padding, or any for a codec that doesn't pad
+        let too_much_padding = FrameHeader {
+            padding: 12,
+            ..header(FrameVersion::V7)
+        };
+        assert!(matches!(
+            error(&too_much_padding.encode(PROTOCOL_NAME)),
+            ProtocolError::BadPadding {
+                padding: 12,
+                codec: CodecId::Golay24,
+                max: 11
+            }
+        ));
+        let hamming_padding = FrameHeader {
+            codec: CodecId::Hamming,
+            padding: 1,
+            ..header(FrameVersion::V9)
+        };
+        assert!(matches!(
+            error(&hamming_padding.encode(PROTOCOL_NAME)),
+            ProtocolError::BadPadding { max: 0, .. }
+        ));
+
+        let too_deep = FrameHeader {
+            interleave_depth: MAX_INTERLEAVE_DEPTH + 1,
+            ..header(FrameVersion::V10)
+        };
+        assert!(matches!(
+            error(&too_deep.encode(PROTOCOL_NAME)),
+            ProtocolError::InterleaveTooDeep {
+                depth: 65,
+                max: MAX_INTERLEAVE_DEPTH
+            }
+        ));
+
+        let current = header(FrameVersion::V8);
+        let mut fields = current.fields();
+        fields[fields_size(FrameVersion::V8) - 1] = 0x7f;
+        let mut unknown_packet_type = protect(b"GUS\x08".to_vec());
+        unknown_packet_type.extend(protect(fields));
+        assert!(matches!(
+            error(&unknown_packet_type),
+            ProtocolError::UnknownPacketType(0x7f)
+        ));
+        assert!(matches!(
+            error(&header(FrameVersion::V2).encode(PROTOCOL_NAME)[..19]),
+            ProtocolError::TooShort {
+                needed: 20,
+                got: 19
+            }
+        ));
+    }
+
+    #[test]
+    fn test_checksum() {
+        // a field changed and protected again, past what the codewords see
+        let current = header(FrameVersion::CURRENT);
+        let encoded = current.encode(PROTOCOL_NAME);
+        let mut fields = current.fields();
+        let expected = header_checksum(b"GUS\x0c", &fields);
+        fields[FIELDS_SIZE] ^= 0x01;
+        fields.push(expected);
+        let mut forged = protect(b"GUS\x0c".to_vec());
+        forged.extend(protect(fields.clone()));
+        forged.extend(&encoded[FrameHeader::size(FrameVersion::CURRENT)..]);
+        let actual = header_checksum(b"GUS\x0c", &fields[..fields.len() - 1]);
+        assert!(matches!(
+            FrameHeader::decode(&forged),
+            Err(ProtocolError::HeaderChecksum { expected: e, actual: a })
+                if e == expected && a == actual
+        ));
+
+        // a byte wrecked anywhere in the fixed header either comes back as it
+        // was or not at all, never as some other header
+        for offset in 0..FrameHeader::size(FrameVersion::CURRENT) {
+            for error in 1..=u8::MAX {
+                let mut corrupted = encoded.clone();
+                corrupted[offset] ^= error;
+                if let Ok(decoded) = FrameHeader::decode(&corrupted) {
+                    assert_eq!(decoded.header, current, "byte {offset} ^ {error:#04x}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extensions() {
+        // the extensions right after the fixed header, with no seed between
+        let current = FrameHeader {
+            scramble_seed: None,
+            ..header(FrameVersion::CURRENT)
+        };
+        let encoded = current.encode(PROTOCOL_NAME);
+        let fixed = FrameHeader::size(FrameVersion::CURRENT);
+
+        // the hint only takes in the extensions once it has the fixed header
+        assert_eq!(FrameHeader::size_hint(&encoded[..20]).unwrap(), fixed);
+        assert_eq!(
+            FrameHeader::size_hint(&encoded[..fixed]).unwrap(),
+            encoded.len()
+        );
+        assert_eq!(FrameHeader::size_hint(&encoded).unwrap(), encoded.len());
+
+        // the extensions cut short
+        assert!(matches!(
+            FrameHeader::decode(&encoded[..encoded.len() - 1]),
+            Err(ProtocolError::TooShort { needed, got })
+                if needed == encoded.len() && got == needed - 1
+        ));
+
+        // a length claiming more extensions than there are
+        let mut fields = current.fields();
+        fields[EXTENSIONS_LEN_OFFSET..EXTENSIONS_LEN_OFFSET + EXTENSIONS_LEN_SIZE]
+            .copy_from_slice(&500u16.to_le_bytes());
+        fields.push(header_checksum(b"GUS\x0c", &fields));
+        let mut long = protect(b"GUS\x0c".to_vec());
+        long.extend(protect(fields));
+        long.extend(&encoded[fixed..]);
+        assert!(matches!(
+            FrameHeader::decode(&long),
+            Err(ProtocolError::TooShort { needed, .. }) if needed == fixed + protected_size(500)
+        ));
+
+        // entries that don't add up to the length
+        let mut bytes = current.extensions.to_bytes(Endianness::Little);
+        bytes[1] += 1;
+        let mut malformed = encoded[..fixed].to_vec();
+        malformed.extend(protect(bytes));
+        assert!(matches!(
+            FrameHeader::decode(&malformed),
+            Err(ProtocolError::MalformedExtensions { offset: 13 })
+        ));
+
+        // none at all leaves the frame as it was but for the length
+        let bare = FrameHeader {
+            flags: Flags::CRC32,
+            extensions: HeaderExtensions::new(),
+            ..current
+        };
+        let encoded = bare.encode(PROTOCOL_NAME);
+        assert_eq!(encoded.len(), fixed);
+        assert_eq!(FrameHeader::decode(&encoded).unwrap().header, bare);
+    }
+
+    #[test]
+    fn test_field_errors() {
+        assert!(matches!(
+            Length::from_le_bytes(&[0; 15]),
+            Err(ProtocolError::TooShort {
+                needed: 16,
+                got: 15
+            })
+        ));
+        assert!(matches!(
+            Fragment::from_le_bytes(&[0; 11]),
+            Err(ProtocolError::TooShort {
+                needed: 12,
+                got: 11
+            })
+        ));
+
+        #[cfg(target_pointer_width = "32")]
+        {
+            let mut bytes = [0; Length::SIZE];
+            bytes[4] = 1;
+            assert!(matches!(
+                Length::from_le_bytes(&bytes),
+                Err(ProtocolError::LengthOverflow(0x1_0000_0000))
+            ));
+        }
+    }
+}
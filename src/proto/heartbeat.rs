@@ -0,0 +1,195 @@
+//! Telling a quiet sender from a dead one: a sender with nothing to send
+//! sends a heartbeat every so often, and the receiver takes a link that's
+//! gone without a frame of any kind for long enough as dead.
+//!
+//! Neither end reads the clock itself, they're told the time, so whatever
+//! drives them decides how often to look.
+
+use std::time::{Duration, Instant};
+
+use crate::proto::{GUSProtocol, PacketType};
+
+/// The sending end, keeping track of when it last sent anything.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    interval: Duration,
+    started: Instant,
+    last_sent: Instant,
+    frames_sent: u64,
+}
+
+impl Heartbeat {
+    /// A sender started at `now`, sending a heartbeat once it's been
+    /// `interval` without sending anything else.
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            started: now,
+            last_sent: now,
+            frames_sent: 0,
+        }
+    }
+
+    /// Counts a frame sent at `now`, putting the next heartbeat off.
+    pub fn sent(&mut self, now: Instant) {
+        self.last_sent = now;
+        self.frames_sent += 1;
+    }
+
+    /// How long until a heartbeat is due, nothing if it already is.
+    pub fn until_due(&self, now: Instant) -> Duration {
+        (self.last_sent + self.interval).saturating_duration_since(now)
+    }
+
+    /// The heartbeat to send at `now`, if one's due, counted as sent.
+    pub fn due(&mut self, now: Instant) -> Option<GUSProtocol> {
+        if !self.until_due(now).is_zero() {
+            return None;
+        }
+        let uptime_ms = now.saturating_duration_since(self.started).as_millis() as u64;
+        let heartbeat = GUSProtocol::heartbeat(uptime_ms, self.frames_sent);
+        self.sent(now);
+        Some(heartbeat)
+    }
+}
+
+/// What the last heartbeat said about its sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SenderStatus {
+    pub uptime: Duration,
+    pub frames_sent: u64,
+}
+
+/// The receiving end, keeping track of when it last heard from the sender.
+#[derive(Debug, Clone)]
+pub struct IdleMonitor {
+    timeout: Duration,
+    last_seen: Instant,
+    /// Whether the sender's been reported idle since it was last seen, so it
+    /// only is once.
+    reported: bool,
+    last_heartbeat: Option<SenderStatus>,
+}
+
+impl IdleMonitor {
+    /// A receiver started at `now`, taking the sender as gone once nothing's
+    /// come from it for `timeout`.
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            timeout,
+            last_seen: now,
+            reported: false,
+            last_heartbeat: None,
+        }
+    }
+
+    /// Takes in a frame received at `now`, of any type: they all say the
+    /// sender's still there.
+    pub fn observe(&mut self, packet: &GUSProtocol, now: Instant) {
+        self.last_seen = now;
+        self.reported = false;
+        if let PacketType::Heartbeat {
+            uptime_ms,
+            frames_sent,
+        } = packet.packet_type
+        {
+            self.last_heartbeat = Some(SenderStatus {
+                uptime: Duration::from_millis(uptime_ms),
+                frames_sent,
+            });
+        }
+    }
+
+    /// How long the sender's been quiet, if it's past the timeout at `now`.
+    /// Only says so once for each time it goes quiet, until a frame comes
+    /// in again.
+    pub fn check(&mut self, now: Instant) -> Option<Duration> {
+        let idle = now.saturating_duration_since(self.last_seen);
+        if self.reported || idle < self.timeout {
+            return None;
+        }
+        self.reported = true;
+        Some(idle)
+    }
+
+    pub fn last_heartbeat(&self) -> Option<SenderStatus> {
+        self.last_heartbeat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::transport::{ChannelTransport, Transport},
+    };
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    /// Sends `packet` from `near` and decodes it off `far`.
+    fn through(
+        near: &mut ChannelTransport,
+        far: &mut ChannelTransport,
+        packet: GUSProtocol,
+    ) -> GUSProtocol {
+        near.send(&packet.encode().unwrap()).unwrap();
+        GUSProtocol::decode(far.recv().unwrap()).unwrap().0
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        let start = Instant::now();
+        let mut heartbeat = Heartbeat::new(5 * SECOND, start);
+        assert!(heartbeat.due(start + 4 * SECOND).is_none());
+        heartbeat.sent(start + 4 * SECOND);
+        assert_eq!(heartbeat.until_due(start + 6 * SECOND), 3 * SECOND);
+        assert!(heartbeat.due(start + 8 * SECOND).is_none());
+
+        let beat = heartbeat.due(start + 9 * SECOND).unwrap();
+        assert_eq!(
+            beat.packet_type,
+            PacketType::Heartbeat {
+                uptime_ms: 9000,
+                frames_sent: 1,
+            }
+        );
+        // and counts as sent itself
+        assert!(heartbeat.due(start + 10 * SECOND).is_none());
+        assert!(matches!(
+            heartbeat.due(start + 14 * SECOND).unwrap().packet_type,
+            PacketType::Heartbeat { frames_sent: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let (mut near, mut far) = ChannelTransport::pair();
+        let start = Instant::now();
+        let mut monitor = IdleMonitor::new(10 * SECOND, start);
+        assert_eq!(monitor.check(start + 9 * SECOND), None);
+        assert_eq!(monitor.check(start + 10 * SECOND), Some(10 * SECOND));
+        // once is enough
+        assert_eq!(monitor.check(start + 30 * SECOND), None);
+
+        // a heartbeat puts it off, and says how the sender's doing
+        let mut heartbeat = Heartbeat::new(5 * SECOND, start);
+        let beat = heartbeat.due(start + 31 * SECOND).unwrap();
+        monitor.observe(&through(&mut near, &mut far, beat), start + 31 * SECOND);
+        assert_eq!(monitor.check(start + 40 * SECOND), None);
+        assert_eq!(
+            monitor.last_heartbeat(),
+            Some(SenderStatus {
+                uptime: 31 * SECOND,
+                frames_sent: 0,
+            })
+        );
+
+        // and so does data
+        let data = GUSProtocol::data(BitVec::random(40, &mut rand::rng()));
+        monitor.observe(&through(&mut near, &mut far, data), start + 40 * SECOND);
+        assert_eq!(monitor.check(start + 49 * SECOND), None);
+        assert_eq!(monitor.check(start + 50 * SECOND), Some(10 * SECOND));
+        assert!(monitor.last_heartbeat().is_some());
+    }
+}
@@ -0,0 +1,252 @@
+//! Packets as JSON, or anything else serde writes, for tools and scripts to
+//! read.
+
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+
+use crate::proto::{
+    CodecId, ContentType, CorrectionInfo, DecodeReport, ExtType, Flags, Fragment, FrameVersion,
+    GUSProtocol, IntegrityStatus, Latency, PacketType,
+};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const BASE64_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// How the bytes of a packet's data and extensions are written out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PayloadEncoding {
+    /// Lowercase, two digits a byte.
+    #[default]
+    Hex,
+    /// The standard alphabet, padded.
+    Base64,
+}
+
+impl PayloadEncoding {
+    fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            PayloadEncoding::Hex => bytes
+                .iter()
+                .flat_map(|byte| [byte >> 4, byte & 0x0f])
+                .map(|digit| HEX_DIGITS[digit as usize] as char)
+                .collect(),
+            PayloadEncoding::Base64 => {
+                let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+                for chunk in bytes.chunks(3) {
+                    let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+                        group | (byte as u32) << (16 - 8 * i)
+                    });
+                    for i in 0..4 {
+                        match i <= chunk.len() {
+                            true => {
+                                let digit = group >> (18 - 6 * i) & 0x3f;
+                                encoded.push(BASE64_DIGITS[digit as usize] as char);
+                            }
+                            false => encoded.push('='),
+                        }
+                    }
+                }
+                encoded
+            }
+        }
+    }
+}
+
+/// A packet to serialize with its bytes in `encoding`, see
+/// [`GUSProtocol::with_payload_encoding`]. Serializing the packet itself
+/// writes them in hex.
+#[derive(Debug, Clone, Copy)]
+pub struct PacketWithEncoding<'a> {
+    packet: &'a GUSProtocol,
+    encoding: PayloadEncoding,
+}
+
+/// Bytes, with how many of their bits count.
+#[derive(Serialize)]
+struct Bits {
+    bits: usize,
+    bytes: String,
+}
+
+#[derive(Serialize)]
+struct Extension<'a> {
+    #[serde(rename = "type")]
+    ext_type: ExtType,
+    value: &'a str,
+}
+
+impl Serialize for PacketWithEncoding<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let packet = self.packet;
+        let values: Vec<(_, String)> = packet
+            .extensions
+            .iter()
+            .map(|(ext_type, value)| (ext_type, self.encoding.encode(value)))
+            .collect();
+        let extensions: Vec<Extension> = values
+            .iter()
+            .map(|(ext_type, value)| Extension {
+                ext_type: *ext_type,
+                value,
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("GUSProtocol", 11)?;
+        state.serialize_field(
+            "protocol_name",
+            &String::from_utf8_lossy(&packet.protocol_name),
+        )?;
+        state.serialize_field("version", &packet.version)?;
+        state.serialize_field("content_type", &packet.content_type)?;
+        state.serialize_field("fragment", &packet.fragment)?;
+        state.serialize_field("flags", &packet.flags)?;
+        state.serialize_field("codec", &packet.codec)?;
+        state.serialize_field("packet_type", &packet.packet_type)?;
+        state.serialize_field("extensions", &extensions)?;
+        state.serialize_field("interleave_depth", &packet.interleave_depth)?;
+        state.serialize_field("scramble_seed", &packet.scramble_seed)?;
+        state.serialize_field(
+            "data",
+            &Bits {
+                bits: packet.data.len(),
+                bytes: self.encoding.encode(packet.data.as_bitslice().as_bytes()),
+            },
+        )?;
+        state.end()
+    }
+}
+
+impl Serialize for GUSProtocol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.with_payload_encoding(PayloadEncoding::Hex)
+            .serialize(serializer)
+    }
+}
+
+/// What [`GUSProtocol::to_json_summary`] writes.
+#[derive(Serialize)]
+struct Summary<'a> {
+    version: FrameVersion,
+    packet_type: &'a PacketType,
+    content_type: ContentType,
+    fragment: Fragment,
+    flags: Flags,
+    codec: CodecId,
+    data_bits: usize,
+    frame_bytes: usize,
+    extensions: usize,
+    /// `None` for a packet that wasn't decoded, whose data hasn't been
+    /// checked against anything yet.
+    integrity: Option<IntegrityStatus>,
+    corrected: Option<&'a CorrectionInfo>,
+    latency: Option<Latency>,
+}
+
+impl GUSProtocol {
+    /// The packet, to serialize with its bytes written out in `encoding`.
+    pub fn with_payload_encoding(&self, encoding: PayloadEncoding) -> PacketWithEncoding<'_> {
+        PacketWithEncoding {
+            packet: self,
+            encoding,
+        }
+    }
+
+    /// A line of JSON saying what the packet is, without its data: what
+    /// version, codec and packet type it goes out as, how long it is, and,
+    /// given the `report` it was decoded with, whether its checksum was
+    /// checked and what was corrected.
+    pub fn to_json_summary(&self, report: Option<&DecodeReport>) -> String {
+        let summary = Summary {
+            version: report.map_or(self.version, |report| report.version),
+            packet_type: &self.packet_type,
+            content_type: self.content_type,
+            fragment: self.fragment,
+            flags: self.flags,
+            codec: self.codec,
+            data_bits: self.data.len(),
+            frame_bytes: report.map_or_else(|| self.encoded_len(), |report| report.frame_bytes),
+            extensions: self.extensions.len(),
+            integrity: report.map(|report| report.integrity),
+            corrected: report.and_then(|report| report.corrected.as_ref()),
+            latency: report.and_then(|report| report.latency),
+        };
+        serde_json::to_string(&summary).expect("a summary always serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encoding::bitvec::BitVec, proto::NackReason};
+
+    fn packet() -> GUSProtocol {
+        GUSProtocol::builder()
+            .data(BitVec::from_whole_bytes(b"hi!".to_vec()))
+            .content_type(ContentType::Text)
+            .fragment(Fragment {
+                message_id: 7,
+                index: 0,
+                count: 1,
+            })
+            .extension(ExtType::Filename, "a.txt")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_encodings() {
+        for (bytes, base64) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xff\x00\x10", "/wAQ"),
+        ] {
+            assert_eq!(PayloadEncoding::Base64.encode(bytes), base64);
+        }
+        assert_eq!(PayloadEncoding::Hex.encode(b"\x00\xab\x1f"), "00ab1f");
+    }
+
+    #[test]
+    fn test_packet() {
+        let packet = packet();
+        assert_eq!(
+            serde_json::to_string(&packet).unwrap(),
+            r#"{"protocol_name":"GUS","version":12,"content_type":"Text","fragment":{"message_id":7,"index":0,"count":1},"flags":1,"codec":"Hamming","packet_type":"Data","extensions":[{"type":"Filename","value":"612e747874"}],"interleave_depth":0,"scramble_seed":null,"data":{"bits":24,"bytes":"686921"}}"#
+        );
+        let json =
+            serde_json::to_value(packet.with_payload_encoding(PayloadEncoding::Base64)).unwrap();
+        assert_eq!(json["data"]["bytes"], "aGkh");
+        assert_eq!(json["extensions"][0]["value"], "YS50eHQ=");
+
+        let nack = GUSProtocol::nack(3, NackReason::Integrity);
+        assert_eq!(
+            serde_json::to_value(&nack).unwrap()["packet_type"].to_string(),
+            r#"{"Nack":{"message_id":3,"reason":"Integrity"}}"#
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let packet = packet();
+        let frame_bytes = packet.encoded_len();
+        assert_eq!(
+            packet.to_json_summary(None),
+            format!(
+                r#"{{"version":12,"packet_type":"Data","content_type":"Text","fragment":{{"message_id":7,"index":0,"count":1}},"flags":1,"codec":"Hamming","data_bits":24,"frame_bytes":{frame_bytes},"extensions":1,"integrity":null,"corrected":null,"latency":null}}"#
+            )
+        );
+
+        let mut encoded = packet.encode().unwrap();
+        let last = encoded.len() - 1;
+        encoded[last] ^= 0x80;
+        let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+        let summary: serde_json::Value =
+            serde_json::from_str(&decoded.to_json_summary(Some(&report))).unwrap();
+        assert_eq!(summary["integrity"], "Verified");
+        assert_eq!(summary["frame_bytes"], frame_bytes);
+        assert_eq!(summary["corrected"]["payload"].as_array().unwrap().len(), 1);
+        assert!(summary.get("data").is_none());
+    }
+}
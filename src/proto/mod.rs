@@ -1,137 +1,386 @@
-use rand::Rng;
-
 use crate::{
-    encoding::bitvec::BitVec,
-    encoding::hamming::{Hamming, HammingCode, HammingError},
+    encoding::bitvec::{BitSlice, BitVec},
+    encoding::channel::Channel,
+    encoding::hamming::{DecodeStatus, Hamming, HammingError},
+    encoding::interleave::Interleaver,
+    encoding::source::BitSource,
 };
 
-const USIZE_SIZE: usize = std::mem::size_of::<usize>();
-
-/// Length structure to hold the length of data and bits
-///
-/// Should take up either 8 or 16 bytes depending on the architecture (32 or 64 bits)
-#[derive(Debug, Clone)]
-pub struct Length {
-    pub data_length: usize,
-    pub bits_length: usize,
-}
-
-impl Length {
-    pub fn to_le_bytes(&self) -> [u8; USIZE_SIZE * 2] {
-        let mut bytes = [0u8; USIZE_SIZE * 2];
-        bytes[0..USIZE_SIZE].copy_from_slice(&self.data_length.to_le_bytes());
-        bytes[USIZE_SIZE..USIZE_SIZE * 2].copy_from_slice(&self.bits_length.to_le_bytes());
-        bytes
-    }
-
-    pub fn from_le_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
-        let data_length = usize::from_le_bytes(bytes[0..USIZE_SIZE].try_into()?);
-        let bits_length = usize::from_le_bytes(bytes[USIZE_SIZE..USIZE_SIZE * 2].try_into()?);
+mod arq;
+mod builder;
+mod cobs;
+mod codec;
+mod error;
+mod extensions;
+mod frame_ref;
+#[cfg(feature = "tokio")]
+mod framed;
+mod handshake;
+mod header;
+mod heartbeat;
+#[cfg(feature = "serde")]
+mod json;
+mod packet;
+mod payload;
+mod peek;
+mod read;
+mod reassembly;
+mod report;
+mod sequence;
+mod sync;
+pub mod transfer;
+pub mod transport;
+mod write;
 
-        Ok(Self {
-            data_length,
-            bits_length,
-        })
-    }
-}
+pub use arq::{ArqConfig, ReliableSender, UNKNOWN_MESSAGE, recv_reliable, send_reliable};
+pub use builder::GUSProtocolBuilder;
+pub use codec::CodecId;
+pub use error::ProtocolError;
+pub use extensions::{ExtType, HeaderExtensions, now_micros};
+pub use frame_ref::GusFrameRef;
+#[cfg(feature = "tokio")]
+pub use framed::GusCodec;
+pub use handshake::{Capabilities, SessionParams};
+pub use header::{
+    ContentType, Endianness, Flags, Fragment, FrameVersion, Length, MAX_INTERLEAVE_DEPTH,
+};
+use header::{FrameHeader, PROTOCOL_NAME};
+pub use heartbeat::{Heartbeat, IdleMonitor, SenderStatus};
+#[cfg(feature = "serde")]
+pub use json::{PacketWithEncoding, PayloadEncoding};
+pub use packet::{NackReason, PacketType};
+use payload::Payload;
+pub use peek::PeekedHeader;
+pub use reassembly::{Reassembler, Reassembly};
+pub use report::{CorrectionInfo, DecodeReport, IntegrityStatus, Latency};
+pub use sequence::{SequenceStats, SequenceTracker, Sequenced};
+pub use write::EncodeSummary;
 
 /// Great Unused Standard Protocol (imaginary ;P)
 #[derive(Debug, Clone)]
 pub struct GUSProtocol {
     pub protocol_name: Vec<u8>,
-    pub version: u8,
+    pub version: FrameVersion,
+    pub content_type: ContentType,
+    pub fragment: Fragment,
+    /// Only go out from version 6 on. A new packet has a checksum.
+    pub flags: Flags,
+    /// Only goes out from version 7 on, earlier versions are always sent
+    /// with [`CodecId::Hamming`].
+    pub codec: CodecId,
+    /// Only goes out from version 8 on, earlier versions only send data. The
+    /// data of a control packet is its payload as sent.
+    pub packet_type: PacketType,
+    /// Only go out from version 9 on.
+    pub extensions: HeaderExtensions,
+    /// Rows the payload's codeword is interleaved over on the wire, so a burst
+    /// of errors lands in different blocks of the codec; 0 or 1 for none. Only
+    /// goes out from version 10 on, and at most [`MAX_INTERLEAVE_DEPTH`].
+    pub interleave_depth: u8,
+    /// Where the scrambler starts from, if the payload is scrambled on its
+    /// way into the codec, so it has no long runs of the same bit on the
+    /// wire. Only goes out from version 11 on.
+    pub scramble_seed: Option<u32>,
     pub data: BitVec,
 }
 
 impl GUSProtocol {
-    const CURRENT_VERSION: u8 = 1;
-    const PROTOCOL_NAME: &[u8; 3] = b"GUS";
-
     pub fn new(data: BitVec) -> Result<Self, HammingError> {
-        let protocol_name = Self::PROTOCOL_NAME.to_vec();
-        let version = Self::CURRENT_VERSION;
+        let protocol_name = PROTOCOL_NAME.to_vec();
+        let version = FrameVersion::CURRENT;
 
         Ok(Self {
             data,
             protocol_name,
             version,
+            content_type: ContentType::default(),
+            fragment: Fragment::default(),
+            flags: Flags::CRC32,
+            codec: CodecId::default(),
+            packet_type: PacketType::Data,
+            extensions: HeaderExtensions::new(),
+            interleave_depth: 0,
+            scramble_seed: None,
         })
     }
 
-    pub fn encode(self) -> Result<Vec<u8>, HammingError> {
-        let mut encoded_data = Hamming.encode(&self.data)?;
+    /// A packet whose payload goes out protected by `codec` rather than plain
+    /// Hamming. The receiver reads which one it was off the header.
+    pub fn new_with_codec(data: BitVec, codec: CodecId) -> Result<Self, HammingError> {
+        Ok(Self {
+            codec,
+            ..Self::new(data)?
+        })
+    }
 
-        // give a 50% chance of corrupting a bit
-        let mut rng = rand::rng();
-        if rng.random_bool(0.5) {
-            let bit_to_corrupt = rng.random_range(0..encoded_data.len());
-            encoded_data
-                .toggle(bit_to_corrupt)
-                .map_err(|_| HammingError::UnexpectedOutOfBounds)?;
+    /// Splits `data` into packets of at most `max_fragment_bits` bits each, to
+    /// be put back together by a [`Reassembler`]. Data that fits in one comes
+    /// back as the single packet [`GUSProtocol::new`] makes of it, the rest
+    /// gets a random message id shared by all of its fragments.
+    ///
+    /// Fragments only go on the wire as such from version 5 on.
+    ///
+    /// # Panics
+    ///
+    /// If `max_fragment_bits` is 0, or the data takes more than `u32::MAX`
+    /// fragments.
+    pub fn fragment(data: BitVec, max_fragment_bits: usize) -> Vec<GUSProtocol> {
+        assert!(max_fragment_bits > 0, "fragments must hold at least a bit");
+        if data.len() <= max_fragment_bits {
+            return vec![Self::new(data).expect("new never fails")];
         }
 
-        let length = Length {
-            data_length: encoded_data.true_len(),
-            bits_length: encoded_data.len(),
+        let count = u32::try_from(data.len().div_ceil(max_fragment_bits))
+            .expect("too many fragments for one message");
+        let message_id = rand::random();
+        (0..count)
+            .map(|index| {
+                let start = index as usize * max_fragment_bits;
+                let end = (start + max_fragment_bits).min(data.len());
+                let bits = data
+                    .slice(start..end)
+                    .expect("fragments lie within the data");
+                Self {
+                    fragment: Fragment {
+                        message_id,
+                        index,
+                        count,
+                    },
+                    ..Self::new(bits).expect("new never fails")
+                }
+            })
+            .collect()
+    }
+
+    /// Wraps any [`BitSource`], e.g. the bytes of a string.
+    pub fn from_bits<S: BitSource + ?Sized>(data: &S) -> Result<Self, HammingError> {
+        Self::new(data.to_bitvec())
+    }
+
+    pub fn with_content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Bytes the packet takes up once encoded, i.e. where the frame after it
+    /// starts when several are sent back to back.
+    pub fn encoded_len(&self) -> usize {
+        let bits = self.wire_codec().encoded_bits(self.payload().bit_len());
+        self.header_size() + bits.div_ceil(8)
+    }
+
+    /// Bytes the header of this packet's frame takes up, extensions and all.
+    fn header_size(&self) -> usize {
+        let seed = match self.wire_seed() {
+            Some(_) => FrameHeader::seed_size(),
+            None => 0,
         };
+        match self.version >= FrameVersion::V9 {
+            true => {
+                FrameHeader::size(self.version)
+                    + seed
+                    + FrameHeader::extensions_size(&self.extensions)
+            }
+            false => FrameHeader::size(self.version),
+        }
+    }
+
+    /// Encodes the packet as is. Nothing is corrupted here, the same packet
+    /// always encodes to the same bytes; see [`GUSProtocol::encode_over`] for
+    /// that.
+    pub fn encode(self) -> Result<Vec<u8>, ProtocolError> {
+        let mut encoded = Vec::with_capacity(self.encoded_len());
+        self.encode_to(&mut encoded)?;
+        Ok(encoded)
+    }
 
-        let mut encoded = Vec::with_capacity(length.data_length + 4 + USIZE_SIZE * 2);
-        let length_bytes = length.to_le_bytes();
+    /// Encodes the packet as a frame of `version`, e.g. for a receiver that
+    /// doesn't know the current one yet. Whatever the older version has no
+    /// room for, like the content type, is left out.
+    pub fn encode_as(mut self, version: FrameVersion) -> Result<Vec<u8>, ProtocolError> {
+        self.version = version;
+        self.encode()
+    }
+
+    /// Encodes the packet, sending the codeword through `channel` before it's
+    /// framed. The header is never corrupted.
+    ///
+    /// The content type only makes it into frames of version 4 and up, the
+    /// fragment fields into those of version 5 and up, the flags, checksum and
+    /// all, into those of version 6 and up, the codec into those of version 7
+    /// and up, and the interleaving into those of version 10 and up.
+    pub fn encode_over(self, channel: &mut impl Channel) -> Result<Vec<u8>, ProtocolError> {
+        let payload = self.payload();
+        let mut encoded_data = BitVec::new();
+        match self.wire_codec() {
+            CodecId::Hamming => Hamming.encode_into(&payload, &mut encoded_data)?,
+            codec => encoded_data = codec.build().encode(&payload.to_bitvec())?,
+        }
+        if let Some(interleaver) = self.wire_interleaver()? {
+            encoded_data = interleaver.interleave(&encoded_data);
+        }
+        channel.transmit(&mut encoded_data);
+
+        let header = self.header(
+            &payload,
+            Length {
+                data_length: encoded_data.true_len(),
+                bits_length: encoded_data.len(),
+            },
+        );
 
-        encoded.extend(self.protocol_name);
-        encoded.push(self.version);
-        encoded.extend(length_bytes);
+        let mut encoded = header.encode(&self.protocol_name);
         encoded.extend(encoded_data.into_inner());
 
         Ok(encoded)
     }
 
-    pub fn decode(encoded_data: Vec<u8>) -> Result<(Self, bool), anyhow::Error> {
-        if encoded_data.len() < (4 + USIZE_SIZE * 2) {
-            return Err(anyhow::anyhow!("Invalid data length"));
+    /// The header of this packet's frame, for a codeword of `length` holding
+    /// `payload`.
+    fn header(&self, payload: &Payload, length: Length) -> FrameHeader {
+        let codec = self.wire_codec();
+        let padding = codec.padding(payload.bit_len());
+        let mut flags = self.wire_flags();
+        if !payload.is_compressed() {
+            flags.remove(Flags::COMPRESSED);
         }
+        FrameHeader {
+            version: self.version,
+            content_type: self.content_type,
+            fragment: self.fragment,
+            flags,
+            codec,
+            padding: u8::try_from(padding)
+                .expect("no registered codec pads more than a byte's worth"),
+            packet_type: match self.version >= FrameVersion::V8 {
+                true => self.packet_type.byte(),
+                false => PacketType::Data.byte(),
+            },
+            extensions: match self.version >= FrameVersion::V9 {
+                true => self.extensions.clone(),
+                false => HeaderExtensions::new(),
+            },
+            interleave_depth: match self.version >= FrameVersion::V10 {
+                true => self.interleave_depth,
+                false => 0,
+            },
+            scramble_seed: self.wire_seed(),
+            length,
+        }
+    }
 
-        let protocol_name = &encoded_data[0..3];
-        if protocol_name != Self::PROTOCOL_NAME {
-            return Err(anyhow::anyhow!("Invalid protocol name"));
+    /// The flags as they go out in this packet's version.
+    fn wire_flags(&self) -> Flags {
+        match self.version >= FrameVersion::V6 {
+            true => self.flags,
+            false => Flags::empty(),
         }
+    }
 
-        let version = encoded_data[3];
-        if version != Self::CURRENT_VERSION {
-            return Err(anyhow::anyhow!("Unsupported version"));
+    /// The codec as it goes out in this packet's version.
+    fn wire_codec(&self) -> CodecId {
+        match self.version >= FrameVersion::V7 {
+            true => self.codec,
+            false => CodecId::Hamming,
         }
+    }
+
+    /// The interleaver the codeword goes out through in this packet's
+    /// version, if any. Fails for a depth no receiver would take.
+    fn wire_interleaver(&self) -> Result<Option<Interleaver>, ProtocolError> {
+        if self.version < FrameVersion::V10 {
+            return Ok(None);
+        }
+        interleaver(self.interleave_depth)
+    }
+
+    /// The scrambler seed as it goes out in this packet's version.
+    fn wire_seed(&self) -> Option<u32> {
+        self.scramble_seed
+            .filter(|_| self.version >= FrameVersion::V11)
+    }
+
+    /// The bits that go into the codeword.
+    fn payload(&self) -> Payload<'_> {
+        let flags = self.wire_flags();
+        Payload::new(
+            &self.data,
+            flags.contains(Flags::CRC32),
+            flags.contains(Flags::COMPRESSED),
+        )
+        .scrambled(self.wire_seed())
+    }
 
-        let length_bytes = &encoded_data[4..(4 + USIZE_SIZE * 2)];
-        let length = Length::from_le_bytes(length_bytes)?;
+    /// Decodes a frame, correcting what the codes can, and reports what that
+    /// took.
+    pub fn decode(encoded_data: Vec<u8>) -> Result<(Self, DecodeReport), ProtocolError> {
+        let (frame, report) = Self::decode_ref(&encoded_data)?;
+        Ok((frame.into_owned(), report))
+    }
 
-        let data =
-            encoded_data[(4 + USIZE_SIZE * 2)..(4 + USIZE_SIZE * 2 + length.data_length)].to_vec();
-        if data.len() != length.data_length as usize {
-            return Err(anyhow::anyhow!("Data length mismatch"));
+    /// Decodes the payload `codeword` with `codec`, dropping the `padding` it
+    /// added, after undoing the `interleave_depth` it was sent with.
+    fn decode_payload(
+        codec: CodecId,
+        padding: u8,
+        interleave_depth: u8,
+        codeword: BitSlice,
+    ) -> Result<(BitVec, DecodeStatus), ProtocolError> {
+        if let Some(interleaver) = interleaver(interleave_depth)? {
+            let deinterleaved = interleaver.deinterleave(&codeword.to_bitvec());
+            let (data, status) =
+                Self::decode_payload(codec, padding, 0, deinterleaved.as_bitslice())?;
+            return Ok((data, interleaver.map_status(codeword.len(), status)));
         }
-        let data = BitVec::from_bytes(data, length.bits_length as usize);
 
-        // decode the hamming code
-        let decoded_data = Hamming
-            .decode(&data)
-            .map_err(|_| anyhow::anyhow!("Failed to decode Hamming code"))?;
+        if codec == CodecId::Hamming {
+            // straight off the borrowed codeword, only the data is copied
+            let mut data = BitVec::with_capacity(codeword.len());
+            let info = Hamming.decode_slice(codeword, &mut data)?;
+            if info.status.is_uncorrectable() {
+                return Err(HammingError::Uncorrectable {
+                    syndrome: Hamming.slice_syndrome(codeword)?,
+                }
+                .into());
+            }
+            return Ok((data, info.status));
+        }
 
-        let corrected_error = decoded_data.1 != 0;
+        let mut outcome = codec.build().decode(&codeword.to_bitvec())?;
+        if outcome.status.is_uncorrectable() {
+            return Err(ProtocolError::CorruptedPayload);
+        }
+        let data_len = outcome
+            .data
+            .len()
+            .checked_sub(usize::from(padding))
+            .ok_or(HammingError::UnexpectedOutOfBounds)?;
+        outcome.data.retain(|i, _| i < data_len);
+        Ok((outcome.data, outcome.status))
+    }
+}
 
-        Ok((
-            Self {
-                protocol_name: protocol_name.to_vec(),
-                version,
-                data: decoded_data.0,
-            },
-            corrected_error,
-        ))
+/// The interleaver for `depth` rows, none for 0 or 1.
+fn interleaver(depth: u8) -> Result<Option<Interleaver>, ProtocolError> {
+    match depth {
+        0 | 1 => Ok(None),
+        depth if depth > MAX_INTERLEAVE_DEPTH => Err(ProtocolError::InterleaveTooDeep {
+            depth,
+            max: MAX_INTERLEAVE_DEPTH,
+        }),
+        depth => Ok(Some(
+            Interleaver::new(depth.into()).expect("the depth is at least 2"),
+        )),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::encoding::{
+        channel::{BinarySymmetricChannel, IdealChannel},
+        hamming::HammingCode,
+    };
 
     #[test]
     fn test_gus_protocol() {
@@ -149,4 +398,827 @@ mod tests {
         assert_eq!(gus.data.len(), decoded.data.len());
         assert_eq!(gus.data.into_inner(), decoded.data.into_inner());
     }
+
+    #[test]
+    fn test_wire_format() {
+        let length = Length {
+            data_length: 0x0102,
+            bits_length: 0x0a0b0c0d,
+        };
+        let bytes = [
+            0x02, 0x01, 0, 0, 0, 0, 0, 0, //
+            0x0d, 0x0c, 0x0b, 0x0a, 0, 0, 0, 0,
+        ];
+        assert_eq!(length.to_le_bytes(), bytes);
+        assert_eq!(Length::from_le_bytes(&bytes).unwrap(), length);
+        assert!(Length::from_le_bytes(&bytes[..15]).is_err());
+        let bytes = [
+            0, 0, 0, 0, 0, 0, 0x01, 0x02, //
+            0, 0, 0, 0, 0x0a, 0x0b, 0x0c, 0x0d,
+        ];
+        assert_eq!(length.to_bytes(Endianness::Big), bytes);
+        assert_eq!(Length::from_bytes(&bytes, Endianness::Big).unwrap(), length);
+
+        // 1011 encodes to p1 p2 d1 p4 d2 d3 d4 = 0110011
+        let data = BitVec::from_vec(vec![true, false, true, true]);
+        let packet = GUSProtocol::new(data.clone())
+            .unwrap()
+            .with_content_type(ContentType::Text);
+        let golden: [(FrameVersion, &[u8]); 12] = [
+            // version 1 frames from 64-bit hosts look the same as version 2
+            (
+                FrameVersion::V1,
+                b"GUS\x01\x01\0\0\0\0\0\0\0\x07\0\0\0\0\0\0\0\x66",
+            ),
+            (
+                FrameVersion::V2,
+                b"GUS\x02\x01\0\0\0\0\0\0\0\x07\0\0\0\0\0\0\0\x66",
+            ),
+            // version 3 sends the same header as a 169 bit extended Hamming
+            // codeword
+            (
+                FrameVersion::V3,
+                b"\xd9\x75\xaa\x98\x0c\x04\0\x01\0\0\0\0\0\x0e\0\0\0\0\0\0\0\0\x66",
+            ),
+            // version 4 splits it in a lead and the fields, adding the content
+            // type
+            (
+                FrameVersion::V4,
+                b"\x49\x75\xaa\x99\x12\x91\x10\x08\0\0\0\0\x01\0\0\x0e\0\0\0\0\0\0\0\x80\x66",
+            ),
+            // version 5 adds the fragment fields, here message 0, fragment 0
+            // of 1
+            (
+                FrameVersion::V5,
+                b"\x19\x75\xaa\x98\x16\x90\x11\x08\0\0\0\0\0\0\0\x0e\0\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\0\x66",
+            ),
+            // version 6 adds the flags, and with them a CRC-32 after the data,
+            // which makes for a 42 bit codeword
+            (
+                FrameVersion::V6,
+                b"\xd9\x75\xaa\x98\x1a\xc1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\x26\xcb\xb7\x11\x32\0",
+            ),
+            // version 7 adds the codec id and padding, both 0 for plain
+            // Hamming
+            (
+                FrameVersion::V7,
+                b"\x89\x75\xaa\x99\x1e\xc1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\x26\xcb\xb7\x11\x32\0",
+            ),
+            // version 8 adds the packet type, 0 for data
+            (
+                FrameVersion::V8,
+                b"\x99\x75\xaa\x99\x20\xc1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\0\x26\xcb\xb7\x11\x32\0",
+            ),
+            // version 9 adds the length of the extensions, 0 as there are
+            // none, so nothing else comes after the fields
+            (
+                FrameVersion::V9,
+                b"\xc9\x75\xaa\x98\x24\xc1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\0\0\0\x26\xcb\xb7\x11\x32\0",
+            ),
+            // version 10 adds the interleaving depth, 0 for none
+            (
+                FrameVersion::V10,
+                b"\x09\x75\xaa\x98\x28\xc1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\0\0\0\0\x26\xcb\xb7\x11\x32\0",
+            ),
+            // version 11 only adds what goes after the fields when the
+            // payload is scrambled, and it isn't
+            (
+                FrameVersion::V11,
+                b"\x59\x75\xaa\x99\x2c\xc1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\0\0\0\0\x26\xcb\xb7\x11\x32\0",
+            ),
+            // version 12 adds a checksum of the lead and the fields
+            (
+                FrameVersion::V12,
+                b"\x89\x75\xaa\x98\x32\xd1\x11\x30\x01\0\0\0\x01\0\0\x54\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01\0\0\0\0\0\0\x25\xc0\x26\xcb\xb7\x11\x32\0",
+            ),
+        ];
+        for (version, bytes) in golden {
+            let encoded = packet.clone().encode_as(version).unwrap();
+            assert_eq!(encoded, bytes, "version {version}");
+
+            let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+            assert_eq!(decoded.version, version);
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+            assert_eq!(report.corrected, None);
+            let content_type = match version {
+                FrameVersion::V4
+                | FrameVersion::V5
+                | FrameVersion::V6
+                | FrameVersion::V7
+                | FrameVersion::V8
+                | FrameVersion::V9
+                | FrameVersion::V10
+                | FrameVersion::V11
+                | FrameVersion::V12 => ContentType::Text,
+                _ => ContentType::Binary,
+            };
+            assert_eq!(decoded.content_type, content_type);
+        }
+        assert_eq!(
+            packet.clone().encode().unwrap(),
+            golden[golden.len() - 1].1,
+            "the current version is the default"
+        );
+
+        // the same version 8 frame with a big-endian header: the flags byte
+        // says so, and the lengths and fragment fields are flipped around
+        let mut big_endian = packet.clone();
+        big_endian.flags.insert(Flags::BIG_ENDIAN);
+        let encoded = big_endian.clone().encode_as(FrameVersion::V8).unwrap();
+        assert_eq!(encoded, b"\x99\x75\xaa\x99\x20\x81\x11\0\x01\0\0\0\x01\0\x0c\0\0\0\0\0\0\0\x2a\0\0\0\0\0\0\0\0\0\0\0\x01\x03\0\0\0\0\x26\xcb\xb7\x11\x32\0");
+        let (decoded, _) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(decoded.flags, big_endian.flags);
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_big_endian() {
+        let data = BitVec::random(1000, &mut rand::rng());
+        for packet in GUSProtocol::fragment(data.clone(), 300) {
+            let mut big = packet.clone();
+            big.flags.insert(Flags::BIG_ENDIAN);
+            let encoded = big.encode().unwrap();
+            let little = packet.clone().encode().unwrap();
+            // only the header differs
+            assert_eq!(encoded.len(), little.len());
+            let header = FrameHeader::size(FrameVersion::CURRENT);
+            assert_ne!(encoded[..header], little[..header]);
+            assert_eq!(encoded[header..], little[header..]);
+
+            let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+            assert_eq!(decoded.data.to_vec(), packet.data.to_vec());
+            assert_eq!(decoded.fragment, packet.fragment);
+            assert_eq!(decoded.flags.endianness(), Endianness::Big);
+            assert_eq!(report.frame_bytes, little.len());
+        }
+
+        // versions without flags have no way to say so
+        let big = GUSProtocol {
+            flags: Flags::BIG_ENDIAN,
+            ..GUSProtocol::new(data.clone()).unwrap()
+        };
+        let (decoded, _) = GUSProtocol::decode(big.encode_as(FrameVersion::V5).unwrap()).unwrap();
+        assert_eq!(decoded.flags.endianness(), Endianness::Little);
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let data = BitVec::random(300, &mut rand::rng());
+        let mut packet = GUSProtocol::new(data.clone()).unwrap();
+        packet
+            .extensions
+            .insert(ExtType::Filename, "report.txt")
+            .unwrap();
+        packet
+            .extensions
+            .insert(ExtType::Origin, "probe-7")
+            .unwrap();
+        // from a newer sender, and meaningless here
+        packet
+            .extensions
+            .insert(ExtType::Unknown(0xf0), [0xde, 0xad])
+            .unwrap();
+
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let mut packet = packet.clone();
+            if endianness == Endianness::Big {
+                packet.flags.insert(Flags::BIG_ENDIAN);
+            }
+            let encoded = packet.clone().encode().unwrap();
+            assert_eq!(packet.encoded_len(), encoded.len());
+
+            let (decoded, report) = GUSProtocol::decode(encoded.clone()).unwrap();
+            assert_eq!(decoded.extensions, packet.extensions);
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+            assert_eq!(report.frame_bytes, encoded.len());
+            // unknown ones go back out as they came
+            assert_eq!(decoded.encode().unwrap(), encoded);
+
+            let mut reader = std::io::Cursor::new(&encoded);
+            let (read, _) = GUSProtocol::decode_from(&mut reader).unwrap();
+            assert_eq!(read.extensions, packet.extensions);
+            assert_eq!(reader.position() as usize, encoded.len());
+        }
+
+        // an extension corrupted on the way is corrected with the header
+        let mut encoded = packet.clone().encode().unwrap();
+        encoded[FrameHeader::size(FrameVersion::CURRENT) + 4] ^= 0x10;
+        let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(decoded.extensions, packet.extensions);
+        assert_eq!(report.corrected.unwrap().header.len(), 1);
+
+        // a length claiming more extensions than the frame holds
+        let mut header = packet.header(
+            &packet.payload(),
+            Length {
+                data_length: 0,
+                bits_length: 0,
+            },
+        );
+        header
+            .extensions
+            .insert(ExtType::Origin, vec![0; 1000])
+            .unwrap();
+        let mut encoded = header.encode(PROTOCOL_NAME);
+        encoded.truncate(FrameHeader::size(FrameVersion::CURRENT) + 100);
+        assert!(matches!(
+            GUSProtocol::decode(encoded),
+            Err(ProtocolError::TooShort { needed, got })
+                if needed > got && got == FrameHeader::size(FrameVersion::CURRENT) + 100
+        ));
+
+        // older versions have nowhere to put them
+        let (decoded, _) =
+            GUSProtocol::decode(packet.encode_as(FrameVersion::V8).unwrap()).unwrap();
+        assert!(decoded.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_cross_version_round_trip() {
+        // whatever version the sender falls back to, the receiver needs no
+        // telling which one it is
+        let data = BitVec::random(100, &mut rand::rng());
+        for version in FrameVersion::ALL {
+            let encoded = GUSProtocol::new(data.clone())
+                .unwrap()
+                .encode_as(version)
+                .unwrap();
+            let (decoded, _) = GUSProtocol::decode(encoded.clone()).unwrap();
+            assert_eq!(decoded.version, version);
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+            assert_eq!(decoded.encoded_len(), encoded.len());
+
+            // and the decoded packet re-encodes as the same version
+            let reencoded = decoded.clone().encode().unwrap();
+            assert_eq!(GUSProtocol::decode(reencoded).unwrap().0.version, version);
+        }
+    }
+
+    #[test]
+    fn test_codecs() {
+        // the receiver goes by the codec in the header, padding and all
+        let data = BitVec::random(101, &mut rand::rng());
+        for codec in CodecId::ALL {
+            let packet = GUSProtocol::new_with_codec(data.clone(), codec).unwrap();
+            let mut encoded = packet.clone().encode().unwrap();
+            assert_eq!(packet.encoded_len(), encoded.len(), "{codec}");
+
+            let (decoded, report) = GUSProtocol::decode(encoded.clone()).unwrap();
+            assert_eq!(decoded.codec, codec);
+            assert_eq!(decoded.data.to_vec(), data.to_vec(), "{codec}");
+            assert_eq!(report.corrected, None, "{codec}");
+
+            encoded[FrameHeader::size(FrameVersion::CURRENT) + 3] ^= 0x08;
+            let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+            assert_eq!(decoded.data.to_vec(), data.to_vec(), "{codec}");
+            assert_eq!(report.corrected.unwrap().payload.len(), 1, "{codec}");
+        }
+
+        // earlier versions have nowhere to put it
+        let packet = GUSProtocol::new_with_codec(data.clone(), CodecId::Golay24).unwrap();
+        let encoded = packet.encode_as(FrameVersion::V6).unwrap();
+        let (decoded, _) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(decoded.codec, CodecId::Hamming);
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_padding() {
+        for codec in [CodecId::Hamming74, CodecId::Hamming1511, CodecId::Golay24] {
+            let block = codec.max_padding() + 1;
+            for len in 0..=3 * block {
+                let data = BitVec::random(len, &mut rand::rng());
+                let packet = GUSProtocol::builder()
+                    .data(data.clone())
+                    .codec(codec)
+                    .build()
+                    .unwrap();
+                let encoded = packet.encode().unwrap();
+                let header = FrameHeader::decode(&encoded).unwrap().header;
+                assert_eq!(usize::from(header.padding), codec.padding(len + 32));
+                assert!(usize::from(header.padding) < block, "{codec} {len}");
+
+                let (decoded, _) = GUSProtocol::decode(encoded.clone()).unwrap();
+                assert_eq!(decoded.data.to_vec(), data.to_vec(), "{codec} {len}");
+
+                // padding that eats into the checksum gives it away
+                let size = FrameHeader::size(FrameVersion::CURRENT);
+                let mut tampered = FrameHeader {
+                    padding: ((header.padding as usize + 1) % block) as u8,
+                    ..header
+                }
+                .encode(PROTOCOL_NAME);
+                tampered.extend(&encoded[size..]);
+                assert!(
+                    matches!(
+                        GUSProtocol::decode(tampered),
+                        Err(ProtocolError::Integrity { .. } | ProtocolError::MissingChecksum { .. })
+                    ),
+                    "{codec} {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_interleave() {
+        for depth in [1, 4, 16] {
+            for codec in [CodecId::Hamming, CodecId::Hamming74] {
+                // ragged lengths leave the last row short
+                for len in [1, 37, 100, 333] {
+                    let data = BitVec::random(len, &mut rand::rng());
+                    let packet = GUSProtocol::builder()
+                        .data(data.clone())
+                        .codec(codec)
+                        .interleave(depth)
+                        .build()
+                        .unwrap();
+                    let encoded = packet.clone().encode().unwrap();
+                    assert_eq!(encoded.len(), packet.encoded_len());
+
+                    let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+                    assert_eq!(decoded.data.to_vec(), data.to_vec(), "{codec} {len}");
+                    assert_eq!(decoded.interleave_depth, depth);
+                    assert_eq!(report.corrected, None);
+                }
+            }
+        }
+
+        // 4 bits in a row on the wire land in 4 different blocks
+        let data = BitVec::random(64, &mut rand::rng());
+        let burst = |depth| {
+            let packet = GUSProtocol::builder()
+                .data(data.clone())
+                .codec(CodecId::Hamming74)
+                .interleave(depth)
+                .build()
+                .unwrap();
+            let mut encoded = packet.encode().unwrap();
+            let start = FrameHeader::size(FrameVersion::CURRENT) * 8 + 20;
+            for bit in start..start + 4 {
+                encoded[bit / 8] ^= 0x80 >> (bit % 8);
+            }
+            GUSProtocol::decode(encoded)
+        };
+        for depth in [4, 8] {
+            let (decoded, report) = burst(depth).unwrap();
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+            assert_eq!(report.corrected.unwrap().payload, vec![20, 21, 22, 23]);
+        }
+        assert!(burst(0).is_err());
+
+        // deeper than a receiver would take
+        let mut packet = GUSProtocol::new(data).unwrap();
+        packet.interleave_depth = MAX_INTERLEAVE_DEPTH + 1;
+        assert!(matches!(
+            packet.encode(),
+            Err(ProtocolError::InterleaveTooDeep { depth: 65, .. })
+        ));
+    }
+
+    #[test]
+    fn test_scramble() {
+        for seed in [None, Some(0xace1_2345)] {
+            for codec in [CodecId::Hamming, CodecId::Golay24] {
+                for len in [1, 100, 333] {
+                    let data = BitVec::random(len, &mut rand::rng());
+                    let mut builder = GUSProtocol::builder().data(data.clone()).codec(codec);
+                    if let Some(seed) = seed {
+                        builder = builder.scramble(seed);
+                    }
+                    let packet = builder.build().unwrap();
+                    let encoded = packet.clone().encode().unwrap();
+                    assert_eq!(encoded.len(), packet.encoded_len());
+                    // streamed or not, the same bits go out
+                    assert_eq!(
+                        packet.encode_over(&mut IdealChannel).unwrap(),
+                        encoded,
+                        "{codec} {len}"
+                    );
+
+                    let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+                    assert_eq!(decoded.data.to_vec(), data.to_vec(), "{codec} {len}");
+                    assert_eq!(decoded.scramble_seed, seed);
+                    assert_eq!(decoded.flags.contains(Flags::SCRAMBLED), seed.is_some());
+                    assert_eq!(report.integrity, IntegrityStatus::Verified);
+                }
+            }
+        }
+
+        // a payload of zeros goes out as zeros, unless it's scrambled
+        let zeros = |seed: Option<u32>| {
+            let mut builder = GUSProtocol::builder()
+                .data(BitVec::zeros(512))
+                .checksum(false);
+            if let Some(seed) = seed {
+                builder = builder.scramble(seed);
+            }
+            let packet = builder.build().unwrap();
+            let header_size = packet.header_size();
+            packet.encode().unwrap().split_off(header_size)
+        };
+        assert!(zeros(None).iter().all(|&byte| byte == 0));
+        let scrambled = zeros(Some(0xace1_2345));
+        let ones: u32 = scrambled.iter().map(|byte| byte.count_ones()).sum();
+        let bits = scrambled.len() as u32 * 8;
+        assert!(ones > bits / 4 && ones < bits * 3 / 4, "{ones} of {bits}");
+
+        // and a flipped bit is still the codec's to fix
+        let data = BitVec::random(200, &mut rand::rng());
+        let mut encoded = GUSProtocol::builder()
+            .data(data.clone())
+            .scramble(7)
+            .build()
+            .unwrap()
+            .encode()
+            .unwrap();
+        let last = encoded.len() - 1;
+        encoded[last] ^= 0x80;
+        let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(report.corrected.unwrap().payload.len(), 1);
+    }
+
+    /// A current frame with whatever lengths, and `payload` after them.
+    fn frame(length: Length, payload: &[u8]) -> Vec<u8> {
+        let header = FrameHeader {
+            version: FrameVersion::CURRENT,
+            content_type: ContentType::Binary,
+            length,
+            fragment: Fragment::default(),
+            flags: Flags::empty(),
+            codec: CodecId::Hamming,
+            padding: 0,
+            packet_type: 0,
+            extensions: HeaderExtensions::new(),
+            interleave_depth: 0,
+            scramble_seed: None,
+        };
+        let mut frame = header.encode(PROTOCOL_NAME);
+        frame.extend(payload);
+        frame
+    }
+
+    #[test]
+    fn test_content_type() {
+        let data = BitVec::random(40, &mut rand::rng());
+        for content_type in [
+            ContentType::Binary,
+            ContentType::Text,
+            ContentType::Hex,
+            ContentType::Base64,
+            ContentType::Bytes,
+            ContentType::Unknown(7),
+        ] {
+            let packet = GUSProtocol::new(data.clone())
+                .unwrap()
+                .with_content_type(content_type);
+            let (decoded, _) = GUSProtocol::decode(packet.encode().unwrap()).unwrap();
+            assert_eq!(decoded.content_type, content_type);
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+        }
+
+        // older versions don't carry it
+        let mut packet = GUSProtocol::new(data)
+            .unwrap()
+            .with_content_type(ContentType::Text);
+        packet.version = FrameVersion::V3;
+        let (decoded, _) = GUSProtocol::decode(packet.encode().unwrap()).unwrap();
+        assert_eq!(decoded.content_type, ContentType::Binary);
+    }
+
+    #[test]
+    fn test_header_correction_reported() {
+        let data = BitVec::random(50, &mut rand::rng());
+        let mut encoded = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+        encoded[10] ^= 0x10;
+
+        let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(
+            report.corrected,
+            Some(CorrectionInfo {
+                header: vec![83],
+                payload: vec![],
+            })
+        );
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_gus_protocol_over_channel() {
+        let original_data = BitVec::random(100, &mut rand::rng());
+        let packet = GUSProtocol::new(original_data.clone()).unwrap();
+
+        let encoded = packet.clone().encode().unwrap();
+        assert_eq!(encoded, packet.clone().encode().unwrap());
+        let (_, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(report.corrected, None);
+
+        // find a seed that flips exactly one bit of the 107 bit codeword
+        let seed = (0..)
+            .find(|&seed| {
+                let mut bits = BitVec::zeros(107);
+                BinarySymmetricChannel::new(0.01, Some(seed)).transmit(&mut bits);
+                (0..107).filter(|&i| bits.get(i) == Some(true)).count() == 1
+            })
+            .unwrap();
+
+        let mut channel = BinarySymmetricChannel::new(0.01, Some(seed));
+        let encoded = packet.clone().encode_over(&mut channel).unwrap();
+        let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(report.corrected.as_ref().unwrap().payload.len(), 1);
+        assert_eq!(decoded.data.to_vec(), original_data.to_vec());
+
+        // the same seed corrupts the same bit every time
+        let mut channel = BinarySymmetricChannel::new(0.01, Some(seed));
+        let again = packet.encode_over(&mut channel).unwrap();
+        assert_eq!(GUSProtocol::decode(again).unwrap().1, report);
+    }
+
+    #[test]
+    fn test_tiny_payloads() {
+        for len in 0..=3 {
+            let data = BitVec::random(len, &mut rand::rng());
+            let encoded = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+            let (decoded, report) = GUSProtocol::decode(encoded.clone()).unwrap();
+            assert_eq!(report.corrected, None);
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+
+            // a flipped payload bit is corrected like any other
+            let header = FrameHeader::size(FrameVersion::CURRENT);
+            for bit in 0..Hamming.encode(&data).unwrap().len() {
+                let mut corrupted = encoded.clone();
+                corrupted[header + bit / 8] ^= 0x80 >> (bit % 8);
+                let (decoded, report) = GUSProtocol::decode(corrupted).unwrap();
+                assert_eq!(report.corrected.unwrap().payload, vec![bit]);
+                assert_eq!(decoded.data.to_vec(), data.to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(
+            GUSProtocol::decode(Vec::new()),
+            Err(ProtocolError::Codec(HammingError::EmptyInput))
+        ));
+
+        // claim more bits than the data bytes hold
+        let payload = Hamming.encode(&BitVec::ones(4)).unwrap().into_inner();
+        let too_long = frame(
+            Length {
+                data_length: 1,
+                bits_length: 63,
+            },
+            &payload,
+        );
+        let error = GUSProtocol::decode(too_long).unwrap_err();
+        assert!(matches!(
+            error,
+            ProtocolError::InconsistentLengths {
+                data_length: 1,
+                bits_length: 63
+            }
+        ));
+        assert_eq!(error.to_string(), "Header declares 63 bits in 1 bytes");
+
+        // more bytes than there are
+        let header = FrameHeader::size(FrameVersion::CURRENT);
+        let error = GUSProtocol::decode(frame(
+            Length {
+                data_length: 2,
+                bits_length: 7,
+            },
+            &payload,
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ProtocolError::TooShort { needed, got } if needed == header + 2 && got == header + 1
+        ));
+
+        // a bit count no codeword can have
+        let invalid = frame(
+            Length {
+                data_length: 1,
+                bits_length: 8,
+            },
+            &payload,
+        );
+        assert!(matches!(
+            GUSProtocol::decode(invalid),
+            Err(ProtocolError::Codec(HammingError::InvalidCodewordLength {
+                got: 8,
+                expected_for_r: 7
+            }))
+        ));
+
+        // not even a whole header
+        let whole = frame(
+            Length {
+                data_length: 1,
+                bits_length: 7,
+            },
+            &payload,
+        );
+        assert!(matches!(
+            GUSProtocol::decode(whole[..30].to_vec()),
+            Err(ProtocolError::TooShort {
+                needed: 39,
+                got: 25
+            })
+        ));
+
+        let mut foreign = GUSProtocol::new(BitVec::ones(4)).unwrap();
+        foreign.protocol_name = b"GUT".to_vec();
+        assert!(matches!(
+            GUSProtocol::decode(foreign.encode().unwrap()),
+            Err(ProtocolError::BadMagic(magic)) if &magic == b"GUT"
+        ));
+    }
+
+    #[test]
+    fn test_hostile_lengths() {
+        let payload = Hamming.encode(&BitVec::ones(4)).unwrap().into_inner();
+        let lengths = [
+            0,
+            1,
+            7,
+            8,
+            u32::MAX as usize,
+            usize::MAX / 8,
+            usize::MAX / 8 + 1,
+            usize::MAX - 1,
+            usize::MAX,
+        ];
+        for data_length in lengths {
+            for bits_length in lengths {
+                let encoded = frame(
+                    Length {
+                        data_length,
+                        bits_length,
+                    },
+                    &payload,
+                );
+                // an error or not, but never a panic, however it's cut
+                let _ = GUSProtocol::decode_ref(&encoded);
+                for len in 0..encoded.len() {
+                    let decoded = GUSProtocol::decode_ref(&encoded[..len]);
+                    assert!(decoded.is_err() || data_length == 0, "cut at {len}");
+                }
+                if data_length > 1 {
+                    assert!(GUSProtocol::decode(encoded).is_err());
+                }
+            }
+        }
+
+        // a data length that runs past the end of the address space
+        assert!(matches!(
+            GUSProtocol::decode(frame(
+                Length {
+                    data_length: usize::MAX,
+                    bits_length: 7,
+                },
+                &payload,
+            )),
+            Err(ProtocolError::LengthOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_integrity_check() {
+        let data = BitVec::random(100, &mut rand::rng());
+        let packet = GUSProtocol::new(data.clone()).unwrap();
+        assert!(packet.flags.contains(Flags::CRC32));
+        let encoded = packet.clone().encode().unwrap();
+        let header = FrameHeader::size(FrameVersion::CURRENT);
+        let flip = |encoded: &mut Vec<u8>, bits: &[usize]| {
+            for bit in bits {
+                encoded[header + bit / 8] ^= 0x80 >> (bit % 8);
+            }
+        };
+
+        let (decoded, report) = GUSProtocol::decode(encoded.clone()).unwrap();
+        assert_eq!(decoded.flags, Flags::CRC32);
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(report.integrity, IntegrityStatus::Verified);
+
+        let mut corrected = encoded.clone();
+        flip(&mut corrected, &[40]);
+        let (decoded, report) = GUSProtocol::decode(corrected).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(report.corrected.unwrap().payload, vec![40]);
+
+        // positions 3 and 5 give syndrome 6, so the codec flips a third data
+        // bit and calls it corrected
+        let mut fooled = encoded;
+        flip(&mut fooled, &[2, 4]);
+        assert!(matches!(
+            GUSProtocol::decode(fooled),
+            Err(ProtocolError::Integrity { expected, actual }) if expected != actual
+        ));
+
+        // without the checksum nothing notices
+        let mut unchecked = packet.encode_as(FrameVersion::V5).unwrap();
+        let header = FrameHeader::size(FrameVersion::V5);
+        for bit in [2, 4] {
+            unchecked[header + bit / 8] ^= 0x80 >> (bit % 8);
+        }
+        let (decoded, report) = GUSProtocol::decode(unchecked).unwrap();
+        assert_ne!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(report.integrity, IntegrityStatus::Unchecked);
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_compression() {
+        let text = "the quick brown fox jumps over the lazy dog. ".repeat(230);
+        let data = BitVec::from_whole_bytes(text.as_bytes()[..10 << 10].to_vec());
+        let compressed = GUSProtocol::builder()
+            .data(data.clone())
+            .content_type(ContentType::Text)
+            .compress(true)
+            .build()
+            .unwrap();
+        let plain = GUSProtocol::new(data.clone()).unwrap();
+        let encoded = compressed.clone().encode().unwrap();
+        assert_eq!(encoded.len(), compressed.encoded_len());
+        assert!(encoded.len() * 10 < plain.encode().unwrap().len());
+
+        let (decoded, report) = GUSProtocol::decode(encoded.clone()).unwrap();
+        assert!(decoded.flags.contains(Flags::COMPRESSED));
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(report.payload_bits, data.len());
+
+        // corrected before it's inflated
+        let mut flipped = encoded;
+        let last = flipped.len() - 1;
+        flipped[last] ^= 0x80;
+        let (decoded, report) = GUSProtocol::decode(flipped).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert!(report.corrected.is_some());
+
+        // nothing to gain from it, so it's left off
+        for data in [BitVec::from_whole_bytes((0..=255).collect()), BitVec::new()] {
+            let packet = GUSProtocol::builder()
+                .data(data.clone())
+                .compress(true)
+                .build()
+                .unwrap();
+            let encoded = packet.encode().unwrap();
+            assert_eq!(
+                encoded,
+                GUSProtocol::new(data.clone()).unwrap().encode().unwrap()
+            );
+            let (decoded, _) = GUSProtocol::decode(encoded).unwrap();
+            assert_eq!(decoded.flags, Flags::CRC32);
+            assert_eq!(decoded.data.to_vec(), data.to_vec());
+        }
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_decompression_error() {
+        let data = BitVec::from_whole_bytes("hello ".repeat(20).into_bytes());
+        let packet = GUSProtocol::builder()
+            .data(data)
+            .checksum(false)
+            .compress(true)
+            .build()
+            .unwrap();
+        let mut encoded = packet.encode().unwrap();
+
+        // positions 3 and 9 give syndrome 10, so the codec also flips data
+        // bit 5, one of the bits saying what kind of block the stream starts
+        // with, and nothing checks it
+        let header = FrameHeader::size(FrameVersion::CURRENT);
+        for bit in [2, 8] {
+            encoded[header + bit / 8] ^= 0x80 >> (bit % 8);
+        }
+        assert!(matches!(
+            GUSProtocol::decode(encoded),
+            Err(ProtocolError::Decompression(_))
+        ));
+    }
+
+    #[test]
+    fn test_uncorrectable_packet() {
+        let packet = GUSProtocol::new(BitVec::random(100, &mut rand::rng())).unwrap();
+        let mut encoded = packet.encode().unwrap();
+
+        // positions 27 and 140 of the 140 bit codeword, 100 data bits and a 32
+        // bit checksum, give syndrome 151
+        let header = FrameHeader::size(FrameVersion::CURRENT);
+        for bit in [26, 139] {
+            encoded[header + bit / 8] ^= 0x80 >> (bit % 8);
+        }
+
+        assert!(matches!(
+            GUSProtocol::decode(encoded),
+            Err(ProtocolError::Codec(HammingError::Uncorrectable {
+                syndrome: 151
+            }))
+        ));
+    }
 }
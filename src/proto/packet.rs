@@ -0,0 +1,327 @@
+//! What a frame is for: data, or one of the control frames a receiver answers
+//! with.
+
+use crate::{
+    encoding::bitvec::BitVec,
+    proto::{Capabilities, GUSProtocol, ProtocolError},
+};
+
+/// Why a receiver is asking for a message again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NackReason {
+    /// More errors than the codec could correct.
+    Uncorrectable,
+    /// The data didn't match its checksum.
+    Integrity,
+    /// A frame this receiver can't read, e.g. of a newer version.
+    Unsupported,
+    /// A reason this version doesn't know about.
+    Unknown(u8),
+}
+
+impl From<u8> for NackReason {
+    fn from(byte: u8) -> Self {
+        match byte {
+            0 => Self::Uncorrectable,
+            1 => Self::Integrity,
+            2 => Self::Unsupported,
+            byte => Self::Unknown(byte),
+        }
+    }
+}
+
+impl From<NackReason> for u8 {
+    fn from(reason: NackReason) -> Self {
+        match reason {
+            NackReason::Uncorrectable => 0,
+            NackReason::Integrity => 1,
+            NackReason::Unsupported => 2,
+            NackReason::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// What a frame is for. Sent as a byte in the header from version 8 on,
+/// frames of earlier versions are all data.
+///
+/// Control frames carry what they say in a small payload of their own,
+/// protected like any other, as little-endian fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PacketType {
+    /// The payload is data, to be shown or put back together with the other
+    /// fragments of its message.
+    #[default]
+    Data,
+    /// Message `message_id` came through.
+    Ack { message_id: u32 },
+    /// Message `message_id` didn't, and should be sent again.
+    Nack { message_id: u32, reason: NackReason },
+    /// The sender failed, with an error `code` of its own choosing.
+    Error { code: u16 },
+    /// Opens a handshake, saying what the end sending it can do.
+    Hello(Capabilities),
+    /// Answers a hello with what the other end can do.
+    HelloAck(Capabilities),
+    /// The sender is still there, with nothing to send: it's been up for
+    /// `uptime_ms` milliseconds and sent `frames_sent` frames before this
+    /// one.
+    Heartbeat { uptime_ms: u64, frames_sent: u64 },
+}
+
+impl PacketType {
+    /// Every packet type byte this version knows about.
+    const KNOWN: [u8; 7] = [0, 1, 2, 3, 4, 5, 6];
+
+    /// The packet type byte on the wire.
+    pub fn byte(&self) -> u8 {
+        match self {
+            PacketType::Data => 0,
+            PacketType::Ack { .. } => 1,
+            PacketType::Nack { .. } => 2,
+            PacketType::Error { .. } => 3,
+            PacketType::Hello(_) => 4,
+            PacketType::HelloAck(_) => 5,
+            PacketType::Heartbeat { .. } => 6,
+        }
+    }
+
+    /// Checks a packet type byte off the wire, before the payload it goes
+    /// with is there to read the rest from.
+    pub(super) fn check(byte: u8) -> Result<u8, ProtocolError> {
+        match Self::KNOWN.contains(&byte) {
+            true => Ok(byte),
+            false => Err(ProtocolError::UnknownPacketType(byte)),
+        }
+    }
+
+    /// The payload of a control frame, `None` for data.
+    fn payload(&self) -> Option<Vec<u8>> {
+        match *self {
+            PacketType::Data => None,
+            PacketType::Ack { message_id } => Some(message_id.to_le_bytes().to_vec()),
+            PacketType::Nack { message_id, reason } => {
+                let mut payload = message_id.to_le_bytes().to_vec();
+                payload.push(reason.into());
+                Some(payload)
+            }
+            PacketType::Error { code } => Some(code.to_le_bytes().to_vec()),
+            PacketType::Hello(caps) | PacketType::HelloAck(caps) => Some(caps.to_bytes().to_vec()),
+            PacketType::Heartbeat {
+                uptime_ms,
+                frames_sent,
+            } => {
+                let mut payload = uptime_ms.to_le_bytes().to_vec();
+                payload.extend(frames_sent.to_le_bytes());
+                Some(payload)
+            }
+        }
+    }
+
+    /// Reads the packet type of a frame whose header says `byte` and whose
+    /// payload came out as `data`.
+    pub(super) fn from_payload(byte: u8, data: &BitVec) -> Result<Self, ProtocolError> {
+        let expected = match byte {
+            0 => return Ok(PacketType::Data),
+            1 => 4,
+            2 => 5,
+            3 => 2,
+            4 | 5 => Capabilities::SIZE,
+            6 => 16,
+            byte => return Err(ProtocolError::UnknownPacketType(byte)),
+        };
+        if data.len() != expected * 8 {
+            return Err(ProtocolError::ControlPayload {
+                packet_type: byte,
+                expected: expected * 8,
+                bits: data.len(),
+            });
+        }
+
+        let bytes = data.clone().into_inner();
+        let message_id = || u32::from_le_bytes(bytes[..4].try_into().expect("checked above"));
+        Ok(match byte {
+            1 => PacketType::Ack {
+                message_id: message_id(),
+            },
+            2 => PacketType::Nack {
+                message_id: message_id(),
+                reason: bytes[4].into(),
+            },
+            3 => PacketType::Error {
+                code: u16::from_le_bytes([bytes[0], bytes[1]]),
+            },
+            6 => PacketType::Heartbeat {
+                uptime_ms: u64::from_le_bytes(bytes[..8].try_into().expect("checked above")),
+                frames_sent: u64::from_le_bytes(bytes[8..].try_into().expect("checked above")),
+            },
+            byte => {
+                let caps = Capabilities::from_bytes(bytes[..].try_into().expect("checked above"));
+                match byte {
+                    4 => PacketType::Hello(caps),
+                    _ => PacketType::HelloAck(caps),
+                }
+            }
+        })
+    }
+}
+
+impl GUSProtocol {
+    /// A data packet, the same as [`GUSProtocol::new`].
+    pub fn data(data: BitVec) -> Self {
+        Self::new(data).expect("new never fails")
+    }
+
+    /// Tells the sender message `message_id` came through.
+    pub fn ack(message_id: u32) -> Self {
+        Self::control(PacketType::Ack { message_id })
+    }
+
+    /// Asks the sender for message `message_id` again.
+    pub fn nack(message_id: u32, reason: NackReason) -> Self {
+        Self::control(PacketType::Nack { message_id, reason })
+    }
+
+    /// Tells the other end this one failed.
+    pub fn error(code: u16) -> Self {
+        Self::control(PacketType::Error { code })
+    }
+
+    /// Opens a handshake with what this end can do, see
+    /// [`Capabilities::negotiate`].
+    pub fn hello(caps: Capabilities) -> Self {
+        Self::control(PacketType::Hello(caps))
+    }
+
+    /// Answers a hello with what this end can do.
+    pub fn hello_ack(caps: Capabilities) -> Self {
+        Self::control(PacketType::HelloAck(caps))
+    }
+
+    /// Tells the receiver this end is still there, having been up for
+    /// `uptime_ms` milliseconds and sent `frames_sent` frames, see
+    /// [`Heartbeat`](crate::proto::Heartbeat).
+    pub fn heartbeat(uptime_ms: u64, frames_sent: u64) -> Self {
+        Self::control(PacketType::Heartbeat {
+            uptime_ms,
+            frames_sent,
+        })
+    }
+
+    fn control(packet_type: PacketType) -> Self {
+        let payload = packet_type
+            .payload()
+            .expect("control frames have a payload");
+        Self {
+            packet_type,
+            ..Self::data(BitVec::from_whole_bytes(payload))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::FrameVersion;
+
+    #[test]
+    fn test_round_trip() {
+        let data = BitVec::random(100, &mut rand::rng());
+        let packets = [
+            (GUSProtocol::data(data.clone()), PacketType::Data),
+            (
+                GUSProtocol::ack(0xdeadbeef),
+                PacketType::Ack {
+                    message_id: 0xdeadbeef,
+                },
+            ),
+            (
+                GUSProtocol::nack(7, NackReason::Integrity),
+                PacketType::Nack {
+                    message_id: 7,
+                    reason: NackReason::Integrity,
+                },
+            ),
+            (
+                GUSProtocol::nack(8, NackReason::Unknown(200)),
+                PacketType::Nack {
+                    message_id: 8,
+                    reason: NackReason::Unknown(200),
+                },
+            ),
+            (GUSProtocol::error(500), PacketType::Error { code: 500 }),
+            (
+                GUSProtocol::hello(Capabilities::default()),
+                PacketType::Hello(Capabilities::default()),
+            ),
+            (
+                GUSProtocol::hello_ack(Capabilities {
+                    max_version: 200,
+                    ..Capabilities::default()
+                }),
+                PacketType::HelloAck(Capabilities {
+                    max_version: 200,
+                    ..Capabilities::default()
+                }),
+            ),
+            (
+                GUSProtocol::heartbeat(90_000, 12),
+                PacketType::Heartbeat {
+                    uptime_ms: 90_000,
+                    frames_sent: 12,
+                },
+            ),
+        ];
+        for (packet, packet_type) in packets {
+            let mut encoded = packet.encode().unwrap();
+            let (decoded, _) = GUSProtocol::decode(encoded.clone()).unwrap();
+            assert_eq!(decoded.packet_type, packet_type);
+
+            // the payload is protected like any other
+            let last = encoded.len() - 1;
+            encoded[last] ^= 0x80;
+            let (decoded, report) = GUSProtocol::decode(encoded).unwrap();
+            assert_eq!(decoded.packet_type, packet_type);
+            assert!(report.corrected.is_some());
+        }
+        assert_eq!(
+            GUSProtocol::decode(GUSProtocol::data(data.clone()).encode().unwrap())
+                .unwrap()
+                .0
+                .data
+                .to_vec(),
+            data.to_vec()
+        );
+
+        // older versions only ever sent data
+        let encoded = GUSProtocol::ack(1).encode_as(FrameVersion::V7).unwrap();
+        assert_eq!(
+            GUSProtocol::decode(encoded).unwrap().0.packet_type,
+            PacketType::Data
+        );
+    }
+
+    #[test]
+    fn test_rejects() {
+        assert!(matches!(
+            PacketType::check(7),
+            Err(ProtocolError::UnknownPacketType(7))
+        ));
+        assert!(matches!(
+            PacketType::from_payload(9, &BitVec::new()),
+            Err(ProtocolError::UnknownPacketType(9))
+        ));
+
+        // an ack with a nack's payload
+        let nack = GUSProtocol::nack(7, NackReason::Uncorrectable);
+        assert!(matches!(
+            PacketType::from_payload(1, &nack.data),
+            Err(ProtocolError::ControlPayload {
+                packet_type: 1,
+                expected: 32,
+                bits: 40
+            })
+        ));
+    }
+}
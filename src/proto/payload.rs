@@ -0,0 +1,238 @@
+//! The bits that go into a frame's codeword: the data, and from version 6 on
+//! a CRC-32 of it after, so it's protected by the code like the data is.
+//!
+//! With the `compress` feature, the data can go in deflated. The checksum is
+//! of what went in, so it's checked before the data is inflated again.
+//!
+//! From version 11 on the whole of it, checksum and all, can go in
+//! scrambled, and is descrambled before the checksum is checked.
+
+use std::borrow::Cow;
+
+use crate::{
+    encoding::{
+        bitvec::BitVec,
+        checksum::{Checksum, Crc32},
+        scrambler::Lfsr,
+        source::BitSource,
+    },
+    proto::ProtocolError,
+};
+
+/// Bits of the checksum after the data.
+const CHECKSUM_BITS: usize = 32;
+
+/// x^31 + x^28 + 1, the PRBS31 polynomial: as wide a register as [`Lfsr`]
+/// takes, so all of a seed but its top bit counts.
+const SCRAMBLER_POLYNOMIAL: u32 = 0x9000_0001;
+
+/// The most bytes a compressed payload inflates to, so a small frame can't
+/// claim gigabytes of data.
+#[cfg(feature = "compress")]
+const MAX_INFLATED_BYTES: u64 = 256 << 20;
+
+/// The data with its checksum after it, if it has one.
+pub(super) struct Payload<'a> {
+    data: Cow<'a, BitVec>,
+    compressed: bool,
+    checksum: Option<BitVec>,
+    /// What the bits are XORed with on the way out, if they're scrambled.
+    keystream: Option<BitVec>,
+}
+
+impl<'a> Payload<'a> {
+    /// The payload of `data`, deflated if `compress` is set and that makes
+    /// it shorter. Data that isn't whole bytes, or without the `compress`
+    /// feature any data, goes in as it is.
+    pub fn new(data: &'a BitVec, with_checksum: bool, compress: bool) -> Self {
+        let data = match compress {
+            true => Self::deflate(data).map_or(Cow::Borrowed(data), Cow::Owned),
+            false => Cow::Borrowed(data),
+        };
+        let checksum = with_checksum.then(|| {
+            BitVec::from_uint(u64::from(Crc32.compute(&data)), CHECKSUM_BITS)
+                .expect("a u32 fits in 32 bits")
+        });
+        Self {
+            compressed: matches!(data, Cow::Owned(_)),
+            data,
+            checksum,
+            keystream: None,
+        }
+    }
+
+    /// The same payload, scrambled with `seed` if there is one.
+    pub fn scrambled(mut self, seed: Option<u32>) -> Self {
+        self.keystream = seed.map(|seed| Self::scrambler(seed).keystream(self.bit_len()));
+        self
+    }
+
+    /// The scrambler a payload goes through with `seed`. A seed of 0, or of
+    /// just the top bit, never gets it going and leaves the payload as it is.
+    fn scrambler(seed: u32) -> Lfsr {
+        Lfsr::new(SCRAMBLER_POLYNOMIAL, seed)
+    }
+
+    /// Undoes [`Payload::scrambled`] on the corrected `payload`.
+    pub fn descramble(payload: &BitVec, seed: u32) -> BitVec {
+        Self::scrambler(seed).descramble(payload)
+    }
+
+    /// Whether the data went in deflated.
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// `data` deflated, if it's whole bytes and comes out shorter.
+    #[cfg(feature = "compress")]
+    fn deflate(data: &BitVec) -> Option<BitVec> {
+        use std::io::Write;
+
+        use flate2::{Compression, write::DeflateEncoder};
+
+        if !data.len().is_multiple_of(8) {
+            return None;
+        }
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(data.as_bitslice().as_bytes())
+            .expect("writing to a Vec doesn't fail");
+        let deflated = encoder.finish().expect("writing to a Vec doesn't fail");
+        (deflated.len() * 8 < data.len()).then(|| BitVec::from_whole_bytes(deflated))
+    }
+
+    #[cfg(not(feature = "compress"))]
+    fn deflate(_data: &BitVec) -> Option<BitVec> {
+        None
+    }
+
+    /// Inflates data that went in deflated, once it's been corrected and
+    /// checked.
+    #[cfg(feature = "compress")]
+    pub fn inflate(data: BitVec) -> Result<BitVec, ProtocolError> {
+        use std::io::Read;
+
+        use flate2::read::DeflateDecoder;
+
+        let mut inflated = Vec::new();
+        DeflateDecoder::new(data.as_bitslice().as_bytes())
+            .take(MAX_INFLATED_BYTES + 1)
+            .read_to_end(&mut inflated)
+            .map_err(ProtocolError::Decompression)?;
+        if inflated.len() as u64 > MAX_INFLATED_BYTES {
+            return Err(ProtocolError::Decompression(std::io::Error::other(
+                format!("inflates past {MAX_INFLATED_BYTES} bytes"),
+            )));
+        }
+        Ok(BitVec::from_whole_bytes(inflated))
+    }
+
+    /// Never called, frames flagged as compressed don't get past their
+    /// header without the `compress` feature.
+    #[cfg(not(feature = "compress"))]
+    pub fn inflate(_data: BitVec) -> Result<BitVec, ProtocolError> {
+        Err(ProtocolError::UnsupportedFlags(
+            crate::proto::Flags::COMPRESSED.bits(),
+        ))
+    }
+
+    /// Takes the checksum off the end of `payload` and checks the data
+    /// against it.
+    pub fn verify(payload: BitVec) -> Result<BitVec, ProtocolError> {
+        let data_len =
+            payload
+                .len()
+                .checked_sub(CHECKSUM_BITS)
+                .ok_or(ProtocolError::MissingChecksum {
+                    bits: payload.len(),
+                })?;
+        let expected = payload
+            .to_uint_range(data_len..payload.len())
+            .expect("the checksum is the last 32 bits") as u32;
+        let data = payload
+            .slice(0..data_len)
+            .expect("the data is in front of the checksum");
+
+        let actual = Crc32.compute(&data);
+        if actual != expected {
+            return Err(ProtocolError::Integrity { expected, actual });
+        }
+        Ok(data)
+    }
+}
+
+impl BitSource for Payload<'_> {
+    fn bit_len(&self) -> usize {
+        self.data.len() + self.checksum.as_ref().map_or(0, BitVec::len)
+    }
+
+    fn bit(&self, index: usize) -> Option<bool> {
+        let bit = match index.checked_sub(self.data.len()) {
+            None => self.data.get(index),
+            Some(index) => self.checksum.as_ref()?.get(index),
+        }?;
+        match &self.keystream {
+            Some(keystream) => Some(bit ^ keystream.get(index)?),
+            None => Some(bit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_round_trip() {
+        let data = BitVec::random(45, &mut rand::rng());
+        let payload = Payload::new(&data, true, false).to_bitvec();
+        assert_eq!(payload.len(), 45 + CHECKSUM_BITS);
+        assert_eq!(
+            Payload::verify(payload.clone()).unwrap().to_vec(),
+            data.to_vec()
+        );
+
+        // any one flipped bit, data or checksum, gives it away
+        for i in 0..payload.len() {
+            let mut corrupted = payload.clone();
+            corrupted.toggle(i).unwrap();
+            let error = Payload::verify(corrupted).unwrap_err();
+            assert!(matches!(error, ProtocolError::Integrity { .. }), "bit {i}");
+        }
+
+        let plain = Payload::new(&data, false, false);
+        assert_eq!(plain.bit_len(), 45);
+        assert_eq!(plain.bit(45), None);
+
+        assert!(matches!(
+            Payload::verify(BitVec::zeros(31)),
+            Err(ProtocolError::MissingChecksum { bits: 31 })
+        ));
+        // the checksum of nothing is all there is
+        let empty = Payload::new(&BitVec::new(), true, false).to_bitvec();
+        assert!(Payload::verify(empty).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_inflate() {
+        let data = BitVec::from_whole_bytes(b"abcabcabcabcabcabcabcabc".to_vec());
+        let payload = Payload::new(&data, false, true);
+        assert!(payload.is_compressed());
+        assert_eq!(
+            Payload::inflate(payload.to_bitvec()).unwrap().to_vec(),
+            data.to_vec()
+        );
+
+        // an odd bit out, or nothing to gain, and it goes in as it is
+        let odd = BitVec::zeros(8 * 24 + 1);
+        assert!(!Payload::new(&odd, false, true).is_compressed());
+        assert!(!Payload::new(&BitVec::new(), false, true).is_compressed());
+
+        // a block of the reserved type
+        assert!(matches!(
+            Payload::inflate(BitVec::from_whole_bytes(vec![0xff; 8])),
+            Err(ProtocolError::Decompression(_))
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+//! Reading just the header of a frame, for looking at captures without
+//! decoding them.
+
+use std::{fmt, ops::Range};
+
+use crate::proto::{
+    CodecId, ContentType, Flags, Fragment, FrameVersion, GUSProtocol, HeaderExtensions, Length,
+    ProtocolError,
+    header::{FrameHeader, PROTOCOL_NAME},
+    report::positions,
+};
+
+/// What the header of a frame says, see [`GUSProtocol::peek_header`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeekedHeader {
+    pub version: FrameVersion,
+    /// Always binary for frames before version 4.
+    pub content_type: ContentType,
+    pub length: Length,
+    /// Always whole for frames before version 5.
+    pub fragment: Fragment,
+    /// Always empty for frames before version 6.
+    pub flags: Flags,
+    /// Always Hamming for frames before version 7.
+    pub codec: CodecId,
+    /// Bits of padding the codec added after the data.
+    pub padding: u8,
+    /// Always empty for frames before version 9.
+    pub extensions: HeaderExtensions,
+    /// Rows the payload was interleaved over, 0 or 1 for none.
+    pub interleave_depth: u8,
+    pub scramble_seed: Option<u32>,
+    /// Bits of the header that were corrected, counted from the start of the
+    /// frame.
+    pub corrected: Vec<usize>,
+    /// Where the payload is in the frame, going by the header. It needn't be
+    /// there at all.
+    pub payload: Range<usize>,
+}
+
+impl PeekedHeader {
+    /// Bytes the header takes up, extensions and all.
+    pub fn header_size(&self) -> usize {
+        self.payload.start
+    }
+}
+
+impl GUSProtocol {
+    /// Reads the header off the front of `buffer`, without so much as looking
+    /// at the payload after it, so a capture cut short after the header
+    /// still says what the frame was.
+    ///
+    /// Fails like [`GUSProtocol::decode`] does on the header: on a magic
+    /// that isn't "GUS", there's no telling what follows it, or on lengths
+    /// that don't make sense together.
+    pub fn peek_header(buffer: &[u8]) -> Result<PeekedHeader, ProtocolError> {
+        let decoded = FrameHeader::decode(buffer)?;
+        let payload = decoded.size..decoded.frame_size()?;
+        let FrameHeader {
+            version,
+            content_type,
+            length,
+            fragment,
+            flags,
+            codec,
+            padding,
+            extensions,
+            interleave_depth,
+            scramble_seed,
+            ..
+        } = decoded.header;
+        Ok(PeekedHeader {
+            version,
+            content_type,
+            length,
+            fragment,
+            flags,
+            codec,
+            padding,
+            extensions,
+            interleave_depth,
+            scramble_seed,
+            corrected: positions(&decoded.status),
+            payload,
+        })
+    }
+}
+
+impl fmt::Display for PeekedHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} version {} frame, {} header bytes",
+            String::from_utf8_lossy(PROTOCOL_NAME),
+            self.version,
+            self.header_size()
+        )?;
+        match self.corrected.len() {
+            0 => writeln!(f)?,
+            corrected => writeln!(f, ", {corrected} bits corrected")?,
+        }
+        writeln!(f, "content type: {:?}", self.content_type)?;
+        writeln!(
+            f,
+            "length: {} bytes, {} bits of payload",
+            self.length.data_length, self.length.bits_length
+        )?;
+        let Fragment {
+            message_id,
+            index,
+            count,
+        } = self.fragment;
+        writeln!(f, "fragment: {index} of {count} of message {message_id}")?;
+
+        let names: Vec<_> = [
+            (Flags::CRC32, "crc32"),
+            (Flags::BIG_ENDIAN, "big-endian"),
+            (Flags::COMPRESSED, "compressed"),
+            (Flags::SCRAMBLED, "scrambled"),
+        ]
+        .into_iter()
+        .filter(|&(flag, _)| self.flags.contains(flag))
+        .map(|(_, name)| name)
+        .collect();
+        match names.is_empty() {
+            true => writeln!(f, "flags: {:#04x}", self.flags.bits())?,
+            false => writeln!(
+                f,
+                "flags: {:#04x} ({})",
+                self.flags.bits(),
+                names.join(", ")
+            )?,
+        }
+
+        writeln!(f, "codec: {}, {} bits of padding", self.codec, self.padding)?;
+        match self.interleave_depth {
+            0 | 1 => writeln!(f, "interleaving: none")?,
+            depth => writeln!(f, "interleaving: {depth} deep")?,
+        }
+        match self.scramble_seed {
+            Some(seed) => writeln!(f, "scrambler seed: {seed:#010x}")?,
+            None => writeln!(f, "scrambler seed: none")?,
+        }
+        for (ext_type, value) in self.extensions.iter() {
+            writeln!(f, "extension {ext_type:?}: {value:02x?}")?;
+        }
+        write!(
+            f,
+            "payload: bytes {} to {}",
+            self.payload.start, self.payload.end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encoding::bitvec::BitVec, proto::ExtType};
+
+    fn packet() -> GUSProtocol {
+        GUSProtocol::builder()
+            .data(BitVec::from_whole_bytes(b"peek".to_vec()))
+            .content_type(ContentType::Text)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_peek() {
+        let encoded = packet().encode().unwrap();
+        let peeked = GUSProtocol::peek_header(&encoded).unwrap();
+        assert_eq!(peeked.version, FrameVersion::CURRENT);
+        assert_eq!(peeked.content_type, ContentType::Text);
+        assert_eq!(peeked.flags, Flags::CRC32);
+        assert_eq!(peeked.codec, CodecId::Hamming);
+        // the data and its CRC-32, and 7 parity bits
+        assert_eq!(peeked.length.bits_length, 71);
+        assert_eq!(peeked.payload, peeked.header_size()..encoded.len());
+        assert!(peeked.corrected.is_empty());
+
+        // nothing of the payload needs to be there
+        let header = &encoded[..peeked.header_size()];
+        assert_eq!(GUSProtocol::peek_header(header).unwrap(), peeked);
+        assert!(GUSProtocol::decode(header.to_vec()).is_err());
+        assert!(matches!(
+            GUSProtocol::peek_header(&header[..header.len() - 1]),
+            Err(ProtocolError::TooShort { .. })
+        ));
+
+        let mut corrupted = encoded.clone();
+        corrupted[10] ^= 0x04;
+        assert_eq!(
+            GUSProtocol::peek_header(&corrupted)
+                .unwrap()
+                .corrected
+                .len(),
+            1
+        );
+        assert!(matches!(
+            GUSProtocol::peek_header(b"NOPE, not a frame at all, nope"),
+            Err(ProtocolError::BadMagic(_)) | Err(ProtocolError::CorruptedHeader)
+        ));
+    }
+
+    #[test]
+    fn test_optional_fields() {
+        let optional = GUSProtocol::builder()
+            .data(BitVec::from_whole_bytes(b"peek".to_vec()))
+            .fragment(Fragment {
+                message_id: 9,
+                index: 1,
+                count: 2,
+            })
+            .codec(CodecId::Hamming74)
+            .interleave(4)
+            .scramble(0xace1_2345)
+            .extension(ExtType::Filename, "a.txt")
+            .sequence(3)
+            .build()
+            .unwrap();
+        let encoded = optional.encode().unwrap();
+        let peeked = GUSProtocol::peek_header(&encoded).unwrap();
+        assert_eq!(peeked.fragment.index, 1);
+        assert_eq!(peeked.codec, CodecId::Hamming74);
+        assert_eq!(peeked.interleave_depth, 4);
+        assert_eq!(peeked.scramble_seed, Some(0xace1_2345));
+        assert!(peeked.flags.contains(Flags::SCRAMBLED));
+        assert_eq!(
+            peeked.extensions.get(ExtType::Filename),
+            Some(&b"a.txt"[..])
+        );
+        assert_eq!(peeked.extensions.sequence(), Some(3));
+        assert_eq!(peeked.payload.end, encoded.len());
+        let header = &encoded[..peeked.header_size()];
+        assert_eq!(GUSProtocol::peek_header(header).unwrap(), peeked);
+
+        let shown = peeked.to_string();
+        assert!(shown.starts_with(&format!(
+            "GUS version 12 frame, {} header bytes\n",
+            peeked.header_size()
+        )));
+        for line in [
+            "fragment: 1 of 2 of message 9",
+            "flags: 0x09 (crc32, scrambled)",
+            "codec: hamming-7-4, 0 bits of padding",
+            "interleaving: 4 deep",
+            "scrambler seed: 0xace12345",
+            "extension Filename: [61, 2e, 74, 78, 74]",
+            "extension Sequence: [03, 00, 00, 00]",
+        ] {
+            assert!(shown.lines().any(|shown| shown == line), "{line}\n{shown}");
+        }
+        assert_eq!(
+            shown.lines().last().unwrap(),
+            format!(
+                "payload: bytes {} to {}",
+                peeked.header_size(),
+                encoded.len()
+            )
+        );
+
+        // a version 2 header, with none of them
+        let encoded = packet().encode_as(FrameVersion::V2).unwrap();
+        let peeked = GUSProtocol::peek_header(&encoded[..20]).unwrap();
+        assert_eq!(peeked.version, FrameVersion::V2);
+        assert_eq!(peeked.fragment, Fragment::default());
+        assert_eq!(peeked.flags, Flags::empty());
+        assert_eq!(peeked.payload, 20..encoded.len());
+        assert!(peeked.extensions.is_empty());
+    }
+}
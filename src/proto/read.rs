@@ -0,0 +1,356 @@
+//! Decoding frames straight off a [`Read`], or a buffer of them, one at a
+//! time.
+
+use std::io::Read;
+
+use crate::proto::{DecodeReport, GUSProtocol, ProtocolError, header::FrameHeader};
+
+/// Reads from `reader` until `frame` holds `len` bytes, or the stream ends.
+fn fill(reader: &mut impl Read, frame: &mut Vec<u8>, len: usize) -> Result<(), ProtocolError> {
+    if frame.len() < len {
+        reader
+            .by_ref()
+            .take((len - frame.len()) as u64)
+            .read_to_end(frame)?;
+    }
+    match frame.len() {
+        0 => Err(ProtocolError::EndOfStream),
+        got if got < len => Err(ProtocolError::TooShort { needed: len, got }),
+        _ => Ok(()),
+    }
+}
+
+impl GUSProtocol {
+    /// The largest frame [`GUSProtocol::read_frame`] takes, so a hostile or
+    /// corrupted length can't have it read, and hold, whatever it says. Well
+    /// past anything sent in one piece, split larger data into fragments.
+    pub const MAX_FRAME_BYTES: usize = 1 << 30;
+
+    /// Reads one frame off `reader` and decodes it, like
+    /// [`GUSProtocol::decode`]. Only the bytes of the frame are read, see
+    /// [`GUSProtocol::read_frame`].
+    pub fn decode_from(reader: &mut impl Read) -> Result<(Self, DecodeReport), ProtocolError> {
+        Self::decode(Self::read_frame(reader)?)
+    }
+
+    /// Reads the bytes of one frame off `reader` without decoding the
+    /// payload: the header, then as many payload bytes as it says, so the
+    /// reader is left at the start of whatever comes next.
+    ///
+    /// A header corrupted badly enough to be misread can take a few bytes more
+    /// than it should, but then there's no telling where the next frame starts
+    /// anyway.
+    ///
+    /// A stream that ends before the frame does gives
+    /// [`ProtocolError::TooShort`], one that ends before it even starts
+    /// [`ProtocolError::EndOfStream`].
+    ///
+    /// Frames longer than [`GUSProtocol::MAX_FRAME_BYTES`] are refused, see
+    /// [`GUSProtocol::read_frame_with_max`] for another limit.
+    pub fn read_frame(reader: &mut impl Read) -> Result<Vec<u8>, ProtocolError> {
+        Self::read_frame_with_max(reader, Self::MAX_FRAME_BYTES)
+    }
+
+    /// Like [`GUSProtocol::read_frame`], refusing frames whose header says
+    /// they're longer than `max_frame_bytes` with
+    /// [`ProtocolError::FrameTooLarge`], before reading any of the payload.
+    pub fn read_frame_with_max(
+        reader: &mut impl Read,
+        max_frame_bytes: usize,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut frame = Vec::with_capacity(FrameHeader::MIN_SIZE);
+        let mut header_size = FrameHeader::MIN_SIZE;
+        // the hint grows as far as the extensions once the fixed header is in
+        loop {
+            fill(reader, &mut frame, header_size)?;
+            match FrameHeader::size_hint(&frame)? {
+                hint if hint > header_size => header_size = hint,
+                _ => break,
+            }
+        }
+
+        let frame_size = FrameHeader::decode(&frame)?.frame_size()?;
+        if frame_size > max_frame_bytes {
+            return Err(ProtocolError::FrameTooLarge {
+                len: frame_size,
+                max: max_frame_bytes,
+            });
+        }
+        fill(reader, &mut frame, frame_size)?;
+
+        Ok(frame)
+    }
+
+    /// Decodes the frame at the start of `buffer`, returning it along with
+    /// how many bytes of `buffer` it took up. Whatever comes after it is left
+    /// alone, the next frame or otherwise, for the caller to go on from.
+    ///
+    /// A `buffer` that holds only the start of a frame gives
+    /// [`ProtocolError::TooShort`], with `needed` as many bytes as it takes
+    /// to get any further: a stream driver can wait for that many and try
+    /// again. It's only a lower bound until the whole header is in, since
+    /// the header says how long the rest is.
+    pub fn decode_prefix(buffer: &[u8]) -> Result<(Self, DecodeReport, usize), ProtocolError> {
+        let too_short = |needed| ProtocolError::TooShort {
+            needed,
+            got: buffer.len(),
+        };
+        let header_size = FrameHeader::size_hint(buffer)?;
+        if buffer.len() < header_size {
+            return Err(too_short(header_size));
+        }
+        let frame_size = FrameHeader::decode(buffer)?.frame_size()?;
+        let frame = buffer.get(..frame_size).ok_or(too_short(frame_size))?;
+        let (packet, report) = Self::decode_ref(frame)?;
+        Ok((packet.into_owned(), report, frame_size))
+    }
+
+    /// Decodes every frame in `buffer`, sent back to back as
+    /// [`GUSProtocol::encode`] makes them, in order.
+    ///
+    /// The first frame that doesn't decode, a partial one at the end
+    /// included, fails the whole buffer with [`ProtocolError::BadFrame`] at
+    /// the offset it starts at. The frames after it aren't tried: a header
+    /// that's off can't say where the next frame starts, and one that isn't
+    /// was sent by something to be told about. See
+    /// [`GUSProtocol::decode_next`] for skipping past what doesn't decode.
+    pub fn decode_all(buffer: &[u8]) -> Result<Vec<(Self, DecodeReport)>, ProtocolError> {
+        let mut rest = buffer;
+        let mut frames = Vec::new();
+        loop {
+            let offset = buffer.len() - rest.len();
+            let decoded = match Self::read_frame(&mut rest) {
+                Err(ProtocolError::EndOfStream) => return Ok(frames),
+                frame => frame.and_then(Self::decode),
+            };
+            frames.push(decoded.map_err(|e| ProtocolError::BadFrame {
+                offset,
+                source: Box::new(e),
+            })?);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{FrameVersion, Length, PROTOCOL_NAME},
+    };
+
+    #[test]
+    fn test_back_to_back() {
+        let mut rng = rand::rng();
+        let first = BitVec::random(100, &mut rng);
+        let second = BitVec::random(7, &mut rng);
+        let mut stream = GUSProtocol::new(first.clone()).unwrap().encode().unwrap();
+        stream.extend(
+            GUSProtocol::new(second.clone())
+                .unwrap()
+                .encode_as(FrameVersion::V2)
+                .unwrap(),
+        );
+        let frames_end = stream.len();
+        stream.extend(b"garbage");
+        let mut reader = Cursor::new(stream);
+
+        let (packet, report) = GUSProtocol::decode_from(&mut reader).unwrap();
+        assert_eq!(packet.data.to_vec(), first.to_vec());
+        assert_eq!(report.corrected, None);
+        assert_eq!(reader.position() as usize, report.frame_bytes);
+
+        let (packet, _) = GUSProtocol::decode_from(&mut reader).unwrap();
+        assert_eq!(packet.version, FrameVersion::V2);
+        assert_eq!(packet.data.to_vec(), second.to_vec());
+        assert_eq!(reader.position() as usize, frames_end);
+
+        // not enough left for a header
+        assert!(matches!(
+            GUSProtocol::decode_from(&mut reader),
+            Err(ProtocolError::TooShort { needed: 20, got: 7 })
+        ));
+        assert!(matches!(
+            GUSProtocol::decode_from(&mut reader),
+            Err(ProtocolError::EndOfStream)
+        ));
+    }
+
+    #[test]
+    fn test_end_of_stream() {
+        let frame = GUSProtocol::new(BitVec::random(64, &mut rand::rng()))
+            .unwrap()
+            .encode()
+            .unwrap();
+
+        let mut empty = Cursor::new(Vec::new());
+        assert!(matches!(
+            GUSProtocol::decode_from(&mut empty),
+            Err(ProtocolError::EndOfStream)
+        ));
+
+        // cut anywhere, the frame comes up short rather than ending the stream
+        for len in 1..frame.len() {
+            let mut reader = Cursor::new(&frame[..len]);
+            match GUSProtocol::decode_from(&mut reader) {
+                Err(ProtocolError::TooShort { needed, got }) => {
+                    assert_eq!(got, len);
+                    assert!(needed > len && needed <= frame.len(), "cut at {len}");
+                }
+                other => panic!("cut at {len}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_hostile_length() {
+        let packet = GUSProtocol::new(BitVec::random(64, &mut rand::rng())).unwrap();
+        let mut frame = packet
+            .header(
+                &packet.payload(),
+                Length {
+                    data_length: 1 << 60,
+                    bits_length: 1 << 62,
+                },
+            )
+            .encode(PROTOCOL_NAME);
+        frame.extend([0xa5; 100]);
+
+        // refused from the header alone, nothing past it is read
+        let header = frame.len() - 100;
+        let mut reader = Cursor::new(&frame);
+        assert!(matches!(
+            GUSProtocol::read_frame(&mut reader),
+            Err(ProtocolError::FrameTooLarge { len, max: GUSProtocol::MAX_FRAME_BYTES })
+                if len == header + (1 << 60)
+        ));
+        assert_eq!(reader.position() as usize, header);
+
+        let encoded = packet.encode().unwrap();
+        assert!(matches!(
+            GUSProtocol::read_frame_with_max(&mut Cursor::new(&encoded), encoded.len() - 1),
+            Err(ProtocolError::FrameTooLarge { .. })
+        ));
+        assert_eq!(
+            GUSProtocol::read_frame_with_max(&mut Cursor::new(&encoded), encoded.len()).unwrap(),
+            encoded
+        );
+    }
+
+    #[test]
+    fn test_decode_all() {
+        let mut rng = rand::rng();
+        let sent: Vec<BitVec> = [100, 7, 300]
+            .into_iter()
+            .map(|len| BitVec::random(len, &mut rng))
+            .collect();
+        let frames: Vec<Vec<u8>> = sent
+            .iter()
+            .map(|data| GUSProtocol::new(data.clone()).unwrap().encode().unwrap())
+            .collect();
+        let buffer = frames.concat();
+
+        let decoded = GUSProtocol::decode_all(&buffer).unwrap();
+        assert_eq!(decoded.len(), 3);
+        for ((packet, report), (data, frame)) in decoded.iter().zip(sent.iter().zip(&frames)) {
+            assert_eq!(packet.data.to_vec(), data.to_vec());
+            assert_eq!(report.frame_bytes, frame.len());
+        }
+        assert!(GUSProtocol::decode_all(&[]).unwrap().is_empty());
+
+        // a middle frame with more errors than its code can correct
+        let mut corrupted = buffer.clone();
+        let second = frames[0].len();
+        let payload = second + FrameHeader::size(FrameVersion::CURRENT);
+        for byte in &mut corrupted[payload..second + frames[1].len()] {
+            *byte ^= 0xff;
+        }
+        let error = GUSProtocol::decode_all(&corrupted).unwrap_err();
+        assert!(matches!(
+            &error,
+            ProtocolError::BadFrame { offset, .. } if *offset == second
+        ));
+        assert!(error.to_string().contains(&second.to_string()));
+
+        // and a frame cut short at the end
+        let third = second + frames[1].len();
+        let cut = &buffer[..buffer.len() - 1];
+        assert!(matches!(
+            GUSProtocol::decode_all(cut),
+            Err(ProtocolError::BadFrame { offset, source })
+                if offset == third && matches!(*source, ProtocolError::TooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_prefix() {
+        let data = BitVec::from_whole_bytes(b"prefix".to_vec());
+        let frame = GUSProtocol::data(data.clone()).encode().unwrap();
+
+        // garbage after the frame is none of its business
+        let mut buffer = frame.clone();
+        buffer.extend_from_slice(b"\xff\x00 not a frame");
+        let (decoded, report, consumed) = GUSProtocol::decode_prefix(&buffer).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(consumed, frame.len());
+        assert_eq!(report.frame_bytes, frame.len());
+
+        // a byte at a time, waiting on as many as it asks for
+        let mut received = Vec::new();
+        let mut waits = 0;
+        for &byte in &frame {
+            received.push(byte);
+            match GUSProtocol::decode_prefix(&received) {
+                Err(ProtocolError::TooShort { needed, got }) => {
+                    assert_eq!(got, received.len());
+                    assert!(needed > got && needed <= frame.len(), "{needed} of {got}");
+                    waits += 1;
+                }
+                Ok((decoded, _, consumed)) => {
+                    assert_eq!(decoded.data.to_vec(), data.to_vec());
+                    assert_eq!(consumed, frame.len());
+                }
+                Err(e) => panic!("{e} after {} bytes", received.len()),
+            }
+        }
+        assert_eq!(waits, frame.len() - 1);
+        assert!(matches!(
+            GUSProtocol::decode_prefix(&[]),
+            Err(ProtocolError::TooShort { got: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_prefix_sequential() {
+        let messages: [&[u8]; 2] = [b"first", b"and the second"];
+        let buffer: Vec<u8> = messages
+            .iter()
+            .flat_map(|message| {
+                GUSProtocol::data(BitVec::from_whole_bytes(message.to_vec()))
+                    .encode()
+                    .unwrap()
+            })
+            .collect();
+
+        let mut offset = 0;
+        let mut decoded = Vec::new();
+        while offset < buffer.len() {
+            let (packet, _, consumed) = GUSProtocol::decode_prefix(&buffer[offset..]).unwrap();
+            decoded.push(packet.data.into_inner());
+            offset += consumed;
+        }
+        assert_eq!(decoded, messages);
+        assert_eq!(offset, buffer.len());
+    }
+
+    #[test]
+    fn test_garbage() {
+        let mut reader = Cursor::new([0x55; 64]);
+        assert!(matches!(
+            GUSProtocol::decode_from(&mut reader),
+            Err(ProtocolError::CorruptedHeader)
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+//! Putting fragmented messages back together, see [`GUSProtocol::fragment`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    encoding::bitvec::BitVec,
+    proto::{Fragment, GUSProtocol, ProtocolError},
+};
+
+/// What pushing a fragment into a [`Reassembler`] came to.
+#[derive(Debug, Clone)]
+pub enum Reassembly {
+    /// The fragment completed its message, which is all here.
+    Complete(BitVec),
+    /// The message is still missing fragments.
+    Pending,
+    /// The fragment had already been pushed, it was dropped.
+    Duplicate,
+}
+
+/// The fragments of one message received so far.
+#[derive(Debug, Clone)]
+struct Message {
+    count: u32,
+    fragments: BTreeMap<u32, BitVec>,
+}
+
+/// Collects fragments, in any order, until their message is complete.
+#[derive(Debug, Clone, Default)]
+pub struct Reassembler {
+    messages: HashMap<u32, Message>,
+    /// Messages already handed out, so late duplicates don't start them over.
+    completed: HashSet<u32>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes in a decoded packet. A packet holding a whole message comes
+    /// straight back out, without touching anything kept for other messages.
+    ///
+    /// Fails if the fragment doesn't agree with the ones before it on how many
+    /// fragments the message has, or its index is out of range.
+    pub fn push(&mut self, packet: GUSProtocol) -> Result<Reassembly, ProtocolError> {
+        let Fragment {
+            message_id,
+            index,
+            count,
+        } = packet.fragment;
+        if count == 1 && index == 0 {
+            return Ok(Reassembly::Complete(packet.data));
+        }
+        if index >= count {
+            return Err(ProtocolError::FragmentOutOfRange {
+                message_id,
+                index,
+                count,
+            });
+        }
+        if self.completed.contains(&message_id) {
+            return Ok(Reassembly::Duplicate);
+        }
+
+        let message = self.messages.entry(message_id).or_insert_with(|| Message {
+            count,
+            fragments: BTreeMap::new(),
+        });
+        if message.count != count {
+            return Err(ProtocolError::FragmentCountMismatch {
+                message_id,
+                index,
+                count,
+                expected: message.count,
+            });
+        }
+        if message.fragments.contains_key(&index) {
+            return Ok(Reassembly::Duplicate);
+        }
+        message.fragments.insert(index, packet.data);
+        if message.fragments.len() < count as usize {
+            return Ok(Reassembly::Pending);
+        }
+
+        let message = self
+            .messages
+            .remove(&message_id)
+            .expect("the message was just updated");
+        self.completed.insert(message_id);
+        let mut data = BitVec::new();
+        for fragment in message.fragments.values() {
+            data.append(fragment);
+        }
+        Ok(Reassembly::Complete(data))
+    }
+
+    /// Ids of the messages still missing fragments.
+    pub fn pending(&self) -> impl Iterator<Item = u32> + '_ {
+        self.messages.keys().copied()
+    }
+
+    /// Indices of the fragments of `message_id` not received yet, in order,
+    /// or `None` if no fragment of it is pending.
+    pub fn missing(&self, message_id: u32) -> Option<Vec<u32>> {
+        let message = self.messages.get(&message_id)?;
+        Some(
+            (0..message.count)
+                .filter(|index| !message.fragments.contains_key(index))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `reassembly` came to, with the data of a complete message as bits.
+    fn outcome(reassembly: Reassembly) -> Result<Vec<bool>, &'static str> {
+        match reassembly {
+            Reassembly::Complete(data) => Ok(data.to_vec()),
+            Reassembly::Pending => Err("pending"),
+            Reassembly::Duplicate => Err("duplicate"),
+        }
+    }
+
+    /// The fragments of `data`, through the wire and back.
+    fn fragments(data: &BitVec, max_fragment_bits: usize) -> Vec<GUSProtocol> {
+        GUSProtocol::fragment(data.clone(), max_fragment_bits)
+            .into_iter()
+            .map(|packet| GUSProtocol::decode(packet.encode().unwrap()).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let data = BitVec::random(100, &mut rand::rng());
+        let mut packets = fragments(&data, 16);
+        assert_eq!(packets.len(), 7);
+        let message_id = packets[0].fragment.message_id;
+        assert!(packets.iter().all(|packet| {
+            packet.fragment.message_id == message_id && packet.fragment.count == 7
+        }));
+
+        packets.reverse();
+        packets.swap(1, 4);
+        let last = packets.pop().unwrap();
+        let mut reassembler = Reassembler::new();
+        for packet in packets {
+            assert_eq!(outcome(reassembler.push(packet).unwrap()), Err("pending"));
+        }
+        assert_eq!(reassembler.pending().collect::<Vec<_>>(), vec![message_id]);
+        assert_eq!(outcome(reassembler.push(last).unwrap()), Ok(data.to_vec()));
+        assert_eq!(reassembler.pending().count(), 0);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let data = BitVec::random(40, &mut rand::rng());
+        let packets = fragments(&data, 16);
+        let mut reassembler = Reassembler::new();
+
+        assert_eq!(
+            outcome(reassembler.push(packets[1].clone()).unwrap()),
+            Err("pending")
+        );
+        assert_eq!(
+            outcome(reassembler.push(packets[1].clone()).unwrap()),
+            Err("duplicate")
+        );
+        assert_eq!(
+            outcome(reassembler.push(packets[0].clone()).unwrap()),
+            Err("pending")
+        );
+        assert_eq!(
+            outcome(reassembler.push(packets[2].clone()).unwrap()),
+            Ok(data.to_vec())
+        );
+        // late ones don't start the message over
+        assert_eq!(
+            outcome(reassembler.push(packets[0].clone()).unwrap()),
+            Err("duplicate")
+        );
+        assert_eq!(reassembler.pending().count(), 0);
+    }
+
+    #[test]
+    fn test_missing() {
+        let data = BitVec::random(80, &mut rand::rng());
+        let packets = fragments(&data, 10);
+        let message_id = packets[0].fragment.message_id;
+        let mut reassembler = Reassembler::new();
+        assert_eq!(reassembler.missing(message_id), None);
+
+        for (index, packet) in packets.into_iter().enumerate() {
+            if index != 3 && index != 6 {
+                reassembler.push(packet).unwrap();
+            }
+        }
+        assert_eq!(reassembler.missing(message_id), Some(vec![3, 6]));
+    }
+
+    #[test]
+    fn test_whole_message() {
+        // data that fits goes out exactly as it did before fragmenting
+        let data = BitVec::random(64, &mut rand::rng());
+        let packets = GUSProtocol::fragment(data.clone(), 64);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(
+            packets[0].clone().encode().unwrap(),
+            GUSProtocol::new(data.clone()).unwrap().encode().unwrap()
+        );
+
+        let mut reassembler = Reassembler::new();
+        let packet = GUSProtocol::decode(packets[0].clone().encode().unwrap())
+            .unwrap()
+            .0;
+        assert_eq!(
+            outcome(reassembler.push(packet.clone()).unwrap()),
+            Ok(data.to_vec())
+        );
+        // and isn't remembered, the same packet is as good the next time
+        assert_eq!(
+            outcome(reassembler.push(packet).unwrap()),
+            Ok(data.to_vec())
+        );
+        assert_eq!(reassembler.pending().count(), 0);
+
+        // empty data is a single, empty fragment
+        let packets = fragments(&BitVec::new(), 8);
+        assert_eq!(packets.len(), 1);
+        assert!(packets[0].data.is_empty());
+    }
+
+    #[test]
+    fn test_inconsistent() {
+        let data = BitVec::random(30, &mut rand::rng());
+        let mut packets = fragments(&data, 10);
+        let mut reassembler = Reassembler::new();
+        reassembler.push(packets[0].clone()).unwrap();
+
+        packets[1].fragment.count = 4;
+        assert!(matches!(
+            reassembler.push(packets[1].clone()),
+            Err(ProtocolError::FragmentCountMismatch {
+                index: 1,
+                count: 4,
+                expected: 3,
+                ..
+            })
+        ));
+        packets[1].fragment.index = 4;
+        assert!(matches!(
+            reassembler.push(packets[1].clone()),
+            Err(ProtocolError::FragmentOutOfRange {
+                index: 4,
+                count: 4,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+//! What [`GUSProtocol::decode`] found out about a frame besides its packet.
+
+use crate::{encoding::hamming::DecodeStatus, proto::FrameVersion};
+
+#[cfg(doc)]
+use crate::proto::GUSProtocol;
+
+/// How a frame came through decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodeReport {
+    /// What was corrected, `None` if the frame arrived as it was sent.
+    pub corrected: Option<CorrectionInfo>,
+    /// Bits of data in the packet.
+    pub payload_bits: usize,
+    /// Bytes the frame took up, header and payload.
+    pub frame_bytes: usize,
+    pub version: FrameVersion,
+    pub integrity: IntegrityStatus,
+    /// How long the frame took to get here, if it says when it was sent.
+    pub latency: Option<Latency>,
+}
+
+/// Where decoding a frame flipped bits back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CorrectionInfo {
+    /// Corrected bits of the header, counted from the start of the frame.
+    /// Always empty for version 1 and 2 frames, whose header isn't protected.
+    pub header: Vec<usize>,
+    /// Corrected bits of the payload codeword, see
+    /// [`mapping::codeword_to_data`](crate::encoding::mapping::codeword_to_data)
+    /// for which data bit each one was.
+    pub payload: Vec<usize>,
+}
+
+impl CorrectionInfo {
+    /// The corrections of a frame whose header and payload came through as
+    /// `header` and `payload`, or `None` if there weren't any.
+    pub(super) fn new(header: &DecodeStatus, payload: &DecodeStatus) -> Option<Self> {
+        let info = Self {
+            header: positions(header),
+            payload: positions(payload),
+        };
+        (!info.header.is_empty() || !info.payload.is_empty()).then_some(info)
+    }
+
+    /// Bits corrected in all.
+    pub fn count(&self) -> usize {
+        self.header.len() + self.payload.len()
+    }
+}
+
+/// Whether the data was checked against a checksum. A mismatch doesn't
+/// decode at all, see [`ProtocolError::Integrity`](crate::proto::ProtocolError::Integrity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegrityStatus {
+    /// The data matches the checksum sent with it.
+    Verified,
+    /// The frame carried no checksum, as before version 6.
+    Unchecked,
+}
+
+/// The time between a frame being sent and decoded, going by the sender's
+/// clock for one and the receiver's for the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Latency {
+    /// Microseconds the frame spent on the way.
+    InFlight { micros: u64 },
+    /// The frame says it was sent this many microseconds after it arrived,
+    /// so the two clocks disagree by at least that much and there's nothing
+    /// to tell about the time in flight.
+    ClockSkew { micros: u64 },
+}
+
+impl Latency {
+    /// The latency of a frame sent at `sent_at` and received at
+    /// `received_at`, both in microseconds since the Unix epoch.
+    pub fn between(sent_at: u64, received_at: u64) -> Self {
+        match received_at.checked_sub(sent_at) {
+            Some(micros) => Self::InFlight { micros },
+            None => Self::ClockSkew {
+                micros: sent_at - received_at,
+            },
+        }
+    }
+}
+
+/// Codeword positions `status` corrected.
+pub(super) fn positions(status: &DecodeStatus) -> Vec<usize> {
+    match status {
+        DecodeStatus::Clean | DecodeStatus::Uncorrectable => Vec::new(),
+        DecodeStatus::Corrected { codeword_pos, .. } => vec![*codeword_pos],
+        DecodeStatus::CorrectedMany { codeword_positions } => codeword_positions.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{GUSProtocol, ProtocolError, header::FrameHeader},
+    };
+
+    #[test]
+    fn test_clean_frame() {
+        let data = BitVec::random(100, &mut rand::rng());
+        let encoded = GUSProtocol::new(data).unwrap().encode().unwrap();
+        let frame_bytes = encoded.len();
+
+        let (_, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(
+            report,
+            DecodeReport {
+                corrected: None,
+                payload_bits: 100,
+                frame_bytes,
+                version: FrameVersion::CURRENT,
+                integrity: IntegrityStatus::Verified,
+                latency: None,
+            }
+        );
+
+        let encoded = GUSProtocol::new(BitVec::random(100, &mut rand::rng()))
+            .unwrap()
+            .encode_as(FrameVersion::V5)
+            .unwrap();
+        let (_, report) = GUSProtocol::decode(encoded).unwrap();
+        assert_eq!(report.version, FrameVersion::V5);
+        assert_eq!(report.integrity, IntegrityStatus::Unchecked);
+    }
+
+    #[test]
+    fn test_corrected_frame() {
+        let data = BitVec::random(100, &mut rand::rng());
+        let mut encoded = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+        // a bit of the fields, and the 41st bit of the payload codeword
+        encoded[10] ^= 0x10;
+        encoded[FrameHeader::size(FrameVersion::CURRENT) + 5] ^= 0x40;
+
+        let (decoded, report) = GUSProtocol::decode(encoded.clone()).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+        assert_eq!(
+            report.corrected,
+            Some(CorrectionInfo {
+                header: vec![83],
+                payload: vec![41],
+            })
+        );
+        assert_eq!(report.corrected.unwrap().count(), 2);
+        assert_eq!(report.frame_bytes, encoded.len());
+        assert_eq!(report.integrity, IntegrityStatus::Verified);
+    }
+
+    #[test]
+    fn test_truncated_frame() {
+        let encoded = GUSProtocol::new(BitVec::random(100, &mut rand::rng()))
+            .unwrap()
+            .encode()
+            .unwrap();
+        let header = FrameHeader::size(FrameVersion::CURRENT);
+
+        assert!(matches!(
+            GUSProtocol::decode(encoded[..encoded.len() - 1].to_vec()),
+            Err(ProtocolError::TooShort { needed, got })
+                if needed == encoded.len() && got == encoded.len() - 1
+        ));
+        assert!(matches!(
+            GUSProtocol::decode(encoded[..header - 1].to_vec()),
+            Err(ProtocolError::TooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn test_latency() {
+        let sent_at = 1_700_000_000_000_000;
+        assert_eq!(
+            Latency::between(sent_at, sent_at + 2500),
+            Latency::InFlight { micros: 2500 }
+        );
+        assert_eq!(
+            Latency::between(sent_at, sent_at),
+            Latency::InFlight { micros: 0 }
+        );
+        // a sender whose clock is ahead of the receiver's
+        assert_eq!(
+            Latency::between(sent_at, sent_at - 700),
+            Latency::ClockSkew { micros: 700 }
+        );
+        assert_eq!(
+            Latency::between(u64::MAX, 0),
+            Latency::ClockSkew { micros: u64::MAX }
+        );
+
+        let mut packet = GUSProtocol::new(BitVec::random(100, &mut rand::rng())).unwrap();
+        let (_, report) = GUSProtocol::decode(packet.clone().encode().unwrap()).unwrap();
+        assert_eq!(report.latency, None);
+
+        packet.set_sent_at(sent_at).unwrap();
+        let encoded = packet.encode().unwrap();
+        let (_, report) = GUSProtocol::decode_ref_at(&encoded, || sent_at + 1234).unwrap();
+        assert_eq!(report.latency, Some(Latency::InFlight { micros: 1234 }));
+        let (_, report) = GUSProtocol::decode_ref_at(&encoded, || sent_at - 1).unwrap();
+        assert_eq!(report.latency, Some(Latency::ClockSkew { micros: 1 }));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let report = DecodeReport {
+            corrected: Some(CorrectionInfo {
+                header: vec![],
+                payload: vec![7],
+            }),
+            payload_bits: 13,
+            frame_bytes: 40,
+            version: FrameVersion::V6,
+            integrity: IntegrityStatus::Verified,
+            latency: Some(Latency::InFlight { micros: 1500 }),
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            json,
+            r#"{"corrected":{"header":[],"payload":[7]},"payload_bits":13,"frame_bytes":40,"version":6,"integrity":"Verified","latency":{"InFlight":{"micros":1500}}}"#
+        );
+        assert_eq!(serde_json::from_str::<DecodeReport>(&json).unwrap(), report);
+        assert!(serde_json::from_str::<FrameVersion>("13").is_err());
+    }
+}
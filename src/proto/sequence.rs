@@ -0,0 +1,227 @@
+//! Putting data frames back in the order they were sent, by their
+//! [`GUSProtocol::sequence`] number, for links that repeat, reorder or lose
+//! them: UDP, or a sender sending again a frame whose ack got lost.
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+#[cfg(doc)]
+use crate::proto::GUSProtocol;
+
+/// What comes out of a [`SequenceTracker`], in the order it was sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sequenced<T> {
+    /// The next frame.
+    Frame(T),
+    /// The sequence numbers of frames that never came, and aren't waited on
+    /// any longer.
+    Lost(RangeInclusive<u32>),
+}
+
+/// How the frames pushed into a [`SequenceTracker`] came in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SequenceStats {
+    /// Frames taken in, not counting duplicates.
+    pub received: u64,
+    /// Frames dropped for having been taken in already, or for coming after
+    /// their number was given up on.
+    pub duplicates: u64,
+    /// Frames that came in after one sent after them.
+    pub reordered: u64,
+    /// Sequence numbers given up on.
+    pub lost: u64,
+}
+
+/// Hands frames back out in sequence order, dropping duplicates. A frame
+/// that comes in ahead of the next one is held on to until the missing ones
+/// come in, or until one is `window` frames ahead of them, when they're
+/// given up on as lost.
+///
+/// The first frame pushed is taken to be the first one sent. Sequence
+/// numbers don't wrap around, a sender numbers at most [`u32::MAX`] frames.
+#[derive(Debug, Clone)]
+pub struct SequenceTracker<T> {
+    window: u64,
+    /// The number of the next frame to hand out, once there's been one.
+    next: Option<u64>,
+    buffered: BTreeMap<u64, T>,
+    stats: SequenceStats,
+}
+
+impl<T> Default for SequenceTracker<T> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_WINDOW)
+    }
+}
+
+impl<T> SequenceTracker<T> {
+    /// How far ahead frames get before the ones missing behind them are
+    /// given up on, unless told otherwise.
+    pub const DEFAULT_WINDOW: u32 = 16;
+
+    /// A tracker waiting on missing frames until one is `window` ahead of
+    /// them. A window of 0 gives up on a frame as soon as one after it comes
+    /// in.
+    pub fn new(window: u32) -> Self {
+        Self {
+            window: window as u64,
+            next: None,
+            buffered: BTreeMap::new(),
+            stats: SequenceStats::default(),
+        }
+    }
+
+    /// Takes in the frame numbered `sequence`, returning whatever it lets
+    /// out, in order: the frame itself if it was the next one, those held on
+    /// to after it, and any gap given up on in between.
+    pub fn push(&mut self, sequence: u32, frame: T) -> Vec<Sequenced<T>> {
+        let sequence = sequence as u64;
+        let next = *self.next.get_or_insert(sequence);
+        if sequence < next || self.buffered.contains_key(&sequence) {
+            log::debug!("Dropped a duplicate of frame {sequence}");
+            self.stats.duplicates += 1;
+            return Vec::new();
+        }
+
+        self.stats.received += 1;
+        if self.buffered.range(sequence..).next().is_some() {
+            self.stats.reordered += 1;
+        } else if sequence > next && self.buffered.is_empty() {
+            log::debug!("Frame {next} is missing, holding on to frame {sequence}");
+        }
+        self.buffered.insert(sequence, frame);
+        self.release(self.window)
+    }
+
+    /// Hands out every frame still held on to, giving up on the ones missing
+    /// between them, for once nothing else is coming.
+    pub fn finish(&mut self) -> Vec<Sequenced<T>> {
+        self.release(0)
+    }
+
+    pub fn stats(&self) -> SequenceStats {
+        self.stats
+    }
+
+    /// Hands out the frames from the next one on, skipping gaps that have a
+    /// frame `window` or more ahead of them.
+    fn release(&mut self, window: u64) -> Vec<Sequenced<T>> {
+        let mut released = Vec::new();
+        let Some(mut next) = self.next else {
+            return released;
+        };
+        while let Some((&first, _)) = self.buffered.first_key_value() {
+            if first != next {
+                let newest = *self.buffered.keys().next_back().expect("there's a first");
+                if newest - next < window {
+                    break;
+                }
+                log::debug!("Gave up on frames {next} to {}", first - 1);
+                self.stats.lost += first - next;
+                released.push(Sequenced::Lost(next as u32..=(first - 1) as u32));
+            }
+            let frame = self.buffered.remove(&first).expect("it's the first");
+            released.push(Sequenced::Frame(frame));
+            next = first + 1;
+        }
+        self.next = Some(next);
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pushes the frames numbered `sequences`, each with its own number for
+    /// a value, returning everything let out.
+    fn push_all(tracker: &mut SequenceTracker<u32>, sequences: &[u32]) -> Vec<Sequenced<u32>> {
+        sequences
+            .iter()
+            .flat_map(|&sequence| tracker.push(sequence, sequence))
+            .collect()
+    }
+
+    fn frames(sequences: impl IntoIterator<Item = u32>) -> Vec<Sequenced<u32>> {
+        sequences.into_iter().map(Sequenced::Frame).collect()
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut tracker = SequenceTracker::new(4);
+        assert_eq!(push_all(&mut tracker, &[5, 6, 6, 7, 5]), frames(5..=7));
+        // one held on to counts too
+        assert!(push_all(&mut tracker, &[9, 9]).is_empty());
+        assert_eq!(
+            tracker.stats(),
+            SequenceStats {
+                received: 4,
+                duplicates: 3,
+                reordered: 0,
+                lost: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_gap_filled() {
+        let mut tracker = SequenceTracker::new(4);
+        assert_eq!(push_all(&mut tracker, &[0, 1]), frames(0..=1));
+        assert!(push_all(&mut tracker, &[3, 5, 4]).is_empty());
+        assert_eq!(push_all(&mut tracker, &[2]), frames(2..=5));
+        assert_eq!(push_all(&mut tracker, &[6]), frames([6]));
+        assert_eq!(
+            tracker.stats(),
+            SequenceStats {
+                received: 7,
+                duplicates: 0,
+                reordered: 2,
+                lost: 0,
+            }
+        );
+        assert!(tracker.finish().is_empty());
+    }
+
+    #[test]
+    fn test_gap_lost() {
+        let mut tracker = SequenceTracker::new(3);
+        assert_eq!(push_all(&mut tracker, &[0]), frames([0]));
+        // 1 and 2 never come, and are given up on once 4 is 3 ahead of 1
+        assert!(push_all(&mut tracker, &[3]).is_empty());
+        let mut expected = vec![Sequenced::Lost(1..=2)];
+        expected.extend(frames(3..=4));
+        assert_eq!(push_all(&mut tracker, &[4]), expected);
+        // 2 turning up after all is too late
+        assert!(push_all(&mut tracker, &[2]).is_empty());
+        assert_eq!(push_all(&mut tracker, &[5]), frames([5]));
+        assert_eq!(
+            tracker.stats(),
+            SequenceStats {
+                received: 4,
+                duplicates: 1,
+                reordered: 0,
+                lost: 2,
+            }
+        );
+
+        // with no window, a gap is lost as soon as it's seen
+        let mut tracker = SequenceTracker::new(0);
+        let mut expected = frames([0]);
+        expected.push(Sequenced::Lost(1..=1));
+        expected.extend(frames([2]));
+        assert_eq!(push_all(&mut tracker, &[0, 2]), expected);
+    }
+
+    #[test]
+    fn test_finish() {
+        let mut tracker = SequenceTracker::new(10);
+        assert_eq!(push_all(&mut tracker, &[10, 12, 15]), frames([10]));
+        let mut expected = vec![Sequenced::Lost(11..=11), Sequenced::Frame(12)];
+        expected.extend([Sequenced::Lost(13..=14), Sequenced::Frame(15)]);
+        assert_eq!(tracker.finish(), expected);
+        assert_eq!(tracker.stats().lost, 3);
+        assert!(tracker.finish().is_empty());
+        // nothing pushed, nothing to finish
+        assert!(SequenceTracker::<u32>::default().finish().is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+//! Finding frames in a buffer with junk around them, e.g. one captured off a
+//! serial line.
+
+use crate::{
+    encoding::hamming::HammingCodeBase,
+    proto::{DecodeReport, GUSProtocol, ProtocolError, header::FrameHeader},
+};
+
+/// Bytes of the frame `buffer` starts with, if it starts with a header that
+/// decodes, magic and all, whose lengths add up and fit in the buffer.
+fn frame_len(buffer: &[u8]) -> Option<usize> {
+    let decoded = FrameHeader::decode(buffer).ok()?;
+    let length = decoded.header.length;
+    if !length.adds_up() || HammingCodeBase::data_len(length.bits_length).is_err() {
+        return None;
+    }
+    decoded
+        .size
+        .checked_add(length.data_length)
+        .filter(|&len| len <= buffer.len())
+}
+
+impl GUSProtocol {
+    /// Offset of the first frame in `buffer`, skipping whatever comes before
+    /// it. A frame is only found if it's all there: a magic that happens to
+    /// turn up in junk goes unnoticed unless what follows it passes for the
+    /// rest of a header, with lengths that fit in the buffer.
+    pub fn find_frame(buffer: &[u8]) -> Option<usize> {
+        let last = buffer.len().checked_sub(FrameHeader::MIN_SIZE)?;
+        (0..=last).find(|&offset| frame_len(&buffer[offset..]).is_some())
+    }
+
+    /// Decodes the first frame in `buffer` that decodes, skipping junk before
+    /// it, and returns the offset just past it, where to look for the next.
+    ///
+    /// Something that looks like a frame but doesn't decode is skipped too,
+    /// and only its error comes back if nothing after it does either.
+    pub fn decode_next(buffer: &[u8]) -> Result<(Self, DecodeReport, usize), ProtocolError> {
+        let mut offset = 0;
+        let mut error = None;
+        while let Some(found) = Self::find_frame(&buffer[offset..]) {
+            let start = offset + found;
+            let end = start + frame_len(&buffer[start..]).expect("a frame was just found here");
+            if start > 0 {
+                log::debug!("Skipped {start} bytes before a frame");
+            }
+
+            match Self::decode(buffer[start..end].to_vec()) {
+                Ok((packet, report)) => return Ok((packet, report, end)),
+                Err(e) => {
+                    log::warn!("Skipping what looked like a frame at {start}: {e:#}");
+                    error = Some(e);
+                    offset = start + 1;
+                }
+            }
+        }
+
+        Err(error.unwrap_or(ProtocolError::NoFrame(buffer.len())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::{bitvec::BitVec, hamming::HammingError},
+        proto::FrameVersion,
+    };
+
+    const JUNK: &[u8] = b"\0\xff\x13noise on the line\r\n\x7f";
+
+    /// Random data in a frame of each version, with the data for checking.
+    fn frames() -> Vec<(Vec<u8>, BitVec)> {
+        let mut rng = rand::rng();
+        FrameVersion::ALL
+            .into_iter()
+            .map(|version| {
+                let data = BitVec::random(60, &mut rng);
+                let frame = GUSProtocol::new(data.clone())
+                    .unwrap()
+                    .encode_as(version)
+                    .unwrap();
+                (frame, data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_junk_prefixed() {
+        for (frame, data) in frames() {
+            let mut buffer = JUNK.to_vec();
+            buffer.extend(&frame);
+            assert_eq!(GUSProtocol::find_frame(&buffer), Some(JUNK.len()));
+
+            let (packet, report, end) = GUSProtocol::decode_next(&buffer).unwrap();
+            assert_eq!(packet.data.to_vec(), data.to_vec());
+            assert_eq!(report.frame_bytes, frame.len());
+            assert_eq!(end, buffer.len());
+        }
+    }
+
+    #[test]
+    fn test_junk_suffixed() {
+        for (frame, data) in frames() {
+            let mut buffer = frame.clone();
+            buffer.extend(JUNK);
+            assert_eq!(GUSProtocol::find_frame(&buffer), Some(0));
+
+            let (packet, _, end) = GUSProtocol::decode_next(&buffer).unwrap();
+            assert_eq!(packet.data.to_vec(), data.to_vec());
+            assert_eq!(end, frame.len());
+            assert!(GUSProtocol::decode_next(&buffer[end..]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_junk_interleaved() {
+        let frames = frames();
+        let mut buffer = Vec::new();
+        for (frame, _) in &frames {
+            buffer.extend(JUNK);
+            buffer.extend(frame);
+        }
+        buffer.extend(JUNK);
+
+        let mut offset = 0;
+        for (frame, data) in &frames {
+            let (packet, report, end) = GUSProtocol::decode_next(&buffer[offset..]).unwrap();
+            assert_eq!(packet.data.to_vec(), data.to_vec());
+            assert_eq!(end, JUNK.len() + frame.len());
+            assert_eq!(report.frame_bytes, frame.len());
+            offset += end;
+        }
+        assert!(matches!(
+            GUSProtocol::decode_next(&buffer[offset..]),
+            Err(ProtocolError::NoFrame(len)) if len == JUNK.len()
+        ));
+    }
+
+    #[test]
+    fn test_false_magic() {
+        let (frame, data) = frames().pop().unwrap();
+
+        // a magic and version with lengths running past the end of the buffer
+        let mut buffer = b"GUS\x02\xe8\x03\0\0\0\0\0\0\x40\x1f\0\0\0\0\0\0".to_vec();
+        // and one whose lengths don't add up
+        buffer.extend(b"GUS\x02\x01\0\0\0\0\0\0\0\x09\0\0\0\0\0\0\0");
+        buffer.extend(&frame);
+        assert_eq!(GUSProtocol::find_frame(&buffer), Some(40));
+
+        let (packet, _, end) = GUSProtocol::decode_next(&buffer).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+        assert_eq!(end, buffer.len());
+
+        // a truncated frame is no frame yet
+        assert_eq!(GUSProtocol::find_frame(&buffer[..buffer.len() - 1]), None);
+        assert_eq!(GUSProtocol::find_frame(b"GUS"), None);
+    }
+
+    #[test]
+    fn test_skips_corrupted_frame() {
+        let mut frames = frames();
+        let (good, data) = frames.pop().unwrap();
+        let (mut bad, _) = frames.remove(4);
+        // positions 8 and 64 of the version 5 frame's 67 bit codeword give
+        // syndrome 72, which the codec can only detect
+        let header = FrameHeader::size(FrameVersion::V5);
+        bad[header] ^= 0x01;
+        bad[header + 7] ^= 0x01;
+
+        let mut buffer = bad.clone();
+        buffer.extend(&good);
+        let (packet, _, end) = GUSProtocol::decode_next(&buffer).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+        assert_eq!(end, buffer.len());
+
+        // and with nothing after it, its error is what's left
+        assert!(matches!(
+            GUSProtocol::decode_next(&bad),
+            Err(ProtocolError::Codec(HammingError::Uncorrectable {
+                syndrome: 72
+            }))
+        ));
+    }
+}
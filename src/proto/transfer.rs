@@ -0,0 +1,340 @@
+//! Sending a whole file over a link that answers: [`send_file`] reads it a
+//! chunk at a time into numbered data frames, sending each until it's acked,
+//! and [`receive_file`] writes them back out in order.
+//!
+//! Each chunk goes out as a whole message of its own, its message id the
+//! same as its [`ExtType::Sequence`] number, and the last one also says how
+//! many bytes there were in all with an [`ExtType::TransferSize`], so the
+//! receiver knows when it's done and that nothing went missing.
+
+use std::io::{Read, Write};
+
+use crate::{
+    encoding::bitvec::BitVec,
+    proto::{
+        ArqConfig, CodecId, ExtType, Fragment, GUSProtocol, PacketType, ProtocolError,
+        ReliableSender, SequenceTracker, Sequenced, recv_reliable, transport::Transport,
+    },
+};
+
+/// How [`send_file`] sends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferConfig {
+    /// Bytes of the file in each frame, at least 1.
+    pub chunk_bytes: usize,
+    pub codec: CodecId,
+    pub arq: ArqConfig,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        Self {
+            chunk_bytes: 1024,
+            codec: CodecId::default(),
+            arq: ArqConfig::default(),
+        }
+    }
+}
+
+/// How a transfer went, as far as the end it comes from can tell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransferSummary {
+    /// Bytes of the file.
+    pub bytes: u64,
+    /// Data frames it took, not counting the ones sent again.
+    pub frames: u64,
+    /// Bits the receiver corrected, 0 on the sending end.
+    pub corrected: u64,
+    /// Frames sent again after a nack. The receiver only sees the ones that
+    /// got through twice, as duplicates.
+    pub retransmissions: u64,
+}
+
+/// Sends everything `reader` has on `transport`, a chunk of
+/// [`TransferConfig::chunk_bytes`] to a frame, waiting for each to be acked
+/// before reading the next. An empty file is a single, empty frame.
+///
+/// Fails once a frame's been nacked more than the config's
+/// [`ArqConfig::max_retries`] times.
+pub fn send_file(
+    mut reader: impl Read,
+    transport: &mut impl Transport,
+    config: &TransferConfig,
+) -> Result<TransferSummary, ProtocolError> {
+    let chunk_bytes = config.chunk_bytes.max(1);
+    let mut sender = ReliableSender::new(config.arq.clone());
+    let mut summary = TransferSummary::default();
+    let mut chunk = read_chunk(&mut reader, chunk_bytes)?;
+
+    for sequence in 0.. {
+        // a short chunk can only be the last, a full one needs a look ahead
+        let next = match chunk.len() == chunk_bytes {
+            true => read_chunk(&mut reader, chunk_bytes)?,
+            false => Vec::new(),
+        };
+        summary.bytes += chunk.len() as u64;
+
+        let mut packet = GUSProtocol {
+            fragment: Fragment {
+                message_id: sequence,
+                index: 0,
+                count: 1,
+            },
+            codec: config.codec,
+            ..GUSProtocol::data(BitVec::from_whole_bytes(chunk))
+        };
+        packet.set_sequence(sequence)?;
+        if next.is_empty() {
+            packet
+                .extensions
+                .insert(ExtType::TransferSize, summary.bytes.to_le_bytes())?;
+        }
+        let attempts = sender.send(transport, &packet)?;
+        summary.frames += 1;
+        summary.retransmissions += (attempts - 1) as u64;
+
+        if next.is_empty() {
+            break;
+        }
+        chunk = next;
+    }
+    Ok(summary)
+}
+
+/// The receiving end of [`send_file`]: acks every frame that decodes, nacks
+/// every one that doesn't, and writes each chunk to `writer` once the ones
+/// before it are written, until the last.
+///
+/// Fails if the sender stops before the last frame, skips one, or the last
+/// one says the file was a different size than what came.
+pub fn receive_file(
+    transport: &mut impl Transport,
+    mut writer: impl Write,
+) -> Result<TransferSummary, ProtocolError> {
+    // frames go one at a time, there's nothing to wait for out of order
+    let mut tracker = SequenceTracker::new(0);
+    let mut summary = TransferSummary::default();
+
+    loop {
+        let (packet, report) = match recv_reliable(transport) {
+            Ok(received) => received,
+            Err(ProtocolError::EndOfStream) => {
+                return Err(ProtocolError::TransferIncomplete { got: summary.bytes });
+            }
+            Err(e) => return Err(e),
+        };
+        match packet.packet_type {
+            PacketType::Data => {}
+            PacketType::Heartbeat { .. } => continue,
+            got => {
+                return Err(ProtocolError::UnexpectedPacket {
+                    expected: "data",
+                    got,
+                });
+            }
+        }
+        let Some(sequence) = packet.sequence() else {
+            return Err(ProtocolError::UnexpectedPacket {
+                expected: "numbered data",
+                got: packet.packet_type,
+            });
+        };
+        summary.corrected += report.corrected.map_or(0, |corrected| corrected.count()) as u64;
+
+        for released in tracker.push(sequence, packet) {
+            let packet = match released {
+                Sequenced::Frame(packet) => packet,
+                Sequenced::Lost(lost) => {
+                    return Err(ProtocolError::TransferGap {
+                        first: *lost.start(),
+                        last: *lost.end(),
+                    });
+                }
+            };
+            let total = packet.extensions.transfer_size();
+            let chunk = packet.data.into_inner();
+            writer.write_all(&chunk)?;
+            summary.bytes += chunk.len() as u64;
+            summary.frames += 1;
+
+            if let Some(expected) = total {
+                writer.flush()?;
+                if expected != summary.bytes {
+                    return Err(ProtocolError::TransferSize {
+                        expected,
+                        got: summary.bytes,
+                    });
+                }
+                summary.retransmissions = tracker.stats().duplicates;
+                return Ok(summary);
+            }
+        }
+    }
+}
+
+/// Reads up to `size` bytes, fewer only at the end of `reader`.
+fn read_chunk(reader: &mut impl Read, size: usize) -> Result<Vec<u8>, ProtocolError> {
+    let mut chunk = Vec::with_capacity(size);
+    reader.take(size as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::{RngCore, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        encoding::channel::{BinarySymmetricChannel, Channel},
+        proto::transport::ChannelTransport,
+    };
+
+    /// Sends through `inner`, over a binary symmetric channel.
+    struct Noisy {
+        inner: ChannelTransport,
+        channel: BinarySymmetricChannel,
+    }
+
+    impl Transport for Noisy {
+        fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+            let mut bits = BitVec::from_whole_bytes(frame.to_vec());
+            self.channel.transmit(&mut bits);
+            self.inner.send(&bits.into_inner())
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+            self.inner.recv()
+        }
+    }
+
+    /// Sends `file` from one thread and receives it on another, returning
+    /// what each end says and what was written.
+    fn transfer(
+        file: Vec<u8>,
+        config: TransferConfig,
+        flip_probability: f64,
+    ) -> (TransferSummary, TransferSummary, Vec<u8>) {
+        let (near, mut far) = ChannelTransport::pair();
+        let mut near = Noisy {
+            inner: near,
+            channel: BinarySymmetricChannel::new(flip_probability, Some(631)),
+        };
+        let receiver = thread::spawn(move || {
+            let mut written = Vec::new();
+            let summary = receive_file(&mut far, &mut written).unwrap();
+            (summary, written)
+        });
+        let sent = send_file(&file[..], &mut near, &config).unwrap();
+        let (received, written) = receiver.join().unwrap();
+        (sent, received, written)
+    }
+
+    #[test]
+    fn test_noisy_transfer() {
+        let mut file = vec![0; 32 << 10];
+        StdRng::seed_from_u64(7).fill_bytes(&mut file);
+        let config = TransferConfig {
+            codec: CodecId::Hamming1511,
+            arq: ArqConfig {
+                max_retries: 10,
+                ..ArqConfig::default()
+            },
+            ..TransferConfig::default()
+        };
+        // noisy enough for some frames to take more than one try
+        let (sent, received, written) = transfer(file.clone(), config, 2e-3);
+        assert!(written == file, "the file came out different");
+        assert_eq!(sent.frames, 32);
+        assert_eq!(received.bytes, sent.bytes);
+        assert!(received.corrected > 100, "{received:?}");
+        assert!(sent.retransmissions > 0, "{sent:?}");
+    }
+
+    /// A few MB, as a file would be. Slow in a debug build, run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_large_noisy_transfer() {
+        let mut file = vec![0; 2 << 20];
+        StdRng::seed_from_u64(7).fill_bytes(&mut file);
+        let config = TransferConfig {
+            chunk_bytes: 4096,
+            codec: CodecId::Hamming1511,
+            arq: ArqConfig {
+                max_retries: 10,
+                ..ArqConfig::default()
+            },
+        };
+        let (sent, received, written) = transfer(file.clone(), config, 1e-4);
+        assert!(written == file, "the file came out different");
+
+        assert_eq!(sent.bytes, file.len() as u64);
+        assert_eq!(sent.frames, 512);
+        assert_eq!(received.bytes, sent.bytes);
+        assert_eq!(received.frames, sent.frames);
+        // plenty of single flips to fix, and a few frames with more
+        assert!(received.corrected > 100, "{received:?}");
+        assert!(sent.retransmissions > 0, "{sent:?}");
+        assert_eq!(sent.corrected, 0);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let config = TransferConfig {
+            chunk_bytes: 10,
+            ..TransferConfig::default()
+        };
+        for (len, frames) in [(0, 1), (7, 1), (10, 1), (11, 2), (30, 3)] {
+            let file: Vec<u8> = (0..len).collect();
+            let (sent, received, written) = transfer(file.clone(), config.clone(), 0.0);
+            assert_eq!(written, file);
+            assert_eq!(sent.frames, frames, "{len} bytes");
+            assert_eq!(received.frames, frames, "{len} bytes");
+            assert_eq!(received.retransmissions, 0);
+        }
+    }
+
+    #[test]
+    fn test_incomplete() {
+        let (mut near, mut far) = ChannelTransport::pair();
+        let mut packet = GUSProtocol::data(BitVec::from_whole_bytes(b"abc".to_vec()));
+        packet.set_sequence(0).unwrap();
+        near.send(&packet.clone().encode().unwrap()).unwrap();
+        // a gap
+        packet.set_sequence(2).unwrap();
+        near.send(&packet.clone().encode().unwrap()).unwrap();
+        drop(near);
+        assert!(matches!(
+            receive_file(&mut far, Vec::new()),
+            Err(ProtocolError::TransferGap { first: 1, last: 1 })
+        ));
+
+        // the sender going away before the last frame
+        let (mut near, mut far) = ChannelTransport::pair();
+        packet.set_sequence(0).unwrap();
+        near.send(&packet.clone().encode().unwrap()).unwrap();
+        drop(near);
+        assert!(matches!(
+            receive_file(&mut far, Vec::new()),
+            Err(ProtocolError::TransferIncomplete { got: 3 })
+        ));
+
+        // and a last frame that doesn't add up
+        let (mut near, mut far) = ChannelTransport::pair();
+        packet
+            .extensions
+            .insert(ExtType::TransferSize, 4u64.to_le_bytes())
+            .unwrap();
+        near.send(&packet.encode().unwrap()).unwrap();
+        assert!(matches!(
+            receive_file(&mut far, Vec::new()),
+            Err(ProtocolError::TransferSize {
+                expected: 4,
+                got: 3
+            })
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+//! How frames get from one end to the other, whole frames at a time, so the
+//! code on either end doesn't care whether it's a pipe or anything else.
+
+use std::{
+    collections::VecDeque,
+    io::{self, BufReader, Read, Stdin, Stdout, Write},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use crate::proto::{GUSProtocol, ProtocolError};
+
+mod tcp;
+mod udp;
+#[cfg(unix)]
+mod unix;
+
+pub use tcp::TcpTransport;
+pub use udp::{DEFAULT_MAX_DATAGRAM_BYTES, UdpTransport};
+#[cfg(unix)]
+pub use unix::{UnixSocketListener, UnixTransport};
+
+/// The largest frame a socket transport takes unless told otherwise, well
+/// past anything sent in one piece.
+pub const DEFAULT_MAX_FRAME_BYTES: usize = 16 << 20;
+
+/// Moves encoded frames, one at a time, in the order they were sent.
+pub trait Transport {
+    /// Sends the bytes of one frame.
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError>;
+
+    /// Waits for the bytes of the next frame, or
+    /// [`ProtocolError::EndOfStream`] once the other end is done sending.
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError>;
+}
+
+impl<T: Transport + ?Sized> Transport for &mut T {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        (**self).send(frame)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        (**self).recv()
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        (**self).send(frame)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        (**self).recv()
+    }
+}
+
+/// Frames written to stdout back to back and read off stdin, for a sender
+/// piped into a receiver. Frames tell themselves apart by their headers.
+pub struct StdioTransport {
+    stdin: BufReader<Stdin>,
+    stdout: Stdout,
+}
+
+impl StdioTransport {
+    pub fn new() -> Self {
+        Self {
+            stdin: BufReader::new(std::io::stdin()),
+            stdout: std::io::stdout(),
+        }
+    }
+}
+
+impl Default for StdioTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for StdioTransport {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        let mut stdout = self.stdout.lock();
+        stdout.write_all(frame)?;
+        Ok(stdout.flush()?)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        GUSProtocol::read_frame(&mut self.stdin)
+    }
+}
+
+/// Sends `frame` on a stream, after its length as a big-endian `u32`, which
+/// is all it takes to tell frames apart on a stream that doesn't lose bytes.
+fn write_prefixed(writer: &mut impl Write, frame: &[u8]) -> Result<(), ProtocolError> {
+    let len = u32::try_from(frame.len()).map_err(|_| ProtocolError::FrameTooLarge {
+        len: frame.len(),
+        max: u32::MAX as usize,
+    })?;
+    writer.write_all(&len.to_be_bytes()).map_err(stream_error)?;
+    writer.write_all(frame).map_err(stream_error)?;
+    writer.flush().map_err(stream_error)
+}
+
+/// Reads a frame [`write_prefixed`] sent, refusing any longer than `max`
+/// bytes before reading it.
+///
+/// A timeout partway through a frame leaves the stream somewhere in the
+/// middle of it, there's no reading another frame off it after that.
+fn read_prefixed(reader: &mut impl Read, max: usize) -> Result<Vec<u8>, ProtocolError> {
+    let mut prefix = [0; 4];
+    match read_full(reader, &mut prefix)? {
+        0 => return Err(ProtocolError::EndOfStream),
+        4 => {}
+        got => return Err(ProtocolError::ConnectionLost { needed: 4, got }),
+    }
+
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > max {
+        return Err(ProtocolError::FrameTooLarge { len, max });
+    }
+    let mut frame = vec![0; len];
+    match read_full(reader, &mut frame)? {
+        got if got < len => Err(ProtocolError::ConnectionLost { needed: len, got }),
+        _ => Ok(frame),
+    }
+}
+
+/// Reads into `buffer` until it's full or the stream ends, and says how much
+/// it got. A reset ends the stream here as a close would, it's up to the
+/// caller whether that's a lost connection or just the end.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, ProtocolError> {
+    let mut got = 0;
+    while got < buffer.len() {
+        match reader.read(&mut buffer[got..]) {
+            Ok(0) => break,
+            Ok(read) => got += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+                ) =>
+            {
+                break;
+            }
+            Err(e) => return Err(stream_error(e)),
+        }
+    }
+    Ok(got)
+}
+
+/// What an error on a socket means for the frames on it.
+fn stream_error(error: io::Error) -> ProtocolError {
+    match error.kind() {
+        // which one depends on the platform
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => ProtocolError::Timeout,
+        _ => ProtocolError::Io(error),
+    }
+}
+
+/// Frames one end of a [`ChannelTransport`] sent the other.
+#[derive(Default)]
+struct Queue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
+
+#[derive(Default)]
+struct QueueState {
+    frames: VecDeque<Vec<u8>>,
+    /// Whether the sending end is gone, so no more frames are coming.
+    closed: bool,
+}
+
+/// One end of an in-memory link, see [`ChannelTransport::pair`]. Either end
+/// can go to another thread.
+pub struct ChannelTransport {
+    outgoing: Arc<Queue>,
+    incoming: Arc<Queue>,
+}
+
+impl ChannelTransport {
+    /// Two ends of a link, what one sends the other receives. Receiving waits
+    /// for a frame, until the other end is dropped.
+    pub fn pair() -> (Self, Self) {
+        let (there, back) = (Arc::new(Queue::default()), Arc::new(Queue::default()));
+        (
+            Self {
+                outgoing: there.clone(),
+                incoming: back.clone(),
+            },
+            Self {
+                outgoing: back,
+                incoming: there,
+            },
+        )
+    }
+}
+
+impl Transport for ChannelTransport {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        let mut state = self.outgoing.state.lock().expect("queue lock poisoned");
+        state.frames.push_back(frame.to_vec());
+        self.outgoing.ready.notify_one();
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        let mut state = self.incoming.state.lock().expect("queue lock poisoned");
+        loop {
+            if let Some(frame) = state.frames.pop_front() {
+                return Ok(frame);
+            }
+            if state.closed {
+                return Err(ProtocolError::EndOfStream);
+            }
+            state = self
+                .incoming
+                .ready
+                .wait(state)
+                .expect("queue lock poisoned");
+        }
+    }
+}
+
+impl Drop for ChannelTransport {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.outgoing.state.lock() {
+            state.closed = true;
+        }
+        self.outgoing.ready.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::{bitvec::BitVec, channel::BinarySymmetricChannel},
+        proto::{PacketType, Reassembler, Reassembly},
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let data = BitVec::random(1000, &mut rand::rng());
+        let (mut sender, mut receiver) = ChannelTransport::pair();
+
+        // the same as the sender and receiver do over stdio, flips and all
+        let mut channel = BinarySymmetricChannel::new(0.001, Some(7));
+        for packet in GUSProtocol::fragment(data.clone(), 300) {
+            sender
+                .send(&packet.encode_over(&mut channel).unwrap())
+                .unwrap();
+        }
+        drop(sender);
+
+        let mut reassembler = Reassembler::new();
+        let mut messages = Vec::new();
+        loop {
+            let frame = match receiver.recv() {
+                Ok(frame) => frame,
+                Err(ProtocolError::EndOfStream) => break,
+                Err(e) => panic!("{e}"),
+            };
+            let (packet, _) = GUSProtocol::decode(frame).unwrap();
+            if let Reassembly::Complete(message) = reassembler.push(packet).unwrap() {
+                messages.push(message);
+            }
+        }
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_both_ways() {
+        let (mut near, mut far) = ChannelTransport::pair();
+        let echo = std::thread::spawn(move || {
+            while let Ok(frame) = far.recv() {
+                let (packet, _) = GUSProtocol::decode(frame).unwrap();
+                far.send(
+                    &GUSProtocol::ack(packet.fragment.message_id)
+                        .encode()
+                        .unwrap(),
+                )
+                .unwrap();
+            }
+        });
+
+        for message_id in 0..3 {
+            let mut packet = GUSProtocol::data(BitVec::random(50, &mut rand::rng()));
+            packet.fragment.message_id = message_id;
+            near.send(&packet.encode().unwrap()).unwrap();
+
+            let (reply, _) = GUSProtocol::decode(near.recv().unwrap()).unwrap();
+            assert_eq!(reply.packet_type, PacketType::Ack { message_id });
+        }
+        drop(near);
+        echo.join().unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+//! Frames over a TCP connection, each after its length.
+
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::proto::{
+    ProtocolError,
+    transport::{DEFAULT_MAX_FRAME_BYTES, Transport, read_prefixed, stream_error, write_prefixed},
+};
+
+/// One end of a TCP connection, sending each frame after its length as a
+/// big-endian `u32`.
+pub struct TcpTransport {
+    stream: TcpStream,
+    max_frame_bytes: usize,
+}
+
+impl TcpTransport {
+    /// Connects to `addr`, trying each address it resolves to in turn, giving
+    /// each up to `timeout` if there is one.
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        timeout: Option<Duration>,
+    ) -> Result<Self, ProtocolError> {
+        let mut last_error = None;
+        for addr in addr.to_socket_addrs()? {
+            let connected = match timeout {
+                Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                None => TcpStream::connect(addr),
+            };
+            match connected {
+                Ok(stream) => {
+                    log::debug!("Connected to {addr}");
+                    return Ok(Self::from_stream(stream));
+                }
+                Err(e) => last_error = Some((addr, e)),
+            }
+        }
+
+        Err(match last_error {
+            Some((addr, e)) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                ProtocolError::ConnectionRefused(addr.to_string())
+            }
+            Some((_, e)) => stream_error(e),
+            None => io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to").into(),
+        })
+    }
+
+    /// Waits for the next connection on `listener`.
+    pub fn accept(listener: &TcpListener) -> Result<Self, ProtocolError> {
+        let (stream, peer) = listener.accept()?;
+        log::debug!("Accepted a connection from {peer}");
+        Ok(Self::from_stream(stream))
+    }
+
+    pub fn from_stream(stream: TcpStream) -> Self {
+        // frames are sent whole, there's nothing to gain from waiting for more
+        if let Err(e) = stream.set_nodelay(true) {
+            log::debug!("Couldn't disable Nagle's algorithm: {e}");
+        }
+        Self {
+            stream,
+            max_frame_bytes: DEFAULT_MAX_FRAME_BYTES,
+        }
+    }
+
+    /// How long sending or receiving a frame may block before giving up with
+    /// [`ProtocolError::Timeout`], `None` to wait for as long as it takes.
+    pub fn set_timeout(&self, timeout: Option<Duration>) -> Result<(), ProtocolError> {
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// The largest frame [`Transport::recv`] takes, so a corrupted or hostile
+    /// length can't make it allocate whatever it says.
+    /// [`DEFAULT_MAX_FRAME_BYTES`] unless set.
+    pub fn with_max_frame_bytes(mut self, max_frame_bytes: usize) -> Self {
+        self.max_frame_bytes = max_frame_bytes;
+        self
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr, ProtocolError> {
+        Ok(self.stream.peer_addr()?)
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        write_prefixed(&mut self.stream, frame)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        read_prefixed(&mut self.stream, self.max_frame_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, thread};
+
+    use super::*;
+    use crate::{encoding::bitvec::BitVec, proto::GUSProtocol};
+
+    /// A listener on a free port, and where to reach it.
+    fn listener() -> (TcpListener, SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        (listener, addr)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (listener, addr) = listener();
+        let data = BitVec::random(500, &mut rand::rng());
+
+        let expected = data.clone();
+        let receiver = thread::spawn(move || {
+            let mut transport = TcpTransport::accept(&listener).unwrap();
+            let (packet, report) = GUSProtocol::decode(transport.recv().unwrap()).unwrap();
+            assert_eq!(packet.data.to_vec(), expected.to_vec());
+            assert!(report.corrected.is_some());
+            assert!(matches!(transport.recv(), Err(ProtocolError::EndOfStream)));
+        });
+
+        let mut transport = TcpTransport::connect(addr, Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(transport.peer_addr().unwrap(), addr);
+        let mut frame = GUSProtocol::new(data).unwrap().encode().unwrap();
+        // a bit of the payload flipped on the way
+        let last = frame.len() - 1;
+        frame[last] ^= 0x80;
+        transport.send(&frame).unwrap();
+        drop(transport);
+
+        receiver.join().unwrap();
+    }
+
+    #[test]
+    fn test_refused() {
+        // a port that was free a moment ago
+        let addr = listener().1;
+        assert!(matches!(
+            TcpTransport::connect(addr, Some(Duration::from_secs(5))),
+            Err(ProtocolError::ConnectionRefused(refused)) if refused == addr.to_string()
+        ));
+    }
+
+    #[test]
+    fn test_lost_mid_frame() {
+        let (listener, addr) = listener();
+        let sender = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            // a 100 byte frame, cut short
+            stream.write_all(&100u32.to_be_bytes()).unwrap();
+            stream.write_all(&[0xa5; 10]).unwrap();
+        });
+
+        let mut transport = TcpTransport::accept(&listener).unwrap();
+        sender.join().unwrap();
+        assert!(matches!(
+            transport.recv(),
+            Err(ProtocolError::ConnectionLost {
+                needed: 100,
+                got: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_limits() {
+        let (listener, addr) = listener();
+        let sender = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(&u32::MAX.to_be_bytes()).unwrap();
+            // and then nothing, for longer than the receiver waits
+            thread::sleep(Duration::from_millis(500));
+        });
+
+        let mut transport = TcpTransport::accept(&listener)
+            .unwrap()
+            .with_max_frame_bytes(1024);
+        assert!(matches!(
+            transport.recv(),
+            Err(ProtocolError::FrameTooLarge {
+                len: 0xffff_ffff,
+                max: 1024
+            })
+        ));
+
+        transport
+            .set_timeout(Some(Duration::from_millis(50)))
+            .unwrap();
+        assert!(matches!(transport.recv(), Err(ProtocolError::Timeout)));
+        sender.join().unwrap();
+    }
+}
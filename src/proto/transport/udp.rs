@@ -0,0 +1,203 @@
+//! Frames over UDP, one per datagram.
+
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use crate::proto::{
+    ProtocolError,
+    transport::{Transport, stream_error},
+};
+
+/// The most a datagram can carry over IPv4, and so the default limit.
+pub const DEFAULT_MAX_DATAGRAM_BYTES: usize = 65_507;
+
+/// A UDP socket sending each frame as a datagram of its own. Nothing makes
+/// sure datagrams arrive, or arrive in order, the frames only have their codes
+/// to get them through corruption.
+///
+/// There's no end to the frames either: [`Transport::recv`] waits for the next
+/// one for as long as the read timeout allows, and never gives
+/// [`ProtocolError::EndOfStream`].
+pub struct UdpTransport {
+    socket: UdpSocket,
+    /// Where frames go, the connected address or else whoever sent the last
+    /// one.
+    peer: Option<SocketAddr>,
+    connected: bool,
+    max_datagram_bytes: usize,
+}
+
+impl UdpTransport {
+    /// A socket on `addr` taking frames from anyone. It can only send once it
+    /// received, back to whoever sent the last frame.
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, ProtocolError> {
+        let socket = UdpSocket::bind(addr)?;
+        log::debug!("Listening for datagrams on {}", socket.local_addr()?);
+        Ok(Self {
+            socket,
+            peer: None,
+            connected: false,
+            max_datagram_bytes: DEFAULT_MAX_DATAGRAM_BYTES,
+        })
+    }
+
+    /// A socket on any free port sending frames to `addr`, and only taking
+    /// them from there.
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, ProtocolError> {
+        let peer = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+        })?;
+        let local: SocketAddr = match peer {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(peer)?;
+        Ok(Self {
+            socket,
+            peer: Some(peer),
+            connected: true,
+            max_datagram_bytes: DEFAULT_MAX_DATAGRAM_BYTES,
+        })
+    }
+
+    /// How long [`Transport::recv`] waits for a datagram before giving up with
+    /// [`ProtocolError::Timeout`], `None` to wait for as long as it takes.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), ProtocolError> {
+        Ok(self.socket.set_read_timeout(timeout)?)
+    }
+
+    /// The largest frame sent or taken, [`DEFAULT_MAX_DATAGRAM_BYTES`] unless
+    /// set. Keeping it under the path's MTU keeps datagrams from being split
+    /// up on the way, and lost whole if any piece is.
+    pub fn with_max_datagram_bytes(mut self, max_datagram_bytes: usize) -> Self {
+        self.max_datagram_bytes = max_datagram_bytes;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, ProtocolError> {
+        Ok(self.socket.local_addr()?)
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        if frame.len() > self.max_datagram_bytes {
+            return Err(ProtocolError::DatagramTooLarge {
+                len: frame.len(),
+                max: self.max_datagram_bytes,
+            });
+        }
+        let sent = match (self.connected, self.peer) {
+            (true, _) => self.socket.send(frame),
+            (false, Some(peer)) => self.socket.send_to(frame, peer),
+            (false, None) => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "nobody to send to before a frame arrives",
+            )),
+        };
+        sent.map_err(stream_error)?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        // a byte more than allowed, to tell a datagram that's too large from
+        // one that's just large enough
+        let mut datagram = vec![0; self.max_datagram_bytes + 1];
+        let (len, from) = self.socket.recv_from(&mut datagram).map_err(stream_error)?;
+        if len > self.max_datagram_bytes {
+            return Err(ProtocolError::DatagramTooLarge {
+                len,
+                max: self.max_datagram_bytes,
+            });
+        }
+        if !self.connected {
+            self.peer = Some(from);
+        }
+        datagram.truncate(len);
+        Ok(datagram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{GUSProtocol, PacketType},
+    };
+
+    /// A bound socket on a free loopback port, and one connected to it.
+    fn pair() -> (UdpTransport, UdpTransport) {
+        let receiver = UdpTransport::bind("127.0.0.1:0").unwrap();
+        let sender = UdpTransport::connect(receiver.local_addr().unwrap()).unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        sender
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        (sender, receiver)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (mut sender, mut receiver) = pair();
+        let data = BitVec::random(500, &mut rand::rng());
+        let frame = GUSProtocol::new(data.clone()).unwrap().encode().unwrap();
+        sender.send(&frame).unwrap();
+
+        let (packet, _) = GUSProtocol::decode(receiver.recv().unwrap()).unwrap();
+        assert_eq!(packet.data.to_vec(), data.to_vec());
+
+        // and back to whoever sent it
+        receiver
+            .send(&GUSProtocol::ack(1).encode().unwrap())
+            .unwrap();
+        let (reply, _) = GUSProtocol::decode(sender.recv().unwrap()).unwrap();
+        assert_eq!(reply.packet_type, PacketType::Ack { message_id: 1 });
+    }
+
+    #[test]
+    fn test_oversized() {
+        let (sender, mut receiver) = pair();
+        let mut sender = sender.with_max_datagram_bytes(100);
+        let frame = GUSProtocol::new(BitVec::random(1000, &mut rand::rng()))
+            .unwrap()
+            .encode()
+            .unwrap();
+        let error = sender.send(&frame).unwrap_err();
+        assert!(matches!(
+            error,
+            ProtocolError::DatagramTooLarge { len, max: 100 } if len == frame.len()
+        ));
+        assert!(error.to_string().contains("fragments"));
+
+        // a receiver with a lower limit than the sender's
+        let mut sender = sender.with_max_datagram_bytes(DEFAULT_MAX_DATAGRAM_BYTES);
+        sender.send(&frame).unwrap();
+        receiver.max_datagram_bytes = 100;
+        assert!(matches!(
+            receiver.recv(),
+            Err(ProtocolError::DatagramTooLarge { max: 100, .. })
+        ));
+
+        // a receiver that hasn't heard from anyone has nobody to answer
+        let mut lonely = UdpTransport::bind("127.0.0.1:0").unwrap();
+        assert!(lonely.send(&frame[..10]).is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let (_sender, mut receiver) = pair();
+        receiver
+            .set_read_timeout(Some(Duration::from_millis(50)))
+            .unwrap();
+        let start = std::time::Instant::now();
+        assert!(matches!(receiver.recv(), Err(ProtocolError::Timeout)));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,298 @@
+//! Frames over a Unix domain socket, each after its length, for a sender and
+//! receiver on the same machine meeting at a path.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File, TryLockError},
+    io,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::proto::{
+    ProtocolError,
+    transport::{DEFAULT_MAX_FRAME_BYTES, Transport, read_prefixed, stream_error, write_prefixed},
+};
+
+/// How many bytes of path a socket address holds, the last of them for the
+/// terminating 0.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SUN_PATH_LEN: usize = 108;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SUN_PATH_LEN: usize = 104;
+
+/// One end of a Unix socket connection, sending each frame after its length
+/// as a big-endian `u32`, the same as [`TcpTransport`](super::TcpTransport).
+pub struct UnixTransport {
+    stream: UnixStream,
+    max_frame_bytes: usize,
+}
+
+/// A socket [`UnixTransport::listen`] listens on, holding the lock that keeps
+/// other receivers off the path for as long as it's around.
+#[derive(Debug)]
+pub struct UnixSocketListener {
+    listener: UnixListener,
+    _lock: File,
+}
+
+impl UnixTransport {
+    /// Listens on a socket at `path`. A socket file left there by a listener
+    /// that's gone is removed first, one something still listens on isn't.
+    ///
+    /// Listeners take a lock on `path` with `.lock` appended first, so one
+    /// still there is told apart without connecting to it. A socket left
+    /// behind while the lock is free is only removed once connecting to it
+    /// is refused, in case some other program made it.
+    pub fn listen(path: impl AsRef<Path>) -> Result<UnixSocketListener, ProtocolError> {
+        let path = path.as_ref();
+        check_len(path)?;
+        let lock = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path(path))?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(ProtocolError::SocketInUse(path.display().to_string()));
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        let listener = match UnixListener::bind(path) {
+            Ok(listener) => listener,
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse && is_socket(path) => {
+                remove_stale(path)?;
+                UnixListener::bind(path)?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        log::debug!("Listening on {}", path.display());
+        Ok(UnixSocketListener {
+            listener,
+            _lock: lock,
+        })
+    }
+    /// Connects to the socket at `path`.
+    pub fn connect(path: impl AsRef<Path>) -> Result<Self, ProtocolError> {
+        let path = path.as_ref();
+        check_len(path)?;
+        match UnixStream::connect(path) {
+            Ok(stream) => {
+                log::debug!("Connected to {}", path.display());
+                Ok(Self::from_stream(stream))
+            }
+            // nothing there, or only a socket nobody listens on anymore
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+                ) =>
+            {
+                Err(ProtocolError::ConnectionRefused(path.display().to_string()))
+            }
+            Err(e) => Err(stream_error(e)),
+        }
+    }
+
+    /// Waits for the next connection on `listener`.
+    pub fn accept(listener: &UnixSocketListener) -> Result<Self, ProtocolError> {
+        let (stream, _) = listener.listener.accept()?;
+        log::debug!("Accepted a connection");
+        Ok(Self::from_stream(stream))
+    }
+
+    pub fn from_stream(stream: UnixStream) -> Self {
+        Self {
+            stream,
+            max_frame_bytes: DEFAULT_MAX_FRAME_BYTES,
+        }
+    }
+
+    /// How long sending or receiving a frame may block before giving up with
+    /// [`ProtocolError::Timeout`], `None` to wait for as long as it takes.
+    pub fn set_timeout(&self, timeout: Option<Duration>) -> Result<(), ProtocolError> {
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// The largest frame [`Transport::recv`] takes, [`DEFAULT_MAX_FRAME_BYTES`]
+    /// unless set.
+    pub fn with_max_frame_bytes(mut self, max_frame_bytes: usize) -> Self {
+        self.max_frame_bytes = max_frame_bytes;
+        self
+    }
+}
+
+impl Transport for UnixTransport {
+    fn send(&mut self, frame: &[u8]) -> Result<(), ProtocolError> {
+        write_prefixed(&mut self.stream, frame)
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        read_prefixed(&mut self.stream, self.max_frame_bytes)
+    }
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut lock = OsString::from(path);
+    lock.push(".lock");
+    lock.into()
+}
+
+/// Removes the socket at `path` if nothing answers on it anymore.
+fn remove_stale(path: &Path) -> Result<(), ProtocolError> {
+    match UnixStream::connect(path) {
+        Ok(_) => Err(ProtocolError::SocketInUse(path.display().to_string())),
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+            log::debug!("Removing the stale socket at {}", path.display());
+            Ok(fs::remove_file(path)?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn is_socket(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket())
+}
+
+/// Makes sure `path` fits in a socket address, which std would only say
+/// with a vague invalid input error.
+fn check_len(path: &Path) -> Result<(), ProtocolError> {
+    let len = path.as_os_str().len();
+    match len < SUN_PATH_LEN {
+        true => Ok(()),
+        false => Err(ProtocolError::SocketPathTooLong {
+            path: path.display().to_string(),
+            len,
+            max: SUN_PATH_LEN - 1,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{GUSProtocol, PacketType},
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gus.sock");
+        let listener = UnixTransport::listen(&path).unwrap();
+
+        let mut rng = rand::rng();
+        let sent: Vec<BitVec> = [500, 7, 2000]
+            .into_iter()
+            .map(|len| BitVec::random(len, &mut rng))
+            .collect();
+        let expected: Vec<Vec<bool>> = sent.iter().map(BitVec::to_vec).collect();
+        let receiver = thread::spawn(move || {
+            let mut transport = UnixTransport::accept(&listener).unwrap();
+            for expected in expected {
+                let (packet, _) = GUSProtocol::decode(transport.recv().unwrap()).unwrap();
+                assert_eq!(packet.data.to_vec(), expected);
+            }
+            transport
+                .send(&GUSProtocol::ack(3).encode().unwrap())
+                .unwrap();
+            assert!(matches!(transport.recv(), Err(ProtocolError::EndOfStream)));
+        });
+
+        let mut transport = UnixTransport::connect(&path).unwrap();
+        transport.set_timeout(Some(Duration::from_secs(5))).unwrap();
+        for data in sent {
+            transport
+                .send(&GUSProtocol::new(data).unwrap().encode().unwrap())
+                .unwrap();
+        }
+        let (reply, _) = GUSProtocol::decode(transport.recv().unwrap()).unwrap();
+        assert_eq!(reply.packet_type, PacketType::Ack { message_id: 3 });
+        drop(transport);
+
+        receiver.join().unwrap();
+    }
+
+    #[test]
+    fn test_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gus.sock");
+
+        // a listener that went away without cleaning up after itself
+        drop(UnixTransport::listen(&path).unwrap());
+        assert!(path.exists());
+        assert!(matches!(
+            UnixTransport::connect(&path),
+            Err(ProtocolError::ConnectionRefused(_))
+        ));
+
+        let listener = UnixTransport::listen(&path).unwrap();
+        // but one still listening is left alone
+        assert!(matches!(
+            UnixTransport::listen(&path),
+            Err(ProtocolError::SocketInUse(_))
+        ));
+        // and what it accepts is the sender
+        let mut sender = UnixTransport::connect(&path).unwrap();
+        sender.send(&GUSProtocol::ack(7).encode().unwrap()).unwrap();
+        let mut receiver = UnixTransport::accept(&listener).unwrap();
+        let (packet, _) = GUSProtocol::decode(receiver.recv().unwrap()).unwrap();
+        assert_eq!(packet.packet_type, PacketType::Ack { message_id: 7 });
+
+        // and so is anything that isn't a socket
+        let file = dir.path().join("not.sock");
+        fs::write(&file, b"keep me").unwrap();
+        assert!(UnixTransport::listen(&file).is_err());
+        assert_eq!(fs::read(&file).unwrap(), b"keep me");
+    }
+
+    #[test]
+    fn test_foreign_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.sock");
+        // some other program's socket, which takes no lock
+        let other = UnixListener::bind(&path).unwrap();
+        assert!(matches!(
+            UnixTransport::listen(&path),
+            Err(ProtocolError::SocketInUse(_))
+        ));
+        assert!(is_socket(&path));
+
+        // once it's gone its socket is fair game
+        drop(other);
+        let listener = UnixTransport::listen(&path).unwrap();
+        let mut sender = UnixTransport::connect(&path).unwrap();
+        sender.send(&GUSProtocol::ack(1).encode().unwrap()).unwrap();
+        let mut receiver = UnixTransport::accept(&listener).unwrap();
+        let (packet, _) = GUSProtocol::decode(receiver.recv().unwrap()).unwrap();
+        assert_eq!(packet.packet_type, PacketType::Ack { message_id: 1 });
+    }
+
+    #[test]
+    fn test_path_too_long() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s".repeat(SUN_PATH_LEN));
+        let error = UnixTransport::listen(&path).unwrap_err();
+        assert!(matches!(
+            error,
+            ProtocolError::SocketPathTooLong { len, max, .. }
+                if len == path.as_os_str().len() && max == SUN_PATH_LEN - 1
+        ));
+        assert!(error.to_string().contains(&path.display().to_string()));
+        assert!(matches!(
+            UnixTransport::connect(&path),
+            Err(ProtocolError::SocketPathTooLong { .. })
+        ));
+    }
+}
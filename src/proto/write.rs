@@ -0,0 +1,189 @@
+//! Encoding frames straight into a [`Write`], without holding the codeword.
+
+use std::io::Write;
+
+use crate::{
+    encoding::{
+        channel::IdealChannel,
+        hamming::{Hamming, HammingCodeBase, HammingError},
+        mapping,
+        source::BitSource,
+    },
+    proto::{CodecId, GUSProtocol, Length, ProtocolError},
+};
+
+/// Bytes of payload gathered before they're handed to the writer.
+const CHUNK_SIZE: usize = 4096;
+
+/// What [`GUSProtocol::encode_to`] wrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeSummary {
+    pub header_bytes: usize,
+    pub payload_bytes: usize,
+    /// Bits of the Hamming codeword in the payload, the rest is padding.
+    pub payload_bits: usize,
+}
+
+impl EncodeSummary {
+    /// Bytes written in all.
+    pub fn bytes_written(&self) -> usize {
+        self.header_bytes + self.payload_bytes
+    }
+}
+
+impl GUSProtocol {
+    /// Encodes the packet into `writer`, the same bytes as
+    /// [`GUSProtocol::encode`]. The header goes first, then the codeword is
+    /// laid out a chunk at a time, so it's never in memory as a whole.
+    ///
+    /// If writing fails partway through, whatever was written stays written.
+    ///
+    /// Only plain Hamming codewords are laid out as they go, the other codecs
+    /// and interleaved codewords are encoded whole first.
+    pub fn encode_to(self, writer: &mut impl Write) -> Result<EncodeSummary, ProtocolError> {
+        if self.wire_codec() != CodecId::Hamming || self.wire_interleaver()?.is_some() {
+            let header_bytes = self.header_size();
+            let payload_bits = self.wire_codec().encoded_bits(self.payload().bit_len());
+            let frame = self.encode_over(&mut IdealChannel)?;
+            writer.write_all(&frame)?;
+            writer.flush()?;
+            return Ok(EncodeSummary {
+                header_bytes,
+                payload_bytes: frame.len() - header_bytes,
+                payload_bits,
+            });
+        }
+
+        let payload = self.payload();
+        let data_bits = payload.bit_len();
+        let payload_bits = data_bits + HammingCodeBase::calculate_parity_count(data_bits);
+        let payload_bytes = payload_bits.div_ceil(8);
+        let parities = Hamming.parity_word(&payload)?;
+
+        let header = self
+            .header(
+                &payload,
+                Length {
+                    data_length: payload_bytes,
+                    bits_length: payload_bits,
+                },
+            )
+            .encode(&self.protocol_name);
+        writer.write_all(&header)?;
+
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE.min(payload_bytes));
+        let mut byte = 0u8;
+        for i in 0..payload_bits {
+            let bit = match mapping::codeword_to_data(i) {
+                Some(data_index) => payload
+                    .bit(data_index)
+                    .ok_or(HammingError::UnexpectedOutOfBounds)?,
+                // i + 1 is a power of two here, the bit of its parity
+                None => parities & (i + 1) != 0,
+            };
+            byte |= u8::from(bit) << (7 - i % 8);
+
+            if i % 8 == 7 || i + 1 == payload_bits {
+                chunk.push(byte);
+                byte = 0;
+                if chunk.len() == CHUNK_SIZE {
+                    writer.write_all(&chunk)?;
+                    chunk.clear();
+                }
+            }
+        }
+        writer.write_all(&chunk)?;
+        writer.flush()?;
+
+        Ok(EncodeSummary {
+            header_bytes: header.len(),
+            payload_bytes,
+            payload_bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        encoding::bitvec::BitVec,
+        proto::{ContentType, FrameVersion},
+    };
+
+    /// Takes at most 3 bytes per write, and every other write is interrupted.
+    #[derive(Default)]
+    struct Trickle {
+        written: Vec<u8>,
+        calls: usize,
+    }
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.calls += 1;
+            if self.calls.is_multiple_of(2) {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(3);
+            self.written.extend(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_matches_encode() {
+        let mut rng = rand::rng();
+        // empty, within a byte, and several chunks with a partial last byte
+        for len in [0, 1, 4, 11, 1000, 8 * CHUNK_SIZE * 3 + 5] {
+            let data = BitVec::random(len, &mut rng);
+            for version in FrameVersion::ALL {
+                for codec in CodecId::ALL {
+                    let mut packet = GUSProtocol::new_with_codec(data.clone(), codec)
+                        .unwrap()
+                        .with_content_type(ContentType::Text);
+                    packet.version = version;
+
+                    let expected = packet.clone().encode_over(&mut IdealChannel).unwrap();
+                    let mut written = Vec::new();
+                    let summary = packet.clone().encode_to(&mut written).unwrap();
+                    assert_eq!(
+                        written, expected,
+                        "length {len}, version {version}, {codec}"
+                    );
+                    assert_eq!(summary.bytes_written(), expected.len());
+                    assert_eq!(packet.clone().encode().unwrap(), expected);
+                    assert_eq!(packet.encoded_len(), expected.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_short_writes() {
+        let data = BitVec::random(8 * CHUNK_SIZE + 100, &mut rand::rng());
+        let packet = GUSProtocol::new(data.clone()).unwrap();
+
+        let mut writer = Trickle::default();
+        let summary = packet.clone().encode_to(&mut writer).unwrap();
+        assert_eq!(writer.written, packet.encode().unwrap());
+        assert_eq!(summary.bytes_written(), writer.written.len());
+
+        let (decoded, _) = GUSProtocol::decode(writer.written).unwrap();
+        assert_eq!(decoded.data.to_vec(), data.to_vec());
+    }
+
+    #[test]
+    fn test_write_error() {
+        let mut full = [0u8; 30];
+        let packet = GUSProtocol::new(BitVec::random(100, &mut rand::rng())).unwrap();
+        let error = packet.encode_to(&mut &mut full[..]).unwrap_err();
+        assert!(matches!(
+            error,
+            ProtocolError::Io(error) if error.kind() == std::io::ErrorKind::WriteZero
+        ));
+    }
+}
@@ -0,0 +1,44 @@
+//! proptest strategies for property testing against this crate.
+//!
+//! Everything is built on top of `Vec<bool>` strategies so shrinking produces
+//! shorter BitVecs.
+
+use proptest::{collection::vec, option, prelude::*};
+
+use crate::encoding::{
+    bitvec::BitVec,
+    hamming::{Hamming, HammingCode, HammingCodeBase},
+};
+
+/// BitVecs of 0 to `max_bits` bits.
+pub fn bitvec_strategy(max_bits: usize) -> impl Strategy<Value = BitVec> {
+    vec(any::<bool>(), 0..=max_bits).prop_map(BitVec::from_vec)
+}
+
+/// BitVecs of 1 to `max_bits` bits.
+pub fn nonempty_bitvec_strategy(max_bits: usize) -> impl Strategy<Value = BitVec> {
+    vec(any::<bool>(), 1..=max_bits.max(1)).prop_map(BitVec::from_vec)
+}
+
+/// Hamming codewords for `k` random data bits, with either no or a single
+/// flipped bit.
+///
+/// Yields `(data, codeword, flipped)` where `flipped` is the index of the
+/// corrupted codeword bit, if any.
+pub fn codeword_strategy(k: usize) -> impl Strategy<Value = (BitVec, BitVec, Option<usize>)> {
+    let n = k + HammingCodeBase::calculate_parity_count(k);
+
+    (vec(any::<bool>(), k), option::of(0..n.max(1))).prop_map(move |(data, flip)| {
+        let data = BitVec::from_vec(data);
+        let mut codeword = Hamming
+            .encode(&data)
+            .expect("encoding freshly generated data cannot fail");
+
+        let flip = flip.filter(|&flip| flip < n);
+        if let Some(flip) = flip {
+            codeword.toggle(flip).expect("flip is within the codeword");
+        }
+
+        (data, codeword, flip)
+    })
+}
@@ -55,7 +55,11 @@ impl Logger {
                 cli::enums::Mode::from(&args.command),
             )))
             .write_style(env_logger::WriteStyle::Always)
-            .target(env_logger::Target::Pipe(Box::new(SplitWriter::new())))
+            .target(match args.command.writes_stdout() {
+                // stdout's taken, by data that the log mustn't get mixed into
+                true => env_logger::Target::Stderr,
+                false => env_logger::Target::Pipe(Box::new(SplitWriter::new())),
+            })
             .init();
     }
 }
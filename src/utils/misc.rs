@@ -1,39 +1,19 @@
-pub fn string_to_bits(s: &str) -> Vec<bool> {
-    let mut result = Vec::with_capacity(s.len() * 8);
-    for byte in s.as_bytes() {
-        for i in (0..8).rev() {
-            result.push((byte >> i) & 1 == 1);
-        }
-    }
+use anyhow::{anyhow, bail};
 
-    result
-}
-
-pub fn bits_to_string(bits: &[bool]) -> String {
-    let mut result = Vec::with_capacity(bits.len() / 8);
-    for chunk in bits.chunks(8) {
-        let mut byte = 0u8;
-        for (i, &bit) in chunk.iter().enumerate() {
-            if bit {
-                byte |= 1 << (7 - i);
-            }
-        }
-        result.push(byte);
-    }
-
-    String::from_utf8(result).unwrap_or_else(|_| "Invalid UTF-8".to_string())
-}
+const BASE64_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
 pub fn bytestring_to_bitvec(s: &str) -> anyhow::Result<Vec<bool>> {
-    s.chars().fold(Ok(Vec::with_capacity(s.len())), |acc, c| {
-        acc.and_then(|mut vec| match c.to_digit(2).map(|digit| digit != 0) {
-            Some(digit) => {
-                vec.push(digit);
-                Ok(vec)
+    s.chars()
+        .try_fold(Vec::with_capacity(s.len()), |mut vec, c| {
+            match c.to_digit(2).map(|digit| digit != 0) {
+                Some(digit) => {
+                    vec.push(digit);
+                    Ok(vec)
+                }
+                None => Err(anyhow::anyhow!("Invalid digit: {}", c)),
             }
-            None => Err(anyhow::anyhow!("Invalid digit: {}", c)),
         })
-    })
 }
 
 pub fn bits_to_bytestring(bits: &[bool]) -> String {
@@ -41,3 +21,121 @@ pub fn bits_to_bytestring(bits: &[bool]) -> String {
         .map(|&bit| if bit { '1' } else { '0' })
         .collect()
 }
+
+/// The first `max` of `bytes` in lowercase hex, with `...` after if there
+/// are more, for a look at data that can't be shown as it is.
+pub fn hex_preview(bytes: &[u8], max: usize) -> String {
+    let mut preview: String = bytes
+        .iter()
+        .take(max)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    if bytes.len() > max {
+        preview.push_str("...");
+    }
+    preview
+}
+
+/// The bytes `s` spells out in standard base64, padded or not. Errors name
+/// the offset of the character that's wrong.
+pub fn base64_to_bytes(s: &str) -> anyhow::Result<Vec<u8>> {
+    let unpadded = s.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let (mut group, mut digits) = (0u32, 0);
+    for (offset, c) in unpadded.chars().enumerate() {
+        let digit = BASE64_DIGITS
+            .iter()
+            .position(|&digit| digit as char == c)
+            .ok_or_else(|| anyhow!("Invalid base64 character {c:?} at offset {offset}"))?;
+        group = group << 6 | digit as u32;
+        digits += 1;
+        if digits == 4 {
+            bytes.extend_from_slice(&group.to_be_bytes()[1..]);
+            (group, digits) = (0, 0);
+        }
+    }
+
+    // the characters trimmed off are all '=', past anything else
+    let end = unpadded.chars().count();
+    let padding = s.len() - unpadded.len();
+    match digits {
+        0 => {}
+        2 => bytes.push((group >> 4) as u8),
+        3 => bytes.extend_from_slice(&((group >> 2) as u16).to_be_bytes()),
+        _ => bail!(
+            "Base64 can't end on a single character, at offset {}",
+            end - 1
+        ),
+    }
+    if padding > 0 && digits + padding != 4 {
+        bail!("Invalid base64 padding at offset {end}");
+    }
+    Ok(bytes)
+}
+
+/// `bytes` in standard base64, padded.
+pub fn bytes_to_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => {
+                    encoded.push(BASE64_DIGITS[(group >> (18 - 6 * i) & 0x3f) as usize] as char)
+                }
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_preview() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(hex_preview(&bytes[..3], 4), "000102");
+        assert_eq!(hex_preview(&bytes[..4], 4), "00010203");
+        assert_eq!(hex_preview(&bytes, 4), "00010203...");
+        assert_eq!(hex_preview(&[], 4), "");
+    }
+
+    #[test]
+    fn test_base64() {
+        for (bytes, padded, unpadded) in [
+            (&b""[..], "", ""),
+            (b"f", "Zg==", "Zg"),
+            (b"fo", "Zm8=", "Zm8"),
+            (b"foo", "Zm9v", "Zm9v"),
+            (b"foob", "Zm9vYg==", "Zm9vYg"),
+            (b"\xff\xfe\x00\x80", "//4AgA==", "//4AgA"),
+        ] {
+            assert_eq!(bytes_to_base64(bytes), padded);
+            assert_eq!(base64_to_bytes(padded).unwrap(), bytes);
+            assert_eq!(base64_to_bytes(unpadded).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_base64_invalid() {
+        for (base64, error) in [
+            ("Zm9-", "Invalid base64 character '-' at offset 3"),
+            ("Zg=a", "Invalid base64 character '=' at offset 2"),
+            (
+                "Zm9vY",
+                "Base64 can't end on a single character, at offset 4",
+            ),
+            ("Zg=", "Invalid base64 padding at offset 2"),
+            ("Zm9v=", "Invalid base64 padding at offset 4"),
+            ("Zm8==", "Invalid base64 padding at offset 3"),
+        ] {
+            let e = base64_to_bytes(base64).unwrap_err();
+            assert_eq!(e.to_string(), error, "{base64}");
+        }
+    }
+}
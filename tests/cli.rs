@@ -0,0 +1,234 @@
+//! The sender piped into the receiver, as the binary runs them.
+
+use std::io::Write;
+
+use assert_cmd::{Command, assert::Assert, cargo::cargo_bin_cmd};
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use tempfile::NamedTempFile;
+
+fn command(args: &[&str]) -> Command {
+    let mut command = cargo_bin_cmd!("hamming_rust");
+    command.args(args);
+    command
+}
+
+/// Runs a sender with `sender_args`, piped into a receiver with
+/// `receiver_args`, returning what the receiver printed.
+fn pipe(sender_args: &[&str], receiver_args: &[&str]) -> Assert {
+    pipe_input(&[], sender_args, receiver_args)
+}
+
+/// Like [`pipe`], with `input` on the sender's stdin.
+fn pipe_input(input: &[u8], sender_args: &[&str], receiver_args: &[&str]) -> Assert {
+    // the sender reads all of it before it writes anything, so the frames
+    // can be handed over whole
+    let sent = command(sender_args).write_stdin(input).assert().success();
+    command(receiver_args)
+        .write_stdin(sent.get_output().stdout.clone())
+        .assert()
+}
+
+/// The message the receiver printed to `stdout`, which the logger indents
+/// line by line.
+fn shown(received: &Assert) -> String {
+    let stdout = String::from_utf8_lossy(&received.get_output().stdout);
+    let lines: Vec<_> = stdout
+        .lines()
+        .skip_while(|line| !line.ends_with("Received data:"))
+        .skip(1)
+        .map_while(|line| line.strip_prefix(" |  "))
+        .collect();
+    lines.join("\n")
+}
+
+fn file_with(contents: &[u8]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(contents).unwrap();
+    file
+}
+
+#[test]
+fn test_text_file() {
+    let file = file_with("a line, \"quoted\",\nand another, ünïcode too\n".as_bytes());
+    let path = file.path().to_str().unwrap();
+    let received = pipe(&["-t", "text", "sender", "--file", path], &["receiver"]).success();
+    assert_eq!(shown(&received), std::fs::read_to_string(path).unwrap());
+}
+
+#[test]
+fn test_binary_file() {
+    // raw bytes, not a string of 0s and 1s
+    let contents = [0x00, 0xff, 0x0a, 0x30, 0x31, 0x80];
+    let file = file_with(&contents);
+    let path = file.path().to_str().unwrap();
+    let received = pipe(
+        &["sender", "--file", path, "-m", "16"],
+        &["-t", "binary", "receiver"],
+    )
+    .success();
+
+    let bits: String = contents.iter().map(|byte| format!("{byte:08b}")).collect();
+    assert_eq!(shown(&received), bits);
+}
+
+#[test]
+fn test_missing_file() {
+    let missing = tempfile::tempdir().unwrap().path().join("missing");
+    command(&["sender", "--file", missing.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(contains("Error reading"));
+
+    // and it's one or the other
+    command(&["sender", "--file", "x", "-d", "0101"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+/// Bytes of every value, over and over, as binary as it gets.
+fn fixture(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 256) as u8).collect()
+}
+
+#[test]
+fn test_output_file() {
+    let contents = fixture(5000);
+    let file = file_with(&contents);
+    let path = file.path().to_str().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("received");
+    // there already, to be emptied
+    std::fs::write(&output, vec![1; 10000]).unwrap();
+
+    pipe(
+        &["sender", "--file", path, "-m", "8192"],
+        &["receiver", "--output", output.to_str().unwrap()],
+    )
+    .success()
+    // the log says how much, not what
+    .stdout(contains("Wrote 5000 bytes (40000 bits)"))
+    .stdout(contains("Received data").not());
+    assert!(std::fs::read(&output).unwrap() == contents);
+}
+
+#[test]
+fn test_output_stdout() {
+    let contents = fixture(300);
+    let file = file_with(&contents);
+    let path = file.path().to_str().unwrap();
+    pipe(&["sender", "--file", path], &["receiver", "--output", "-"])
+        .success()
+        .stdout(contents)
+        .stderr(contains("Wrote 300 bytes"));
+}
+
+#[test]
+fn test_output_unwritable() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("missing").join("received");
+    // failing before it reads any of its input
+    command(&["receiver", "--output", output.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(contains("Error creating"));
+}
+
+#[test]
+fn test_stdin() {
+    let message = "piped in, over\nseveral lines\n";
+    let received = pipe_input(
+        message.as_bytes(),
+        &["-t", "text", "sender"],
+        &["-t", "text", "receiver"],
+    )
+    .success();
+    assert_eq!(shown(&received), message);
+
+    // bytes for binary too, bigger than a pipe holds at once
+    let contents = fixture(100_000);
+    pipe_input(
+        &contents,
+        &["sender", "-m", "65536"],
+        &["receiver", "--output", "-"],
+    )
+    .success()
+    .stdout(contents);
+}
+
+#[test]
+fn test_stdin_not_text() {
+    command(&["-t", "text", "sender"])
+        .write_stdin([0xff, 0xfe])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(contains("stdin is not valid UTF-8"));
+}
+
+#[test]
+fn test_hex() {
+    // the receiver goes by the frame's content type
+    let received = pipe(
+        &["-t", "hex", "sender", "--data", "00FF10ab"],
+        &["receiver"],
+    )
+    .success();
+    assert_eq!(shown(&received), "00ff10ab");
+
+    for (data, error) in [
+        ("00ff1", "Unpaired hex digit at index 4"),
+        ("00fx10", "Invalid hex digit at index 3"),
+    ] {
+        command(&["-t", "hex", "sender", "--data", data])
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(contains(error));
+    }
+}
+
+#[test]
+fn test_base64() {
+    // none of it UTF-8
+    for (data, shown_back) in [("//4AgA==", "//4AgA=="), ("//4AgA", "//4AgA==")] {
+        let received = pipe(&["-t", "base64", "sender", "--data", data], &["receiver"]).success();
+        assert_eq!(shown(&received), shown_back);
+    }
+
+    pipe(
+        &["-t", "base64", "sender", "--data", "//4AgA=="],
+        &["receiver", "--output", "-"],
+    )
+    .success()
+    .stdout(&[0xff, 0xfe, 0x00, 0x80][..]);
+
+    command(&["-t", "base64", "sender", "--data", "//4A*A=="])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(contains("'*' at offset 4"));
+}
+
+#[test]
+fn test_bytes() {
+    let every_byte: Vec<u8> = (0..=255).collect();
+    let file = file_with(&every_byte);
+    let path = file.path().to_str().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("received");
+    pipe(
+        &["-t", "bytes", "sender", "--file", path],
+        &["receiver", "--output", output.to_str().unwrap()],
+    )
+    .success();
+    assert_eq!(std::fs::read(&output).unwrap(), every_byte);
+
+    // and without it, only a look at the start
+    let preview: String = (0..32).map(|byte| format!("{byte:02x}")).collect();
+    pipe(&["-t", "bytes", "sender", "--file", path], &["receiver"])
+        .success()
+        .stdout(contains(format!("Received 256 bytes: {preview}...\n")))
+        .stdout(contains("Received data").not());
+}